@@ -162,11 +162,6 @@ fn main() {
         }
     };
 
-    println!(
-        "Local size will be set to: ({}, {}, 1)",
-        local_size_x, local_size_y
-    );
-
     let spec_consts = cs::SpecializationConstants {
         red: 0.2,
         green: 0.5,
@@ -185,6 +180,13 @@ fn main() {
         .unwrap(),
     );
 
+    // The pipeline reports the local size of the shader with the specialization constants applied.
+    let [local_size_x, local_size_y, local_size_z] = pipeline.local_size().unwrap();
+    println!(
+        "Local size has been set to: ({}, {}, {})",
+        local_size_x, local_size_y, local_size_z
+    );
+
     let image = StorageImage::new(
         device.clone(),
         ImageDimensions::Dim2d {
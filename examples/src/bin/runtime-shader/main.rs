@@ -35,7 +35,6 @@ use vulkano::image::{ImageUsage, SwapchainImage};
 use vulkano::instance::Instance;
 use vulkano::pipeline::shader::{
     GraphicsShaderType, ShaderInterface, ShaderInterfaceEntry, ShaderModule,
-    SpecializationConstants,
 };
 use vulkano::pipeline::viewport::Viewport;
 use vulkano::pipeline::GraphicsPipeline;
@@ -220,7 +219,7 @@ fn main() {
             CStr::from_bytes_with_nul_unchecked(b"main\0"),
             [],   // No descriptor sets.
             None, // No push constants.
-            <()>::descriptors(),
+            [],
             vertex_input,
             vertex_output,
             GraphicsShaderType::Vertex,
@@ -232,7 +231,7 @@ fn main() {
             CStr::from_bytes_with_nul_unchecked(b"main\0"),
             [],   // No descriptor sets.
             None, // No push constants.
-            <()>::descriptors(),
+            [],
             fragment_input,
             fragment_output,
             GraphicsShaderType::Fragment,
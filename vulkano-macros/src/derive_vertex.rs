@@ -0,0 +1,127 @@
+// Copyright (c) 2021 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::punctuated::Punctuated;
+use syn::{Data, DeriveInput, Error, Fields, Ident, LitStr, Result, Token};
+
+pub(super) fn derive_vertex(ast: DeriveInput) -> Result<TokenStream> {
+    let struct_name = &ast.ident;
+
+    let fields = match &ast.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &ast,
+                    "`Vertex` can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &ast,
+                "`Vertex` can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut members = Vec::with_capacity(fields.len());
+    let mut where_predicates = Vec::new();
+
+    for field in fields {
+        let field_name = field.ident.as_ref().unwrap();
+        let field_ty = &field.ty;
+        let mut names = vec![LitStr::new(&field_name.to_string(), field_name.span())];
+        let mut format = None;
+
+        for attr in &field.attrs {
+            if attr.path.is_ident("name") {
+                let meta =
+                    attr.parse_args_with(Punctuated::<LitStr, Token![,]>::parse_terminated)?;
+                if meta.is_empty() {
+                    return Err(Error::new_spanned(
+                        attr,
+                        "expected at least one name, e.g. `#[name(\"in_position\")]`",
+                    ));
+                }
+                names = meta.into_iter().collect();
+            } else if attr.path.is_ident("format") {
+                if format.is_some() {
+                    return Err(Error::new_spanned(attr, "duplicate `format` attribute"));
+                }
+                format = Some(attr.parse_args::<Ident>()?);
+            }
+        }
+
+        let member_ty = match format {
+            Some(format) => quote_spanned! {format.span()=>
+                let (ty, array_size) = (
+                    ::vulkano::pipeline::vertex::VertexMemberTy::Format(
+                        ::vulkano::format::Format::#format,
+                    ),
+                    ::vulkano::pipeline::vertex::VertexMemberFormatCheck::<
+                        #field_ty,
+                        {
+                            match ::vulkano::format::Format::#format.size() {
+                                Some(size) => size as usize,
+                                None => 0,
+                            }
+                        },
+                    >::ARRAY_SIZE,
+                );
+            },
+            None => {
+                where_predicates
+                    .push(quote! { #field_ty: ::vulkano::pipeline::vertex::VertexMember });
+                quote! {
+                    let (ty, array_size) =
+                        <#field_ty as ::vulkano::pipeline::vertex::VertexMember>::format();
+                }
+            }
+        };
+
+        members.push(quote! {
+            if #( name == #names )||* {
+                #member_ty
+
+                let dummy = ::std::mem::MaybeUninit::<Self>::uninit();
+                let dummy_ptr = dummy.as_ptr();
+                let member_ptr = unsafe { ::std::ptr::addr_of!((*dummy_ptr).#field_name) };
+
+                return Some(::vulkano::pipeline::vertex::VertexMemberInfo {
+                    offset: member_ptr as usize - dummy_ptr as usize,
+                    ty,
+                    array_size,
+                });
+            }
+        });
+    }
+
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let existing_predicates = where_clause.map(|clause| &clause.predicates);
+
+    Ok(quote! {
+        #[allow(unsafe_code)]
+        unsafe impl #impl_generics ::vulkano::pipeline::vertex::Vertex for #struct_name #ty_generics
+        where
+            Self: 'static + Send + Sync,
+            #( #where_predicates, )*
+            #existing_predicates
+        {
+            #[inline]
+            fn member(name: &str) -> Option<::vulkano::pipeline::vertex::VertexMemberInfo> {
+                #( #members )*
+
+                None
+            }
+        }
+    })
+}
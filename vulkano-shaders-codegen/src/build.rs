@@ -0,0 +1,554 @@
+// Copyright (c) 2021 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Generating shader modules from a build script.
+//!
+//! Invoking the `shader!` macro compiles the shader and generates its code every time the crate
+//! that contains it is compiled. A [`Builder`] does the same work from `build.rs` instead, so
+//! that it only happens again when one of the shader files changes.
+//!
+//! The builder compiles each shader file to SPIR-V in `OUT_DIR`, writes the module that the
+//! macro would generate for the same shaders to a file in `OUT_DIR`, and prints a
+//! `cargo:rerun-if-changed` line for each shader file and for every file that they include.
+//! The generated file is then included in the crate with `include!`:
+//!
+//! ```no_run
+//! // build.rs
+//! use vulkano_shaders_codegen::build::{Builder, ShaderFile};
+//! use vulkano_shaders_codegen::ShaderKind;
+//!
+//! fn main() {
+//!     Builder::new("shaders.rs")
+//!         .shader(ShaderFile::new("src/shaders/vs.glsl", ShaderKind::Vertex).prefix("Vertex"))
+//!         .shader(ShaderFile::new("src/shaders/fs.glsl", ShaderKind::Fragment).prefix("Fragment"))
+//!         .include_directory("src/shaders/include")
+//!         .build()
+//!         .unwrap();
+//! }
+//! ```
+//!
+//! ```ignore
+//! // src/main.rs
+//! mod shaders {
+//!     include!(concat!(env!("OUT_DIR"), "/shaders.rs"));
+//! }
+//!
+//! let vs = shaders::VertexShader::load(device.clone()).unwrap();
+//! let fs = shaders::FragmentShader::load(device.clone()).unwrap();
+//! ```
+//!
+//! Like the `shaders` option of the macro, each shader has a prefix that is prepended to the
+//! names of its items. A single shader with the default empty prefix generates the same
+//! `Shader` and `SpecializationConstants` items as a macro invocation with a single shader.
+
+use crate::codegen::{self, HlslOptions, ShaderKind, SourceLanguage};
+use crate::{path_to_str, read_file_to_string, TypesMeta};
+use shaderc::{EnvVersion, SpirvVersion};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io::Error as IoError;
+use std::iter::empty;
+use std::path::{Path, PathBuf};
+
+/// A shader file to compile with a [`Builder`].
+#[derive(Debug, Clone)]
+pub struct ShaderFile {
+    path: PathBuf,
+    kind: ShaderKind,
+    prefix: String,
+    language: SourceLanguage,
+    entry_point: String,
+}
+
+impl ShaderFile {
+    /// Starts describing a shader file of the given kind. The path is relative to the directory
+    /// of the `Cargo.toml` of the crate being built.
+    ///
+    /// The shader is written in GLSL, has an entry point named `main` and an empty prefix
+    /// unless specified otherwise.
+    #[inline]
+    pub fn new<P>(path: P, kind: ShaderKind) -> ShaderFile
+    where
+        P: Into<PathBuf>,
+    {
+        ShaderFile {
+            path: path.into(),
+            kind,
+            prefix: String::new(),
+            language: SourceLanguage::GLSL,
+            entry_point: "main".to_owned(),
+        }
+    }
+
+    /// Sets the prefix that is prepended to the names of the items generated for this shader.
+    #[inline]
+    pub fn prefix<S>(mut self, prefix: S) -> ShaderFile
+    where
+        S: Into<String>,
+    {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Sets the language that the shader is written in.
+    #[inline]
+    pub fn language(mut self, language: SourceLanguage) -> ShaderFile {
+        self.language = language;
+        self
+    }
+
+    /// Sets the name of the entry point function of the shader.
+    #[inline]
+    pub fn entry_point<S>(mut self, entry_point: S) -> ShaderFile
+    where
+        S: Into<String>,
+    {
+        self.entry_point = entry_point.into();
+        self
+    }
+}
+
+/// Compiles shader files and generates their Rust code from a build script.
+///
+/// The options match those of the `shader!` macro.
+pub struct Builder {
+    out_file: String,
+    out_dir: Option<PathBuf>,
+    root: Option<PathBuf>,
+    shaders: Vec<ShaderFile>,
+    include_directories: Vec<PathBuf>,
+    macro_defines: Vec<(String, String)>,
+    vulkan_version: Option<EnvVersion>,
+    spirv_version: Option<SpirvVersion>,
+    exact_entrypoint_interface: bool,
+    shared_constants: bool,
+    hlsl_options: HlslOptions,
+    types_meta: Option<String>,
+}
+
+impl Builder {
+    /// Starts building a module, which is written to the file named `out_file` in the output
+    /// directory.
+    #[inline]
+    pub fn new<S>(out_file: S) -> Builder
+    where
+        S: Into<String>,
+    {
+        Builder {
+            out_file: out_file.into(),
+            out_dir: None,
+            root: None,
+            shaders: Vec::new(),
+            include_directories: Vec::new(),
+            macro_defines: Vec::new(),
+            vulkan_version: None,
+            spirv_version: None,
+            exact_entrypoint_interface: false,
+            shared_constants: false,
+            hlsl_options: HlslOptions::default(),
+            types_meta: None,
+        }
+    }
+
+    /// Adds a shader to the module.
+    #[inline]
+    pub fn shader(mut self, shader: ShaderFile) -> Builder {
+        self.shaders.push(shader);
+        self
+    }
+
+    /// Adds a directory that is searched for files included with `#include <...>`. The path is
+    /// relative to the root directory.
+    #[inline]
+    pub fn include_directory<P>(mut self, directory: P) -> Builder
+    where
+        P: Into<PathBuf>,
+    {
+        self.include_directories.push(directory.into());
+        self
+    }
+
+    /// Defines a preprocessor macro for all the shaders.
+    #[inline]
+    pub fn define<N, V>(mut self, name: N, value: V) -> Builder
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        self.macro_defines.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets the Vulkan version that the shaders are compiled for. The default is Vulkan 1.0.
+    #[inline]
+    pub fn vulkan_version(mut self, version: EnvVersion) -> Builder {
+        self.vulkan_version = Some(version);
+        self
+    }
+
+    /// Sets the SPIR-V version that the shaders are compiled to. The default is the highest
+    /// version supported by the Vulkan version.
+    #[inline]
+    pub fn spirv_version(mut self, version: SpirvVersion) -> Builder {
+        self.spirv_version = Some(version);
+        self
+    }
+
+    /// Only includes the variables that are used by the entry point in its interface. This is the
+    /// `exact_entrypoint_interface` option of the macro.
+    #[inline]
+    pub fn exact_entrypoint_interface(mut self, exact: bool) -> Builder {
+        self.exact_entrypoint_interface = exact;
+        self
+    }
+
+    /// Generates a single `SpecializationConstants` struct for all the shaders. This is the
+    /// `shared_constants` option of the macro.
+    #[inline]
+    pub fn shared_constants(mut self, shared: bool) -> Builder {
+        self.shared_constants = shared;
+        self
+    }
+
+    /// Sets the options that apply to the HLSL shaders.
+    #[inline]
+    pub fn hlsl_options(mut self, options: HlslOptions) -> Builder {
+        self.hlsl_options = options;
+        self
+    }
+
+    /// Sets the derives, `use` declarations and trait implementations of the generated structs,
+    /// written with the same syntax as the contents of the `types_meta` option of the macro.
+    #[inline]
+    pub fn types_meta<S>(mut self, types_meta: S) -> Builder
+    where
+        S: Into<String>,
+    {
+        self.types_meta = Some(types_meta.into());
+        self
+    }
+
+    /// Sets the directory that the paths of the shaders and of the include directories are
+    /// relative to. The default is the value of the `CARGO_MANIFEST_DIR` environment variable.
+    #[inline]
+    pub fn root<P>(mut self, root: P) -> Builder
+    where
+        P: Into<PathBuf>,
+    {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// Sets the directory that the generated files are written to. The default is the value of
+    /// the `OUT_DIR` environment variable, which Cargo sets when running build scripts.
+    #[inline]
+    pub fn out_dir<P>(mut self, out_dir: P) -> Builder
+    where
+        P: Into<PathBuf>,
+    {
+        self.out_dir = Some(out_dir.into());
+        self
+    }
+
+    /// Compiles the shaders and writes the generated module. Returns the path of the module.
+    ///
+    /// The SPIR-V code of each shader is also written to the output directory, next to the
+    /// module, with the name of the module followed by the prefix of the shader.
+    pub fn build(self) -> Result<PathBuf, BuildError> {
+        let out_dir = match self.out_dir {
+            Some(ref out_dir) => out_dir.clone(),
+            None => env::var_os("OUT_DIR")
+                .map(PathBuf::from)
+                .ok_or(BuildError::MissingOutDir)?,
+        };
+        let root = match self.root {
+            Some(ref root) => root.clone(),
+            None => env::var_os("CARGO_MANIFEST_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from(".")),
+        };
+
+        if self.shaders.is_empty() {
+            return Err(BuildError::NoShaders);
+        }
+
+        let mut prefixes = HashSet::new();
+        for shader in &self.shaders {
+            if !prefixes.insert(shader.prefix.as_str()) {
+                return Err(BuildError::DuplicatePrefix(shader.prefix.clone()));
+            }
+        }
+
+        let types_meta = match self.types_meta {
+            Some(ref types_meta) => syn::parse_str::<TypesMeta>(types_meta)
+                .map_err(|err| BuildError::InvalidTypesMeta(err.to_string()))?,
+            None => TypesMeta::default(),
+        };
+
+        let include_paths = self
+            .include_directories
+            .iter()
+            .map(|directory| root.join(directory))
+            .collect::<Vec<_>>();
+        let out_stem = Path::new(&self.out_file)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.out_file.clone());
+
+        let mut shaders_code = Vec::with_capacity(self.shaders.len());
+        let mut types_code = Vec::with_capacity(self.shaders.len());
+        let mut types_registry = HashMap::new();
+
+        for shader in &self.shaders {
+            let full_path = root.join(&shader.path);
+            println!("cargo:rerun-if-changed={}", path_to_str(&full_path));
+
+            let source_code =
+                read_file_to_string(&full_path).map_err(|error| BuildError::IoError {
+                    path: full_path.clone(),
+                    error,
+                })?;
+
+            let (content, includes) = codegen::compile(
+                Some(path_to_str(&shader.path).to_owned()),
+                &root,
+                &source_code,
+                shader.kind,
+                &include_paths,
+                &self.macro_defines,
+                self.vulkan_version,
+                self.spirv_version,
+                None,
+                shader.language,
+                &shader.entry_point,
+                &self.hlsl_options,
+            )
+            .map_err(|diagnostics| BuildError::CompileError {
+                path: full_path.clone(),
+                diagnostics,
+            })?;
+
+            for include in &includes {
+                println!("cargo:rerun-if-changed={}", include);
+            }
+
+            let spirv_path = if shader.prefix.is_empty() {
+                out_dir.join(format!("{}.spv", out_stem))
+            } else {
+                out_dir.join(format!("{}.{}.spv", out_stem, shader.prefix))
+            };
+            fs::write(&spirv_path, content.as_binary_u8()).map_err(|error| {
+                BuildError::IoError {
+                    path: spirv_path,
+                    error,
+                }
+            })?;
+
+            // Cargo already reruns the build script when a shader changes, so the generated code
+            // doesn't need to depend on the shader files.
+            let (code, types) = codegen::reflect(
+                shader.prefix.as_str(),
+                content.as_binary(),
+                &types_meta,
+                empty(),
+                self.exact_entrypoint_interface,
+                self.shared_constants,
+                &mut types_registry,
+            )
+            .map_err(|error| BuildError::ReflectError {
+                path: full_path.clone(),
+                error,
+            })?;
+
+            shaders_code.push(code);
+            types_code.push(types);
+        }
+
+        let module = codegen::write_module(&shaders_code, &types_code, &types_meta);
+        let module_path = out_dir.join(&self.out_file);
+        fs::write(&module_path, module.to_string()).map_err(|error| BuildError::IoError {
+            path: module_path.clone(),
+            error,
+        })?;
+
+        Ok(module_path)
+    }
+}
+
+/// Error that can happen when building shaders with a [`Builder`].
+#[derive(Debug)]
+pub enum BuildError {
+    /// No output directory was given, and the `OUT_DIR` environment variable isn't set.
+    MissingOutDir,
+    /// No shader was added to the builder.
+    NoShaders,
+    /// Two shaders have the same prefix, so their items would have the same names.
+    DuplicatePrefix(String),
+    /// The `types_meta` option couldn't be parsed.
+    InvalidTypesMeta(String),
+    /// Reading or writing a file failed.
+    IoError { path: PathBuf, error: IoError },
+    /// A shader failed to compile. The diagnostics of the compiler are given one per line.
+    CompileError { path: PathBuf, diagnostics: String },
+    /// The code of a compiled shader couldn't be generated.
+    ReflectError {
+        path: PathBuf,
+        error: codegen::Error,
+    },
+}
+
+impl error::Error for BuildError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            BuildError::IoError { ref error, .. } => Some(error),
+            BuildError::ReflectError { ref error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for BuildError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            BuildError::MissingOutDir => write!(
+                fmt,
+                "the `OUT_DIR` environment variable is not set; `Builder::build` must be called \
+                from a build script or be given an output directory"
+            ),
+            BuildError::NoShaders => write!(fmt, "no shader was added to the builder"),
+            BuildError::DuplicatePrefix(ref prefix) => {
+                write!(fmt, "more than one shader has the prefix {:?}", prefix)
+            }
+            BuildError::InvalidTypesMeta(ref err) => {
+                write!(fmt, "failed to parse the types meta: {}", err)
+            }
+            BuildError::IoError {
+                ref path,
+                ref error,
+            } => write!(fmt, "failed to access `{}`: {}", path.display(), error),
+            BuildError::CompileError {
+                ref path,
+                ref diagnostics,
+            } => write!(
+                fmt,
+                "failed to compile `{}`:\n{}",
+                path.display(),
+                diagnostics
+            ),
+            BuildError::ReflectError {
+                ref path,
+                ref error,
+            } => write!(
+                fmt,
+                "failed to generate the code of `{}`: {}",
+                path.display(),
+                error
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Creates an empty directory for a test to write its shaders and output to.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!(
+            "vulkano-shaders-codegen-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_build() {
+        let dir = test_dir("build");
+        fs::write(
+            dir.join("common.glsl"),
+            "struct Light { vec4 color; float intensity; };",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("shader.frag"),
+            r#"
+            #version 450
+            #include "common.glsl"
+            layout(set = 0, binding = 0) uniform Data { Light light; } data;
+            layout(location = 0) out vec4 f_color;
+            void main() { f_color = data.light.color * data.light.intensity; }
+            "#,
+        )
+        .unwrap();
+
+        let module_path = Builder::new("shaders.rs")
+            .shader(ShaderFile::new("shader.frag", ShaderKind::Fragment).prefix("Light"))
+            .root(&dir)
+            .out_dir(&dir)
+            .build()
+            .unwrap();
+
+        assert_eq!(module_path, dir.join("shaders.rs"));
+        let module = fs::read_to_string(&module_path).unwrap();
+        assert!(module.contains("pub struct LightShader"));
+        assert!(module.contains("pub mod ty"));
+        assert!(!module.contains("include_bytes"));
+
+        let spirv = fs::read(dir.join("shaders.Light.spv")).unwrap();
+        assert_eq!(spirv.len() % 4, 0);
+        assert_eq!(&spirv[..4], &0x07230203u32.to_le_bytes());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let dir = test_dir("build-errors");
+        fs::write(
+            dir.join("shader.vert"),
+            "#version 450\nvoid main() { gl_Position = undefined; }",
+        )
+        .unwrap();
+
+        let builder = || Builder::new("shaders.rs").root(&dir).out_dir(&dir);
+        let shader = || ShaderFile::new("shader.vert", ShaderKind::Vertex);
+
+        match builder().build() {
+            Err(BuildError::NoShaders) => (),
+            r => panic!("{:?}", r),
+        }
+        match builder().shader(shader()).shader(shader()).build() {
+            Err(BuildError::DuplicatePrefix(ref prefix)) if prefix.is_empty() => (),
+            r => panic!("{:?}", r),
+        }
+        match builder().shader(shader()).build() {
+            Err(BuildError::CompileError { diagnostics, .. }) => {
+                assert!(diagnostics.contains("undefined"), "{}", diagnostics)
+            }
+            r => panic!("{:?}", r),
+        }
+        match builder()
+            .shader(ShaderFile::new("missing.vert", ShaderKind::Vertex))
+            .build()
+        {
+            Err(BuildError::IoError { path, .. }) => assert_eq!(path, dir.join("missing.vert")),
+            r => panic!("{:?}", r),
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -7,6 +7,7 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use crate::descriptor_sets;
 use crate::entry_point;
 use crate::read_file_to_string;
 use crate::spec_consts;
@@ -14,9 +15,15 @@ use crate::structs;
 use crate::RegisteredType;
 use crate::TypesMeta;
 use proc_macro2::{Span, TokenStream};
-pub use shaderc::{CompilationArtifact, IncludeType, ResolvedInclude, ShaderKind};
+pub use shaderc::{
+    CompilationArtifact, IncludeType, OptimizationLevel, ResolvedInclude, ResourceKind, ShaderKind,
+    SourceLanguage,
+};
 use shaderc::{CompileOptions, Compiler, EnvVersion, SpirvVersion, TargetEnv};
+use std::cmp;
 use std::collections::HashMap;
+use std::error;
+use std::fmt;
 use std::iter::Iterator;
 use std::path::Path;
 use std::{
@@ -25,11 +32,17 @@ use std::{
 };
 use syn::Ident;
 use vulkano::{
+    pipeline::shader::{EntryPointInfo, ShaderCreationError},
     spirv::{Capability, Instruction, Spirv, SpirvError, StorageClass},
     Version,
 };
 
-pub(super) fn path_to_str(path: &Path) -> &str {
+/// Converts a path to a string, as expected by `include_bytes!` and by the shader compiler.
+///
+/// # Panics
+///
+/// - Panics if the path isn't valid unicode.
+pub fn path_to_str(path: &Path) -> &str {
     path.to_str().expect(
         "Could not stringify the file to be included. Make sure the path consists of \
                  valid unicode characters.",
@@ -45,6 +58,7 @@ fn include_callback(
     root_source_has_path: bool,
     base_path: &impl AsRef<Path>,
     mut includes_tracker: RefMut<Vec<String>>,
+    mut include_parents: RefMut<HashMap<String, String>>,
 ) -> Result<ResolvedInclude, String> {
     let file_to_include = match directive_type {
         IncludeType::Relative => {
@@ -140,6 +154,9 @@ fn include_callback(
     })?;
 
     includes_tracker.push(file_to_include_string.clone());
+    include_parents
+        .entry(file_to_include_string.clone())
+        .or_insert_with(|| contained_within_path_raw.to_owned());
 
     Ok(ResolvedInclude {
         resolved_name: file_to_include_string,
@@ -147,6 +164,22 @@ fn include_callback(
     })
 }
 
+/// Options that only apply to shaders written in HLSL.
+#[derive(Clone, Debug, Default)]
+pub struct HlslOptions {
+    /// Shifts applied to the binding numbers of each kind of register, like the
+    /// `-fvk-{b,t,s,u}-shift` options of DXC.
+    pub binding_shifts: Vec<(ResourceKind, u32)>,
+    /// Explicit `(register, set, binding)` mappings, e.g. `("t4", 1, 2)`. These take precedence
+    /// over the register-space mapping and the binding shifts.
+    pub register_bindings: Vec<(String, u32, u32)>,
+}
+
+/// Compiles a GLSL or HLSL shader to SPIR-V.
+///
+/// `path` is the path of the shader file relative to `base_path`, or `None` if the source is
+/// embedded. Returns the compiled shader and the paths of all the files it includes. On failure,
+/// returns the compiler diagnostics, one per line.
 pub fn compile(
     path: Option<String>,
     base_path: &impl AsRef<Path>,
@@ -156,9 +189,14 @@ pub fn compile(
     macro_defines: &[(impl AsRef<str>, impl AsRef<str>)],
     vulkan_version: Option<EnvVersion>,
     spirv_version: Option<SpirvVersion>,
+    optimization_level: Option<OptimizationLevel>,
+    source_language: SourceLanguage,
+    entry_point: &str,
+    hlsl_options: &HlslOptions,
 ) -> Result<(CompilationArtifact, Vec<String>), String> {
     let includes_tracker = RefCell::new(Vec::new());
-    let mut compiler = Compiler::new().ok_or("failed to create GLSL compiler")?;
+    let include_parents = RefCell::new(HashMap::new());
+    let mut compiler = Compiler::new().ok_or("failed to create shader compiler")?;
     let mut compile_options = CompileOptions::new().ok_or("failed to initialize compile option")?;
 
     compile_options.set_target_env(
@@ -170,10 +208,38 @@ pub fn compile(
         compile_options.set_target_spirv(spirv_version);
     }
 
+    if let Some(optimization_level) = optimization_level {
+        compile_options.set_optimization_level(optimization_level);
+    }
+
+    compile_options.set_source_language(source_language);
+
+    if source_language == SourceLanguage::HLSL {
+        // Map `register(xN, spaceM)` to set M and binding N, and give a binding to the resources
+        // that don't have a register.
+        compile_options.set_hlsl_io_mapping(true);
+        compile_options.set_auto_bind_uniforms(true);
+        compile_options.set_hlsl_offsets(true);
+
+        for &(kind, shift) in hlsl_options.binding_shifts.iter() {
+            compile_options.set_binding_base(kind, shift);
+        }
+
+        for (register, set, binding) in hlsl_options.register_bindings.iter() {
+            compile_options.set_hlsl_register_set_and_binding(
+                register,
+                &set.to_string(),
+                &binding.to_string(),
+            );
+        }
+    }
+
     let root_source_path = if let &Some(ref path) = &path {
         path
-    } else {
+    } else if source_language == SourceLanguage::HLSL {
         // An arbitrary placeholder file name for embedded shaders
+        "shader.hlsl"
+    } else {
         "shader.glsl"
     };
 
@@ -189,6 +255,7 @@ pub fn compile(
                 path.is_some(),
                 base_path,
                 includes_tracker.borrow_mut(),
+                include_parents.borrow_mut(),
             )
         },
     );
@@ -198,15 +265,94 @@ pub fn compile(
     }
 
     let content = compiler
-        .compile_into_spirv(&code, ty, root_source_path, "main", Some(&compile_options))
-        .map_err(|e| e.to_string())?;
+        .compile_into_spirv(
+            &code,
+            ty,
+            root_source_path,
+            entry_point,
+            Some(&compile_options),
+        )
+        .map_err(|e| match e {
+            shaderc::Error::CompilationError(_, ref diagnostics) => format_diagnostics(
+                diagnostics,
+                root_source_path,
+                path.is_none(),
+                &include_parents.borrow(),
+            ),
+            _ => e.to_string(),
+        })?;
 
     let includes = includes_tracker.borrow().clone();
 
     Ok((content, includes))
 }
 
-pub(super) fn reflect<'a, I>(
+/// Rewrites the diagnostics of shaderc so that they point to the file and line where each error
+/// is located, followed by the chain of files that included it. Returns one diagnostic per line.
+fn format_diagnostics(
+    diagnostics: &str,
+    root_source_path: &str,
+    root_is_embedded: bool,
+    include_parents: &HashMap<String, String>,
+) -> String {
+    let describe_file = |file: &str| {
+        if root_is_embedded && file == root_source_path {
+            "the embedded source".to_owned()
+        } else {
+            format!("`{}`", file)
+        }
+    };
+
+    diagnostics
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (file, line_number, message) = match split_diagnostic(line) {
+                Some(split) => split,
+                None => return line.to_owned(),
+            };
+
+            let mut formatted = format!(
+                "{} (line {} of {})",
+                message,
+                line_number,
+                describe_file(file)
+            );
+            // Bounded by the number of includes in case a file includes one of its includers.
+            let mut current = file;
+            for _ in 0..include_parents.len() {
+                let parent = match include_parents.get(current) {
+                    Some(parent) => parent.as_str(),
+                    None => break,
+                };
+                formatted += &format!(", included from {}", describe_file(parent));
+                current = parent;
+            }
+
+            formatted
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Splits a diagnostic of the form `file:line: message`. The file name may itself contain
+/// colons, for example on Windows.
+fn split_diagnostic(diagnostic: &str) -> Option<(&str, u32, &str)> {
+    diagnostic.match_indices(':').find_map(|(index, _)| {
+        let (file, rest) = (&diagnostic[..index], &diagnostic[index + 1..]);
+        let end = rest.find(':')?;
+        let line_number = rest[..end].parse().ok()?;
+        Some((file, line_number, rest[end + 1..].trim_start()))
+    })
+}
+
+/// Generates the Rust code for a SPIR-V shader.
+///
+/// Returns the shader module code, and the structs that belong in the `ty` module. `prefix` is
+/// prepended to the names of the generated items, and `input_paths` are the files that the
+/// generated code depends on, so that it is rebuilt when they change. Structs that were already
+/// generated for another shader in `types_registry` are not generated again.
+pub fn reflect<'a, I>(
     prefix: &'a str,
     words: &[u32],
     types_meta: &TypesMeta,
@@ -221,31 +367,22 @@ where
     let struct_name = Ident::new(&format!("{}Shader", prefix), Span::call_site());
     let spirv = Spirv::new(words)?;
 
-    // checking whether each required capability is enabled in the Vulkan device
-    let mut cap_checks: Vec<TokenStream> = vec![];
+    // Collecting the requirements of the shader on the Vulkan device. Each requirement is a list
+    // of alternatives, any one of which is enough.
+    let mut requirements: Vec<&'static [DeviceRequirement]> = Vec::new();
     match spirv.version() {
         Version::V1_0 => {}
         Version::V1_1 | Version::V1_2 | Version::V1_3 => {
-            cap_checks.push(quote! {
-                if device.api_version() < Version::V1_1 {
-                    panic!("Device API version 1.1 required");
-                }
-            });
+            requirements.push(&[DeviceRequirement::Version(1, 1)]);
         }
         Version::V1_4 => {
-            cap_checks.push(quote! {
-                if device.api_version() < Version::V1_2
-                    && !device.enabled_extensions().khr_spirv_1_4 {
-                    panic!("Device API version 1.2 or extension VK_KHR_spirv_1_4 required");
-                }
-            });
+            requirements.push(&[
+                DeviceRequirement::Version(1, 2),
+                DeviceRequirement::Extension("khr_spirv_1_4"),
+            ]);
         }
         Version::V1_5 => {
-            cap_checks.push(quote! {
-                if device.api_version() < Version::V1_2 {
-                    panic!("Device API version 1.2 required");
-                }
-            });
+            requirements.push(&[DeviceRequirement::Version(1, 2)]);
         }
         _ => return Err(Error::UnsupportedSpirvVersion),
     }
@@ -258,72 +395,104 @@ where
                     result_id: _,
                     storage_class,
                     initializer: _,
-                } => storage_class_requirement(storage_class),
+                } => storage_class_requirement(storage_class)?,
                 Instruction::TypePointer {
                     result_id: _,
                     storage_class,
                     ty: _,
-                } => storage_class_requirement(storage_class),
-                Instruction::Capability { capability } => capability_requirement(capability),
+                } => storage_class_requirement(storage_class)?,
+                Instruction::Capability { capability } => capability_requirement(capability)?,
                 _ => &[],
             }
         };
 
-        if dev_req.len() == 0 {
+        if dev_req.len() == 0 || requirements.contains(&dev_req) {
             continue;
         }
 
-        let (conditions, messages): (Vec<_>, Vec<_>) = dev_req
-            .iter()
-            .map(|req| match req {
-                DeviceRequirement::Extension(extension) => {
-                    let ident = Ident::new(extension, Span::call_site());
-                    (
-                        quote! { device.enabled_extensions().#ident },
-                        format!("extension {}", extension),
-                    )
-                }
-                DeviceRequirement::Feature(feature) => {
-                    let ident = Ident::new(feature, Span::call_site());
-                    (
-                        quote! { device.enabled_features().#ident },
-                        format!("feature {}", feature),
-                    )
-                }
-                DeviceRequirement::Version(major, minor) => {
-                    let ident = format_ident!("V{}_{}", major, minor);
-                    (
-                        quote! { device.api_version() >= crate::Version::#ident },
-                        format!("API version {}.{}", major, minor),
-                    )
-                }
-            })
-            .unzip();
-        let messages = messages.join(", ");
+        requirements.push(dev_req);
+    }
+
+    let requirement_values = requirements.iter().map(|alternatives| {
+        let values = alternatives.iter().map(|req| match req {
+            DeviceRequirement::Extension(extension) => quote! {
+                ::vulkano::pipeline::shader::ShaderRequirement::DeviceExtension(#extension)
+            },
+            DeviceRequirement::Feature(feature) => quote! {
+                ::vulkano::pipeline::shader::ShaderRequirement::Feature(#feature)
+            },
+            DeviceRequirement::Version(major, minor) => quote! {
+                ::vulkano::pipeline::shader::ShaderRequirement::ApiVersion(
+                    ::vulkano::Version::major_minor(#major, #minor)
+                )
+            },
+        });
 
-        cap_checks.push(quote! {
-            if !std::array::IntoIter::new([#(#conditions),*]).all(|x| x) {
-                panic!("One of the following must be enabled on the device: {}", #messages);
+        quote! { &[ #( #values ),* ] }
+    });
+    let requirement_checks = requirements.iter().map(|alternatives| {
+        let conditions = alternatives.iter().map(|req| match req {
+            DeviceRequirement::Extension(extension) => {
+                let ident = Ident::new(extension, Span::call_site());
+                quote! { device.enabled_extensions().#ident }
+            }
+            DeviceRequirement::Feature(feature) => {
+                let ident = Ident::new(feature, Span::call_site());
+                quote! { device.enabled_features().#ident }
             }
+            DeviceRequirement::Version(major, minor) => quote! {
+                device.api_version() >= ::vulkano::Version::major_minor(#major, #minor)
+            },
         });
+
+        quote! { #( #conditions )||* }
+    });
+    let requirements_len = requirements.len();
+
+    // The requirements returned by `Shader::requirements`, made of the first alternative of each
+    // requirement.
+    let mut required_version = (1, 0);
+    let mut required_extensions = Vec::new();
+    let mut required_features = Vec::new();
+    for alternatives in requirements.iter() {
+        match alternatives[0] {
+            DeviceRequirement::Extension(extension) => {
+                if !required_extensions.contains(&extension) {
+                    required_extensions.push(extension);
+                }
+            }
+            DeviceRequirement::Feature(feature) => {
+                if !required_features.contains(&feature) {
+                    required_features.push(feature);
+                }
+            }
+            DeviceRequirement::Version(major, minor) => {
+                required_version = cmp::max(required_version, (major, minor));
+            }
+        }
     }
+    let (required_major, required_minor) = required_version;
+    let required_extensions = required_extensions
+        .into_iter()
+        .map(|extension| Ident::new(extension, Span::call_site()));
+    let required_features = required_features
+        .into_iter()
+        .map(|feature| Ident::new(feature, Span::call_site()));
+
+    // Without `exact_entrypoint_interface`, every entry point has all the descriptors of the
+    // module, from which the descriptor set builders are generated.
+    let module_entry_points = EntryPointInfo::from_spirv(words, false)?;
+    let entry_points = if exact_entrypoint_interface {
+        EntryPointInfo::from_spirv(words, true)?
+    } else {
+        module_entry_points.clone()
+    };
 
     // writing one method for each entry point of this module
-    let mut entry_points_inside_impl: Vec<TokenStream> = vec![];
-    for instruction in spirv
-        .iter_entry_point()
-        .filter(|instruction| matches!(instruction, Instruction::EntryPoint { .. }))
-    {
-        let entry_point = entry_point::write_entry_point(
-            prefix,
-            &spirv,
-            instruction,
-            types_meta,
-            exact_entrypoint_interface,
-            shared_constants,
-        );
-        entry_points_inside_impl.push(entry_point);
-    }
+    let entry_points_inside_impl: Vec<TokenStream> = entry_points
+        .iter()
+        .map(|info| entry_point::write_entry_point(&spirv, info))
+        .collect();
 
     let include_bytes = input_paths.map(|s| {
         quote! {
@@ -333,14 +502,31 @@ where
         }
     });
 
-    let structs = structs::write_structs(prefix, &spirv, types_meta, types_registry);
+    let structs = structs::write_structs(prefix, &spirv, types_meta, types_registry)?;
     let specialization_constants = spec_consts::write_specialization_constants(
         prefix,
         &spirv,
         types_meta,
         shared_constants,
         types_registry,
+    )?;
+    let descriptor_sets = descriptor_sets::write_descriptor_set_builders(
+        prefix,
+        module_entry_points
+            .first()
+            .map_or(&[][..], |info| info.descriptor_set_layout_descs()),
     );
+    let push_constants = match structs::push_constants_struct_name(&spirv) {
+        Some(name) => {
+            let alias = Ident::new(&format!("{}PushConstants", prefix), Span::call_site());
+            let name = Ident::new(&name, Span::call_site());
+            quote! {
+                /// The push constants of the shader, to pass to `push_constants_typed`.
+                pub type #alias = ty::#name;
+            }
+        }
+        None => quote! {},
+    };
     let shader_code = quote! {
         pub struct #struct_name {
             shader: ::std::sync::Arc<::vulkano::pipeline::shader::ShaderModule>,
@@ -348,14 +534,31 @@ where
 
         impl #struct_name {
             /// Loads the shader in Vulkan as a `ShaderModule`.
+            ///
+            /// Returns an error listing the requirements that aren't met if the device doesn't
+            /// have the API version, extensions or features needed by the shader.
             #[inline]
             #[allow(unsafe_code)]
             pub fn load(device: ::std::sync::Arc<::vulkano::device::Device>)
-                        -> Result<#struct_name, ::vulkano::OomError>
+                        -> Result<#struct_name, ::vulkano::pipeline::shader::ShaderLoadError>
             {
                 let _bytes = ( #( #include_bytes),* );
 
-                #( #cap_checks )*
+                static REQUIREMENTS: &[&[::vulkano::pipeline::shader::ShaderRequirement]] =
+                    &[ #( #requirement_values ),* ];
+                let requirements_met: [bool; #requirements_len] = [ #( #requirement_checks ),* ];
+                let missing: ::std::vec::Vec<_> = REQUIREMENTS
+                    .iter()
+                    .zip(requirements_met.iter())
+                    .filter_map(|(&requirement, &met)| if met { None } else { Some(requirement) })
+                    .collect();
+
+                if !missing.is_empty() {
+                    return Err(
+                        ::vulkano::pipeline::shader::ShaderLoadError::RequirementsNotMet(missing)
+                    );
+                }
+
                 static WORDS: &[u32] = &[ #( #words ),* ];
 
                 unsafe {
@@ -365,6 +568,26 @@ where
                 }
             }
 
+            /// Returns the API version, extensions and features that the device must have to
+            /// load the shader.
+            ///
+            /// When a requirement can be met in several ways, only the first one is included.
+            #[allow(dead_code)]
+            #[inline]
+            pub fn requirements() -> ::vulkano::pipeline::shader::ShaderRequirements {
+                ::vulkano::pipeline::shader::ShaderRequirements {
+                    api_version: ::vulkano::Version::major_minor(#required_major, #required_minor),
+                    device_extensions: ::vulkano::device::DeviceExtensions {
+                        #( #required_extensions: true, )*
+                        ..::vulkano::device::DeviceExtensions::none()
+                    },
+                    features: ::vulkano::device::Features {
+                        #( #required_features: true, )*
+                        ..::vulkano::device::Features::none()
+                    },
+                }
+            }
+
             /// Returns the module that was created.
             #[allow(dead_code)]
             #[inline]
@@ -376,16 +599,113 @@ where
         }
 
         #specialization_constants
+
+        #descriptor_sets
+
+        #push_constants
     };
 
     Ok((shader_code, structs))
 }
 
+/// Generates the module that contains the code of one or more shaders returned by `reflect`.
+///
+/// This is the code generated by an invocation of the `shader!` macro.
+pub fn write_module(
+    shaders_code: &[TokenStream],
+    types_code: &[TokenStream],
+    types_meta: &TypesMeta,
+) -> TokenStream {
+    let uses = &types_meta.uses;
+
+    quote! {
+        #[allow(unused_imports)]
+        use std::sync::Arc;
+        #[allow(unused_imports)]
+        use std::vec::IntoIter as VecIntoIter;
+
+        #[allow(unused_imports)]
+        use vulkano::device::Device;
+        #[allow(unused_imports)]
+        use vulkano::descriptor_set::layout::DescriptorDesc;
+        #[allow(unused_imports)]
+        use vulkano::descriptor_set::layout::DescriptorDescTy;
+        #[allow(unused_imports)]
+        use vulkano::descriptor_set::layout::DescriptorDescImage;
+        #[allow(unused_imports)]
+        use vulkano::descriptor_set::layout::DescriptorSetDesc;
+        #[allow(unused_imports)]
+        use vulkano::descriptor_set::layout::DescriptorSetLayout;
+        #[allow(unused_imports)]
+        use vulkano::descriptor_set::DescriptorSet;
+        #[allow(unused_imports)]
+        use vulkano::format::Format;
+        #[allow(unused_imports)]
+        use vulkano::image::view::ImageViewType;
+        #[allow(unused_imports)]
+        use vulkano::pipeline::layout::PipelineLayout;
+        #[allow(unused_imports)]
+        use vulkano::pipeline::layout::PipelineLayoutPcRange;
+        #[allow(unused_imports)]
+        use vulkano::pipeline::shader::ShaderStages;
+        #[allow(unused_imports)]
+        use vulkano::pipeline::shader::SpecializationConstantRequirements;
+        #[allow(unused_imports)]
+        use vulkano::pipeline::shader::SpecializationConstantType;
+        #[allow(unused_imports)]
+        use vulkano::pipeline::shader::SpecializationConstants as SpecConstsTrait;
+        #[allow(unused_imports)]
+        use vulkano::pipeline::shader::SpecializationMapEntry;
+        #[allow(unused_imports)]
+        use vulkano::Version;
+
+        #(
+            #shaders_code
+        )*
+
+        pub mod ty {
+            #( #uses )*
+
+            #(
+                #types_code
+            )*
+        }
+    }
+}
+
+/// Error that can happen when generating the code of a shader.
 #[derive(Debug)]
 pub enum Error {
     UnsupportedSpirvVersion,
     IoError(IoError),
     SpirvError(SpirvError),
+    /// The shader uses something that can't be represented in the generated code.
+    Reflection(String),
+}
+
+impl error::Error for Error {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::IoError(ref err) => Some(err),
+            Error::SpirvError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            Error::UnsupportedSpirvVersion => {
+                write!(fmt, "the SPIR-V version of the shader is not supported")
+            }
+            Error::IoError(ref err) => write!(fmt, "{}", err),
+            Error::SpirvError(ref err) => write!(fmt, "failed to parse the SPIR-V code: {}", err),
+            Error::Reflection(ref msg) => write!(fmt, "{}", msg),
+        }
+    }
 }
 
 impl From<IoError> for Error {
@@ -402,10 +722,21 @@ impl From<SpirvError> for Error {
     }
 }
 
+impl From<ShaderCreationError> for Error {
+    #[inline]
+    fn from(err: ShaderCreationError) -> Error {
+        match err {
+            ShaderCreationError::SpirvError(err) => Error::SpirvError(err),
+            ShaderCreationError::ReflectionError(msg) => Error::Reflection(msg),
+            err @ ShaderCreationError::OomError(_) => Error::Reflection(err.to_string()),
+        }
+    }
+}
+
 /// Returns the Vulkan device requirement for a SPIR-V `OpCapability`.
 #[rustfmt::skip]
-fn capability_requirement(cap: &Capability) -> &'static [DeviceRequirement] {
-    match *cap {
+fn capability_requirement(cap: &Capability) -> Result<&'static [DeviceRequirement], Error> {
+    Ok(match *cap {
         Capability::Matrix => &[],
         Capability::Shader => &[],
         Capability::InputAttachment => &[],
@@ -551,15 +882,15 @@ fn capability_requirement(cap: &Capability) -> &'static [DeviceRequirement] {
             &[DeviceRequirement::Feature("storage_push_constant16")]
         }
         Capability::StorageInputOutput16 => &[DeviceRequirement::Feature("storage_input_output16")],
-        Capability::GroupNonUniform => todo!(),
-        Capability::GroupNonUniformVote => todo!(),
-        Capability::GroupNonUniformArithmetic => todo!(),
-        Capability::GroupNonUniformBallot => todo!(),
-        Capability::GroupNonUniformShuffle => todo!(),
-        Capability::GroupNonUniformShuffleRelative => todo!(),
-        Capability::GroupNonUniformClustered => todo!(),
-        Capability::GroupNonUniformQuad => todo!(),
-        Capability::GroupNonUniformPartitionedNV => todo!(),
+        Capability::GroupNonUniform => return Err(unsupported_capability(cap)),
+        Capability::GroupNonUniformVote => return Err(unsupported_capability(cap)),
+        Capability::GroupNonUniformArithmetic => return Err(unsupported_capability(cap)),
+        Capability::GroupNonUniformBallot => return Err(unsupported_capability(cap)),
+        Capability::GroupNonUniformShuffle => return Err(unsupported_capability(cap)),
+        Capability::GroupNonUniformShuffleRelative => return Err(unsupported_capability(cap)),
+        Capability::GroupNonUniformClustered => return Err(unsupported_capability(cap)),
+        Capability::GroupNonUniformQuad => return Err(unsupported_capability(cap)),
+        Capability::GroupNonUniformPartitionedNV => return Err(unsupported_capability(cap)),
         Capability::SampleMaskPostDepthCoverage => {
             &[DeviceRequirement::Extension("ext_post_depth_coverage")]
         }
@@ -616,11 +947,11 @@ fn capability_requirement(cap: &Capability) -> &'static [DeviceRequirement] {
         Capability::VulkanMemoryModelDeviceScope => &[DeviceRequirement::Feature(
             "vulkan_memory_model_device_scope",
         )],
-        Capability::DenormPreserve => todo!(),
-        Capability::DenormFlushToZero => todo!(),
-        Capability::SignedZeroInfNanPreserve => todo!(),
-        Capability::RoundingModeRTE => todo!(),
-        Capability::RoundingModeRTZ => todo!(),
+        Capability::DenormPreserve => return Err(unsupported_capability(cap)),
+        Capability::DenormFlushToZero => return Err(unsupported_capability(cap)),
+        Capability::SignedZeroInfNanPreserve => return Err(unsupported_capability(cap)),
+        Capability::RoundingModeRTE => return Err(unsupported_capability(cap)),
+        Capability::RoundingModeRTZ => return Err(unsupported_capability(cap)),
         Capability::ComputeDerivativeGroupQuadsNV => {
             &[DeviceRequirement::Feature("compute_derivative_group_quads")]
         }
@@ -676,51 +1007,53 @@ fn capability_requirement(cap: &Capability) -> &'static [DeviceRequirement] {
         // Capability::WorkgroupMemoryExplicitLayoutKHR => &[DeviceRequirement::Feature("workgroup_memory_explicit_layout")],
         // Capability::WorkgroupMemoryExplicitLayout8BitAccessKHR => &[DeviceRequirement::Feature("workgroup_memory_explicit_layout8_bit_access")],
         // Capability::WorkgroupMemoryExplicitLayout16BitAccessKHR => &[DeviceRequirement::Feature("workgroup_memory_explicit_layout16_bit_access")],
-        Capability::Addresses => panic!(),        // not supported
-        Capability::Linkage => panic!(),          // not supported
-        Capability::Kernel => panic!(),           // not supported
-        Capability::Vector16 => panic!(),         // not supported
-        Capability::Float16Buffer => panic!(),    // not supported
-        Capability::ImageBasic => panic!(),       // not supported
-        Capability::ImageReadWrite => panic!(),   // not supported
-        Capability::ImageMipmap => panic!(),      // not supported
-        Capability::Pipes => panic!(),            // not supported
-        Capability::Groups => panic!(),           // not supported
-        Capability::DeviceEnqueue => panic!(),    // not supported
-        Capability::LiteralSampler => panic!(),   // not supported
-        Capability::AtomicStorage => panic!(),    // not supported
-        Capability::ImageRect => panic!(),        // not supported
-        Capability::SampledRect => panic!(),      // not supported
-        Capability::GenericPointer => panic!(),   // not supported
-        Capability::SubgroupDispatch => panic!(), // not supported
-        Capability::NamedBarrier => panic!(),     // not supported
-        Capability::PipeStorage => panic!(),      // not supported
-        Capability::AtomicStorageOps => panic!(), // not supported
-        Capability::Float16ImageAMD => panic!(),  // not supported
-        Capability::ShaderStereoViewNV => panic!(), // not supported
-        Capability::FragmentFullyCoveredEXT => panic!(), // not supported
-        Capability::SubgroupShuffleINTEL => panic!(), // not supported
-        Capability::SubgroupBufferBlockIOINTEL => panic!(), // not supported
-        Capability::SubgroupImageBlockIOINTEL => panic!(), // not supported
-        Capability::SubgroupImageMediaBlockIOINTEL => panic!(), // not supported
-        Capability::SubgroupAvcMotionEstimationINTEL => panic!(), // not supported
-        Capability::SubgroupAvcMotionEstimationIntraINTEL => panic!(), // not supported
-        Capability::SubgroupAvcMotionEstimationChromaINTEL => panic!(), // not supported
-        Capability::FunctionPointersINTEL => panic!(), // not supported
-        Capability::IndirectReferencesINTEL => panic!(), // not supported
-        Capability::FPGAKernelAttributesINTEL => panic!(), // not supported
-        Capability::FPGALoopControlsINTEL => panic!(), // not supported
-        Capability::FPGAMemoryAttributesINTEL => panic!(), // not supported
-        Capability::FPGARegINTEL => panic!(), // not supported
-        Capability::UnstructuredLoopControlsINTEL => panic!(), // not supported
-        Capability::KernelAttributesINTEL => panic!(), // not supported
-        Capability::BlockingPipesINTEL => panic!(), // not supported
-    }
+        Capability::Addresses => return Err(unsupported_capability(cap)),
+        Capability::Linkage => return Err(unsupported_capability(cap)),
+        Capability::Kernel => return Err(unsupported_capability(cap)),
+        Capability::Vector16 => return Err(unsupported_capability(cap)),
+        Capability::Float16Buffer => return Err(unsupported_capability(cap)),
+        Capability::ImageBasic => return Err(unsupported_capability(cap)),
+        Capability::ImageReadWrite => return Err(unsupported_capability(cap)),
+        Capability::ImageMipmap => return Err(unsupported_capability(cap)),
+        Capability::Pipes => return Err(unsupported_capability(cap)),
+        Capability::Groups => return Err(unsupported_capability(cap)),
+        Capability::DeviceEnqueue => return Err(unsupported_capability(cap)),
+        Capability::LiteralSampler => return Err(unsupported_capability(cap)),
+        Capability::AtomicStorage => return Err(unsupported_capability(cap)),
+        Capability::ImageRect => return Err(unsupported_capability(cap)),
+        Capability::SampledRect => return Err(unsupported_capability(cap)),
+        Capability::GenericPointer => return Err(unsupported_capability(cap)),
+        Capability::SubgroupDispatch => return Err(unsupported_capability(cap)),
+        Capability::NamedBarrier => return Err(unsupported_capability(cap)),
+        Capability::PipeStorage => return Err(unsupported_capability(cap)),
+        Capability::AtomicStorageOps => return Err(unsupported_capability(cap)),
+        Capability::Float16ImageAMD => return Err(unsupported_capability(cap)),
+        Capability::ShaderStereoViewNV => return Err(unsupported_capability(cap)),
+        Capability::FragmentFullyCoveredEXT => return Err(unsupported_capability(cap)),
+        Capability::SubgroupShuffleINTEL => return Err(unsupported_capability(cap)),
+        Capability::SubgroupBufferBlockIOINTEL => return Err(unsupported_capability(cap)),
+        Capability::SubgroupImageBlockIOINTEL => return Err(unsupported_capability(cap)),
+        Capability::SubgroupImageMediaBlockIOINTEL => return Err(unsupported_capability(cap)),
+        Capability::SubgroupAvcMotionEstimationINTEL => return Err(unsupported_capability(cap)),
+        Capability::SubgroupAvcMotionEstimationIntraINTEL => return Err(unsupported_capability(cap)),
+        Capability::SubgroupAvcMotionEstimationChromaINTEL => return Err(unsupported_capability(cap)),
+        Capability::FunctionPointersINTEL => return Err(unsupported_capability(cap)),
+        Capability::IndirectReferencesINTEL => return Err(unsupported_capability(cap)),
+        Capability::FPGAKernelAttributesINTEL => return Err(unsupported_capability(cap)),
+        Capability::FPGALoopControlsINTEL => return Err(unsupported_capability(cap)),
+        Capability::FPGAMemoryAttributesINTEL => return Err(unsupported_capability(cap)),
+        Capability::FPGARegINTEL => return Err(unsupported_capability(cap)),
+        Capability::UnstructuredLoopControlsINTEL => return Err(unsupported_capability(cap)),
+        Capability::KernelAttributesINTEL => return Err(unsupported_capability(cap)),
+        Capability::BlockingPipesINTEL => return Err(unsupported_capability(cap)),
+    })
 }
 
 /// Returns the Vulkan device requirement for a SPIR-V storage class.
-fn storage_class_requirement(storage_class: &StorageClass) -> &'static [DeviceRequirement] {
-    match *storage_class {
+fn storage_class_requirement(
+    storage_class: &StorageClass,
+) -> Result<&'static [DeviceRequirement], Error> {
+    Ok(match *storage_class {
         StorageClass::UniformConstant => &[],
         StorageClass::Input => &[],
         StorageClass::Uniform => &[],
@@ -736,17 +1069,37 @@ fn storage_class_requirement(storage_class: &StorageClass) -> &'static [DeviceRe
         StorageClass::StorageBuffer => &[DeviceRequirement::Extension(
             "khr_storage_buffer_storage_class",
         )],
-        StorageClass::CallableDataKHR => todo!(),
-        StorageClass::IncomingCallableDataKHR => todo!(),
-        StorageClass::RayPayloadKHR => todo!(),
-        StorageClass::HitAttributeKHR => todo!(),
-        StorageClass::IncomingRayPayloadKHR => todo!(),
-        StorageClass::ShaderRecordBufferKHR => todo!(),
-        StorageClass::PhysicalStorageBuffer => todo!(),
-        StorageClass::CodeSectionINTEL => todo!(),
-    }
+        StorageClass::CallableDataKHR => return Err(unsupported_storage_class(storage_class)),
+        StorageClass::IncomingCallableDataKHR => {
+            return Err(unsupported_storage_class(storage_class))
+        }
+        StorageClass::RayPayloadKHR => return Err(unsupported_storage_class(storage_class)),
+        StorageClass::HitAttributeKHR => return Err(unsupported_storage_class(storage_class)),
+        StorageClass::IncomingRayPayloadKHR => {
+            return Err(unsupported_storage_class(storage_class))
+        }
+        StorageClass::ShaderRecordBufferKHR => {
+            return Err(unsupported_storage_class(storage_class))
+        }
+        StorageClass::PhysicalStorageBuffer => {
+            return Err(unsupported_storage_class(storage_class))
+        }
+        StorageClass::CodeSectionINTEL => return Err(unsupported_storage_class(storage_class)),
+    })
+}
+
+fn unsupported_capability(cap: &Capability) -> Error {
+    Error::Reflection(format!("the `{:?}` capability is not supported", cap))
+}
+
+fn unsupported_storage_class(storage_class: &StorageClass) -> Error {
+    Error::Reflection(format!(
+        "the `{:?}` storage class is not supported",
+        storage_class
+    ))
 }
 
+#[derive(Clone, Copy, PartialEq)]
 enum DeviceRequirement {
     Feature(&'static str),
     Extension(&'static str),
@@ -776,13 +1129,12 @@ mod tests {
     }
 
     #[test]
-    fn test_bad_alignment() {
+    fn test_padded_array_stride() {
         // vec3/mat3/mat3x* are problematic in arrays since their rust
         // representations don't have the same array stride as the SPIR-V
         // ones. E.g. in a vec3[2], the second element starts on the 16th
         // byte, but in a rust [[f32;3];2], the second element starts on the
-        // 12th byte. Since we can't generate code for these types, we should
-        // create an error instead of generating incorrect code.
+        // 12th byte. The elements must be wrapped in a padded struct.
         let includes: [PathBuf; 0] = [];
         let defines: [(String, String); 0] = [];
         let (comp, _) = compile(
@@ -803,13 +1155,27 @@ mod tests {
             &defines,
             None,
             None,
+            None,
+            SourceLanguage::GLSL,
+            "main",
+            &HlslOptions::default(),
         )
         .unwrap();
         let spirv = Spirv::new(comp.as_binary()).unwrap();
-        let res = std::panic::catch_unwind(|| {
+        let structs =
             structs::write_structs("", &spirv, &TypesMeta::default(), &mut HashMap::new())
-        });
-        assert!(res.is_err());
+                .unwrap()
+                .to_string();
+        assert!(structs.contains(
+            &quote! {
+                pub struct f32x3_stride16 {
+                    pub value: [f32; 3usize],
+                    pub _dummy0: [u8; 4usize],
+                }
+            }
+            .to_string()
+        ));
+        assert!(structs.contains(&quote! { pub vs: [f32x3_stride16; 2usize], }.to_string()));
     }
     #[test]
     fn test_trivial_alignment() {
@@ -833,10 +1199,14 @@ mod tests {
             &defines,
             None,
             None,
+            None,
+            SourceLanguage::GLSL,
+            "main",
+            &HlslOptions::default(),
         )
         .unwrap();
         let spirv = Spirv::new(comp.as_binary()).unwrap();
-        structs::write_structs("", &spirv, &TypesMeta::default(), &mut HashMap::new());
+        structs::write_structs("", &spirv, &TypesMeta::default(), &mut HashMap::new()).unwrap();
     }
     #[test]
     fn test_wrap_alignment() {
@@ -865,10 +1235,241 @@ mod tests {
             &defines,
             None,
             None,
+            None,
+            SourceLanguage::GLSL,
+            "main",
+            &HlslOptions::default(),
         )
         .unwrap();
         let spirv = Spirv::new(comp.as_binary()).unwrap();
-        structs::write_structs("", &spirv, &TypesMeta::default(), &mut HashMap::new());
+        structs::write_structs("", &spirv, &TypesMeta::default(), &mut HashMap::new()).unwrap();
+    }
+
+    #[test]
+    fn test_matrix_stride() {
+        let includes: [PathBuf; 0] = [];
+        let defines: [(String, String); 0] = [];
+        let (comp, _) = compile(
+            None,
+            &Path::new(""),
+            "
+        #version 450
+        layout(binding=0) uniform UBO {
+            mat3 a;
+            layout(row_major) mat2x3 b;
+            mat4 c;
+        };
+        void main() {}
+        ",
+            ShaderKind::Vertex,
+            &includes,
+            &defines,
+            None,
+            None,
+            None,
+            SourceLanguage::GLSL,
+            "main",
+            &HlslOptions::default(),
+        )
+        .unwrap();
+        let spirv = Spirv::new(comp.as_binary()).unwrap();
+        let structs =
+            structs::write_structs("", &spirv, &TypesMeta::default(), &mut HashMap::new())
+                .unwrap()
+                .to_string();
+        assert!(structs.contains(&quote! { pub a: [[f32; 4usize]; 3usize], }.to_string()));
+        assert!(structs.contains(&quote! { pub b: [[f32; 4usize]; 3usize], }.to_string()));
+        assert!(structs.contains(&quote! { pub c: [[f32; 4usize]; 4usize], }.to_string()));
+        assert!(structs.contains(&quote! { let _: [(); 48usize] = [(); OFFSET_1]; }.to_string()));
+        assert!(structs.contains(&quote! { let _: [(); 96usize] = [(); OFFSET_2]; }.to_string()));
+        assert!(structs.contains(
+            &quote! { let _: [(); 160usize] = [(); ::std::mem::size_of::<UBO>()]; }.to_string()
+        ));
+    }
+
+    #[test]
+    fn test_scalar_block_layout() {
+        let includes: [PathBuf; 0] = [];
+        let defines: [(String, String); 0] = [];
+        let (comp, _) = compile(
+            None,
+            &Path::new(""),
+            "
+        #version 450
+        #extension GL_EXT_scalar_block_layout : require
+        layout(scalar, binding=0) buffer SSBO {
+            vec3 a;
+            float b;
+            vec3 c[2];
+        };
+        void main() {}
+        ",
+            ShaderKind::Vertex,
+            &includes,
+            &defines,
+            None,
+            None,
+            None,
+            SourceLanguage::GLSL,
+            "main",
+            &HlslOptions::default(),
+        )
+        .unwrap();
+        let spirv = Spirv::new(comp.as_binary()).unwrap();
+        let structs =
+            structs::write_structs("", &spirv, &TypesMeta::default(), &mut HashMap::new())
+                .unwrap()
+                .to_string();
+        assert!(structs.contains(&quote! { pub c: [[f32; 3usize]; 2usize], }.to_string()));
+        assert!(!structs.contains("_dummy"));
+        assert!(structs.contains(
+            &quote! { let _: [(); 40usize] = [(); ::std::mem::size_of::<SSBO>()]; }.to_string()
+        ));
+    }
+
+    #[test]
+    fn test_requirements() {
+        let includes: [PathBuf; 0] = [];
+        let defines: [(String, String); 0] = [];
+        let (comp, _) = compile(
+            None,
+            &Path::new(""),
+            "
+        #version 450
+        #extension GL_ARB_gpu_shader_int64 : require
+        layout(binding=0) buffer Data {
+            int64_t a;
+            int64_t b;
+        };
+        void main() {
+            a += b;
+        }
+        ",
+            ShaderKind::Compute,
+            &includes,
+            &defines,
+            None,
+            None,
+            None,
+            SourceLanguage::GLSL,
+            "main",
+            &HlslOptions::default(),
+        )
+        .unwrap();
+        let (shader_code, _) = reflect(
+            "",
+            comp.as_binary(),
+            &TypesMeta::default(),
+            std::iter::empty(),
+            false,
+            false,
+            &mut HashMap::new(),
+        )
+        .unwrap();
+        let shader_code = shader_code.to_string();
+        assert!(shader_code.contains(
+            &quote! { ::vulkano::pipeline::shader::ShaderRequirement::Feature("shader_int64") }
+                .to_string()
+        ));
+        assert!(shader_code.contains(&quote! { shader_int64: true, }.to_string()));
+        assert_eq!(shader_code.matches("\"shader_int64\"").count(), 1);
+    }
+
+    #[test]
+    fn test_struct_signature_mismatch() {
+        let includes: [PathBuf; 0] = [];
+        let defines: [(String, String); 0] = [];
+        let mut types_registry = HashMap::new();
+
+        for (prefix, member) in [("First", "uint a;"), ("Second", "float a;")].iter() {
+            let (comp, _) = compile(
+                None,
+                &Path::new(""),
+                &format!(
+                    "
+        #version 450
+        layout(binding=0) buffer Data {{
+            {}
+        }};
+        void main() {{}}
+        ",
+                    member
+                ),
+                ShaderKind::Compute,
+                &includes,
+                &defines,
+                None,
+                None,
+                None,
+                SourceLanguage::GLSL,
+                "main",
+                &HlslOptions::default(),
+            )
+            .unwrap();
+            let result = reflect(
+                prefix,
+                comp.as_binary(),
+                &TypesMeta::default(),
+                std::iter::empty(),
+                false,
+                false,
+                &mut types_registry,
+            );
+
+            if *prefix == "First" {
+                result.unwrap();
+            } else {
+                match result {
+                    Err(Error::Reflection(message)) => {
+                        assert!(message.contains("declare structs with the same name \"Data\""))
+                    }
+                    _ => panic!("the mismatched structs were not reported"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let includes: [PathBuf; 0] = [];
+        let defines: [(String, String); 0] = [];
+        let err = compile(
+            None,
+            &Path::new(""),
+            "
+        #version 450
+        void main() {
+            undeclared = 1;
+        }
+        ",
+            ShaderKind::Vertex,
+            &includes,
+            &defines,
+            None,
+            None,
+            None,
+            SourceLanguage::GLSL,
+            "main",
+            &HlslOptions::default(),
+        )
+        .err()
+        .unwrap();
+        assert!(err.contains("undeclared"));
+        assert!(err.contains("(line 4 of the embedded source)"));
+
+        let mut include_parents = HashMap::new();
+        include_parents.insert("b.glsl".to_owned(), "a.glsl".to_owned());
+        include_parents.insert("a.glsl".to_owned(), "shader.glsl".to_owned());
+        assert_eq!(
+            format_diagnostics(
+                "b.glsl:4: error: 'x' : undeclared identifier\nC:\\c.glsl:1: error: other\n",
+                "shader.glsl",
+                true,
+                &include_parents,
+            ),
+            "error: 'x' : undeclared identifier (line 4 of `b.glsl`), included from `a.glsl`, \
+            included from the embedded source\nerror: other (line 1 of `C:\\c.glsl`)",
+        );
     }
 
     #[test]
@@ -890,6 +1491,10 @@ mod tests {
             &defines,
             None,
             None,
+            None,
+            SourceLanguage::GLSL,
+            "main",
+            &HlslOptions::default(),
         )
         .expect("Cannot resolve include files");
 
@@ -910,6 +1515,10 @@ mod tests {
             &defines,
             None,
             None,
+            None,
+            SourceLanguage::GLSL,
+            "main",
+            &HlslOptions::default(),
         )
         .expect("Cannot resolve include files");
         assert_eq!(
@@ -937,6 +1546,10 @@ mod tests {
             &defines,
             None,
             None,
+            None,
+            SourceLanguage::GLSL,
+            "main",
+            &HlslOptions::default(),
         )
         .expect("Cannot resolve include files");
         assert_eq!(
@@ -974,6 +1587,10 @@ mod tests {
             &defines,
             None,
             None,
+            None,
+            SourceLanguage::GLSL,
+            "main",
+            &HlslOptions::default(),
         )
         .expect("Cannot resolve include files");
         assert_eq!(
@@ -1000,6 +1617,10 @@ mod tests {
             &defines,
             None,
             None,
+            None,
+            SourceLanguage::GLSL,
+            "main",
+            &HlslOptions::default(),
         )
         .expect("Cannot resolve include files");
         assert_eq!(
@@ -1036,6 +1657,10 @@ mod tests {
             &no_defines,
             None,
             None,
+            None,
+            SourceLanguage::GLSL,
+            "main",
+            &HlslOptions::default(),
         );
         assert!(compile_no_defines.is_err());
 
@@ -1048,6 +1673,10 @@ mod tests {
             &defines,
             None,
             None,
+            None,
+            SourceLanguage::GLSL,
+            "main",
+            &HlslOptions::default(),
         );
         compile_defines.expect("Setting shader macros did not work");
     }
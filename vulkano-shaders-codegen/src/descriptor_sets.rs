@@ -0,0 +1,727 @@
+// Copyright (c) 2016 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::structs::sanitize_ident;
+use proc_macro2::{Span, TokenStream};
+use std::collections::HashSet;
+use syn::Ident;
+use vulkano::descriptor_set::layout::{
+    DescriptorDesc, DescriptorDescImage, DescriptorDescTy, DescriptorSetDesc,
+};
+use vulkano::format::Format;
+use vulkano::image::view::ImageViewType;
+use vulkano::pipeline::layout::PipelineLayoutPcRange;
+use vulkano::pipeline::shader::ShaderStages;
+
+/// The kind of resource that is bound to a descriptor, which determines the type of the field
+/// generated for it and the method of `PersistentDescriptorSetBuilder` that binds it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DescriptorKind {
+    Buffer,
+    BufferView,
+    Image,
+    CombinedImageSampler,
+    Sampler,
+}
+
+impl DescriptorKind {
+    fn from_ty(ty: &DescriptorDescTy) -> DescriptorKind {
+        match *ty {
+            DescriptorDescTy::Sampler { .. } => DescriptorKind::Sampler,
+            DescriptorDescTy::CombinedImageSampler { .. } => DescriptorKind::CombinedImageSampler,
+            DescriptorDescTy::SampledImage { .. }
+            | DescriptorDescTy::StorageImage { .. }
+            | DescriptorDescTy::InputAttachment { .. } => DescriptorKind::Image,
+            DescriptorDescTy::UniformTexelBuffer { .. }
+            | DescriptorDescTy::StorageTexelBuffer { .. } => DescriptorKind::BufferView,
+            DescriptorDescTy::UniformBuffer
+            | DescriptorDescTy::StorageBuffer
+            | DescriptorDescTy::UniformBufferDynamic
+            | DescriptorDescTy::StorageBufferDynamic => DescriptorKind::Buffer,
+        }
+    }
+}
+
+/// Writes the array of `DescriptorSetDesc`s of an entry point.
+pub(super) fn write_descriptor_set_layout_descs(sets: &[DescriptorSetDesc]) -> TokenStream {
+    let sets = sets.iter().map(|set| {
+        let bindings = set.bindings().iter().map(|desc| match *desc {
+            Some(ref desc) => {
+                let desc = write_descriptor_desc(desc);
+                quote! { Some(#desc), }
+            }
+            None => quote! { None, },
+        });
+
+        quote! {
+            DescriptorSetDesc::new(
+                [#( #bindings )*]
+            ),
+        }
+    });
+
+    quote! {
+        [
+            #( #sets )*
+        ]
+    }
+}
+
+fn write_descriptor_desc(desc: &DescriptorDesc) -> TokenStream {
+    let ty = write_descriptor_desc_ty(&desc.ty);
+    let descriptor_count = desc.descriptor_count;
+    let stages = write_stages(desc.stages);
+    let variable_count = desc.variable_count;
+    let mutable = desc.mutable;
+    let name = match desc.name {
+        Some(ref name) => {
+            let name = name.as_ref();
+            quote! { Some(::std::borrow::Cow::Borrowed(#name)) }
+        }
+        None => quote! { None },
+    };
+
+    quote! {
+        DescriptorDesc {
+            ty: #ty,
+            descriptor_count: #descriptor_count,
+            stages: #stages,
+            variable_count: #variable_count,
+            mutable: #mutable,
+            name: #name,
+        }
+    }
+}
+
+fn write_descriptor_desc_ty(ty: &DescriptorDescTy) -> TokenStream {
+    match *ty {
+        DescriptorDescTy::Sampler { .. } => {
+            quote! { DescriptorDescTy::Sampler { immutable_samplers: Vec::new() } }
+        }
+        DescriptorDescTy::CombinedImageSampler { ref image_desc, .. } => {
+            let image_desc = write_descriptor_desc_image(image_desc);
+            quote! {
+                DescriptorDescTy::CombinedImageSampler {
+                    image_desc: #image_desc,
+                    immutable_samplers: Vec::new(),
+                }
+            }
+        }
+        DescriptorDescTy::SampledImage { ref image_desc } => {
+            let image_desc = write_descriptor_desc_image(image_desc);
+            quote! { DescriptorDescTy::SampledImage { image_desc: #image_desc } }
+        }
+        DescriptorDescTy::StorageImage { ref image_desc } => {
+            let image_desc = write_descriptor_desc_image(image_desc);
+            quote! { DescriptorDescTy::StorageImage { image_desc: #image_desc } }
+        }
+        DescriptorDescTy::UniformTexelBuffer { format } => {
+            let format = write_optional_format(format);
+            quote! { DescriptorDescTy::UniformTexelBuffer { format: #format } }
+        }
+        DescriptorDescTy::StorageTexelBuffer { format } => {
+            let format = write_optional_format(format);
+            quote! { DescriptorDescTy::StorageTexelBuffer { format: #format } }
+        }
+        DescriptorDescTy::UniformBuffer => quote! { DescriptorDescTy::UniformBuffer },
+        DescriptorDescTy::StorageBuffer => quote! { DescriptorDescTy::StorageBuffer },
+        DescriptorDescTy::UniformBufferDynamic => {
+            quote! { DescriptorDescTy::UniformBufferDynamic }
+        }
+        DescriptorDescTy::StorageBufferDynamic => {
+            quote! { DescriptorDescTy::StorageBufferDynamic }
+        }
+        DescriptorDescTy::InputAttachment { multisampled } => {
+            quote! { DescriptorDescTy::InputAttachment { multisampled: #multisampled } }
+        }
+    }
+}
+
+fn write_descriptor_desc_image(image_desc: &DescriptorDescImage) -> TokenStream {
+    let format = write_optional_format(image_desc.format);
+    let multisampled = image_desc.multisampled;
+    let view_type = match image_desc.view_type {
+        ImageViewType::Dim1d => quote! { ImageViewType::Dim1d },
+        ImageViewType::Dim1dArray => quote! { ImageViewType::Dim1dArray },
+        ImageViewType::Dim2d => quote! { ImageViewType::Dim2d },
+        ImageViewType::Dim2dArray => quote! { ImageViewType::Dim2dArray },
+        ImageViewType::Dim3d => quote! { ImageViewType::Dim3d },
+        ImageViewType::Cube => quote! { ImageViewType::Cube },
+        ImageViewType::CubeArray => quote! { ImageViewType::CubeArray },
+    };
+
+    quote! {
+        DescriptorDescImage {
+            format: #format,
+            multisampled: #multisampled,
+            view_type: #view_type,
+        }
+    }
+}
+
+/// Writes the path of a variant of `Format`.
+pub(super) fn write_format(format: Format) -> TokenStream {
+    let format = Ident::new(&format!("{:?}", format), Span::call_site());
+    quote! { ::vulkano::format::Format::#format }
+}
+
+fn write_optional_format(format: Option<Format>) -> TokenStream {
+    match format {
+        Some(format) => {
+            let format = write_format(format);
+            quote! { Some(#format) }
+        }
+        None => quote! { None },
+    }
+}
+
+/// Writes a `ShaderStages` with the same stages as `stages`.
+pub(super) fn write_stages(stages: ShaderStages) -> TokenStream {
+    let ShaderStages {
+        vertex,
+        tessellation_control,
+        tessellation_evaluation,
+        geometry,
+        fragment,
+        compute,
+    } = stages;
+
+    quote! {
+        ShaderStages {
+            vertex: #vertex,
+            tessellation_control: #tessellation_control,
+            tessellation_evaluation: #tessellation_evaluation,
+            geometry: #geometry,
+            fragment: #fragment,
+            compute: #compute,
+        }
+    }
+}
+
+/// Writes the push constant range of an entry point, as an `Option<PipelineLayoutPcRange>`.
+pub(super) fn write_push_constant_range(range: &Option<PipelineLayoutPcRange>) -> TokenStream {
+    match *range {
+        Some(PipelineLayoutPcRange {
+            offset,
+            size,
+            stages,
+        }) => {
+            let stages = write_stages(stages);
+            quote! {
+                Some(
+                    PipelineLayoutPcRange {
+                        offset: #offset,
+                        size: #size,
+                        stages: #stages,
+                    }
+                )
+            }
+        }
+        None => quote! { None },
+    }
+}
+
+/// Generates, for each descriptor set of the module, a struct with one field per descriptor that
+/// builds a `PersistentDescriptorSet`.
+pub(super) fn write_descriptor_set_builders(
+    prefix: &str,
+    sets: &[DescriptorSetDesc],
+) -> TokenStream {
+    let sets = sets.iter().enumerate().filter_map(|(set_num, set)| {
+        let set_num = set_num as u32;
+        let set_descriptors: Vec<_> = set
+            .bindings()
+            .iter()
+            .enumerate()
+            .filter_map(|(binding_num, desc)| {
+                desc.as_ref().map(|desc| (binding_num as u32, desc))
+            })
+            .collect();
+        if set_descriptors.is_empty() {
+            return None;
+        }
+
+        // Several variables can have the same name, or names that only differ by their case.
+        let mut used_names = HashSet::new();
+        let names: Vec<_> = set_descriptors
+            .iter()
+            .map(|&(binding_num, d)| {
+                let mut name = sanitize_ident(d.name.as_deref().unwrap_or("__unnamed"));
+                if !used_names.insert(name.to_uppercase()) {
+                    name = format!("{}_{}", name, binding_num);
+                    used_names.insert(name.to_uppercase());
+                }
+                name
+            })
+            .collect();
+
+        let struct_name = Ident::new(&format!("{}Set{}", prefix, set_num), Span::call_site());
+        let mut fields = Vec::new();
+        let mut binding_consts = Vec::new();
+        let mut adds = Vec::new();
+        let mut next_binding = 0;
+
+        for (&(binding_num, d), name) in set_descriptors.iter().zip(&names) {
+            let field = Ident::new(name, Span::call_site());
+            let binding_const = Ident::new(
+                &format!("{}_BINDING", name.to_uppercase()),
+                Span::call_site(),
+            );
+
+            let (element_ty, add) = match DescriptorKind::from_ty(&d.ty) {
+                DescriptorKind::Buffer => (
+                    quote! { ::std::sync::Arc<dyn ::vulkano::buffer::BufferAccess> },
+                    quote! { add_buffer(element) },
+                ),
+                DescriptorKind::BufferView => (
+                    quote! {
+                        ::std::sync::Arc<::vulkano::buffer::BufferView<
+                            ::std::sync::Arc<dyn ::vulkano::buffer::BufferAccess>
+                        >>
+                    },
+                    quote! { add_buffer_view(element) },
+                ),
+                DescriptorKind::Image => (
+                    quote! {
+                        ::std::sync::Arc<dyn ::vulkano::image::ImageViewAbstract + Send + Sync>
+                    },
+                    quote! { add_image(element) },
+                ),
+                DescriptorKind::CombinedImageSampler => (
+                    quote! {
+                        (
+                            ::std::sync::Arc<dyn ::vulkano::image::ImageViewAbstract + Send + Sync>,
+                            ::std::sync::Arc<::vulkano::sampler::Sampler>,
+                        )
+                    },
+                    quote! { add_sampled_image(element.0, element.1) },
+                ),
+                DescriptorKind::Sampler => (
+                    quote! { ::std::sync::Arc<::vulkano::sampler::Sampler> },
+                    quote! { add_sampler(element) },
+                ),
+            };
+
+            let skipped = (next_binding..binding_num).map(|_| quote! { builder.add_empty()?; });
+            next_binding = binding_num + 1;
+
+            // Arrays of descriptors are given as a `Vec`, with one element per array element.
+            if d.descriptor_count != 1 || d.variable_count {
+                fields.push(quote! { pub #field: ::std::vec::Vec<#element_ty> });
+                adds.push(quote! {
+                    #( #skipped )*
+                    builder.enter_array()?;
+                    for element in self.#field {
+                        builder.#add?;
+                    }
+                    builder.leave_array()?;
+                });
+            } else {
+                fields.push(quote! { pub #field: #element_ty });
+                adds.push(quote! {
+                    #( #skipped )*
+                    let element = self.#field;
+                    builder.#add?;
+                });
+            }
+
+            binding_consts.push(quote! {
+                pub const #binding_const: u32 = #binding_num;
+            });
+        }
+
+        Some(quote! {
+            /// The resources to bind to the descriptors of one descriptor set of the shader, with
+            /// one field per descriptor, named after the shader variable.
+            ///
+            /// The layout passed to `build` must not contain descriptors that the shader doesn't
+            /// declare.
+            #[allow(non_snake_case)]
+            pub struct #struct_name {
+                #( #fields ),*
+            }
+
+            #[allow(dead_code)]
+            impl #struct_name {
+                /// The number of the descriptor set.
+                pub const SET: u32 = #set_num;
+
+                #( #binding_consts )*
+
+                /// Builds a `PersistentDescriptorSet` with the given layout, binding each
+                /// resource to its descriptor.
+                pub fn build(
+                    self,
+                    layout: ::std::sync::Arc<::vulkano::descriptor_set::layout::DescriptorSetLayout>,
+                ) -> Result<
+                    ::vulkano::descriptor_set::PersistentDescriptorSet,
+                    ::vulkano::descriptor_set::DescriptorSetError,
+                > {
+                    let mut builder = ::vulkano::descriptor_set::PersistentDescriptorSet::start(layout);
+                    #( #adds )*
+                    builder.build()
+                }
+            }
+        })
+    });
+
+    quote! {
+        #( #sets )*
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::{compile, HlslOptions};
+    use crate::TypesMeta;
+    use shaderc::{ResourceKind, ShaderKind, SourceLanguage};
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+    use vulkano::pipeline::shader::EntryPointInfo;
+    use vulkano::spirv::Spirv;
+
+    // Returns the `(set, binding)` of each descriptor of an entry point.
+    fn bindings(info: &EntryPointInfo) -> Vec<(u32, u32)> {
+        let mut bindings = Vec::new();
+        for (set_num, set) in info.descriptor_set_layout_descs().iter().enumerate() {
+            for (binding_num, desc) in set.bindings().iter().enumerate() {
+                if desc.is_some() {
+                    bindings.push((set_num as u32, binding_num as u32));
+                }
+            }
+        }
+        bindings
+    }
+
+    /// `entrypoint1.frag.glsl`:
+    /// ```glsl
+    /// #version 450
+    ///
+    /// layout(set = 0, binding = 0) uniform Uniform {
+    ///     uint data;
+    /// } ubo;
+    ///
+    /// layout(set = 0, binding = 1) buffer Buffer {
+    ///     uint data;
+    /// } bo;
+    ///
+    /// layout(set = 0, binding = 2) uniform sampler textureSampler;
+    /// layout(set = 0, binding = 3) uniform texture2D imageTexture;
+    ///
+    /// layout(push_constant) uniform PushConstant {
+    ///    uint data;
+    /// } push;
+    ///
+    /// layout(input_attachment_index = 0, set = 0, binding = 4) uniform subpassInput inputAttachment;
+    ///
+    /// layout(location = 0) out vec4 outColor;
+    ///
+    /// void entrypoint1() {
+    ///     bo.data = 12;
+    ///     outColor = vec4(
+    ///         float(ubo.data),
+    ///         float(push.data),
+    ///         texture(sampler2D(imageTexture, textureSampler), vec2(0.0, 0.0)).x,
+    ///         subpassLoad(inputAttachment).x
+    ///     );
+    /// }
+    /// ```
+    ///
+    /// `entrypoint2.frag.glsl`:
+    /// ```glsl
+    /// #version 450
+    ///
+    /// layout(input_attachment_index = 0, set = 0, binding = 0) uniform subpassInput inputAttachment2;
+    ///
+    /// layout(set = 0, binding = 1) buffer Buffer {
+    ///     uint data;
+    /// } bo2;
+    ///
+    /// layout(set = 0, binding = 2) uniform Uniform {
+    ///     uint data;
+    /// } ubo2;
+    ///
+    /// layout(push_constant) uniform PushConstant {
+    ///    uint data;
+    /// } push2;
+    ///
+    /// void entrypoint2() {
+    ///     bo2.data = ubo2.data + push2.data + int(subpassLoad(inputAttachment2).y);
+    /// }
+    /// ```
+    ///
+    /// Compiled and linked with:
+    /// ```sh
+    /// glslangvalidator -e entrypoint1 --source-entrypoint entrypoint1 -V100 entrypoint1.frag.glsl -o entrypoint1.spv
+    /// glslangvalidator -e entrypoint2 --source-entrypoint entrypoint2 -V100 entrypoint2.frag.glsl -o entrypoint2.spv
+    /// spirv-link entrypoint1.spv entrypoint2.spv -o multiple_entrypoints.spv
+    /// ```
+    #[test]
+    fn test_descriptor_calculation_with_multiple_entrypoints() {
+        let data = include_bytes!("../tests/multiple_entrypoints.spv");
+        let instructions: Vec<u32> = data
+            .chunks(4)
+            .map(|c| {
+                ((c[3] as u32) << 24) | ((c[2] as u32) << 16) | ((c[1] as u32) << 8) | c[0] as u32
+            })
+            .collect();
+        let entry_points = EntryPointInfo::from_spirv(&instructions, true).unwrap();
+
+        // Check first entrypoint
+        let e1_bindings = bindings(entry_points.get(0).expect("Could not find entrypoint1"));
+        assert_eq!(e1_bindings, [(0, 0), (0, 1), (0, 2), (0, 3), (0, 4)]);
+
+        // Check second entrypoint
+        let e2_bindings = bindings(entry_points.get(1).expect("Could not find entrypoint2"));
+        assert_eq!(e2_bindings, [(0, 0), (0, 1), (0, 2)]);
+    }
+
+    #[test]
+    fn test_descriptor_calculation_with_multiple_functions() {
+        let includes: [PathBuf; 0] = [];
+        let defines: [(String, String); 0] = [];
+        let (comp, _) = compile(
+            None,
+            &Path::new(""),
+            "
+        #version 450
+
+        layout(set = 1, binding = 0) buffer Buffer {
+            vec3 data;
+        } bo;
+
+        layout(set = 2, binding = 0) uniform Uniform {
+            float data;
+        } ubo;
+
+        layout(set = 3, binding = 1) uniform sampler textureSampler;
+        layout(set = 3, binding = 2) uniform texture2D imageTexture;
+
+        float withMagicSparkles(float data) {
+            return texture(sampler2D(imageTexture, textureSampler), vec2(data, data)).x;
+        }
+
+        vec3 makeSecretSauce() {
+            return vec3(withMagicSparkles(ubo.data));
+        }
+
+        void main() {
+            bo.data = makeSecretSauce();
+        }
+        ",
+            ShaderKind::Vertex,
+            &includes,
+            &defines,
+            None,
+            None,
+            None,
+            SourceLanguage::GLSL,
+            "main",
+            &HlslOptions::default(),
+        )
+        .unwrap();
+        let entry_points = EntryPointInfo::from_spirv(comp.as_binary(), true).unwrap();
+        let entry_point = entry_points.get(0).expect("Could not find entrypoint");
+        assert_eq!(bindings(entry_point), [(1, 0), (2, 0), (3, 1), (3, 2)]);
+    }
+
+    #[test]
+    fn test_descriptor_calculation_hlsl_binding_shifts() {
+        let includes: [PathBuf; 0] = [];
+        let defines: [(String, String); 0] = [];
+        let hlsl_options = HlslOptions {
+            binding_shifts: vec![
+                (ResourceKind::Texture, 16),
+                (ResourceKind::Sampler, 32),
+                (ResourceKind::UnorderedAccessView, 48),
+            ],
+            register_bindings: Vec::new(),
+        };
+        let (comp, _) = compile(
+            None,
+            &Path::new(""),
+            "
+        cbuffer Params : register(b0) {
+            float scale;
+        };
+
+        Texture2D tex : register(t1, space1);
+        SamplerState samp : register(s2, space1);
+        RWStructuredBuffer<float4> output : register(u0, space2);
+
+        [numthreads(64, 1, 1)]
+        void CSMain(uint3 id : SV_DispatchThreadID) {
+            output[id.x] = tex.SampleLevel(samp, float2(0.0, 0.0), 0) * scale;
+        }
+        ",
+            ShaderKind::Compute,
+            &includes,
+            &defines,
+            None,
+            None,
+            None,
+            SourceLanguage::HLSL,
+            "CSMain",
+            &hlsl_options,
+        )
+        .unwrap();
+        let entry_points = EntryPointInfo::from_spirv(comp.as_binary(), true).unwrap();
+        let entry_point = entry_points.get(0).expect("Could not find entrypoint");
+        assert_eq!(bindings(entry_point), [(0, 0), (1, 17), (1, 34), (2, 48)]);
+    }
+
+    #[test]
+    fn test_descriptor_set_builders() {
+        let includes: [PathBuf; 0] = [];
+        let defines: [(String, String); 0] = [];
+        let (comp, _) = compile(
+            None,
+            &Path::new(""),
+            "
+        #version 450
+
+        layout(set = 0, binding = 0) uniform Uniforms {
+            vec4 color;
+        } uniforms;
+        layout(set = 0, binding = 2) uniform sampler2D tex;
+        layout(set = 0, binding = 3) uniform texture2D layers[4];
+
+        layout(set = 2, binding = 1) buffer Output {
+            vec4 data[];
+        };
+        layout(set = 2, binding = 0) uniform sampler samp;
+
+        void main() {
+            data[0] = uniforms.color + textureLod(tex, vec2(0.0), 0.0)
+                + textureLod(sampler2D(layers[1], samp), vec2(0.0), 0.0);
+        }
+        ",
+            ShaderKind::Compute,
+            &includes,
+            &defines,
+            None,
+            None,
+            None,
+            SourceLanguage::GLSL,
+            "main",
+            &HlslOptions::default(),
+        )
+        .unwrap();
+        let entry_points = EntryPointInfo::from_spirv(comp.as_binary(), false).unwrap();
+
+        let code = write_descriptor_set_builders("", entry_points[0].descriptor_set_layout_descs());
+        let file: syn::File = syn::parse2(code).unwrap();
+        let structs: Vec<_> = file
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                syn::Item::Struct(item) => Some(item),
+                _ => None,
+            })
+            .collect();
+        let consts: Vec<_> = file
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                syn::Item::Impl(item) => Some(item),
+                _ => None,
+            })
+            .flat_map(|item| item.items.iter())
+            .filter_map(|item| match item {
+                syn::ImplItem::Const(item) => Some(item.ident.to_string()),
+                _ => None,
+            })
+            .collect();
+
+        // Set 1 is not used, so no struct is generated for it.
+        let struct_names: Vec<_> = structs.iter().map(|s| s.ident.to_string()).collect();
+        assert_eq!(struct_names, ["Set0", "Set2"]);
+
+        let field_names = |item: &syn::ItemStruct| -> Vec<String> {
+            item.fields
+                .iter()
+                .map(|field| field.ident.as_ref().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(field_names(structs[0]), ["uniforms", "tex", "layers"]);
+        // The block without an instance name is named after its type.
+        assert_eq!(field_names(structs[1]), ["samp", "Output"]);
+
+        assert_eq!(
+            consts,
+            [
+                "SET",
+                "UNIFORMS_BINDING",
+                "TEX_BINDING",
+                "LAYERS_BINDING",
+                "SET",
+                "SAMP_BINDING",
+                "OUTPUT_BINDING",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_push_constant_range_offset() {
+        let includes: [PathBuf; 0] = [];
+        let defines: [(String, String); 0] = [];
+        let (comp, _) = compile(
+            None,
+            &Path::new(""),
+            "
+        #version 450
+
+        layout(push_constant) uniform PushConstants {
+            layout(offset = 16) vec4 color;
+            layout(offset = 32) float scale;
+        } pc;
+
+        layout(location = 0) out vec4 f_color;
+
+        void main() {
+            f_color = pc.color * pc.scale;
+        }
+        ",
+            ShaderKind::Fragment,
+            &includes,
+            &defines,
+            None,
+            None,
+            None,
+            SourceLanguage::GLSL,
+            "main",
+            &HlslOptions::default(),
+        )
+        .unwrap();
+        let spirv = Spirv::new(comp.as_binary()).unwrap();
+
+        // The range of the entry point starts at the first member and ends at the end of the last
+        // one.
+        let entry_points = EntryPointInfo::from_spirv(comp.as_binary(), false).unwrap();
+        let range = entry_points[0].push_constant_range().unwrap();
+        assert_eq!((range.offset, range.size), (16, 20));
+
+        // The generated struct pushes the same range.
+        assert_eq!(
+            crate::structs::push_constants_struct_name(&spirv).as_deref(),
+            Some("PushConstants")
+        );
+        let structs =
+            crate::structs::write_structs("", &spirv, &TypesMeta::default(), &mut HashMap::new())
+                .unwrap()
+                .to_string();
+        assert!(
+            structs.contains("const OFFSET : u32 = 16u32"),
+            "{}",
+            structs
+        );
+        assert!(structs.contains("const SIZE : u32 = 20u32"), "{}", structs);
+    }
+}
@@ -0,0 +1,209 @@
+// Copyright (c) 2016 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::descriptor_sets::{
+    write_descriptor_set_layout_descs, write_format, write_push_constant_range,
+};
+use proc_macro2::{Span, TokenStream};
+use syn::Ident;
+use vulkano::pipeline::shader::{
+    EntryPointInfo, GeometryShaderExecutionMode, GraphicsShaderType, LocalSizeValue,
+    ShaderExecution, ShaderInterface, SpecializationConstantType,
+};
+use vulkano::spirv::Spirv;
+
+/// Writes the method of the shader struct that returns the entry point described by `info`.
+pub(super) fn write_entry_point(spirv: &Spirv, info: &EntryPointInfo) -> TokenStream {
+    let ep_name = info.name().to_str().unwrap();
+
+    let descriptor_set_layout_descs =
+        write_descriptor_set_layout_descs(info.descriptor_set_layout_descs());
+    let push_constant_range = write_push_constant_range(info.push_constant_range());
+    let spec_consts_requirements = write_specialization_constant_requirements(info);
+
+    let (ty, f_call) = match *info.execution() {
+        ShaderExecution::Compute(local_size) => {
+            let local_size = local_size
+                .iter()
+                .map(|&value| write_local_size_value(value));
+
+            (
+                quote! { ::vulkano::pipeline::shader::ComputeEntryPoint },
+                quote! { compute_entry_point(
+                    ::std::ffi::CStr::from_ptr(NAME.as_ptr() as *const _),
+                    #descriptor_set_layout_descs,
+                    #push_constant_range,
+                    #spec_consts_requirements,
+                ).with_local_size([ #( #local_size ),* ])},
+            )
+        }
+        ShaderExecution::Graphics(ty) => {
+            let entry_ty = write_graphics_shader_type(ty);
+            let input_interface = write_interface(info.input());
+            let output_interface = write_interface(info.output());
+
+            (
+                quote! { ::vulkano::pipeline::shader::GraphicsEntryPoint },
+                quote! {
+                    graphics_entry_point(
+                        ::std::ffi::CStr::from_ptr(NAME.as_ptr() as *const _),
+                        #descriptor_set_layout_descs,
+                        #push_constant_range,
+                        #spec_consts_requirements,
+                        #input_interface,
+                        #output_interface,
+                        #entry_ty
+                    )
+                },
+            )
+        }
+    };
+
+    let mut method_name = ep_name.to_owned();
+    method_name.push_str("_entry_point");
+    let method_ident = Ident::new(&method_name, Span::call_site());
+
+    let ep_name_lenp1 = ep_name.chars().count() + 1;
+    let encoded_ep_name = ep_name.chars().map(|c| c as u8).collect::<Vec<_>>();
+
+    // Entry points of a module with a single push constant block carry the layout of its struct,
+    // so that the pipeline layout can check the type given to `push_constants_typed`.
+    let f_call = match crate::structs::push_constants_struct_name(spirv) {
+        Some(name) => {
+            let name = Ident::new(&name, Span::call_site());
+            quote! { #f_call.with_push_constants::<ty::#name>() }
+        }
+        None => f_call,
+    };
+
+    quote! {
+        /// Returns a logical struct describing the entry point named `{ep_name}`.
+        #[inline]
+        #[allow(unsafe_code, non_snake_case)]
+        pub fn #method_ident(&self) -> #ty {
+            unsafe {
+                #[allow(dead_code)]
+                static NAME: [u8; #ep_name_lenp1] = [ #( #encoded_ep_name ),* , 0];
+                self.shader.#f_call
+            }
+        }
+    }
+}
+
+fn write_graphics_shader_type(ty: GraphicsShaderType) -> TokenStream {
+    match ty {
+        GraphicsShaderType::Vertex => {
+            quote! { ::vulkano::pipeline::shader::GraphicsShaderType::Vertex }
+        }
+        GraphicsShaderType::TessellationControl => {
+            quote! { ::vulkano::pipeline::shader::GraphicsShaderType::TessellationControl }
+        }
+        GraphicsShaderType::TessellationEvaluation => {
+            quote! { ::vulkano::pipeline::shader::GraphicsShaderType::TessellationEvaluation }
+        }
+        GraphicsShaderType::Geometry(execution_mode) => {
+            let execution_mode = match execution_mode {
+                GeometryShaderExecutionMode::Points => quote! { Points },
+                GeometryShaderExecutionMode::Lines => quote! { Lines },
+                GeometryShaderExecutionMode::LinesWithAdjacency => quote! { LinesWithAdjacency },
+                GeometryShaderExecutionMode::Triangles => quote! { Triangles },
+                GeometryShaderExecutionMode::TrianglesWithAdjacency => {
+                    quote! { TrianglesWithAdjacency }
+                }
+            };
+
+            quote! {
+                ::vulkano::pipeline::shader::GraphicsShaderType::Geometry(
+                    ::vulkano::pipeline::shader::GeometryShaderExecutionMode::#execution_mode
+                )
+            }
+        }
+        GraphicsShaderType::Fragment => {
+            quote! { ::vulkano::pipeline::shader::GraphicsShaderType::Fragment }
+        }
+    }
+}
+
+fn write_local_size_value(value: LocalSizeValue) -> TokenStream {
+    match value {
+        LocalSizeValue::Literal(value) => {
+            quote! { ::vulkano::pipeline::shader::LocalSizeValue::Literal(#value) }
+        }
+        LocalSizeValue::SpecConstant {
+            constant_id,
+            default_value,
+        } => quote! {
+            ::vulkano::pipeline::shader::LocalSizeValue::SpecConstant {
+                constant_id: #constant_id,
+                default_value: #default_value,
+            }
+        },
+    }
+}
+
+/// Writes an array of `(constant_id, SpecializationConstantRequirements)` describing every
+/// specialization constant of the entry point, ordered by constant id.
+fn write_specialization_constant_requirements(info: &EntryPointInfo) -> TokenStream {
+    let mut spec_constants: Vec<_> = info.spec_constants().iter().collect();
+    spec_constants.sort_by_key(|&(&constant_id, _)| constant_id);
+
+    let requirements = spec_constants
+        .into_iter()
+        .map(|(&constant_id, requirements)| {
+            let ty = match requirements.ty {
+                SpecializationConstantType::Bool => quote! { SpecializationConstantType::Bool },
+                SpecializationConstantType::Int { width, signed } => {
+                    quote! { SpecializationConstantType::Int { width: #width, signed: #signed } }
+                }
+                SpecializationConstantType::Float { width } => {
+                    quote! { SpecializationConstantType::Float { width: #width } }
+                }
+            };
+
+            quote! {
+                (#constant_id, SpecializationConstantRequirements::from(#ty))
+            }
+        });
+
+    quote! {
+        [ #( #requirements ),* ]
+    }
+}
+
+fn write_interface(interface: &ShaderInterface) -> TokenStream {
+    let body = interface.elements().iter().map(|element| {
+        let loc = element.location.start;
+        let loc_end = element.location.end;
+        let format = write_format(element.format);
+        let name = match element.name {
+            Some(ref name) => {
+                let name = name.as_ref();
+                quote! { Some(::std::borrow::Cow::Borrowed(#name)) }
+            }
+            None => quote! { None },
+        };
+
+        quote! {
+            ::vulkano::pipeline::shader::ShaderInterfaceEntry {
+                location: #loc .. #loc_end,
+                format: #format,
+                name: #name
+            },
+        }
+    });
+
+    quote! {
+        #[allow(unsafe_code)]
+        unsafe {
+            ::vulkano::pipeline::shader::ShaderInterface::new_unchecked(vec![
+                #( #body )*
+            ])
+        }
+    }
+}
@@ -0,0 +1,844 @@
+// Copyright (c) 2021 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Reloading shaders when their source files change.
+//!
+//! This is meant to be used during development, to see the effect of editing a shader without
+//! restarting the application. There are two layers:
+//!
+//! - A [`ShaderWatcher`] compiles shaders with a [`ShaderCompiler`] and watches their source
+//!   files and all the files that they include. Polling it recompiles the shaders that are
+//!   affected by a change.
+//! - A [`HotReloader`] additionally turns the compiled shaders into shader modules and rebuilds
+//!   the pipelines that use them. A pipeline is only rebuilt if its layout is still compatible
+//!   with the new version of the shaders, so that the descriptor sets created for it remain
+//!   valid.
+//!
+//! When a shader fails to compile or a pipeline can't be rebuilt, the error is reported and the
+//! previous version keeps being used, so that a typo doesn't bring the application down.
+//!
+//! This module is only available with the `hot-reload` feature, which is disabled by default.
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use vulkano::device::Device;
+//! # use vulkano::pipeline::ComputePipeline;
+//! # let device: Arc<Device> = return;
+//! use vulkano_shaders_codegen::hot_reload::HotReloader;
+//! use vulkano_shaders_codegen::runtime::{ShaderCompiler, ShaderSource};
+//! use vulkano_shaders_codegen::ShaderKind;
+//!
+//! let mut reloader = unsafe { HotReloader::new(device.clone(), ShaderCompiler::new()) };
+//! let source = ShaderSource::from_file("shaders/blur.comp", ShaderKind::Compute).unwrap();
+//! let shader = reloader.add_shader(source).unwrap();
+//!
+//! let pipeline = Arc::new({
+//!     let entry_point = reloader.module(shader).reflected_compute_entry_point("main").unwrap();
+//!     ComputePipeline::new(device.clone(), &entry_point, &(), None, |_| {}).unwrap()
+//! });
+//! let pipeline = reloader.add_pipeline(&[(shader, "main")], pipeline, {
+//!     let device = device.clone();
+//!     move |modules, layout| {
+//!         let entry_point = modules[0].reflected_compute_entry_point("main").unwrap();
+//!         ComputePipeline::with_pipeline_layout(device.clone(), &entry_point, &(), layout.clone(), None)
+//!     }
+//! });
+//!
+//! loop {
+//!     for error in reloader.reload().errors {
+//!         println!("{}", error);
+//!     }
+//!
+//!     let current_pipeline = pipeline.get();
+//!     // ... use `current_pipeline` to record commands ...
+//! #   break;
+//! }
+//! ```
+
+use crate::runtime::{CompileError, CompiledShader, ShaderCompiler, ShaderSource};
+use std::collections::{HashMap, HashSet};
+use std::error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use vulkano::device::Device;
+use vulkano::pipeline::layout::{PipelineLayout, PipelineLayoutSupersetError};
+use vulkano::pipeline::shader::{ShaderCreationError, ShaderModule};
+use vulkano::pipeline::{ComputePipeline, GraphicsPipeline};
+
+/// Detects changes to files.
+///
+/// [`PollWatcher`] is the implementation that is used by default. Other implementations can be
+/// used to rely on the notifications of the operating system instead.
+pub trait Watcher {
+    /// Starts watching a file. Does nothing if the file is already watched.
+    fn watch(&mut self, path: &Path);
+
+    /// Stops watching a file.
+    fn unwatch(&mut self, path: &Path);
+
+    /// Returns the watched files that were modified, created or removed since the last call.
+    fn changes(&mut self) -> Vec<PathBuf>;
+}
+
+/// A [`Watcher`] that compares the modification time and the size of the watched files each
+/// time it is polled.
+#[derive(Debug, Default)]
+pub struct PollWatcher {
+    files: HashMap<PathBuf, Option<FileState>>,
+    interval: Duration,
+    last_poll: Option<Instant>,
+}
+
+// What is compared to detect a change. `None` in `PollWatcher::files` means the file didn't exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileState {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl FileState {
+    fn of(path: &Path) -> Option<FileState> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(FileState {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        })
+    }
+}
+
+impl PollWatcher {
+    /// Creates a watcher that checks the files each time it is polled.
+    #[inline]
+    pub fn new() -> PollWatcher {
+        PollWatcher::default()
+    }
+
+    /// Sets the minimum duration between two checks of the files. Polling the watcher more often
+    /// reports no changes. This avoids querying the file system on every frame.
+    #[inline]
+    pub fn interval(mut self, interval: Duration) -> PollWatcher {
+        self.interval = interval;
+        self
+    }
+}
+
+impl Watcher for PollWatcher {
+    #[inline]
+    fn watch(&mut self, path: &Path) {
+        if !self.files.contains_key(path) {
+            self.files.insert(path.to_owned(), FileState::of(path));
+        }
+    }
+
+    #[inline]
+    fn unwatch(&mut self, path: &Path) {
+        self.files.remove(path);
+    }
+
+    fn changes(&mut self) -> Vec<PathBuf> {
+        let now = Instant::now();
+        if let Some(last_poll) = self.last_poll {
+            if now.duration_since(last_poll) < self.interval {
+                return Vec::new();
+            }
+        }
+        self.last_poll = Some(now);
+
+        let mut changes = Vec::new();
+        for (path, state) in self.files.iter_mut() {
+            let new_state = FileState::of(path);
+            if new_state != *state {
+                *state = new_state;
+                changes.push(path.clone());
+            }
+        }
+        changes
+    }
+}
+
+/// Identifies a shader added to a [`ShaderWatcher`] or a [`HotReloader`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShaderId(usize);
+
+/// Compiles shaders and recompiles them when their source files or the files that they include
+/// change.
+pub struct ShaderWatcher<W = PollWatcher> {
+    compiler: ShaderCompiler,
+    watcher: W,
+    shaders: Vec<Option<WatchedShader>>,
+    watched_files: HashSet<PathBuf>,
+}
+
+struct WatchedShader {
+    source: ShaderSource,
+    compiled: CompiledShader,
+}
+
+impl WatchedShader {
+    // The files whose modification causes the shader to be recompiled.
+    fn files(&self) -> impl Iterator<Item = &Path> {
+        self.source
+            .path()
+            .into_iter()
+            .chain(self.compiled.includes().iter().map(|path| path.as_path()))
+    }
+}
+
+impl ShaderWatcher<PollWatcher> {
+    /// Creates a watcher that polls the files for changes.
+    #[inline]
+    pub fn new(compiler: ShaderCompiler) -> ShaderWatcher<PollWatcher> {
+        ShaderWatcher::with_watcher(compiler, PollWatcher::new())
+    }
+}
+
+impl<W> ShaderWatcher<W>
+where
+    W: Watcher,
+{
+    /// Creates a watcher that uses `watcher` to detect changes.
+    #[inline]
+    pub fn with_watcher(compiler: ShaderCompiler, watcher: W) -> ShaderWatcher<W> {
+        ShaderWatcher {
+            compiler,
+            watcher,
+            shaders: Vec::new(),
+            watched_files: HashSet::new(),
+        }
+    }
+
+    /// Compiles a shader and starts watching its files.
+    ///
+    /// Only shaders created with [`ShaderSource::from_file`] are recompiled when their own source
+    /// changes. The files that a shader includes are watched in any case.
+    pub fn add(&mut self, source: ShaderSource) -> Result<ShaderId, CompileError> {
+        let compiled = self.compiler.compile(&source)?;
+        let id = ShaderId(self.shaders.len());
+        self.shaders.push(Some(WatchedShader { source, compiled }));
+        self.update_watched_files();
+        Ok(id)
+    }
+
+    /// Stops watching a shader. Its files stop being watched, unless another shader uses them.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the shader was already removed.
+    pub fn remove(&mut self, id: ShaderId) {
+        assert!(self.shaders[id.0].take().is_some());
+        self.update_watched_files();
+    }
+
+    /// Returns the latest version of a shader that compiled successfully.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if the shader was removed.
+    #[inline]
+    pub fn compiled(&self, id: ShaderId) -> &CompiledShader {
+        &self.shaders[id.0].as_ref().unwrap().compiled
+    }
+
+    /// Returns the compiler used for the shaders.
+    #[inline]
+    pub fn compiler(&self) -> &ShaderCompiler {
+        &self.compiler
+    }
+
+    /// Recompiles the shaders whose files changed since the last call.
+    ///
+    /// Returns the shaders that were recompiled, along with the outcome. If a shader fails to
+    /// compile, [`compiled`](ShaderWatcher::compiled) keeps returning its previous version.
+    pub fn poll(&mut self) -> Vec<(ShaderId, Result<(), CompileError>)> {
+        let changes: HashSet<PathBuf> = self.watcher.changes().into_iter().collect();
+        if changes.is_empty() {
+            return Vec::new();
+        }
+
+        let compiler = &self.compiler;
+        let results: Vec<_> = self
+            .shaders
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, shader)| {
+                let shader = shader.as_mut()?;
+                if !shader.files().any(|path| changes.contains(path)) {
+                    return None;
+                }
+
+                let result = shader.source.reread().and_then(|source| {
+                    let compiled = compiler.compile(&source)?;
+                    *shader = WatchedShader { source, compiled };
+                    Ok(())
+                });
+                Some((ShaderId(index), result))
+            })
+            .collect();
+
+        self.update_watched_files();
+        results
+    }
+
+    // Makes `watcher` watch exactly the files used by the shaders.
+    fn update_watched_files(&mut self) {
+        let files: HashSet<PathBuf> = self
+            .shaders
+            .iter()
+            .flatten()
+            .flat_map(|shader| shader.files().map(|path| path.to_owned()))
+            .collect();
+
+        for path in self.watched_files.difference(&files) {
+            self.watcher.unwatch(path);
+        }
+        for path in files.difference(&self.watched_files) {
+            self.watcher.watch(path);
+        }
+        self.watched_files = files;
+    }
+}
+
+/// A pipeline that can be rebuilt by a [`HotReloader`].
+pub trait ReloadablePipeline: Send + Sync + 'static {
+    /// Returns the pipeline layout that the pipeline was created with.
+    fn layout(&self) -> &Arc<PipelineLayout>;
+}
+
+impl ReloadablePipeline for ComputePipeline {
+    #[inline]
+    fn layout(&self) -> &Arc<PipelineLayout> {
+        self.layout()
+    }
+}
+
+impl ReloadablePipeline for GraphicsPipeline {
+    #[inline]
+    fn layout(&self) -> &Arc<PipelineLayout> {
+        self.layout()
+    }
+}
+
+/// Identifies a pipeline added to a [`HotReloader`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PipelineId(usize);
+
+/// Gives access to the latest version of a pipeline added to a [`HotReloader`].
+#[derive(Debug)]
+pub struct PipelineHandle<P> {
+    id: PipelineId,
+    pipeline: Arc<Mutex<Arc<P>>>,
+}
+
+impl<P> PipelineHandle<P> {
+    /// Returns the identifier of the pipeline, as used in the reports of the reloader.
+    #[inline]
+    pub fn id(&self) -> PipelineId {
+        self.id
+    }
+
+    /// Returns the latest version of the pipeline.
+    #[inline]
+    pub fn get(&self) -> Arc<P> {
+        self.pipeline.lock().unwrap().clone()
+    }
+}
+
+impl<P> Clone for PipelineHandle<P> {
+    #[inline]
+    fn clone(&self) -> Self {
+        PipelineHandle {
+            id: self.id,
+            pipeline: self.pipeline.clone(),
+        }
+    }
+}
+
+/// Recompiles shaders when their files change, and rebuilds the pipelines that use them.
+pub struct HotReloader<W = PollWatcher> {
+    device: Arc<Device>,
+    shaders: ShaderWatcher<W>,
+    modules: Vec<Option<Arc<ShaderModule>>>,
+    pipelines: Vec<Box<dyn RegisteredPipeline>>,
+}
+
+impl HotReloader<PollWatcher> {
+    /// Creates a reloader that polls the files for changes.
+    ///
+    /// # Safety
+    ///
+    /// - The shaders, and every version of them loaded afterwards, may require some features
+    ///   that are not enabled on the device. This isn't checked.
+    #[inline]
+    pub unsafe fn new(device: Arc<Device>, compiler: ShaderCompiler) -> HotReloader<PollWatcher> {
+        HotReloader::with_watcher(device, compiler, PollWatcher::new())
+    }
+}
+
+impl<W> HotReloader<W>
+where
+    W: Watcher,
+{
+    /// Creates a reloader that uses `watcher` to detect changes.
+    ///
+    /// # Safety
+    ///
+    /// - The shaders, and every version of them loaded afterwards, may require some features
+    ///   that are not enabled on the device. This isn't checked.
+    #[inline]
+    pub unsafe fn with_watcher(
+        device: Arc<Device>,
+        compiler: ShaderCompiler,
+        watcher: W,
+    ) -> HotReloader<W> {
+        HotReloader {
+            device,
+            shaders: ShaderWatcher::with_watcher(compiler, watcher),
+            modules: Vec::new(),
+            pipelines: Vec::new(),
+        }
+    }
+
+    /// Compiles and loads a shader, and starts watching its files.
+    pub fn add_shader(&mut self, source: ShaderSource) -> Result<ShaderId, AddShaderError> {
+        let id = self.shaders.add(source)?;
+        // Safe because of the safety requirements of the constructors.
+        match unsafe { self.shaders.compiled(id).load(self.device.clone()) } {
+            Ok(module) => {
+                self.modules.push(Some(module));
+                Ok(id)
+            }
+            Err(err) => {
+                self.shaders.remove(id);
+                self.modules.push(None);
+                Err(err.into())
+            }
+        }
+    }
+
+    /// Returns the latest version of a shader that compiled and loaded successfully.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `id` doesn't come from this reloader.
+    #[inline]
+    pub fn module(&self, id: ShaderId) -> &Arc<ShaderModule> {
+        self.modules[id.0].as_ref().unwrap()
+    }
+
+    /// Registers a pipeline that uses the given entry points, so that it is rebuilt when one of
+    /// their shaders changes.
+    ///
+    /// `rebuild` receives the latest version of each shader, in the same order as
+    /// `entry_points`, and the layout of the current pipeline. Creating the new pipeline with
+    /// this layout keeps the descriptor sets created for the current one usable. It is only
+    /// called once the new entry points have been checked to be compatible with the layout.
+    ///
+    /// The returned handle gives access to the latest version of the pipeline.
+    pub fn add_pipeline<P, F, E>(
+        &mut self,
+        entry_points: &[(ShaderId, &str)],
+        pipeline: Arc<P>,
+        rebuild: F,
+    ) -> PipelineHandle<P>
+    where
+        P: ReloadablePipeline,
+        F: FnMut(&[Arc<ShaderModule>], &Arc<PipelineLayout>) -> Result<P, E> + 'static,
+        E: error::Error + Send + Sync + 'static,
+    {
+        let handle = PipelineHandle {
+            id: PipelineId(self.pipelines.len()),
+            pipeline: Arc::new(Mutex::new(pipeline)),
+        };
+        self.pipelines.push(Box::new(PipelineEntry {
+            handle: handle.clone(),
+            entry_points: entry_points
+                .iter()
+                .map(|&(shader, name)| (shader, name.to_owned()))
+                .collect(),
+            rebuild,
+        }));
+        handle
+    }
+
+    /// Recompiles the shaders whose files changed since the last call, and rebuilds the
+    /// pipelines that use them.
+    pub fn reload(&mut self) -> ReloadReport {
+        let mut report = ReloadReport::default();
+
+        for (shader, result) in self.shaders.poll() {
+            let result = result
+                .map_err(|error| ReloadError::CompileError { shader, error })
+                .and_then(|()| unsafe {
+                    // Safe because of the safety requirements of the constructors.
+                    self.shaders
+                        .compiled(shader)
+                        .load(self.device.clone())
+                        .map_err(|error| ReloadError::ShaderCreationError { shader, error })
+                });
+
+            match result {
+                Ok(module) => {
+                    self.modules[shader.0] = Some(module);
+                    report.reloaded_shaders.push(shader);
+                }
+                Err(err) => report.errors.push(err),
+            }
+        }
+
+        if report.reloaded_shaders.is_empty() {
+            return report;
+        }
+
+        for (index, pipeline) in self.pipelines.iter_mut().enumerate() {
+            if !pipeline
+                .shaders()
+                .any(|shader| report.reloaded_shaders.contains(&shader))
+            {
+                continue;
+            }
+
+            let id = PipelineId(index);
+            match pipeline.rebuild(id, &self.modules) {
+                Ok(()) => report.rebuilt_pipelines.push(id),
+                Err(err) => report.errors.push(err),
+            }
+        }
+
+        report
+    }
+}
+
+// Type-erased pipeline registered in a `HotReloader`.
+trait RegisteredPipeline {
+    fn shaders(&self) -> Box<dyn Iterator<Item = ShaderId> + '_>;
+
+    fn rebuild(
+        &mut self,
+        id: PipelineId,
+        modules: &[Option<Arc<ShaderModule>>],
+    ) -> Result<(), ReloadError>;
+}
+
+struct PipelineEntry<P, F> {
+    handle: PipelineHandle<P>,
+    entry_points: Vec<(ShaderId, String)>,
+    rebuild: F,
+}
+
+impl<P, F, E> RegisteredPipeline for PipelineEntry<P, F>
+where
+    P: ReloadablePipeline,
+    F: FnMut(&[Arc<ShaderModule>], &Arc<PipelineLayout>) -> Result<P, E>,
+    E: error::Error + Send + Sync + 'static,
+{
+    fn shaders(&self) -> Box<dyn Iterator<Item = ShaderId> + '_> {
+        Box::new(self.entry_points.iter().map(|&(shader, _)| shader))
+    }
+
+    fn rebuild(
+        &mut self,
+        id: PipelineId,
+        modules: &[Option<Arc<ShaderModule>>],
+    ) -> Result<(), ReloadError> {
+        let layout = self.handle.get().layout().clone();
+        let mut entry_point_modules = Vec::with_capacity(self.entry_points.len());
+
+        for &(shader, ref name) in &self.entry_points {
+            let module = modules[shader.0].as_ref().unwrap();
+            let info = module
+                .entry_points()
+                .iter()
+                .find(|info| info.name().to_bytes() == name.as_bytes())
+                .ok_or_else(|| ReloadError::MissingEntryPoint {
+                    pipeline: id,
+                    shader,
+                    name: name.clone(),
+                })?;
+
+            layout
+                .ensure_compatible_with_shader(
+                    info.descriptor_set_layout_descs(),
+                    info.push_constant_range(),
+                )
+                .map_err(|error| ReloadError::IncompatibleLayout {
+                    pipeline: id,
+                    shader,
+                    error,
+                })?;
+
+            entry_point_modules.push(module.clone());
+        }
+
+        let pipeline = (self.rebuild)(&entry_point_modules, &layout).map_err(|error| {
+            ReloadError::PipelineCreationError {
+                pipeline: id,
+                error: Box::new(error),
+            }
+        })?;
+        *self.handle.pipeline.lock().unwrap() = Arc::new(pipeline);
+
+        Ok(())
+    }
+}
+
+/// What happened during a call to [`HotReloader::reload`].
+#[derive(Debug, Default)]
+pub struct ReloadReport {
+    /// The shaders that were recompiled and loaded successfully.
+    pub reloaded_shaders: Vec<ShaderId>,
+    /// The pipelines that were rebuilt.
+    pub rebuilt_pipelines: Vec<PipelineId>,
+    /// The shaders and pipelines that couldn't be updated. They keep their previous version.
+    pub errors: Vec<ReloadError>,
+}
+
+/// Error that can happen when adding a shader to a [`HotReloader`].
+#[derive(Debug)]
+pub enum AddShaderError {
+    /// The shader failed to compile.
+    CompileError(CompileError),
+    /// Creating the shader module failed.
+    ShaderCreationError(ShaderCreationError),
+}
+
+impl error::Error for AddShaderError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            AddShaderError::CompileError(ref err) => Some(err),
+            AddShaderError::ShaderCreationError(ref err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for AddShaderError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(
+            fmt,
+            "{}",
+            match *self {
+                AddShaderError::CompileError(_) => "the shader failed to compile",
+                AddShaderError::ShaderCreationError(_) => "creating the shader module failed",
+            }
+        )
+    }
+}
+
+impl From<CompileError> for AddShaderError {
+    #[inline]
+    fn from(err: CompileError) -> AddShaderError {
+        AddShaderError::CompileError(err)
+    }
+}
+
+impl From<ShaderCreationError> for AddShaderError {
+    #[inline]
+    fn from(err: ShaderCreationError) -> AddShaderError {
+        AddShaderError::ShaderCreationError(err)
+    }
+}
+
+/// Error that can happen when reloading a shader or rebuilding a pipeline.
+#[derive(Debug)]
+pub enum ReloadError {
+    /// The new version of a shader failed to compile.
+    CompileError {
+        shader: ShaderId,
+        error: CompileError,
+    },
+    /// Creating the shader module of the new version of a shader failed.
+    ShaderCreationError {
+        shader: ShaderId,
+        error: ShaderCreationError,
+    },
+    /// The new version of a shader no longer has an entry point used by a pipeline.
+    MissingEntryPoint {
+        pipeline: PipelineId,
+        shader: ShaderId,
+        name: String,
+    },
+    /// The new version of a shader uses descriptors or push constants that are not in the
+    /// layout of a pipeline.
+    IncompatibleLayout {
+        pipeline: PipelineId,
+        shader: ShaderId,
+        error: PipelineLayoutSupersetError,
+    },
+    /// The function that rebuilds a pipeline returned an error.
+    PipelineCreationError {
+        pipeline: PipelineId,
+        error: Box<dyn error::Error + Send + Sync>,
+    },
+}
+
+impl error::Error for ReloadError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            ReloadError::CompileError { ref error, .. } => Some(error),
+            ReloadError::ShaderCreationError { ref error, .. } => Some(error),
+            ReloadError::MissingEntryPoint { .. } => None,
+            ReloadError::IncompatibleLayout { ref error, .. } => Some(error),
+            ReloadError::PipelineCreationError { ref error, .. } => Some(&**error),
+        }
+    }
+}
+
+impl fmt::Display for ReloadError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            ReloadError::CompileError { shader, ref error } => {
+                write!(fmt, "shader {} failed to recompile: {}", shader.0, error)
+            }
+            ReloadError::ShaderCreationError { shader, ref error } => write!(
+                fmt,
+                "creating the shader module of shader {} failed: {}",
+                shader.0, error
+            ),
+            ReloadError::MissingEntryPoint {
+                pipeline,
+                shader,
+                ref name,
+            } => write!(
+                fmt,
+                "shader {} no longer has the entry point `{}` used by pipeline {}",
+                shader.0, name, pipeline.0
+            ),
+            ReloadError::IncompatibleLayout {
+                pipeline,
+                shader,
+                ref error,
+            } => write!(
+                fmt,
+                "shader {} is no longer compatible with the layout of pipeline {}: {}",
+                shader.0, pipeline.0, error
+            ),
+            ReloadError::PipelineCreationError {
+                pipeline,
+                ref error,
+            } => write!(fmt, "rebuilding pipeline {} failed: {}", pipeline.0, error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ShaderKind;
+    use std::env;
+    use std::process;
+
+    // Creates an empty directory for a test to write its shaders to.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!(
+            "vulkano-shaders-codegen-{}-{}",
+            name,
+            process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_poll_watcher() {
+        let dir = test_dir("poll-watcher");
+        let file = dir.join("file.glsl");
+        let missing = dir.join("missing.glsl");
+        fs::write(&file, "a").unwrap();
+
+        let mut watcher = PollWatcher::new();
+        watcher.watch(&file);
+        watcher.watch(&missing);
+        assert!(watcher.changes().is_empty());
+
+        fs::write(&file, "ab").unwrap();
+        assert_eq!(watcher.changes(), vec![file.clone()]);
+        assert!(watcher.changes().is_empty());
+
+        fs::write(&missing, "a").unwrap();
+        assert_eq!(watcher.changes(), vec![missing.clone()]);
+
+        watcher.unwatch(&file);
+        fs::write(&file, "abc").unwrap();
+        assert!(watcher.changes().is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_shader_watcher() {
+        let dir = test_dir("shader-watcher");
+        let include = dir.join("color.glsl");
+        fs::write(&include, "vec4 color() { return vec4(1.0); }").unwrap();
+        fs::write(
+            dir.join("shader.frag"),
+            "#version 450\n#include \"color.glsl\"\n\
+            layout(location = 0) out vec4 f_color;\n\
+            void main() { f_color = color(); }",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("other.frag"),
+            "#version 450\nlayout(location = 0) out vec4 f_color;\n\
+            void main() { f_color = vec4(0.0); }",
+        )
+        .unwrap();
+
+        let mut watcher = ShaderWatcher::new(ShaderCompiler::new());
+        let shader = watcher
+            .add(ShaderSource::from_file(dir.join("shader.frag"), ShaderKind::Fragment).unwrap())
+            .unwrap();
+        let other = watcher
+            .add(ShaderSource::from_file(dir.join("other.frag"), ShaderKind::Fragment).unwrap())
+            .unwrap();
+        assert!(watcher.poll().is_empty());
+        let first = watcher.compiled(shader).words().to_owned();
+        let other_words = watcher.compiled(other).words().to_owned();
+
+        // Changing an included file only recompiles the shaders that include it.
+        fs::write(&include, "vec4 color() { return vec4(0.25); }").unwrap();
+        let results = watcher.poll();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, shader);
+        assert!(results[0].1.is_ok());
+        let second = watcher.compiled(shader).words().to_owned();
+        assert_ne!(second, first);
+        assert_eq!(watcher.compiled(other).words(), &other_words[..]);
+
+        // A compile error is reported and the previous version is kept.
+        fs::write(&include, "vec4 color() { return oops; }").unwrap();
+        let results = watcher.poll();
+        assert_eq!(results.len(), 1);
+        match results[0].1 {
+            Err(CompileError::CompilationError(_)) => (),
+            ref r => panic!("{:?}", r),
+        }
+        assert_eq!(watcher.compiled(shader).words(), &second[..]);
+
+        // Fixing the error recompiles the shader again.
+        fs::write(&include, "vec4 color() { return vec4(1.0); }").unwrap();
+        let results = watcher.poll();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].1.is_ok());
+        assert_eq!(watcher.compiled(shader).words(), &first[..]);
+
+        // Removed shaders are no longer recompiled.
+        watcher.remove(shader);
+        fs::write(&include, "vec4 color() { return vec4(0.5); }").unwrap();
+        assert!(watcher.poll().is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,344 @@
+// Copyright (c) 2021 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! The shader compilation and Rust code generation behind vulkano's `shader!` macro.
+//!
+//! The `vulkano-shaders` crate is a procedural macro crate, and as such can't export anything
+//! other than the macro itself. This crate contains everything that the macro does, so that
+//! the same code can also be generated outside of a macro invocation. Most users only need
+//! [`build::Builder`], which compiles shader files from a build script and writes the modules
+//! that `shader!` would generate to `OUT_DIR`:
+//!
+//! ```no_run
+//! // build.rs
+//! use vulkano_shaders_codegen::build::{Builder, ShaderFile};
+//! use vulkano_shaders_codegen::ShaderKind;
+//!
+//! fn main() {
+//!     Builder::new("vs.rs")
+//!         .shader(ShaderFile::new("src/shaders/vs.glsl", ShaderKind::Vertex))
+//!         .build()
+//!         .unwrap();
+//! }
+//! ```
+//!
+//! ```ignore
+//! // src/main.rs
+//! mod vs {
+//!     include!(concat!(env!("OUT_DIR"), "/vs.rs"));
+//! }
+//! ```
+//!
+//! The generated code is identical to what the macro generates, so the documentation of the
+//! `shader!` macro applies to it.
+//!
+//! Shaders whose source is only known at runtime, for example because they are edited while the
+//! application runs, can be compiled with the [`runtime`] module instead. The `hot_reload` module,
+//! which is only available with the `hot-reload` feature, builds on it to reload shaders and
+//! rebuild pipelines when the shader files change.
+
+#![doc(html_logo_url = "https://raw.githubusercontent.com/vulkano-rs/vulkano/master/logo.png")]
+#![recursion_limit = "1024"]
+#[macro_use]
+extern crate quote;
+#[macro_use]
+extern crate syn;
+
+pub use crate::codegen::{
+    compile, path_to_str, reflect, write_module, CompilationArtifact, Error, HlslOptions,
+    IncludeType, OptimizationLevel, ResolvedInclude, ResourceKind, ShaderKind, SourceLanguage,
+};
+pub use shaderc::{EnvVersion, SpirvVersion};
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{Read, Result as IoResult};
+use std::path::Path;
+use syn::parse::{Parse, ParseStream, Result};
+use syn::{ItemUse, Meta, MetaList, NestedMeta, Path as SynPath, TypeImplTrait};
+
+pub mod build;
+mod codegen;
+mod descriptor_sets;
+mod entry_point;
+#[cfg(feature = "hot-reload")]
+pub mod hot_reload;
+mod parse;
+pub mod runtime;
+mod spec_consts;
+mod structs;
+
+/// The derives, `use` declarations and trait implementations added to the structs generated in
+/// the `ty` module. This is the `types_meta` option of the `shader!` macro.
+pub struct TypesMeta {
+    pub(crate) custom_derives: Vec<SynPath>,
+    pub(crate) clone: bool,
+    pub(crate) copy: bool,
+    pub(crate) display: bool,
+    pub(crate) debug: bool,
+    pub(crate) default: bool,
+    pub(crate) partial_eq: bool,
+    pub(crate) uses: Vec<ItemUse>,
+    pub(crate) impls: Vec<TypeImplTrait>,
+}
+
+impl Default for TypesMeta {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            custom_derives: vec![],
+            clone: true,
+            copy: true,
+            partial_eq: false,
+            debug: false,
+            display: false,
+            default: false,
+            uses: Vec::new(),
+            impls: Vec::new(),
+        }
+    }
+}
+
+impl TypesMeta {
+    #[inline]
+    fn empty() -> Self {
+        Self {
+            custom_derives: Vec::new(),
+            clone: false,
+            copy: false,
+            partial_eq: false,
+            debug: false,
+            display: false,
+            default: false,
+            uses: Vec::new(),
+            impls: Vec::new(),
+        }
+    }
+}
+
+impl Parse for TypesMeta {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut meta = TypesMeta::empty();
+
+        while !input.is_empty() {
+            if input.peek(Token![#]) {
+                input.parse::<Token![#]>()?;
+
+                let in_brackets;
+                bracketed!(in_brackets in input);
+
+                let derive_list: MetaList = in_brackets.parse()?;
+
+                for derive in derive_list.nested {
+                    match derive {
+                        NestedMeta::Meta(Meta::Path(path)) => {
+                            let custom_derive = if let Some(derive_ident) = path.get_ident() {
+                                match derive_ident.to_string().as_str() {
+                                    "Clone" => {
+                                        if meta.default {
+                                            return Err(in_brackets.error("Duplicate Clone derive"));
+                                        }
+
+                                        meta.clone = true;
+
+                                        false
+                                    }
+                                    "Copy" => {
+                                        if meta.copy {
+                                            return Err(in_brackets.error("Duplicate Copy derive"));
+                                        }
+
+                                        meta.copy = true;
+
+                                        false
+                                    }
+                                    "PartialEq" => {
+                                        if meta.partial_eq {
+                                            return Err(
+                                                in_brackets.error("Duplicate PartialEq derive")
+                                            );
+                                        }
+
+                                        meta.partial_eq = true;
+
+                                        false
+                                    }
+                                    "Debug" => {
+                                        if meta.debug {
+                                            return Err(in_brackets.error("Duplicate Debug derive"));
+                                        }
+
+                                        meta.debug = true;
+
+                                        false
+                                    }
+                                    "Display" => {
+                                        if meta.display {
+                                            return Err(
+                                                in_brackets.error("Duplicate Display derive")
+                                            );
+                                        }
+
+                                        meta.display = true;
+
+                                        false
+                                    }
+                                    "Default" => {
+                                        if meta.default {
+                                            return Err(
+                                                in_brackets.error("Duplicate Default derive")
+                                            );
+                                        }
+
+                                        meta.default = true;
+
+                                        false
+                                    }
+                                    _ => true,
+                                }
+                            } else {
+                                true
+                            };
+
+                            if custom_derive {
+                                if meta
+                                    .custom_derives
+                                    .iter()
+                                    .any(|candidate| candidate.eq(&path))
+                                {
+                                    return Err(input.error("Duplicate derive declaration"));
+                                }
+
+                                meta.custom_derives.push(path);
+                            }
+                        }
+                        _ => return Err(in_brackets.error("Unsupported syntax")),
+                    }
+                }
+
+                continue;
+            }
+
+            if input.peek(Token![impl]) {
+                let impl_trait: TypeImplTrait = input.parse()?;
+
+                if meta.impls.iter().any(|candidate| candidate == &impl_trait) {
+                    return Err(input.error("Duplicate \"impl\" declaration"));
+                }
+
+                meta.impls.push(impl_trait);
+
+                continue;
+            }
+
+            if input.peek(Token![use]) {
+                let item_use: ItemUse = input.parse()?;
+
+                if meta.uses.iter().any(|candidate| candidate == &item_use) {
+                    return Err(input.error("Duplicate \"use\" declaration"));
+                }
+
+                meta.uses.push(item_use);
+
+                continue;
+            }
+
+            return Err(input.error("Type meta must by \"use a::b::c\", \"#[derive(Type1, Type2, ..)]\" or \"impl Type\""));
+        }
+
+        Ok(meta)
+    }
+}
+
+pub(crate) fn read_file_to_string(full_path: &Path) -> IoResult<String> {
+    let mut buf = String::new();
+    File::open(full_path).and_then(|mut file| file.read_to_string(&mut buf))?;
+    Ok(buf)
+}
+
+// The 64-bit FNV-1a hash. The standard library doesn't provide a hash that is guaranteed to stay
+// the same between releases, which the names of cache entries and the layout hashes of push
+// constants rely on.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+/// A struct that has been generated in the `ty` module, along with the shader that declared it.
+///
+/// Shaders that are generated into the same module share their structs, so that they must declare
+/// them with the same fields.
+pub struct RegisteredType {
+    pub(crate) shader: String,
+    pub(crate) signature: Vec<(String, Cow<'static, str>)>,
+}
+
+impl RegisteredType {
+    #[inline]
+    pub(crate) fn assert_signatures(
+        &self,
+        type_name: &str,
+        target_type: &Self,
+    ) -> std::result::Result<(), Error> {
+        if self.signature.len() > target_type.signature.len() {
+            return Err(Error::Reflection(format!(
+                "shaders {shader_a:} and {shader_b:} declare structs with the \
+                same name \"{type_name:}\", but the struct from {shader_a:} shader \
+                contains extra field \"{field:}\"",
+                shader_a = self.shader,
+                shader_b = target_type.shader,
+                type_name = type_name,
+                field = self.signature[target_type.signature.len()].0
+            )));
+        }
+
+        if self.signature.len() < target_type.signature.len() {
+            return Err(Error::Reflection(format!(
+                "shaders {shader_a:} and {shader_b:} declare structs with the \
+                same name \"{type_name:}\", but the struct from {shader_b:} shader \
+                contains extra field \"{field:}\"",
+                shader_a = self.shader,
+                shader_b = target_type.shader,
+                type_name = type_name,
+                field = target_type.signature[self.signature.len()].0
+            )));
+        }
+
+        let comparison = self
+            .signature
+            .iter()
+            .zip(target_type.signature.iter())
+            .enumerate();
+
+        for (index, ((a_name, a_type), (b_name, b_type))) in comparison {
+            if a_name != b_name || a_type != b_type {
+                return Err(Error::Reflection(format!(
+                    "shaders {shader_a:} and {shader_b:} declare structs with the \
+                    same name \"{type_name:}\", but the struct from {shader_a:} shader \
+                    contains field \"{a_name:}\" of type \"{a_type:}\" in position {index:}, \
+                    whereas the same struct from {shader_b:} contains field \"{b_name:}\" \
+                    of type \"{b_type:}\" in the same position",
+                    shader_a = self.shader,
+                    shader_b = target_type.shader,
+                    type_name = type_name,
+                    index = index,
+                    a_name = a_name,
+                    a_type = a_type,
+                    b_name = b_name,
+                    b_type = b_type,
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,607 @@
+// Copyright (c) 2021 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Compiling shaders at runtime.
+//!
+//! The `shader!` macro and the build script [`Builder`](crate::build::Builder) both need the
+//! shader sources when the application is compiled. A [`ShaderCompiler`] compiles GLSL or HLSL
+//! sources while the application runs instead, with the same options and the same handling of
+//! `#include` directives. The resulting SPIR-V code is turned into a shader module whose entry
+//! points are obtained by reflecting the code, as there is no generated code to describe them:
+//!
+//! ```no_run
+//! # use std::sync::Arc;
+//! # use vulkano::device::Device;
+//! # let device: Arc<Device> = return;
+//! use vulkano_shaders_codegen::runtime::{ShaderCompiler, ShaderSource};
+//! use vulkano_shaders_codegen::ShaderKind;
+//!
+//! let compiler = ShaderCompiler::new()
+//!     .include_directory("shaders/include")
+//!     .define("MAX_LIGHTS", "16")
+//!     .cache_directory("target/shader-cache");
+//!
+//! let source = ShaderSource::from_file("shaders/blur.comp", ShaderKind::Compute).unwrap();
+//! let shader = compiler.compile(&source).unwrap();
+//! let module = unsafe { shader.load(device.clone()).unwrap() };
+//! let entry_point = module.reflected_compute_entry_point("main").unwrap();
+//! ```
+//!
+//! # Caching
+//!
+//! If a cache directory is given, the compiled SPIR-V code is stored in it, keyed by a hash of
+//! the source and of the compile options. Each entry also records the content of every file that
+//! the shader included, so that an entry is only reused if none of them changed. Failing to read
+//! or write the cache is not an error; the shader is compiled as if there was no cache.
+
+use crate::codegen::{self, HlslOptions, OptimizationLevel, ShaderKind, SourceLanguage};
+use crate::{fnv1a, path_to_str};
+use shaderc::{EnvVersion, SpirvVersion};
+use std::convert::TryInto;
+use std::error;
+use std::fmt;
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::io::Error as IoError;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+use vulkano::device::Device;
+use vulkano::pipeline::shader::{ShaderCreationError, ShaderModule};
+
+/// The source of a shader to compile with a [`ShaderCompiler`].
+#[derive(Debug, Clone)]
+pub struct ShaderSource {
+    code: String,
+    path: Option<PathBuf>,
+    kind: ShaderKind,
+    language: SourceLanguage,
+    entry_point: String,
+}
+
+impl ShaderSource {
+    /// Describes a shader whose source isn't stored in a file. Such a shader can only include
+    /// files with `#include <...>` and an absolute path or the include directories of the
+    /// compiler.
+    ///
+    /// The shader is written in GLSL and has an entry point named `main` unless specified
+    /// otherwise.
+    #[inline]
+    pub fn new<S>(code: S, kind: ShaderKind) -> ShaderSource
+    where
+        S: Into<String>,
+    {
+        ShaderSource {
+            code: code.into(),
+            path: None,
+            kind,
+            language: SourceLanguage::GLSL,
+            entry_point: "main".to_owned(),
+        }
+    }
+
+    /// Reads the source of a shader from a file. Relative `#include` directives are resolved
+    /// from the directory of the file.
+    ///
+    /// The shader is written in GLSL and has an entry point named `main` unless specified
+    /// otherwise.
+    #[inline]
+    pub fn from_file<P>(path: P, kind: ShaderKind) -> Result<ShaderSource, CompileError>
+    where
+        P: Into<PathBuf>,
+    {
+        let path = path.into();
+        let code = fs::read_to_string(&path).map_err(|error| CompileError::IoError {
+            path: path.clone(),
+            error,
+        })?;
+
+        Ok(ShaderSource {
+            code,
+            path: Some(path),
+            kind,
+            language: SourceLanguage::GLSL,
+            entry_point: "main".to_owned(),
+        })
+    }
+
+    /// Sets the language that the shader is written in.
+    #[inline]
+    pub fn language(mut self, language: SourceLanguage) -> ShaderSource {
+        self.language = language;
+        self
+    }
+
+    /// Sets the name of the entry point function of the shader.
+    #[inline]
+    pub fn entry_point<S>(mut self, entry_point: S) -> ShaderSource
+    where
+        S: Into<String>,
+    {
+        self.entry_point = entry_point.into();
+        self
+    }
+
+    /// Returns the path of the file that the source was read from, if any.
+    #[inline]
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Returns the kind of shader.
+    #[inline]
+    pub fn kind(&self) -> ShaderKind {
+        self.kind
+    }
+
+    // Reads the source again from its file, keeping the other settings. Sources that don't come
+    // from a file are returned unchanged.
+    #[cfg(feature = "hot-reload")]
+    pub(crate) fn reread(&self) -> Result<ShaderSource, CompileError> {
+        match self.path {
+            Some(ref path) => Ok(ShaderSource {
+                code: fs::read_to_string(path).map_err(|error| CompileError::IoError {
+                    path: path.clone(),
+                    error,
+                })?,
+                ..self.clone()
+            }),
+            None => Ok(self.clone()),
+        }
+    }
+}
+
+/// Compiles GLSL and HLSL shaders at runtime.
+///
+/// The options match those of the `shader!` macro. Paths are relative to the current directory.
+#[derive(Debug, Clone, Default)]
+pub struct ShaderCompiler {
+    include_directories: Vec<PathBuf>,
+    macro_defines: Vec<(String, String)>,
+    vulkan_version: Option<EnvVersion>,
+    spirv_version: Option<SpirvVersion>,
+    optimization_level: Option<OptimizationLevel>,
+    hlsl_options: HlslOptions,
+    cache_directory: Option<PathBuf>,
+}
+
+impl ShaderCompiler {
+    /// Creates a compiler with the default options, and without a cache.
+    #[inline]
+    pub fn new() -> ShaderCompiler {
+        ShaderCompiler::default()
+    }
+
+    /// Adds a directory that is searched for files included with `#include <...>`.
+    #[inline]
+    pub fn include_directory<P>(mut self, directory: P) -> ShaderCompiler
+    where
+        P: Into<PathBuf>,
+    {
+        self.include_directories.push(directory.into());
+        self
+    }
+
+    /// Defines a preprocessor macro for all the shaders.
+    #[inline]
+    pub fn define<N, V>(mut self, name: N, value: V) -> ShaderCompiler
+    where
+        N: Into<String>,
+        V: Into<String>,
+    {
+        self.macro_defines.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets the Vulkan version that the shaders are compiled for. The default is Vulkan 1.0.
+    #[inline]
+    pub fn vulkan_version(mut self, version: EnvVersion) -> ShaderCompiler {
+        self.vulkan_version = Some(version);
+        self
+    }
+
+    /// Sets the SPIR-V version that the shaders are compiled to. The default is the highest
+    /// version supported by the Vulkan version.
+    #[inline]
+    pub fn spirv_version(mut self, version: SpirvVersion) -> ShaderCompiler {
+        self.spirv_version = Some(version);
+        self
+    }
+
+    /// Sets how much the compiler optimizes the shaders. The default is not to optimize them.
+    #[inline]
+    pub fn optimization_level(mut self, level: OptimizationLevel) -> ShaderCompiler {
+        self.optimization_level = Some(level);
+        self
+    }
+
+    /// Sets the options that apply to the HLSL shaders.
+    #[inline]
+    pub fn hlsl_options(mut self, options: HlslOptions) -> ShaderCompiler {
+        self.hlsl_options = options;
+        self
+    }
+
+    /// Sets the directory where compiled shaders are cached. It is created if it doesn't exist.
+    #[inline]
+    pub fn cache_directory<P>(mut self, directory: P) -> ShaderCompiler
+    where
+        P: Into<PathBuf>,
+    {
+        self.cache_directory = Some(directory.into());
+        self
+    }
+
+    /// Compiles a shader, or returns it from the cache if it was already compiled with the same
+    /// options and none of the files that it includes changed since.
+    pub fn compile(&self, source: &ShaderSource) -> Result<CompiledShader, CompileError> {
+        let key = self.cache_key(source);
+        let cache_path = self
+            .cache_directory
+            .as_ref()
+            .map(|directory| directory.join(format!("{:016x}.spv-cache", fnv1a(key.as_bytes()))));
+
+        if let Some(shader) = cache_path
+            .as_ref()
+            .and_then(|cache_path| read_cache_entry(cache_path, &key))
+        {
+            return Ok(shader);
+        }
+
+        let (content, includes) = codegen::compile(
+            source
+                .path
+                .as_deref()
+                .map(|path| path_to_str(path).to_owned()),
+            &Path::new(""),
+            &source.code,
+            source.kind,
+            &self.include_directories,
+            &self.macro_defines,
+            self.vulkan_version,
+            self.spirv_version,
+            self.optimization_level,
+            source.language,
+            &source.entry_point,
+            &self.hlsl_options,
+        )
+        .map_err(CompileError::CompilationError)?;
+
+        let shader = CompiledShader {
+            words: content.as_binary().to_owned(),
+            includes: includes.into_iter().map(PathBuf::from).collect(),
+            from_cache: false,
+        };
+
+        if let Some(cache_path) = cache_path {
+            // The cache is only an optimization, so failing to write to it isn't an error.
+            let _ = write_cache_entry(&cache_path, &key, &shader);
+        }
+
+        Ok(shader)
+    }
+
+    // Returns a description of everything that affects the compilation of `source`, apart from
+    // the content of the files that it includes.
+    fn cache_key(&self, source: &ShaderSource) -> String {
+        let mut key = String::new();
+        let _ = write!(
+            key,
+            "vulkano-shaders-codegen {}\n{:?} {:?} {:?}\n{:?} {:?} {:?}\n",
+            env!("CARGO_PKG_VERSION"),
+            source.kind,
+            source.language,
+            source.entry_point,
+            self.vulkan_version,
+            self.spirv_version,
+            self.optimization_level,
+        );
+        let _ = writeln!(key, "{:?}", source.path);
+        let _ = writeln!(key, "{:?}", self.include_directories);
+        let _ = writeln!(key, "{:?}", self.macro_defines);
+        let _ = writeln!(key, "{:?}", self.hlsl_options);
+        key.push_str(&source.code);
+        key
+    }
+}
+
+/// A shader compiled by a [`ShaderCompiler`].
+#[derive(Debug, Clone)]
+pub struct CompiledShader {
+    words: Vec<u32>,
+    includes: Vec<PathBuf>,
+    from_cache: bool,
+}
+
+impl CompiledShader {
+    /// Returns the SPIR-V code of the shader.
+    #[inline]
+    pub fn words(&self) -> &[u32] {
+        &self.words
+    }
+
+    /// Returns the paths of all the files that the shader includes, directly or indirectly.
+    #[inline]
+    pub fn includes(&self) -> &[PathBuf] {
+        &self.includes
+    }
+
+    /// Returns true if the shader was loaded from the cache instead of being compiled.
+    #[inline]
+    pub fn from_cache(&self) -> bool {
+        self.from_cache
+    }
+
+    /// Creates a shader module from the compiled code, whose entry points are described by
+    /// reflecting the code. Like with the `shader!` macro by default, every entry point is assumed
+    /// to use all the descriptors of the module.
+    ///
+    /// # Safety
+    ///
+    /// - The shader may require some features that are not enabled on the device. This isn't
+    ///   checked.
+    #[inline]
+    pub unsafe fn load(
+        &self,
+        device: Arc<Device>,
+    ) -> Result<Arc<ShaderModule>, ShaderCreationError> {
+        ShaderModule::from_words_with_reflection(device, &self.words, false)
+    }
+}
+
+/// Error that can happen when compiling a shader at runtime.
+#[derive(Debug)]
+pub enum CompileError {
+    /// Reading the source of the shader failed.
+    IoError { path: PathBuf, error: IoError },
+    /// The shader failed to compile. The diagnostics of the compiler are given one per line.
+    CompilationError(String),
+}
+
+impl error::Error for CompileError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            CompileError::IoError { ref error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for CompileError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            CompileError::IoError {
+                ref path,
+                ref error,
+            } => write!(fmt, "failed to read `{}`: {}", path.display(), error),
+            CompileError::CompilationError(ref diagnostics) => {
+                write!(fmt, "failed to compile the shader:\n{}", diagnostics)
+            }
+        }
+    }
+}
+
+// Identifies cache entries, followed by the version of their layout.
+const CACHE_MAGIC: &[u8; 8] = b"VKSHADER";
+const CACHE_VERSION: u32 = 1;
+
+// Writes a cache entry. An entry is made of:
+//
+// - `CACHE_MAGIC` and `CACHE_VERSION`.
+// - The cache key, which is compared in full as the name of the entry is only a hash of it.
+// - The number of included files, followed by the path and the hash of the content of each one.
+// - The SPIR-V words.
+//
+// Integers are stored in little endian, and strings and arrays are preceded by their length as a
+// `u64`. The entry is written to a temporary file first, so that readers never see a partially
+// written entry.
+fn write_cache_entry(path: &Path, key: &str, shader: &CompiledShader) -> Result<(), IoError> {
+    let mut data = Vec::with_capacity(key.len() + shader.words.len() * 4 + 64);
+    data.extend_from_slice(CACHE_MAGIC);
+    data.extend_from_slice(&CACHE_VERSION.to_le_bytes());
+    write_bytes(&mut data, key.as_bytes());
+    data.extend_from_slice(&(shader.includes.len() as u64).to_le_bytes());
+    for include in &shader.includes {
+        write_bytes(&mut data, path_to_str(include).as_bytes());
+        data.extend_from_slice(&fnv1a(&fs::read(include)?).to_le_bytes());
+    }
+    data.extend_from_slice(&(shader.words.len() as u64).to_le_bytes());
+    for word in &shader.words {
+        data.extend_from_slice(&word.to_le_bytes());
+    }
+
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    let temp_path = path.with_extension(format!("tmp{}", process::id()));
+    fs::write(&temp_path, &data)?;
+    fs::rename(&temp_path, path).map_err(|err| {
+        let _ = fs::remove_file(&temp_path);
+        err
+    })
+}
+
+fn write_bytes(data: &mut Vec<u8>, bytes: &[u8]) {
+    data.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+    data.extend_from_slice(bytes);
+}
+
+// Reads a cache entry. Returns `None` if the entry doesn't exist, is invalid, was written for
+// another key, or if one of the files that the shader includes changed.
+fn read_cache_entry(path: &Path, key: &str) -> Option<CompiledShader> {
+    let data = fs::read(path).ok()?;
+    let mut reader = CacheReader { data: &data };
+
+    if reader.take(CACHE_MAGIC.len())? != CACHE_MAGIC
+        || reader.take(4)? != CACHE_VERSION.to_le_bytes()
+        || reader.bytes()? != key.as_bytes()
+    {
+        return None;
+    }
+
+    let num_includes = reader.u64()?;
+    let mut includes = Vec::new();
+    for _ in 0..num_includes {
+        let include = PathBuf::from(std::str::from_utf8(reader.bytes()?).ok()?);
+        if reader.u64()? != fnv1a(&fs::read(&include).ok()?) {
+            return None;
+        }
+        includes.push(include);
+    }
+
+    let num_words = reader.u64()? as usize;
+    let words = reader
+        .take(num_words.checked_mul(4)?)?
+        .chunks(4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .collect();
+
+    Some(CompiledShader {
+        words,
+        includes,
+        from_cache: true,
+    })
+}
+
+struct CacheReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> CacheReader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (taken, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(taken)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn bytes(&mut self) -> Option<&'a [u8]> {
+        let len = self.u64()?;
+        self.take(len.try_into().ok()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use vulkano::descriptor_set::layout::DescriptorDescTy;
+    use vulkano::pipeline::shader::{EntryPointInfo, LocalSizeValue, ShaderExecution};
+
+    // Creates an empty directory for a test to write its shaders and cache to.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!(
+            "vulkano-shaders-codegen-{}-{}",
+            name,
+            process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_compile_and_reflect() {
+        let source = ShaderSource::new(
+            "
+            #version 450
+            layout(local_size_x = 8, local_size_y_id = 3) in;
+            layout(set = 0, binding = 1) buffer Data { uint values[]; } data;
+            layout(push_constant) uniform PushConstants { uint offset; mat4 transform; } pc;
+            void main() { data.values[gl_GlobalInvocationID.x] += pc.offset; }
+            ",
+            ShaderKind::Compute,
+        );
+        let shader = ShaderCompiler::new().compile(&source).unwrap();
+        assert!(!shader.from_cache());
+
+        let entry_points = EntryPointInfo::from_spirv(shader.words(), false).unwrap();
+        assert_eq!(entry_points.len(), 1);
+        let entry_point = &entry_points[0];
+        assert_eq!(entry_point.name().to_str().unwrap(), "main");
+        match *entry_point.execution() {
+            ShaderExecution::Compute([x, y, _]) => {
+                assert_eq!(x, LocalSizeValue::Literal(8));
+                assert_eq!(
+                    y,
+                    LocalSizeValue::SpecConstant {
+                        constant_id: 3,
+                        default_value: 1
+                    }
+                );
+            }
+            ref execution => panic!("{:?}", execution),
+        }
+
+        let sets = entry_point.descriptor_set_layout_descs();
+        assert_eq!(sets.len(), 1);
+        let descriptor = sets[0].descriptor(1).unwrap();
+        assert!(matches!(descriptor.ty, DescriptorDescTy::StorageBuffer));
+        assert!(descriptor.mutable);
+        assert!(descriptor.stages.compute);
+        assert!(sets[0].descriptor(0).is_none());
+
+        assert_eq!(entry_point.push_constant_range().unwrap().size, 80);
+    }
+
+    #[test]
+    fn test_cache() {
+        let dir = test_dir("runtime-cache");
+        fs::write(dir.join("color.glsl"), "vec4 color() { return vec4(1.0); }").unwrap();
+        fs::write(
+            dir.join("shader.frag"),
+            "#version 450\n#include \"color.glsl\"\n\
+            layout(location = 0) out vec4 f_color;\n\
+            void main() { f_color = color(); }",
+        )
+        .unwrap();
+
+        let compiler = ShaderCompiler::new().cache_directory(dir.join("cache"));
+        let source =
+            ShaderSource::from_file(dir.join("shader.frag"), ShaderKind::Fragment).unwrap();
+
+        let first = compiler.compile(&source).unwrap();
+        assert!(!first.from_cache());
+        assert_eq!(first.includes().len(), 1);
+
+        let second = compiler.compile(&source).unwrap();
+        assert!(second.from_cache());
+        assert_eq!(second.words(), first.words());
+        assert_eq!(second.includes(), first.includes());
+
+        // Changing an option or an included file invalidates the entry.
+        let defined = compiler.clone().define("UNUSED", "1");
+        assert!(!defined.compile(&source).unwrap().from_cache());
+        fs::write(dir.join("color.glsl"), "vec4 color() { return vec4(0.5); }").unwrap();
+        let third = compiler.compile(&source).unwrap();
+        assert!(!third.from_cache());
+        assert_ne!(third.words(), first.words());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_compile_error() {
+        let source = ShaderSource::new("#version 450\nvoid main() { oops; }", ShaderKind::Vertex);
+        match ShaderCompiler::new().compile(&source) {
+            Err(CompileError::CompilationError(diagnostics)) => {
+                assert!(diagnostics.contains("oops"), "{}", diagnostics)
+            }
+            r => panic!("{:?}", r),
+        }
+    }
+}
@@ -7,6 +7,7 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use crate::codegen::Error;
 use crate::TypesMeta;
 use crate::{structs, RegisteredType};
 use proc_macro2::{Span, TokenStream};
@@ -16,21 +17,6 @@ use std::mem;
 use syn::Ident;
 use vulkano::spirv::{Decoration, Instruction, Spirv};
 
-/// Returns true if the document has specialization constants.
-pub fn has_specialization_constants(spirv: &Spirv) -> bool {
-    for instruction in spirv.iter_global() {
-        match instruction {
-            &Instruction::SpecConstantTrue { .. } => return true,
-            &Instruction::SpecConstantFalse { .. } => return true,
-            &Instruction::SpecConstant { .. } => return true,
-            &Instruction::SpecConstantComposite { .. } => return true,
-            _ => (),
-        }
-    }
-
-    false
-}
-
 /// Writes the `SpecializationConstants` struct that contains the specialization constants and
 /// implements the `Default` and the `vulkano::pipeline::shader::SpecializationConstants` traits.
 pub(super) fn write_specialization_constants<'a>(
@@ -39,7 +25,7 @@ pub(super) fn write_specialization_constants<'a>(
     types_meta: &TypesMeta,
     shared_constants: bool,
     types_registry: &'a mut HashMap<String, RegisteredType>,
-) -> TokenStream {
+) -> Result<TokenStream, Error> {
     struct SpecConst {
         name: String,
         constant_id: u32,
@@ -97,9 +83,11 @@ pub(super) fn write_specialization_constants<'a>(
                     Some(mem::size_of::<u32>()),
                     mem::align_of::<u32>(),
                 ),
-                _ => structs::type_from_id(shader, spirv, result_type_id, types_meta),
+                _ => structs::type_from_id(shader, spirv, result_type_id, types_meta)?,
             };
-        let rust_size = rust_size.expect("Found runtime-sized specialization constant");
+        let rust_size = rust_size.ok_or_else(|| {
+            Error::Reflection("specialization constants can't be runtime-sized".to_owned())
+        })?;
 
         let id_info = spirv.id(result_id);
 
@@ -162,13 +150,14 @@ pub(super) fn write_specialization_constants<'a>(
         // Checking with Registry if this struct already registered by another shader, and if their
         // signatures match.
         if let Some(registered) = types_registry.get(name.as_str()) {
-            registered.assert_signatures(name.as_str(), &target_type);
+            registered.assert_signatures(name.as_str(), &target_type)?;
 
             // If the struct already registered and matches this one, skip duplicate.
-            return quote! {};
+            return Ok(quote! {});
         }
 
-        debug_assert!(types_registry.insert(name, target_type).is_none());
+        let prev = types_registry.insert(name, target_type);
+        debug_assert!(prev.is_none());
     }
 
     let map_entries = {
@@ -205,7 +194,7 @@ pub(super) fn write_specialization_constants<'a>(
         struct_member_defaults.push(quote! { #name: #default_value });
     }
 
-    quote! {
+    Ok(quote! {
         #[derive(Debug, Copy, Clone)]
         #[allow(non_snake_case)]
         #[repr(C)]
@@ -229,5 +218,5 @@ pub(super) fn write_specialization_constants<'a>(
                 &DESCRIPTORS
             }
         }
-    }
+    })
 }
@@ -0,0 +1,1175 @@
+// Copyright (c) 2016 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::codegen::Error;
+use crate::{RegisteredType, TypesMeta};
+use proc_macro2::{Span, TokenStream};
+use std::borrow::Cow;
+use std::cmp;
+use std::collections::HashMap;
+use std::mem;
+use syn::Ident;
+use syn::LitStr;
+use vulkano::spirv::{Decoration, Id, Instruction, Spirv, StorageClass};
+
+/// Translates all the structs that are contained in the SPIR-V document as Rust structs.
+pub(super) fn write_structs<'a>(
+    shader: &'a str,
+    spirv: &Spirv,
+    types_meta: &TypesMeta,
+    types_registry: &'a mut HashMap<String, RegisteredType>,
+) -> Result<TokenStream, Error> {
+    let mut structs = Vec::new();
+
+    for instruction in spirv.iter_global() {
+        if let Instruction::TypeStruct {
+            result_id,
+            member_types,
+        } = instruction
+        {
+            structs.push(
+                write_struct(
+                    shader,
+                    spirv,
+                    *result_id,
+                    member_types,
+                    types_meta,
+                    Some(&mut *types_registry),
+                )?
+                .0,
+            );
+        }
+    }
+
+    Ok(quote! {
+        #( #structs )*
+    })
+}
+
+/// A field of a generated struct.
+struct Member {
+    name: Ident,
+    dummy: bool,
+    ty: TokenStream,
+    signature: Cow<'static, str>,
+    /// The offset of the field, as required by the SPIR-V code.
+    offset: usize,
+    /// The size of the field, or `None` if it's a runtime-sized array.
+    size: Option<usize>,
+}
+
+/// The layout of the matrices in a struct member, given by the decorations of the member.
+#[derive(Clone, Copy, Debug)]
+struct MatrixLayout {
+    stride: usize,
+    row_major: bool,
+}
+
+/// A struct wrapping the element type of an array, whose `ArrayStride` is larger than the size
+/// of the element type.
+struct ArrayWrapper {
+    name: Ident,
+    element_ty: TokenStream,
+    element_signature: Cow<'static, str>,
+    element_size: usize,
+    stride: usize,
+}
+
+/// Analyzes a single struct, returns a string containing its Rust definition, plus its size.
+///
+/// The definition is only written if `types_registry` is `Some`.
+fn write_struct<'a>(
+    shader: &'a str,
+    spirv: &Spirv,
+    struct_id: Id,
+    members: &[Id],
+    types_meta: &TypesMeta,
+    types_registry: Option<&'a mut HashMap<String, RegisteredType>>,
+) -> Result<(TokenStream, Option<usize>), Error> {
+    let id_info = spirv.id(struct_id);
+    let name = Ident::new(&struct_name(spirv, struct_id), Span::call_site());
+
+    // The members of this struct.
+    let mut rust_members = Vec::with_capacity(members.len());
+
+    // The wrappers used as element type by the arrays of this struct.
+    let mut wrappers = Vec::new();
+
+    // Padding structs will be named `_paddingN` where `N` is determined by this variable.
+    let mut next_padding_num = 0;
+
+    // Contains the offset of the next field.
+    // Equals to `None` if there's a runtime-sized field in there.
+    let mut current_rust_offset = Some(0);
+
+    // The alignment of the struct, which is the largest alignment of its members.
+    let mut struct_align = 1;
+
+    for (&member, member_info) in members.iter().zip(id_info.iter_members()) {
+        let member_name = sanitize_ident(
+            member_info
+                .iter_name()
+                .find_map(|instruction| match instruction {
+                    Instruction::MemberName { name, .. } => Some(name.as_str()),
+                    _ => None,
+                })
+                .unwrap_or("__unnamed"),
+        );
+
+        // Ignore the whole struct is a member is built in, which includes
+        // `gl_Position` for example.
+        if member_info.iter_decoration().any(|instruction| {
+            matches!(
+                instruction,
+                Instruction::MemberDecorate {
+                    decoration: Decoration::BuiltIn { .. },
+                    ..
+                }
+            )
+        }) {
+            return Ok((quote! {}, None)); // TODO: is this correct? shouldn't it return a correct struct but with a flag or something?
+        }
+
+        // Finding offset of the current member, as requested by the SPIR-V code.
+        let spirv_offset =
+            member_info
+                .iter_decoration()
+                .find_map(|instruction| match instruction {
+                    Instruction::MemberDecorate {
+                        decoration: Decoration::Offset { byte_offset },
+                        ..
+                    } => Some(*byte_offset),
+                    _ => None,
+                });
+
+        // Some structs don't have `Offset` decorations, in the case they are used as local
+        // variables only. Ignoring these.
+        let spirv_offset = match spirv_offset {
+            Some(o) => o as usize,
+            None => return Ok((quote! {}, None)), // TODO: shouldn't we return and let the caller ignore it instead?
+        };
+
+        // Compute infos about the member.
+        let matrix_layout = member_matrix_layout(member_info.iter_decoration());
+        let (ty, signature, rust_size, rust_align) = type_from_id_with_layout(
+            shader,
+            spirv,
+            member,
+            types_meta,
+            matrix_layout,
+            &mut wrappers,
+        )?;
+        struct_align = cmp::max(struct_align, rust_align);
+
+        // We need to add a dummy field if necessary.
+        {
+            let current_rust_offset = match current_rust_offset.as_mut() {
+                Some(current_rust_offset) => current_rust_offset,
+                None => {
+                    return Err(Error::Reflection(format!(
+                        "member `{}` of struct `{}` comes after a runtime-sized member",
+                        member_name, name
+                    )))
+                }
+            };
+
+            if spirv_offset < *current_rust_offset {
+                return Err(Error::Reflection(format!(
+                    "member `{}` of struct `{}` is at offset {}, which overlaps the previous \
+                    member ending at offset {}",
+                    member_name, name, spirv_offset, *current_rust_offset
+                )));
+            }
+
+            if spirv_offset % rust_align != 0 {
+                return Err(Error::Reflection(format!(
+                    "member `{}` of struct `{}` is at offset {}, which is not a multiple of the \
+                    alignment of its Rust type `{}` ({})",
+                    member_name, name, spirv_offset, signature, rust_align
+                )));
+            }
+
+            if spirv_offset != *current_rust_offset {
+                let diff = spirv_offset - *current_rust_offset;
+                rust_members.push(padding_member(
+                    &mut next_padding_num,
+                    *current_rust_offset,
+                    diff,
+                ));
+                *current_rust_offset += diff;
+            }
+        }
+
+        rust_members.push(Member {
+            name: Ident::new(&member_name, Span::call_site()),
+            dummy: false,
+            ty,
+            signature,
+            offset: spirv_offset,
+            size: rust_size,
+        });
+
+        // Updating `current_rust_offset`.
+        if let Some(s) = rust_size {
+            *current_rust_offset.as_mut().unwrap() += s;
+        } else {
+            current_rust_offset = None;
+        }
+    }
+
+    // Try determine the total size of the struct in order to add padding at the end of the
+    // struct. If the struct is used in arrays that all have the same stride, the struct is padded
+    // to that stride so that it can be used as their element type directly. Otherwise it is padded
+    // to its alignment, and the arrays use a wrapper.
+    let mut spirv_req_total_size = None;
+    let mut array_strides = spirv.iter_global().filter_map(|inst| match inst {
+        Instruction::TypeArray {
+            result_id,
+            element_type,
+            ..
+        }
+        | Instruction::TypeRuntimeArray {
+            result_id,
+            element_type,
+        } if *element_type == struct_id => array_stride(spirv, *result_id),
+        _ => None,
+    });
+    if let Some(first) = array_strides.next() {
+        if array_strides.all(|stride| stride == first) {
+            spirv_req_total_size = Some(first);
+        }
+    }
+
+    // Adding the final padding members.
+    if let Some(cur_size) = current_rust_offset {
+        let req_size = match spirv_req_total_size {
+            Some(req_size) if req_size >= cur_size && req_size % struct_align == 0 => req_size,
+            _ => (cur_size + struct_align - 1) / struct_align * struct_align,
+        };
+
+        if req_size > cur_size {
+            rust_members.push(padding_member(
+                &mut next_padding_num,
+                cur_size,
+                req_size - cur_size,
+            ));
+        }
+
+        current_rust_offset = Some(req_size);
+    }
+
+    let total_size = current_rust_offset;
+
+    // The definition is only needed when writing the structs of the shader, not when computing
+    // the size of a struct member.
+    let types_registry = match types_registry {
+        Some(types_registry) => types_registry,
+        None => return Ok((quote! {}, total_size)),
+    };
+
+    let wrappers = wrappers
+        .into_iter()
+        .map(|wrapper| write_array_wrapper(shader, wrapper, types_meta, types_registry))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Checking with Registry if this struct already registered by another shader, and if their
+    // signatures match. If so, skip the duplicate.
+    if !register_type(shader, &name, &rust_members, types_registry)? {
+        return Ok((quote! { #( #wrappers )* }, total_size));
+    }
+
+    let definition = write_definition(&name, &rust_members, total_size, types_meta);
+    let push_constants_impl = if is_push_constants_block(spirv, struct_id) {
+        write_push_constants_impl(&name, &rust_members, total_size)?
+    } else {
+        quote! {}
+    };
+
+    let ast = quote! {
+        #( #wrappers )*
+        #definition
+        #push_constants_impl
+    };
+
+    Ok((ast, total_size))
+}
+
+/// Returns the name of the struct generated for the push constant block of the module, if the
+/// module declares exactly one.
+pub(super) fn push_constants_struct_name(spirv: &Spirv) -> Option<String> {
+    let mut blocks = spirv
+        .iter_global()
+        .filter_map(|instruction| match instruction {
+            &Instruction::TypePointer {
+                ty,
+                storage_class: StorageClass::PushConstant,
+                ..
+            } => Some(ty),
+            _ => None,
+        });
+    let block = blocks.next()?;
+
+    if blocks.any(|other| other != block) {
+        return None;
+    }
+
+    Some(struct_name(spirv, block))
+}
+
+/// Returns true if the struct `id` is the type of a push constant block.
+fn is_push_constants_block(spirv: &Spirv, id: Id) -> bool {
+    spirv.iter_global().any(|instruction| {
+        matches!(
+            instruction,
+            Instruction::TypePointer {
+                ty,
+                storage_class: StorageClass::PushConstant,
+                ..
+            } if *ty == id
+        )
+    })
+}
+
+/// Implements `PushConstantsLayout` for the struct generated for a push constant block.
+///
+/// The layout hash is computed from the offset and the type of each member, so that two structs
+/// that only differ by their names have the same hash. The names are listed in `MEMBERS`.
+fn write_push_constants_impl(
+    name: &Ident,
+    rust_members: &[Member],
+    size: Option<usize>,
+) -> Result<TokenStream, Error> {
+    if size.is_none() {
+        return Err(Error::Reflection(format!(
+            "push constant block `{}` can't be runtime-sized",
+            name
+        )));
+    }
+
+    // Like the push constant range of the entry points, the pushed bytes go from the first member
+    // to the end of the last one, without the padding at the end of the struct.
+    let offset = rust_members
+        .iter()
+        .find(|member| !member.dummy)
+        .map_or(0, |member| member.offset);
+    let end = rust_members
+        .iter()
+        .filter(|member| !member.dummy)
+        .last()
+        .map_or(offset, |member| member.offset + member.size.unwrap_or(0));
+
+    let mut layout = String::new();
+    for member in rust_members.iter().filter(|member| !member.dummy) {
+        layout.push_str(&format!("{}:{};", member.offset, member.signature));
+    }
+    let layout_hash = crate::fnv1a(layout.as_bytes());
+
+    let members = rust_members
+        .iter()
+        .filter(|member| !member.dummy)
+        .map(|member| {
+            let name = member.name.to_string();
+            let offset = member.offset as u32;
+            let size = member.size.unwrap_or(0) as u32;
+            quote! {
+                ::vulkano::pipeline::layout::PushConstantMember {
+                    name: ::std::borrow::Cow::Borrowed(#name),
+                    offset: #offset,
+                    size: #size,
+                }
+            }
+        });
+
+    let size = (end - offset) as u32;
+    let offset = offset as u32;
+
+    Ok(quote! {
+        unsafe impl ::vulkano::pipeline::layout::PushConstantsLayout for #name {
+            const OFFSET: u32 = #offset;
+            const SIZE: u32 = #size;
+            const LAYOUT_HASH: u64 = #layout_hash;
+            const MEMBERS: &'static [::vulkano::pipeline::layout::PushConstantMember] =
+                &[ #( #members ),* ];
+        }
+    })
+}
+
+/// Returns the layout of the matrices in a struct member, from the decorations of the member.
+fn member_matrix_layout<'a>(
+    decorations: impl Iterator<Item = &'a Instruction>,
+) -> Option<MatrixLayout> {
+    let mut stride = None;
+    let mut row_major = false;
+
+    for instruction in decorations {
+        match instruction {
+            Instruction::MemberDecorate {
+                decoration: Decoration::MatrixStride { matrix_stride },
+                ..
+            } => stride = Some(*matrix_stride as usize),
+            Instruction::MemberDecorate {
+                decoration: Decoration::RowMajor,
+                ..
+            } => row_major = true,
+            _ => (),
+        }
+    }
+
+    stride.map(|stride| MatrixLayout { stride, row_major })
+}
+
+/// Returns the `ArrayStride` of the array type `id`.
+fn array_stride(spirv: &Spirv, id: Id) -> Option<usize> {
+    spirv
+        .id(id)
+        .iter_decoration()
+        .find_map(|instruction| match instruction {
+            Instruction::Decorate {
+                decoration: Decoration::ArrayStride { array_stride },
+                ..
+            } => Some(*array_stride as usize),
+            _ => None,
+        })
+}
+
+/// Returns a `_dummyN` member filling `size` bytes at `offset`.
+fn padding_member(next_padding_num: &mut usize, offset: usize, size: usize) -> Member {
+    let padding_num = *next_padding_num;
+    *next_padding_num += 1;
+
+    Member {
+        name: Ident::new(&format!("_dummy{}", padding_num), Span::call_site()),
+        dummy: true,
+        ty: quote! { [u8; #size] },
+        signature: Cow::from(format!("[u8; {}]", size)),
+        offset,
+        size: Some(size),
+    }
+}
+
+/// Adds a generated type to the registry. Returns `false` if a type with the same name was
+/// already registered by another shader, in which case the two must have the same signature.
+fn register_type(
+    shader: &str,
+    name: &Ident,
+    members: &[Member],
+    types_registry: &mut HashMap<String, RegisteredType>,
+) -> Result<bool, Error> {
+    let target_type = RegisteredType {
+        shader: shader.to_string(),
+        signature: members
+            .iter()
+            .map(|member| (member.name.to_string(), member.signature.clone()))
+            .collect(),
+    };
+
+    let name = name.to_string();
+
+    if let Some(registered) = types_registry.get(name.as_str()) {
+        registered.assert_signatures(name.as_str(), &target_type)?;
+        return Ok(false);
+    }
+
+    let prev = types_registry.insert(name, target_type);
+    debug_assert!(prev.is_none());
+    Ok(true)
+}
+
+/// Writes the wrapper struct used as the element type of an array with a larger stride than the
+/// size of its elements.
+fn write_array_wrapper(
+    shader: &str,
+    wrapper: ArrayWrapper,
+    types_meta: &TypesMeta,
+    types_registry: &mut HashMap<String, RegisteredType>,
+) -> Result<TokenStream, Error> {
+    let ArrayWrapper {
+        name,
+        element_ty,
+        element_signature,
+        element_size,
+        stride,
+    } = wrapper;
+    let padding = stride - element_size;
+
+    let members = [
+        Member {
+            name: Ident::new("value", Span::call_site()),
+            dummy: false,
+            ty: element_ty.clone(),
+            signature: element_signature,
+            offset: 0,
+            size: Some(element_size),
+        },
+        Member {
+            name: Ident::new("_dummy0", Span::call_site()),
+            dummy: true,
+            ty: quote! { [u8; #padding] },
+            signature: Cow::from(format!("[u8; {}]", padding)),
+            offset: element_size,
+            size: Some(padding),
+        },
+    ];
+
+    if !register_type(shader, &name, &members, types_registry)? {
+        return Ok(quote! {});
+    }
+
+    let definition = write_definition(&name, &members, Some(stride), types_meta);
+
+    Ok(quote! {
+        #definition
+
+        impl From<#element_ty> for #name {
+            #[inline]
+            fn from(value: #element_ty) -> Self {
+                #name {
+                    value,
+                    _dummy0: [0; #padding],
+                }
+            }
+        }
+    })
+}
+
+/// Writes the definition of a generated struct, its trait implementations and the assertions on
+/// its layout. `size` is `None` if the struct has a runtime-sized member.
+fn write_definition(
+    name: &Ident,
+    rust_members: &[Member],
+    size: Option<usize>,
+    types_meta: &TypesMeta,
+) -> TokenStream {
+    // We can only implement Clone if there's no unsized member in the struct.
+    let (clone_impl, copy_derive) = if size.is_some() && (types_meta.clone || types_meta.copy) {
+        (
+            if types_meta.clone {
+                let mut copies = vec![];
+                for member in rust_members {
+                    let name = &member.name;
+                    copies.push(quote! { #name: self.#name, });
+                }
+
+                // Clone is implemented manually because members can be large arrays
+                // that do not implement Clone, but do implement Copy
+                quote! {
+                    impl Clone for #name {
+                        fn clone(&self) -> Self {
+                            #name {
+                                #( #copies )*
+                            }
+                        }
+                    }
+                }
+            } else {
+                quote! {}
+            },
+            if types_meta.copy {
+                quote! { #[derive(Copy)] }
+            } else {
+                quote! {}
+            },
+        )
+    } else {
+        (quote! {}, quote! {})
+    };
+
+    let partial_eq_impl = if size.is_some() && types_meta.partial_eq {
+        let mut fields = vec![];
+        for member in rust_members {
+            if !member.dummy {
+                let name = &member.name;
+                fields.push(quote! {
+                    if self.#name != other.#name {
+                        return false
+                    }
+                });
+            }
+        }
+
+        quote! {
+            impl PartialEq for #name {
+                fn eq(&self, other: &Self) -> bool {
+                    #( #fields )*
+                    true
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let (debug_impl, display_impl) = if size.is_some() && (types_meta.debug || types_meta.display) {
+        let mut fields = vec![];
+        for member in rust_members {
+            if !member.dummy {
+                let name = &member.name;
+                let name_string = LitStr::new(name.to_string().as_ref(), name.span());
+
+                fields.push(quote! {.field(#name_string, &self.#name)});
+            }
+        }
+
+        let name_string = LitStr::new(name.to_string().as_ref(), name.span());
+
+        (
+            if types_meta.debug {
+                quote! {
+                    impl std::fmt::Debug for #name {
+                        fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+                            formatter
+                                .debug_struct(#name_string)
+                                #( #fields )*
+                                .finish()
+                        }
+                    }
+                }
+            } else {
+                quote! {}
+            },
+            if types_meta.display {
+                quote! {
+                    impl std::fmt::Display for #name {
+                        fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+                            formatter
+                                .debug_struct(#name_string)
+                                #( #fields )*
+                                .finish()
+                        }
+                    }
+                }
+            } else {
+                quote! {}
+            },
+        )
+    } else {
+        (quote! {}, quote! {})
+    };
+
+    let default_impl = if size.is_some() && types_meta.default {
+        quote! {
+            impl Default for #name {
+                fn default() -> Self {
+                    unsafe {
+                        std::mem::MaybeUninit::<Self>::zeroed().assume_init()
+                    }
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // If the struct has unsized members none of custom impls applied.
+    let custom_impls = if size.is_some() {
+        let impls = &types_meta.impls;
+
+        quote! {
+            #( #impls for #name {} )*
+        }
+    } else {
+        quote! {}
+    };
+
+    // If the struct has unsized members none of custom derives applied.
+    let custom_derives = if size.is_some() && !types_meta.custom_derives.is_empty() {
+        let derive_list = &types_meta.custom_derives;
+        quote! { #[derive(#( #derive_list ),*)] }
+    } else {
+        quote! {}
+    };
+
+    let mut members = vec![];
+    for member in rust_members {
+        let name = &member.name;
+        let ty = &member.ty;
+        members.push(quote!(pub #name: #ty,));
+    }
+
+    let layout_checks = write_layout_checks(name, rust_members, size);
+
+    quote! {
+        #[repr(C)]
+        #copy_derive
+        #custom_derives
+        #[allow(non_snake_case, non_camel_case_types)]
+        pub struct #name {
+            #( #members )*
+        }
+        #clone_impl
+        #partial_eq_impl
+        #debug_impl
+        #display_impl
+        #default_impl
+        #custom_impls
+        #layout_checks
+    }
+}
+
+/// Writes compile-time assertions that the members of a generated struct are at the offsets
+/// required by the SPIR-V code, and that the struct has the expected size.
+fn write_layout_checks(name: &Ident, rust_members: &[Member], size: Option<usize>) -> TokenStream {
+    // `repr(C)` only inserts padding before a field whose offset isn't a multiple of its
+    // alignment. So if each field is aligned when placed right after the previous one, the offset
+    // of each field is the sum of the sizes of the fields before it.
+    let mut checks = Vec::with_capacity(rust_members.len() * 3 + 1);
+
+    for (index, member) in rust_members.iter().enumerate() {
+        let offset = format_ident!("OFFSET_{}", index);
+        let ty = &member.ty;
+
+        let previous_end = if index == 0 {
+            quote! { 0 }
+        } else {
+            let previous_offset = format_ident!("OFFSET_{}", index - 1);
+            let previous_ty = &rust_members[index - 1].ty;
+            quote! { #previous_offset + ::std::mem::size_of::<#previous_ty>() }
+        };
+        checks.push(quote! { const #offset: usize = #previous_end; });
+
+        if !member.dummy {
+            let expected_offset = member.offset;
+            checks.push(quote! { let _: [(); #expected_offset] = [(); #offset]; });
+        }
+
+        if member.size.is_some() {
+            checks.push(quote! {
+                let _: [(); 0] = [(); #offset % ::std::mem::align_of::<#ty>()];
+            });
+        }
+    }
+
+    if let Some(size) = size {
+        checks.push(quote! { let _: [(); #size] = [(); ::std::mem::size_of::<#name>()]; });
+    }
+
+    quote! {
+        const _: () = {
+            #( #checks )*
+        };
+    }
+}
+
+/// Returns the type name to put in the Rust struct, and its size and alignment.
+///
+/// The size can be `None` if it's only known at runtime.
+pub(super) fn type_from_id(
+    shader: &str,
+    spirv: &Spirv,
+    searched: Id,
+    types_meta: &TypesMeta,
+) -> Result<(TokenStream, Cow<'static, str>, Option<usize>, usize), Error> {
+    type_from_id_with_layout(shader, spirv, searched, types_meta, None, &mut Vec::new())
+}
+
+/// Same as `type_from_id`, but with the layout of the matrices given by the struct member that
+/// contains the type. The wrappers needed by arrays with a larger stride than the size of their
+/// elements are added to `wrappers`.
+fn type_from_id_with_layout(
+    shader: &str,
+    spirv: &Spirv,
+    searched: Id,
+    types_meta: &TypesMeta,
+    matrix_layout: Option<MatrixLayout>,
+    wrappers: &mut Vec<ArrayWrapper>,
+) -> Result<(TokenStream, Cow<'static, str>, Option<usize>, usize), Error> {
+    let id_info = spirv.id(searched);
+
+    match id_info.instruction() {
+        Instruction::TypeBool { .. } => {
+            return Err(Error::Reflection(
+                "booleans can't be put in structs".to_owned(),
+            ));
+        }
+        Instruction::TypeInt {
+            width, signedness, ..
+        } => match (width, signedness) {
+            (8, 1) => {
+                #[repr(C)]
+                struct Foo {
+                    data: i8,
+                    after: u8,
+                }
+                return Ok((
+                    quote! {i8},
+                    Cow::from("i8"),
+                    Some(std::mem::size_of::<i8>()),
+                    mem::align_of::<Foo>(),
+                ));
+            }
+            (8, 0) => {
+                #[repr(C)]
+                struct Foo {
+                    data: u8,
+                    after: u8,
+                }
+                return Ok((
+                    quote! {u8},
+                    Cow::from("u8"),
+                    Some(std::mem::size_of::<u8>()),
+                    mem::align_of::<Foo>(),
+                ));
+            }
+            (16, 1) => {
+                #[repr(C)]
+                struct Foo {
+                    data: i16,
+                    after: u8,
+                }
+                return Ok((
+                    quote! {i16},
+                    Cow::from("i16"),
+                    Some(std::mem::size_of::<i16>()),
+                    mem::align_of::<Foo>(),
+                ));
+            }
+            (16, 0) => {
+                #[repr(C)]
+                struct Foo {
+                    data: u16,
+                    after: u8,
+                }
+                return Ok((
+                    quote! {u16},
+                    Cow::from("u16"),
+                    Some(std::mem::size_of::<u16>()),
+                    mem::align_of::<Foo>(),
+                ));
+            }
+            (32, 1) => {
+                #[repr(C)]
+                struct Foo {
+                    data: i32,
+                    after: u8,
+                }
+                return Ok((
+                    quote! {i32},
+                    Cow::from("i32"),
+                    Some(std::mem::size_of::<i32>()),
+                    mem::align_of::<Foo>(),
+                ));
+            }
+            (32, 0) => {
+                #[repr(C)]
+                struct Foo {
+                    data: u32,
+                    after: u8,
+                }
+                return Ok((
+                    quote! {u32},
+                    Cow::from("u32"),
+                    Some(std::mem::size_of::<u32>()),
+                    mem::align_of::<Foo>(),
+                ));
+            }
+            (64, 1) => {
+                #[repr(C)]
+                struct Foo {
+                    data: i64,
+                    after: u8,
+                }
+                return Ok((
+                    quote! {i64},
+                    Cow::from("i64"),
+                    Some(std::mem::size_of::<i64>()),
+                    mem::align_of::<Foo>(),
+                ));
+            }
+            (64, 0) => {
+                #[repr(C)]
+                struct Foo {
+                    data: u64,
+                    after: u8,
+                }
+                return Ok((
+                    quote! {u64},
+                    Cow::from("u64"),
+                    Some(std::mem::size_of::<u64>()),
+                    mem::align_of::<Foo>(),
+                ));
+            }
+            _ => {
+                return Err(Error::Reflection(format!(
+                    "no Rust equivalent for an integer of width {}",
+                    width
+                )))
+            }
+        },
+        Instruction::TypeFloat { width, .. } => match width {
+            32 => {
+                #[repr(C)]
+                struct Foo {
+                    data: f32,
+                    after: u8,
+                }
+                return Ok((
+                    quote! {f32},
+                    Cow::from("f32"),
+                    Some(std::mem::size_of::<f32>()),
+                    mem::align_of::<Foo>(),
+                ));
+            }
+            64 => {
+                #[repr(C)]
+                struct Foo {
+                    data: f64,
+                    after: u8,
+                }
+                return Ok((
+                    quote! {f64},
+                    Cow::from("f64"),
+                    Some(std::mem::size_of::<f64>()),
+                    mem::align_of::<Foo>(),
+                ));
+            }
+            _ => {
+                return Err(Error::Reflection(format!(
+                    "no Rust equivalent for a floating-point of width {}",
+                    width
+                )))
+            }
+        },
+        &Instruction::TypeVector {
+            component_type,
+            component_count,
+            ..
+        } => {
+            debug_assert_eq!(mem::align_of::<[u32; 3]>(), mem::align_of::<u32>());
+            let (ty, item, t_size, t_align) = type_from_id_with_layout(
+                shader,
+                spirv,
+                component_type,
+                types_meta,
+                None,
+                wrappers,
+            )?;
+            let array_length = component_count as usize;
+            let size = t_size.map(|s| s * component_count as usize);
+            return Ok((
+                quote! { [#ty; #array_length] },
+                Cow::from(format!("[{}; {}]", item, array_length)),
+                size,
+                t_align,
+            ));
+        }
+        &Instruction::TypeMatrix {
+            column_type,
+            column_count,
+            ..
+        } => {
+            let (component_type, row_count) = match spirv.id(column_type).instruction() {
+                &Instruction::TypeVector {
+                    component_type,
+                    component_count,
+                    ..
+                } => (component_type, component_count as usize),
+                _ => {
+                    return Err(Error::Reflection(format!(
+                        "matrix column type #{} is not a vector",
+                        column_type
+                    )))
+                }
+            };
+            let (ty, item, t_size, t_align) = type_from_id_with_layout(
+                shader,
+                spirv,
+                component_type,
+                types_meta,
+                None,
+                wrappers,
+            )?;
+            let t_size = t_size
+                .ok_or_else(|| Error::Reflection("matrix components must be sized".to_owned()))?;
+            let column_count = column_count as usize;
+
+            // A column-major matrix is stored as an array of columns, and a row-major matrix as an
+            // array of rows. These vectors are `MatrixStride` bytes apart, so they get extra
+            // components if the stride is larger than their size.
+            let (vector_count, vector_length) = match matrix_layout {
+                Some(MatrixLayout {
+                    row_major: true, ..
+                }) => (row_count, column_count),
+                _ => (column_count, row_count),
+            };
+            let stride = matrix_layout.map_or(vector_length * t_size, |layout| layout.stride);
+            if stride < vector_length * t_size || stride % t_size != 0 {
+                return Err(Error::Reflection(format!(
+                    "not possible to generate a Rust matrix with a MatrixStride of {} bytes and \
+                    vectors of {} `{}` components",
+                    stride, vector_length, item
+                )));
+            }
+            let padded_length = stride / t_size;
+            return Ok((
+                quote! { [[#ty; #padded_length]; #vector_count] },
+                Cow::from(format!("[[{}; {}]; {}]", item, padded_length, vector_count)),
+                Some(stride * vector_count),
+                t_align,
+            ));
+        }
+        &Instruction::TypeArray {
+            element_type,
+            length,
+            ..
+        } => {
+            let (ty, item, t_size, t_align) = type_from_id_with_layout(
+                shader,
+                spirv,
+                element_type,
+                types_meta,
+                matrix_layout,
+                wrappers,
+            )?;
+            let t_size = t_size.ok_or_else(|| {
+                Error::Reflection(format!("the elements of array `{}` must be sized", item))
+            })?;
+            let len = match spirv.id(length).instruction() {
+                &Instruction::Constant { ref value, .. } => value,
+                _ => {
+                    return Err(Error::Reflection(format!(
+                        "the length of array `[{}]` must be a constant",
+                        item
+                    )))
+                }
+            };
+            let len = len.iter().rev().fold(0u64, |a, &b| (a << 32) | b as u64);
+            let (ty, item, stride) =
+                array_element(spirv, searched, ty, item, t_size, t_align, wrappers)?;
+            let array_length = len as usize;
+            let size = Some(stride * array_length);
+            return Ok((
+                quote! { [#ty; #array_length] },
+                Cow::from(format!("[{}; {}]", item, array_length)),
+                size,
+                t_align,
+            ));
+        }
+        &Instruction::TypeRuntimeArray { element_type, .. } => {
+            let (ty, item, t_size, t_align) = type_from_id_with_layout(
+                shader,
+                spirv,
+                element_type,
+                types_meta,
+                matrix_layout,
+                wrappers,
+            )?;
+            let t_size = t_size.ok_or_else(|| {
+                Error::Reflection(format!("the elements of array `{}` must be sized", item))
+            })?;
+            let (ty, item, _) =
+                array_element(spirv, searched, ty, item, t_size, t_align, wrappers)?;
+            return Ok((
+                quote! { [#ty] },
+                Cow::from(format!("[{}]", item)),
+                None,
+                t_align,
+            ));
+        }
+        Instruction::TypeStruct { member_types, .. } => {
+            let name_string = struct_name(spirv, searched);
+            let name = Ident::new(&name_string, Span::call_site());
+            let ty = quote! { #name };
+            let (_, size) = write_struct(shader, spirv, searched, member_types, types_meta, None)?;
+            let mut align = 1;
+            for &member_type in member_types {
+                align = cmp::max(
+                    align,
+                    type_from_id(shader, spirv, member_type, types_meta)?.3,
+                );
+            }
+            return Ok((ty, Cow::from(name_string), size, align));
+        }
+        _ => {
+            return Err(Error::Reflection(format!(
+                "type #{} not found or has no Rust equivalent",
+                searched
+            )))
+        }
+    }
+}
+
+/// Returns the element type of the array `array_id` and its stride. If the `ArrayStride` of the
+/// array is larger than the size of the element type, the element type is wrapped in a struct
+/// with padding at the end, which is added to `wrappers`.
+fn array_element(
+    spirv: &Spirv,
+    array_id: Id,
+    ty: TokenStream,
+    signature: Cow<'static, str>,
+    size: usize,
+    align: usize,
+    wrappers: &mut Vec<ArrayWrapper>,
+) -> Result<(TokenStream, Cow<'static, str>, usize), Error> {
+    // Arrays that aren't in a block have no stride.
+    let stride = array_stride(spirv, array_id).unwrap_or(size);
+
+    if stride == size {
+        return Ok((ty, signature, stride));
+    }
+
+    if stride < size || stride % align != 0 {
+        return Err(Error::Reflection(format!(
+            "not possible to generate a Rust array with an ArrayStride of {} bytes and elements \
+            of type `{}`, whose size is {} bytes and alignment is {} bytes",
+            stride, signature, size, align
+        )));
+    }
+
+    // `[[f32; 3]; 2]` becomes `f32x3x2`.
+    let name = format!(
+        "{}_stride{}",
+        sanitize_ident(&signature.replace("; ", "x").replace(&['[', ']'][..], "")),
+        stride
+    );
+    let ident = Ident::new(&name, Span::call_site());
+
+    if !wrappers.iter().any(|wrapper| wrapper.name == ident) {
+        wrappers.push(ArrayWrapper {
+            name: ident.clone(),
+            element_ty: ty,
+            element_signature: signature,
+            element_size: size,
+            stride,
+        });
+    }
+
+    Ok((quote! { #ident }, Cow::from(name), stride))
+}
+
+/// Returns the name of the Rust struct generated for the SPIR-V struct `id`.
+fn struct_name(spirv: &Spirv, id: Id) -> String {
+    sanitize_ident(
+        spirv
+            .id(id)
+            .iter_name()
+            .find_map(|instruction| match instruction {
+                Instruction::Name { name, .. } => Some(name.as_str()),
+                _ => None,
+            })
+            .unwrap_or("__unnamed"),
+    )
+}
+
+/// Turns a SPIR-V name into a valid Rust identifier.
+///
+/// HLSL compilers create names that aren't valid identifiers, such as `$Global` for the block of
+/// global uniforms or `@data` for the contents of structured buffers. Their invalid characters are
+/// replaced with underscores.
+pub(super) fn sanitize_ident(name: &str) -> String {
+    let mut sanitized: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    if sanitized.is_empty() || sanitized.starts_with(|c: char| c.is_ascii_digit()) {
+        sanitized.insert(0, '_');
+    }
+
+    sanitized
+}
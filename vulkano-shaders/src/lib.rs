@@ -24,7 +24,7 @@
 //!
 //! If you want to take a look at what the macro generates, your best options
 //! are to either read through the code that handles the generation (the
-//! [`reflect`][reflect] function in the `vulkano-shaders` crate) or use a tool
+//! [`reflect`][reflect] function in the `vulkano-shaders-codegen` crate) or use a tool
 //! such as [cargo-expand][cargo-expand] to view the expansion of the macro in your
 //! own code. It is unfortunately not possible to provide a `generated_example`
 //! module like some normal macro crates do since derive macros cannot be used from
@@ -38,12 +38,15 @@
 //! `Arc<ShaderModule>`.
 //! * The `Shader::load` constructor. This method takes an `Arc<Device>`, calls
 //! [`ShaderModule::new`][ShaderModule::new] with the passed-in device and the
-//! shader data provided via the macro, and returns `Result<Shader, OomError>`.
-//! Before doing so, it loops through every capability instruction in the shader
-//! data, verifying that the passed-in `Device` has the appropriate features
-//! enabled. **This function currently panics if a feature required by the shader
-//! is not enabled on the device.** At some point in the future it will return
-//! an error instead.
+//! shader data provided via the macro, and returns `Result<Shader, ShaderLoadError>`.
+//! Before doing so, it checks that the passed-in `Device` meets the requirements of the
+//! SPIR-V version and of every capability used by the shader. If it doesn't, a
+//! [`ShaderLoadError::RequirementsNotMet`][ShaderLoadError] listing every missing API
+//! version, extension and feature is returned.
+//! * The `Shader::requirements` function. This returns the minimum API version, the
+//! `DeviceExtensions` and the `Features` needed by the shader, as a
+//! [`ShaderRequirements`][ShaderRequirements]. It doesn't need a device, so its
+//! result can be used to create one, or to choose a fallback shader.
 //! * The `Shader::module` method. This method simply returns a reference to the
 //! `Arc<ShaderModule>` contained within the `shader` field of the `Shader`
 //! struct.
@@ -58,6 +61,20 @@
 //! specialization constant found in the shader data. Implementations of
 //! `Default` and [`SpecializationConstants`][SpecializationConstants] are also
 //! generated for the struct.
+//! * A `SetN` struct for each descriptor set `N` used by the shader. It has one field
+//! per descriptor, named after the shader variable, whose type depends on the kind
+//! of descriptor: `Arc<dyn BufferAccess>` for a uniform or storage block, an
+//! `Arc<dyn ImageViewAbstract>` and an `Arc<Sampler>` for a `sampler2D`, a `Vec` for
+//! an array of descriptors, and so on. Its `build` method takes the
+//! `DescriptorSetLayout` and creates a `PersistentDescriptorSet`, binding each
+//! field to the right descriptor. The struct also has a `SET` constant with the
+//! number of the set, and a `NAME_BINDING` constant with the binding number of
+//! each descriptor.
+//! * A `PushConstants` type alias for the struct of the push constant block, if the
+//! shader has one. The struct implements [`PushConstantsLayout`], and the entry
+//! points of the shader record its layout, so that
+//! `AutoCommandBufferBuilder::push_constants_typed` can check that the pipeline
+//! was created with shaders that expect this exact type.
 //!
 //! All of these generated items will be accessed through the module when the macro was invoked.
 //! If you wanted to store the `Shader` in a struct of your own, you could do something like this:
@@ -65,8 +82,8 @@
 //! ```
 //! # fn main() {}
 //! # use std::sync::Arc;
-//! # use vulkano::OomError;
 //! # use vulkano::device::Device;
+//! # use vulkano::pipeline::shader::ShaderLoadError;
 //! #
 //! # mod vs {
 //! #     vulkano_shaders::shader!{
@@ -89,7 +106,7 @@
 //! }
 //!
 //! impl Shaders {
-//!     pub fn load(device: Arc<Device>) -> Result<Self, OomError> {
+//!     pub fn load(device: Arc<Device>) -> Result<Self, ShaderLoadError> {
 //!         Ok(Self {
 //!             vs: vs::Shader::load(device)?,
 //!         })
@@ -97,6 +114,42 @@
 //! }
 //! ```
 //!
+//! # Struct layout
+//!
+//! The generated structs follow the `Offset`, `ArrayStride`, `MatrixStride` and
+//! `RowMajor`/`ColMajor` decorations of the SPIR-V code exactly, whichever of the std140, std430
+//! or scalar block layouts the shader uses:
+//!
+//! * Gaps between members are filled with `_dummyN: [u8; N]` fields, and so is the end of a
+//!   struct up to the stride of the arrays it is used in, or else up to its alignment.
+//! * Matrices are arrays of columns, or of rows for `row_major` matrices. Each column (or row)
+//!   has extra components if the `MatrixStride` is larger than its size, so a std140 `mat3`
+//!   becomes a `[[f32; 4]; 3]`.
+//! * Arrays whose `ArrayStride` is larger than the size of their elements, such as a `vec3[2]`
+//!   or a `float[4]` in a std140 block, use a wrapper struct as element type. The wrapper is
+//!   named after the element type and the stride, for example `f32x3_stride16`, and has a
+//!   `value` field followed by padding. It implements `From` for the element type.
+//!
+//! Each generated struct comes with compile-time assertions on its size and on the offset of
+//! each of its members, so that a mismatch between the Rust and SPIR-V layouts is a compilation
+//! error rather than corrupted data.
+//!
+//! # Errors
+//!
+//! Mistakes in the macro input are reported as compilation errors pointing at the faulty argument.
+//! Errors in a shader, whether from the compiler or because the macro can't generate Rust code
+//! for part of it, point at the `src`, `path` or `bytes` argument of that shader. Compiler
+//! errors are reported one by one with their line in the source, followed by the chain of files
+//! that included it if the error is in an included file.
+//!
+//! # Build scripts
+//!
+//! The macro compiles its shaders every time the crate that invokes it is compiled. Shaders can
+//! instead be compiled by a build script with the `Builder` of the `vulkano-shaders-codegen`
+//! crate, which writes the same generated code to a file in `OUT_DIR` that is then included with
+//! `include!`. The build script only runs again when one of the shader files changes. See the
+//! [`build`][build] module of that crate for details.
+//!
 //! # Options
 //!
 //! The options available are in the form of the following attributes:
@@ -117,14 +170,46 @@
 //!
 //! ## `src: "..."`
 //!
-//! Provides the raw GLSL source to be compiled in the form of a string. Cannot
+//! Provides the raw GLSL or HLSL source to be compiled in the form of a string. Cannot
 //! be used in conjunction with the `path` or `bytes` field.
 //!
 //! ## `path: "..."`
 //!
-//! Provides the path to the GLSL source to be compiled, relative to `Cargo.toml`.
+//! Provides the path to the GLSL or HLSL source to be compiled, relative to `Cargo.toml`.
 //! Cannot be used in conjunction with the `src` or `bytes` field.
 //!
+//! ## `lang: "..."`
+//!
+//! The language of the source given with `src` or `path`, either `glsl` (the default) or `hlsl`.
+//!
+//! ```
+//! mod cs {
+//!     vulkano_shaders::shader!{
+//!         ty: "compute",
+//!         lang: "hlsl",
+//!         entry_point: "CSMain",
+//!         src: "
+//! RWStructuredBuffer<uint> data : register(u0);
+//!
+//! [numthreads(64, 1, 1)]
+//! void CSMain(uint3 id : SV_DispatchThreadID) {
+//!     data[id.x] *= 12;
+//! }"
+//!     }
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! For HLSL, resources are mapped to descriptors the same way as with DXC's SPIR-V backend:
+//! `register(xN, spaceM)` is placed in descriptor set `M` at binding `N`, shifted by the
+//! `hlsl_binding_shifts` option (see below). Resources without a register are given the next
+//! free binding.
+//!
+//! ## `entry_point: "..."`
+//!
+//! The name of the function to use as entry point, `main` by default. The generated method that
+//! returns the entry point is named `<entry_point>_entry_point`.
+//!
 //! ## `bytes: "..."`
 //!
 //! Provides the path to precompiled SPIR-V bytecode, relative to `Cargo.toml`.
@@ -143,7 +228,8 @@
 //! source structs with the same names between different shaders have the same declaration
 //! signature, and throws a compile-time error if they don't.
 //!
-//! Each entry values expecting `src`, `path`, `bytes`, and `ty` pairs same as above.
+//! Each entry values expecting `src`, `path`, `bytes`, `ty`, `lang` and `entry_point` pairs same
+//! as above. Shaders written in different languages can be mixed in the same invocation.
 //!
 //! Also `SpecializationConstants` can all be shared between shaders by specifying
 //! `shared_constants: true,` entry-flag of the `shaders` map. This feature is turned-off by
@@ -163,6 +249,18 @@
 //! Adds the given macro definitions to the pre-processor. This is equivalent to passing `-DNAME=VALUE`
 //! on the command line.
 //!
+//! ## `hlsl_binding_shifts: [("t", 16), ...]`
+//!
+//! Shifts the binding numbers of the HLSL registers of the given type, which is one of `b`
+//! (constant buffers), `t` (textures and read-only buffers), `s` (samplers) or `u` (unordered
+//! access views). This is equivalent to DXC's `-fvk-{b,t,s,u}-shift` options, and makes it
+//! possible to keep a register layout shared with a DirectX backend.
+//!
+//! ## `hlsl_registers: [("t4", 1, 2), ...]`
+//!
+//! Explicitly places the given HLSL register in a descriptor set and binding, here set 1 and
+//! binding 2. This takes precedence over the register space and `hlsl_binding_shifts`.
+//!
 //! ## `vulkan_version: "major.minor"` and `spirv_version: "major.minor"`
 //!
 //! Sets the Vulkan and SPIR-V versions to compile into, respectively. These map directly to the
@@ -206,174 +304,82 @@
 //! in each entrypoint.
 //!
 //! The macro determines which resources are used by looking at each entrypoint's
-//! interface and bytecode. See [`src/descriptor_sets.rs`][descriptor_sets]
-//! for the exact logic.
+//! interface and bytecode. See [`src/pipeline/shader/reflect.rs`][descriptor_sets]
+//! in vulkano for the exact logic.
 //!
 //! ## `dump: true`
 //!
 //! The crate fails to compile but prints the generated rust code to stdout.
 //!
-//! [reflect]: https://github.com/vulkano-rs/vulkano/blob/master/vulkano-shaders/src/lib.rs#L67
+//! [reflect]: https://github.com/vulkano-rs/vulkano/blob/master/vulkano-shaders-codegen/src/codegen.rs
+//! [build]: https://docs.rs/vulkano-shaders-codegen/*/vulkano_shaders_codegen/build/index.html
 //! [cargo-expand]: https://github.com/dtolnay/cargo-expand
 //! [ShaderModule::new]: https://docs.rs/vulkano/*/vulkano/pipeline/shader/struct.ShaderModule.html#method.new
 //! [OomError]: https://docs.rs/vulkano/*/vulkano/enum.OomError.html
+//! [ShaderLoadError]: https://docs.rs/vulkano/*/vulkano/pipeline/shader/enum.ShaderLoadError.html
+//! [ShaderRequirements]: https://docs.rs/vulkano/*/vulkano/pipeline/shader/struct.ShaderRequirements.html
 //! [pipeline::shader]: https://docs.rs/vulkano/*/vulkano/pipeline/shader/index.html
 //! [descriptor]: https://docs.rs/vulkano/*/vulkano/descriptor/index.html
 //! [ShaderStages]: https://docs.rs/vulkano/*/vulkano/descriptor/descriptor/struct.ShaderStages.html
 //! [SpecializationConstants]: https://docs.rs/vulkano/*/vulkano/pipeline/shader/trait.SpecializationConstants.html
+//! [PushConstantsLayout]: https://docs.rs/vulkano/*/vulkano/pipeline/layout/trait.PushConstantsLayout.html
 //! [pipeline]: https://docs.rs/vulkano/*/vulkano/pipeline/index.html
-//! [descriptor_sets]: https://github.com/vulkano-rs/vulkano/blob/master/vulkano-shaders/src/descriptor_sets.rs#L142
+//! [descriptor_sets]: https://github.com/vulkano-rs/vulkano/blob/master/vulkano/src/pipeline/shader/reflect.rs
 
 #![doc(html_logo_url = "https://raw.githubusercontent.com/vulkano-rs/vulkano/master/logo.png")]
-#![recursion_limit = "1024"]
-#[macro_use]
-extern crate quote;
 #[macro_use]
 extern crate syn;
 extern crate proc_macro;
 
-use crate::codegen::ShaderKind;
-use shaderc::{EnvVersion, SpirvVersion};
-use std::borrow::Cow;
+use proc_macro2::{Span, TokenStream};
 use std::collections::HashMap;
+use std::fmt::Display;
 use std::fs;
-use std::fs::File;
-use std::io::{Read, Result as IoResult};
 use std::path::Path;
 use std::slice::from_raw_parts;
 use std::{env, iter::empty};
 use syn::parse::{Parse, ParseStream, Result};
-use syn::{
-    Ident, ItemUse, LitBool, LitStr, Meta, MetaList, NestedMeta, Path as SynPath, TypeImplTrait,
+use syn::{Error, Ident, LitBool, LitInt, LitStr};
+use vulkano_shaders_codegen as codegen;
+use vulkano_shaders_codegen::{
+    EnvVersion, HlslOptions, ResourceKind, ShaderKind, SourceLanguage, SpirvVersion, TypesMeta,
 };
 
-mod codegen;
-mod descriptor_sets;
-mod entry_point;
-mod parse;
-mod spec_consts;
-mod spirv_search;
-mod structs;
-
 enum SourceKind {
-    Src(String),
-    Path(String),
-    Bytes(String),
-}
-
-struct TypesMeta {
-    custom_derives: Vec<SynPath>,
-    clone: bool,
-    copy: bool,
-    display: bool,
-    debug: bool,
-    default: bool,
-    partial_eq: bool,
-    uses: Vec<ItemUse>,
-    impls: Vec<TypeImplTrait>,
-}
-
-impl Default for TypesMeta {
-    #[inline]
-    fn default() -> Self {
-        Self {
-            custom_derives: vec![],
-            clone: true,
-            copy: true,
-            partial_eq: false,
-            debug: false,
-            display: false,
-            default: false,
-            uses: Vec::new(),
-            impls: Vec::new(),
-        }
-    }
+    Src(LitStr),
+    Path(LitStr),
+    Bytes(LitStr),
 }
 
-impl TypesMeta {
+impl SourceKind {
+    /// Returns the span of the macro argument that gives the source, which is where the errors in
+    /// the shader are reported.
     #[inline]
-    fn empty() -> Self {
-        Self {
-            custom_derives: Vec::new(),
-            clone: false,
-            copy: false,
-            partial_eq: false,
-            debug: false,
-            display: false,
-            default: false,
-            uses: Vec::new(),
-            impls: Vec::new(),
+    fn span(&self) -> Span {
+        match *self {
+            SourceKind::Src(ref lit) | SourceKind::Path(ref lit) | SourceKind::Bytes(ref lit) => {
+                lit.span()
+            }
         }
     }
 }
 
-struct RegisteredType {
-    shader: String,
-    signature: Vec<(String, Cow<'static, str>)>,
-}
-
-impl RegisteredType {
-    #[inline]
-    fn assert_signatures(&self, type_name: &str, target_type: &Self) {
-        if self.signature.len() > target_type.signature.len() {
-            panic!(
-                "Shaders {shader_a:} and {shader_b:} declare structs with the \
-                same name \"`{type_name:}\", but the struct from {shader_a:} shader \
-                contains extra field \"{field:}\"",
-                shader_a = self.shader,
-                shader_b = target_type.shader,
-                type_name = type_name,
-                field = self.signature[target_type.signature.len()].0
-            );
-        }
-
-        if self.signature.len() < target_type.signature.len() {
-            panic!(
-                "Shaders {shader_a:} and {shader_b:} declare structs with the \
-                same name \"{type_name:}\", but the struct from {shader_b:} shader \
-                contains extra field \"{field:}\"",
-                shader_a = self.shader,
-                shader_b = target_type.shader,
-                type_name = type_name,
-                field = target_type.signature[self.signature.len()].0
-            );
-        }
-
-        let comparison = self
-            .signature
-            .iter()
-            .zip(target_type.signature.iter())
-            .enumerate();
-
-        for (index, ((a_name, a_type), (b_name, b_type))) in comparison {
-            if a_name != b_name || a_type != b_type {
-                panic!(
-                    "Shaders {shader_a:} and {shader_b:} declare structs with the \
-                    same name \"{type_name:}\", but the struct from {shader_a:} shader \
-                    contains field \"{a_name:}\" of type \"{a_type:}\" in position {index:}, \
-                    whereas the same struct from {shader_b:} contains field \"{b_name:}\" \
-                    of type \"{b_type:}\" in the same position",
-                    shader_a = self.shader,
-                    shader_b = target_type.shader,
-                    type_name = type_name,
-                    index = index,
-                    a_name = a_name,
-                    a_type = a_type,
-                    b_name = b_name,
-                    b_type = b_type,
-                );
-            }
-        }
-    }
+#[derive(Default)]
+struct ShaderDefinition {
+    kind: Option<ShaderKind>,
+    source: Option<SourceKind>,
+    language: Option<SourceLanguage>,
+    entry_point: Option<String>,
 }
 
 struct MacroInput {
-    dump: bool,
+    dump: Option<LitBool>,
     exact_entrypoint_interface: bool,
+    hlsl_options: HlslOptions,
     include_directories: Vec<String>,
     macro_defines: Vec<(String, String)>,
     shared_constants: bool,
-    shaders: HashMap<String, (ShaderKind, SourceKind)>,
+    shaders: HashMap<String, ShaderDefinition>,
     spirv_version: Option<SpirvVersion>,
     types_meta: TypesMeta,
     vulkan_version: Option<EnvVersion>,
@@ -383,6 +389,7 @@ impl Parse for MacroInput {
     fn parse(input: ParseStream) -> Result<Self> {
         let mut dump = None;
         let mut exact_entrypoint_interface = None;
+        let mut hlsl_options = HlslOptions::default();
         let mut include_directories = Vec::new();
         let mut macro_defines = Vec::new();
         let mut shared_constants = None;
@@ -391,60 +398,77 @@ impl Parse for MacroInput {
         let mut types_meta = None;
         let mut vulkan_version = None;
 
-        fn parse_shader_fields<'k>(
-            output: &mut (Option<ShaderKind>, Option<SourceKind>),
-            name: &'k str,
+        fn parse_shader_fields(
+            output: &mut ShaderDefinition,
+            name: &Ident,
             input: ParseStream,
         ) -> Result<()> {
-            match name {
+            match name.to_string().as_str() {
                 "ty" => {
-                    if output.0.is_some() {
-                        panic!("Only one `ty` can be defined")
+                    if output.kind.is_some() {
+                        return Err(Error::new_spanned(name, "Only one `ty` can be defined"));
                     }
 
                     let ty: LitStr = input.parse()?;
-                    let ty = match ty.value().as_ref() {
+                    let kind = match ty.value().as_ref() {
                         "vertex" => ShaderKind::Vertex,
                         "fragment" => ShaderKind::Fragment,
                         "geometry" => ShaderKind::Geometry,
                         "tess_ctrl" => ShaderKind::TessControl,
                         "tess_eval" => ShaderKind::TessEvaluation,
                         "compute" => ShaderKind::Compute,
-                        _ => panic!("Unexpected shader type, valid values: vertex, fragment, geometry, tess_ctrl, tess_eval, compute")
+                        _ => return Err(Error::new_spanned(ty, "Unexpected shader type, valid values: vertex, fragment, geometry, tess_ctrl, tess_eval, compute")),
                     };
 
-                    output.0 = Some(ty);
+                    output.kind = Some(kind);
                 }
 
-                "bytes" => {
-                    if output.1.is_some() {
-                        panic!(
-                            "Only one of `src`, `path`, or `bytes` can be defined per Shader entry"
-                        )
+                "lang" => {
+                    if output.language.is_some() {
+                        return Err(Error::new_spanned(name, "Only one `lang` can be defined"));
                     }
 
-                    let path: LitStr = input.parse()?;
-                    output.1 = Some(SourceKind::Bytes(path.value()));
+                    let lang: LitStr = input.parse()?;
+                    let language = match lang.value().as_ref() {
+                        "glsl" => SourceLanguage::GLSL,
+                        "hlsl" => SourceLanguage::HLSL,
+                        _ => {
+                            return Err(Error::new_spanned(
+                                lang,
+                                "Unexpected shader language, valid values: glsl, hlsl",
+                            ))
+                        }
+                    };
+
+                    output.language = Some(language);
                 }
 
-                "path" => {
-                    if output.1.is_some() {
-                        panic!(
-                            "Only one of `src`, `path`, or `bytes` can be defined per Shader entry"
-                        )
+                "entry_point" => {
+                    if output.entry_point.is_some() {
+                        return Err(Error::new_spanned(
+                            name,
+                            "Only one `entry_point` can be defined",
+                        ));
                     }
 
-                    let path: LitStr = input.parse()?;
-                    output.1 = Some(SourceKind::Path(path.value()));
+                    let entry_point: LitStr = input.parse()?;
+                    output.entry_point = Some(entry_point.value());
                 }
 
-                "src" => {
-                    if output.1.is_some() {
-                        panic!("Only one of `src`, `path`, `bytes` can be defined per Shader entry")
+                "bytes" | "path" | "src" => {
+                    if output.source.is_some() {
+                        return Err(Error::new_spanned(
+                            name,
+                            "Only one of `src`, `path`, or `bytes` can be defined per Shader entry",
+                        ));
                     }
 
-                    let src: LitStr = input.parse()?;
-                    output.1 = Some(SourceKind::Src(src.value()));
+                    let lit: LitStr = input.parse()?;
+                    output.source = Some(match name.to_string().as_str() {
+                        "bytes" => SourceKind::Bytes(lit),
+                        "path" => SourceKind::Path(lit),
+                        _ => SourceKind::Src(lit),
+                    });
                 }
 
                 other => unreachable!("Unexpected entry key {:?}", other),
@@ -456,39 +480,47 @@ impl Parse for MacroInput {
         while !input.is_empty() {
             let name: Ident = input.parse()?;
             input.parse::<Token![:]>()?;
-            let name = name.to_string();
 
-            match name.as_str() {
-                "bytes" | "src" | "path" | "ty" => {
+            match name.to_string().as_str() {
+                "bytes" | "src" | "path" | "ty" | "lang" | "entry_point" => {
                     if shaders.len() > 1 || (shaders.len() == 1 && !shaders.contains_key("")) {
-                        panic!("Only one of `shaders`, `src`, `path`, or `bytes` can be defined");
+                        return Err(Error::new_spanned(
+                            name,
+                            "Only one of `shaders`, `src`, `path`, or `bytes` can be defined",
+                        ));
                     }
 
                     parse_shader_fields(
                         shaders
                             .entry("".to_string())
                             .or_insert_with(Default::default),
-                        name.as_str(),
+                        &name,
                         input,
                     )?;
                 }
                 "shaders" => {
                     if !shaders.is_empty() {
-                        panic!("Only one of `shaders`, `src`, `path`, or `bytes` can be defined");
+                        return Err(Error::new_spanned(
+                            name,
+                            "Only one of `shaders`, `src`, `path`, or `bytes` can be defined",
+                        ));
                     }
 
                     let in_braces;
                     braced!(in_braces in input);
 
                     while !in_braces.is_empty() {
-                        let prefix: Ident = in_braces.parse()?;
-                        let prefix = prefix.to_string();
+                        let prefix_ident: Ident = in_braces.parse()?;
+                        let prefix = prefix_ident.to_string();
 
-                        if prefix.to_string().as_str() == "shared_constants" {
+                        if prefix.as_str() == "shared_constants" {
                             in_braces.parse::<Token![:]>()?;
 
                             if shared_constants.is_some() {
-                                panic!("Only one `shared_constants` can be defined")
+                                return Err(Error::new_spanned(
+                                    prefix_ident,
+                                    "Only one `shared_constants` can be defined",
+                                ));
                             }
                             let independent_constants_lit: LitBool = in_braces.parse()?;
                             shared_constants = Some(independent_constants_lit.value);
@@ -501,7 +533,10 @@ impl Parse for MacroInput {
                         }
 
                         if shaders.contains_key(&prefix) {
-                            panic!("Shader entry {:?} already defined", prefix);
+                            return Err(Error::new_spanned(
+                                prefix_ident,
+                                format!("Shader entry {:?} already defined", prefix),
+                            ));
                         }
 
                         in_braces.parse::<Token![:]>()?;
@@ -512,20 +547,24 @@ impl Parse for MacroInput {
                         while !in_shader_definition.is_empty() {
                             let name: Ident = in_shader_definition.parse()?;
                             in_shader_definition.parse::<Token![:]>()?;
-                            let name = name.to_string();
 
-                            match name.as_ref() {
-                                "bytes" | "src" | "path" | "ty" => {
+                            match name.to_string().as_str() {
+                                "bytes" | "src" | "path" | "ty" | "lang" | "entry_point" => {
                                     parse_shader_fields(
                                         shaders
                                             .entry(prefix.clone())
                                             .or_insert_with(Default::default),
-                                        name.as_str(),
+                                        &name,
                                         &in_shader_definition,
                                     )?;
                                 }
 
-                                name => panic!("Unknown Shader definition field {:?}", name),
+                                field => {
+                                    return Err(Error::new_spanned(
+                                        &name,
+                                        format!("Unknown Shader definition field {:?}", field),
+                                    ))
+                                }
                             }
 
                             if !in_shader_definition.is_empty() {
@@ -537,15 +576,35 @@ impl Parse for MacroInput {
                             in_braces.parse::<Token![,]>()?;
                         }
 
-                        match shaders.get(&prefix).unwrap() {
-                            (None, _) => panic!("Please specify shader's {} type e.g. `ty: \"vertex\"`", prefix),
-                            (_, None) => panic!("Please specify shader's {} source e.g. `path: \"entry_point.glsl\"`", prefix),
-                            _ => ()
+                        match shaders.get(&prefix) {
+                            Some(ShaderDefinition { kind: None, .. }) | None => {
+                                return Err(Error::new_spanned(
+                                    prefix_ident,
+                                    format!(
+                                        "Please specify shader's {} type e.g. `ty: \"vertex\"`",
+                                        prefix
+                                    ),
+                                ))
+                            }
+                            Some(ShaderDefinition { source: None, .. }) => {
+                                return Err(Error::new_spanned(
+                                    prefix_ident,
+                                    format!(
+                                        "Please specify shader's {} source e.g. \
+                                        `path: \"entry_point.glsl\"`",
+                                        prefix
+                                    ),
+                                ))
+                            }
+                            _ => (),
                         }
                     }
 
                     if shaders.is_empty() {
-                        panic!("At least one Shader entry must be defined");
+                        return Err(Error::new_spanned(
+                            name,
+                            "At least one Shader entry must be defined",
+                        ));
                     }
                 }
                 "define" => {
@@ -568,18 +627,81 @@ impl Parse for MacroInput {
                 }
                 "dump" => {
                     if dump.is_some() {
-                        panic!("Only one `dump` can be defined")
+                        return Err(Error::new_spanned(name, "Only one `dump` can be defined"));
                     }
                     let dump_lit: LitBool = input.parse()?;
-                    dump = Some(dump_lit.value);
+                    dump = Some(dump_lit);
                 }
                 "exact_entrypoint_interface" => {
                     if exact_entrypoint_interface.is_some() {
-                        panic!("Only one `dump` can be defined")
+                        return Err(Error::new_spanned(
+                            name,
+                            "Only one `exact_entrypoint_interface` can be defined",
+                        ));
                     }
                     let lit: LitBool = input.parse()?;
                     exact_entrypoint_interface = Some(lit.value);
                 }
+                "hlsl_binding_shifts" => {
+                    let array_input;
+                    bracketed!(array_input in input);
+
+                    while !array_input.is_empty() {
+                        let tuple_input;
+                        parenthesized!(tuple_input in array_input);
+
+                        let register: LitStr = tuple_input.parse()?;
+                        tuple_input.parse::<Token![,]>()?;
+                        let shift: LitInt = tuple_input.parse()?;
+
+                        let kind = match register.value().as_ref() {
+                            "b" => ResourceKind::Buffer,
+                            "t" => ResourceKind::Texture,
+                            "s" => ResourceKind::Sampler,
+                            "u" => ResourceKind::UnorderedAccessView,
+                            _ => {
+                                return Err(Error::new_spanned(
+                                    &register,
+                                    format!(
+                                        "Unknown HLSL register type {:?}, valid values: b, t, s, u",
+                                        register.value()
+                                    ),
+                                ))
+                            }
+                        };
+                        hlsl_options
+                            .binding_shifts
+                            .push((kind, shift.base10_parse()?));
+
+                        if !array_input.is_empty() {
+                            array_input.parse::<Token![,]>()?;
+                        }
+                    }
+                }
+                "hlsl_registers" => {
+                    let array_input;
+                    bracketed!(array_input in input);
+
+                    while !array_input.is_empty() {
+                        let tuple_input;
+                        parenthesized!(tuple_input in array_input);
+
+                        let register: LitStr = tuple_input.parse()?;
+                        tuple_input.parse::<Token![,]>()?;
+                        let set: LitInt = tuple_input.parse()?;
+                        tuple_input.parse::<Token![,]>()?;
+                        let binding: LitInt = tuple_input.parse()?;
+                        hlsl_options.register_bindings.push((
+                            register.value(),
+                            set.base10_parse()?,
+                            binding.base10_parse()?,
+                        ));
+
+                        if !array_input.is_empty() {
+                            array_input.parse::<Token![,]>()?;
+                        }
+                    }
+                }
                 "include" => {
                     let in_brackets;
                     bracketed!(in_brackets in input);
@@ -603,146 +725,18 @@ impl Parse for MacroInput {
                         "1.3" => SpirvVersion::V1_3,
                         "1.4" => SpirvVersion::V1_4,
                         "1.5" => SpirvVersion::V1_5,
-                        _ => panic!("Unknown SPIR-V version: {}", version.value()),
+                        _ => {
+                            return Err(Error::new_spanned(
+                                &version,
+                                format!("Unknown SPIR-V version: {}", version.value()),
+                            ))
+                        }
                     });
                 }
                 "types_meta" => {
                     let in_braces;
                     braced!(in_braces in input);
-
-                    let mut meta = TypesMeta::empty();
-
-                    while !in_braces.is_empty() {
-                        if in_braces.peek(Token![#]) {
-                            in_braces.parse::<Token![#]>()?;
-
-                            let in_brackets;
-                            bracketed!(in_brackets in in_braces);
-
-                            let derive_list: MetaList = in_brackets.parse()?;
-
-                            for derive in derive_list.nested {
-                                match derive {
-                                    NestedMeta::Meta(Meta::Path(path)) => {
-                                        let custom_derive = if let Some(derive_ident) =
-                                            path.get_ident()
-                                        {
-                                            match derive_ident.to_string().as_str() {
-                                                "Clone" => {
-                                                    if meta.default {
-                                                        return Err(in_brackets
-                                                            .error("Duplicate Clone derive"));
-                                                    }
-
-                                                    meta.clone = true;
-
-                                                    false
-                                                }
-                                                "Copy" => {
-                                                    if meta.copy {
-                                                        return Err(in_brackets
-                                                            .error("Duplicate Copy derive"));
-                                                    }
-
-                                                    meta.copy = true;
-
-                                                    false
-                                                }
-                                                "PartialEq" => {
-                                                    if meta.partial_eq {
-                                                        return Err(in_brackets
-                                                            .error("Duplicate PartialEq derive"));
-                                                    }
-
-                                                    meta.partial_eq = true;
-
-                                                    false
-                                                }
-                                                "Debug" => {
-                                                    if meta.debug {
-                                                        return Err(in_brackets
-                                                            .error("Duplicate Debug derive"));
-                                                    }
-
-                                                    meta.debug = true;
-
-                                                    false
-                                                }
-                                                "Display" => {
-                                                    if meta.display {
-                                                        return Err(in_brackets
-                                                            .error("Duplicate Display derive"));
-                                                    }
-
-                                                    meta.display = true;
-
-                                                    false
-                                                }
-                                                "Default" => {
-                                                    if meta.default {
-                                                        return Err(in_brackets
-                                                            .error("Duplicate Default derive"));
-                                                    }
-
-                                                    meta.default = true;
-
-                                                    false
-                                                }
-                                                _ => true,
-                                            }
-                                        } else {
-                                            true
-                                        };
-
-                                        if custom_derive {
-                                            if meta
-                                                .custom_derives
-                                                .iter()
-                                                .any(|candidate| candidate.eq(&path))
-                                            {
-                                                return Err(
-                                                    in_braces.error("Duplicate derive declaration")
-                                                );
-                                            }
-
-                                            meta.custom_derives.push(path);
-                                        }
-                                    }
-                                    _ => return Err(in_brackets.error("Unsupported syntax")),
-                                }
-                            }
-
-                            continue;
-                        }
-
-                        if in_braces.peek(Token![impl]) {
-                            let impl_trait: TypeImplTrait = in_braces.parse()?;
-
-                            if meta.impls.iter().any(|candidate| candidate == &impl_trait) {
-                                return Err(in_braces.error("Duplicate \"impl\" declaration"));
-                            }
-
-                            meta.impls.push(impl_trait);
-
-                            continue;
-                        }
-
-                        if in_braces.peek(Token![use]) {
-                            let item_use: ItemUse = in_braces.parse()?;
-
-                            if meta.uses.iter().any(|candidate| candidate == &item_use) {
-                                return Err(in_braces.error("Duplicate \"use\" declaration"));
-                            }
-
-                            meta.uses.push(item_use);
-
-                            continue;
-                        }
-
-                        return Err(in_braces.error("Type meta must by \"use a::b::c\", \"#[derive(Type1, Type2, ..)]\" or \"impl Type\""));
-                    }
-
-                    types_meta = Some(meta);
+                    types_meta = Some(in_braces.parse()?);
                 }
                 "vulkan_version" => {
                     let version: LitStr = input.parse()?;
@@ -750,10 +744,20 @@ impl Parse for MacroInput {
                         "1.0" => EnvVersion::Vulkan1_0,
                         "1.1" => EnvVersion::Vulkan1_1,
                         "1.2" => EnvVersion::Vulkan1_2,
-                        _ => panic!("Unknown Vulkan version: {}", version.value()),
+                        _ => {
+                            return Err(Error::new_spanned(
+                                &version,
+                                format!("Unknown Vulkan version: {}", version.value()),
+                            ))
+                        }
                     });
                 }
-                name => panic!("Unknown field {:?}", name),
+                field => {
+                    return Err(Error::new_spanned(
+                        &name,
+                        format!("Unknown field {:?}", field),
+                    ))
+                }
             }
 
             if !input.is_empty() {
@@ -762,29 +766,42 @@ impl Parse for MacroInput {
         }
 
         if shaders.is_empty() {
-            panic!("Please specify at least one shader e.g. `ty: \"vertex\", src: \"glsl source code\"`");
+            return Err(input.error(
+                "Please specify at least one shader e.g. `ty: \"vertex\", src: \"glsl source code\"`",
+            ));
         }
 
         match shaders.get("") {
-            Some((None, _)) => panic!("Please specify shader's type e.g. `ty: \"vertex\"`"),
-            Some((_, None)) => {
-                panic!("Please specify shader's source e.g. `src: \"glsl source code\"`")
+            Some(ShaderDefinition { kind: None, .. }) => {
+                return Err(input.error("Please specify shader's type e.g. `ty: \"vertex\"`"));
+            }
+            Some(ShaderDefinition { source: None, .. }) => {
+                return Err(
+                    input.error("Please specify shader's source e.g. `src: \"glsl source code\"`")
+                );
             }
             _ => (),
         }
 
+        for definition in shaders.values() {
+            if let Some(SourceKind::Bytes(ref bytes)) = definition.source {
+                if definition.language.is_some() || definition.entry_point.is_some() {
+                    return Err(Error::new_spanned(
+                        bytes,
+                        "`lang` and `entry_point` can't be used with precompiled `bytes`",
+                    ));
+                }
+            }
+        }
+
         Ok(Self {
-            dump: dump.unwrap_or(false),
+            dump,
             exact_entrypoint_interface: exact_entrypoint_interface.unwrap_or(false),
+            hlsl_options,
             include_directories,
             macro_defines,
             shared_constants: shared_constants.unwrap_or(false),
-            shaders: shaders
-                .into_iter()
-                .map(|(key, (shader_kind, shader_source))| {
-                    (key, (shader_kind.unwrap(), shader_source.unwrap()))
-                })
-                .collect(),
+            shaders,
             spirv_version,
             types_meta: types_meta.unwrap_or_else(|| TypesMeta::default()),
             vulkan_version,
@@ -792,16 +809,16 @@ impl Parse for MacroInput {
     }
 }
 
-pub(self) fn read_file_to_string(full_path: &Path) -> IoResult<String> {
-    let mut buf = String::new();
-    File::open(full_path).and_then(|mut file| file.read_to_string(&mut buf))?;
-    Ok(buf)
-}
-
 #[proc_macro]
 pub fn shader(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as MacroInput);
 
+    shader_inner(input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+fn shader_inner(input: MacroInput) -> Result<TokenStream> {
     let is_single = input.shaders.len() == 1;
     let root = env::var("CARGO_MANIFEST_DIR").unwrap_or(".".into());
     let root_path = Path::new(&root);
@@ -810,22 +827,46 @@ pub fn shader(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let mut types_code = Vec::with_capacity(input.shaders.len());
     let mut types_registry = HashMap::new();
 
-    for (prefix, (shader_kind, shader_source)) in input.shaders {
+    for (prefix, definition) in input.shaders {
+        let shader_kind = definition.kind.unwrap();
+        let shader_source = definition.source.unwrap();
+        let source_language = definition.language.unwrap_or(SourceLanguage::GLSL);
+        let entry_point = definition.entry_point.as_deref().unwrap_or("main");
+
+        // Errors in the shader are reported on the argument that gives its source.
+        let span = shader_source.span();
+        let error = |message: &dyn Display| {
+            if is_single {
+                Error::new(span, message)
+            } else {
+                Error::new(span, format!("Shader {:?}: {}", prefix, message))
+            }
+        };
+
         let (code, types) = if let SourceKind::Bytes(path) = shader_source {
+            let path = path.value();
             let full_path = root_path.join(&path);
 
             let bytes = if full_path.is_file() {
-                fs::read(full_path).expect(&format!("Error reading source from {:?}", path))
+                fs::read(full_path).map_err(|err| {
+                    error(&format!("Error reading source from {:?}: {}", path, err))
+                })?
             } else {
-                panic!(
+                return Err(error(&format!(
                     "File {:?} was not found; note that the path must be relative to your Cargo.toml",
                     path
-                );
+                )));
             };
 
             // The SPIR-V specification essentially guarantees that
             // a shader will always be an integer number of words
-            assert_eq!(0, bytes.len() % 4);
+            if bytes.len() % 4 != 0 {
+                return Err(error(&format!(
+                    "File {:?} doesn't contain a whole number of SPIR-V words",
+                    path
+                )));
+            }
+
             codegen::reflect(
                 prefix.as_str(),
                 unsafe { from_raw_parts(bytes.as_slice().as_ptr() as *const u32, bytes.len() / 4) },
@@ -835,21 +876,26 @@ pub fn shader(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
                 input.shared_constants,
                 &mut types_registry,
             )
-            .unwrap()
-            .into()
+            .map_err(|err| error(&err))?
         } else {
             let (path, full_path, source_code) = match shader_source {
-                SourceKind::Src(source) => (None, None, source),
+                SourceKind::Src(source) => (None, None, source.value()),
                 SourceKind::Path(path) => {
+                    let path = path.value();
                     let full_path = root_path.join(&path);
-                    let source_code = read_file_to_string(&full_path)
-                        .expect(&format!("Error reading source from {:?}", path));
 
-                    if full_path.is_file() {
-                        (Some(path.clone()), Some(full_path), source_code)
-                    } else {
-                        panic!("File {:?} was not found; note that the path must be relative to your Cargo.toml", path);
+                    if !full_path.is_file() {
+                        return Err(error(&format!(
+                            "File {:?} was not found; note that the path must be relative to your Cargo.toml",
+                            path
+                        )));
                     }
+
+                    let source_code = fs::read_to_string(&full_path).map_err(|err| {
+                        error(&format!("Error reading source from {:?}: {}", path, err))
+                    })?;
+
+                    (Some(path), Some(full_path), source_code)
                 }
                 SourceKind::Bytes(_) => unreachable!(),
             };
@@ -865,7 +911,7 @@ pub fn shader(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
                 })
                 .collect::<Vec<_>>();
 
-            let (content, includes) = match codegen::compile(
+            let (content, includes) = codegen::compile(
                 path,
                 &root_path,
                 &source_code,
@@ -874,16 +920,22 @@ pub fn shader(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
                 &input.macro_defines,
                 input.vulkan_version,
                 input.spirv_version,
-            ) {
-                Ok(ok) => ok,
-                Err(e) => {
-                    if is_single {
-                        panic!("{}", e.replace("(s): ", "(s):\n"))
-                    } else {
-                        panic!("Shader {:?} {}", prefix, e.replace("(s): ", "(s):\n"))
-                    }
+                None,
+                source_language,
+                entry_point,
+                &input.hlsl_options,
+            )
+            .map_err(|diagnostics| {
+                // Each diagnostic of the compiler is reported as a separate error.
+                let mut errors = diagnostics.lines().map(|diagnostic| error(&diagnostic));
+                let mut combined = errors
+                    .next()
+                    .unwrap_or_else(|| error(&"Failed to compile the shader"));
+                for err in errors {
+                    combined.combine(err);
                 }
-            };
+                combined
+            })?;
 
             let input_paths = includes.iter().map(|s| s.as_ref()).chain(
                 full_path
@@ -901,70 +953,19 @@ pub fn shader(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
                 input.shared_constants,
                 &mut types_registry,
             )
-            .unwrap()
-            .into()
+            .map_err(|err| error(&err))?
         };
 
         shaders_code.push(code);
         types_code.push(types);
     }
 
-    let uses = &input.types_meta.uses;
-
-    let result = quote! {
-        #[allow(unused_imports)]
-        use std::sync::Arc;
-        #[allow(unused_imports)]
-        use std::vec::IntoIter as VecIntoIter;
-
-        #[allow(unused_imports)]
-        use vulkano::device::Device;
-        #[allow(unused_imports)]
-        use vulkano::descriptor_set::layout::DescriptorDesc;
-        #[allow(unused_imports)]
-        use vulkano::descriptor_set::layout::DescriptorDescTy;
-        #[allow(unused_imports)]
-        use vulkano::descriptor_set::layout::DescriptorDescImage;
-        #[allow(unused_imports)]
-        use vulkano::descriptor_set::layout::DescriptorSetDesc;
-        #[allow(unused_imports)]
-        use vulkano::descriptor_set::layout::DescriptorSetLayout;
-        #[allow(unused_imports)]
-        use vulkano::descriptor_set::DescriptorSet;
-        #[allow(unused_imports)]
-        use vulkano::format::Format;
-        #[allow(unused_imports)]
-        use vulkano::image::view::ImageViewType;
-        #[allow(unused_imports)]
-        use vulkano::pipeline::layout::PipelineLayout;
-        #[allow(unused_imports)]
-        use vulkano::pipeline::layout::PipelineLayoutPcRange;
-        #[allow(unused_imports)]
-        use vulkano::pipeline::shader::ShaderStages;
-        #[allow(unused_imports)]
-        use vulkano::pipeline::shader::SpecializationConstants as SpecConstsTrait;
-        #[allow(unused_imports)]
-        use vulkano::pipeline::shader::SpecializationMapEntry;
-        #[allow(unused_imports)]
-        use vulkano::Version;
-
-        #(
-            #shaders_code
-        )*
-
-        pub mod ty {
-            #( #uses )*
-
-            #(
-                #types_code
-            )*
-        }
-    };
+    let result = codegen::write_module(&shaders_code, &types_code, &input.types_meta);
 
-    if input.dump {
+    if let Some(dump) = input.dump.filter(|dump| dump.value) {
         println!("{}", result.to_string());
-        panic!("`shader!` rust codegen dumped") // TODO: use span from dump
+        return Err(Error::new_spanned(dump, "`shader!` rust codegen dumped"));
     }
 
-    proc_macro::TokenStream::from(result)
+    Ok(result)
 }
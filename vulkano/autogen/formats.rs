@@ -39,6 +39,7 @@ struct FormatMember {
     compression: Option<Ident>,
     planes: Vec<Ident>,
     size: Option<u64>,
+    texel_layout: Option<TokenStream>,
     type_color: Option<Ident>,
     type_depth: Option<Ident>,
     type_stencil: Option<Ident>,
@@ -185,6 +186,15 @@ fn formats_output(members: &[FormatMember]) -> TokenStream {
                 quote! { Self::#name => Some(#size), }
             })
         });
+    let texel_layout_items = members.iter().filter_map(
+        |FormatMember {
+             name, texel_layout, ..
+         }| {
+            texel_layout
+                .as_ref()
+                .map(|texel_layout| quote! { Self::#name => Some(#texel_layout), })
+        },
+    );
     let type_color_items = members.iter().filter_map(
         |FormatMember {
              name, type_color, ..
@@ -310,13 +320,23 @@ fn formats_output(members: &[FormatMember]) -> TokenStream {
             /// not have a well-defined size. Multi-planar formats store the color components
             /// disjointly in memory, and therefore do not have a well-defined size for all
             /// components as a whole. The individual planes do have a well-defined size.
-            pub fn size(&self) -> Option<DeviceSize> {
+            pub const fn size(&self) -> Option<DeviceSize> {
                 match self {
                     #(#size_items)*
                     _ => None,
                 }
             }
 
+            /// Returns how the components of a single texel of this format are laid out in
+            /// memory. Returns `None` for formats whose texels cannot be accessed individually:
+            /// depth/stencil, block-compressed, multi-planar and chroma-subsampled formats.
+            pub(crate) fn texel_layout(&self) -> Option<TexelLayout> {
+                match self {
+                    #(#texel_layout_items)*
+                    _ => None,
+                }
+            }
+
             /// Returns the numeric data type of the color aspect of this format. Returns `None`
             /// for depth/stencil formats.
             pub fn type_color(&self) -> Option<NumericType> {
@@ -398,6 +418,7 @@ fn formats_members(formats: &[&str]) -> Vec<FormatMember> {
                 compression: None,
                 planes: vec![],
                 size: None,
+                texel_layout: None,
                 type_color: None,
                 type_depth: None,
                 type_stencil: None,
@@ -509,14 +530,13 @@ fn formats_members(formats: &[&str]) -> Vec<FormatMember> {
             } else {
                 // Other formats
 
-                let many_pack = PACK_REGEX
-                    .captures(*parts.last().unwrap())
-                    .map(|captures| {
-                        parts.pop();
-                        let first = captures.get(1).unwrap().as_str();
-                        first == "3" || first == "4"
-                    })
-                    .unwrap_or(false);
+                let pack = PACK_REGEX.captures(*parts.last().unwrap()).map(|captures| {
+                    parts.pop();
+                    let first = captures.get(1).unwrap().as_str();
+                    let bits: u8 = captures.get(2).unwrap().as_str().parse().unwrap();
+                    (first, bits)
+                });
+                let many_pack = matches!(pack, Some(("3", _)) | Some(("4", _)));
 
                 let numeric_type = parts.pop().unwrap();
                 member.aspect_color = true;
@@ -586,6 +606,11 @@ fn formats_members(formats: &[&str]) -> Vec<FormatMember> {
                         member.components = components;
                         member.size = Some(block_size * size_factor);
 
+                        if size_factor == 1 {
+                            member.texel_layout =
+                                Some(texel_layout(&parts, pack.map(|(_, bits)| bits)));
+                        }
+
                         if size_factor != 1 {
                             let captures = COMPONENTS_REGEX.captures(parts[0]).unwrap();
                             let bits: u8 = captures.get(2).unwrap().as_str().parse().unwrap();
@@ -678,6 +703,30 @@ fn components(vulkan_name: &str, parts: &[&str]) -> (u64, [u8; 4]) {
     (total_bits / 8, components)
 }
 
+fn texel_layout(parts: &[&str], word_bits: Option<u8>) -> TokenStream {
+    let components = parts.iter().flat_map(|part| {
+        COMPONENTS_REGEX.captures_iter(part).map(|component| {
+            let letter = Literal::character(component[1].chars().next().unwrap());
+            let bits = Literal::u8_unsuffixed(component[2].parse().unwrap());
+            quote! { (#letter, #bits) }
+        })
+    });
+    let word_bits = match word_bits {
+        Some(bits) => {
+            let bits = Literal::u8_unsuffixed(bits);
+            quote! { Some(#bits) }
+        }
+        None => quote! { None },
+    };
+
+    quote! {
+        TexelLayout {
+            word_bits: #word_bits,
+            components: &[#(#components),*],
+        }
+    }
+}
+
 fn component_index(letter: &str) -> Option<usize> {
     match letter {
         "R" | "D" => Some(0),
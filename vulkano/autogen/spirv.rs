@@ -177,6 +177,70 @@ fn instruction_output(members: &[InstructionMember], spec_constant: bool) -> Tok
         },
     );
 
+    let name_items = members.iter().map(|InstructionMember { name, .. }| {
+        let name_string = name.to_string();
+        quote! {
+            Self::#name { .. } => #name_string,
+        }
+    });
+    let opcode_items = members
+        .iter()
+        .map(|InstructionMember { name, opcode, .. }| {
+            quote! {
+                Self::#name { .. } => #opcode,
+            }
+        });
+    let write_items = members
+        .iter()
+        .map(|InstructionMember { name, operands, .. }| {
+            if operands.is_empty() {
+                quote! {
+                    Self::#name => (),
+                }
+            } else {
+                let names = operands.iter().map(|OperandMember { name, .. }| name);
+                let writes = operands.iter().map(|OperandMember { name, .. }| {
+                    quote! {
+                        Operand::encode(#name, writer);
+                    }
+                });
+
+                quote! {
+                    Self::#name { #(#names),* } => {
+                        #(#writes)*
+                    }
+                }
+            }
+        });
+    let disassemble_items = members
+        .iter()
+        .map(|InstructionMember { name, operands, .. }| {
+            // The result id is written before the name of the instruction.
+            let operands: Vec<_> = operands
+                .iter()
+                .filter(|OperandMember { name, .. }| name != "result_id")
+                .collect();
+
+            if operands.is_empty() {
+                quote! {
+                    Self::#name { .. } => (),
+                }
+            } else {
+                let names = operands.iter().map(|OperandMember { name, .. }| name);
+                let disassembles = operands.iter().map(|OperandMember { name, .. }| {
+                    quote! {
+                        Operand::disassemble(#name, out);
+                    }
+                });
+
+                quote! {
+                    Self::#name { #(#names),* , .. } => {
+                        #(#disassembles)*
+                    }
+                }
+            }
+        });
+
     let doc = if spec_constant {
         "An instruction that is used as the operand of the `SpecConstantOp` instruction."
     } else {
@@ -240,6 +304,32 @@ fn instruction_output(members: &[InstructionMember], spec_constant: bool) -> Tok
                 })
             }
 
+            /// Returns the name of the instruction, without the `Op` prefix.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    #(#name_items)*
+                }
+            }
+
+            /// Returns the opcode of the instruction.
+            pub fn opcode(&self) -> u16 {
+                match self {
+                    #(#opcode_items)*
+                }
+            }
+
+            fn write_operands(&self, writer: &mut InstructionWriter) {
+                match self {
+                    #(#write_items)*
+                }
+            }
+
+            fn disassemble_operands(&self, out: &mut String) {
+                match self {
+                    #(#disassemble_items)*
+                }
+            }
+
             #result_fns
         }
     }
@@ -331,6 +421,7 @@ fn instruction_members(grammar: &SpirvGrammar) -> Vec<InstructionMember> {
 #[derive(Clone, Debug)]
 struct KindEnumMember {
     name: Ident,
+    spirv_name: String,
     value: u32,
     parameters: Vec<OperandMember>,
 }
@@ -389,6 +480,67 @@ fn bit_enum_output(enums: &[(Ident, Vec<KindEnumMember>)]) -> TokenStream {
             },
         );
 
+        let bits_items = members.iter().map(
+            |KindEnumMember {
+                 name,
+                 value,
+                 parameters,
+                 ..
+             }| {
+                let is_set = if parameters.is_empty() {
+                    quote! { self.#name }
+                } else {
+                    quote! { self.#name.is_some() }
+                };
+
+                quote! {
+                    if #is_set {
+                        bits |= #value;
+                    }
+                }
+            },
+        );
+        let params_items = members
+            .iter()
+            .filter(|KindEnumMember { parameters, .. }| !parameters.is_empty())
+            .map(|KindEnumMember { name, .. }| {
+                quote! {
+                    if let Some(params) = &self.#name {
+                        Operand::encode(params, writer);
+                    }
+                }
+            });
+        let disassemble_items = members.iter().map(
+            |KindEnumMember {
+                 name,
+                 spirv_name,
+                 parameters,
+                 ..
+             }| {
+                let is_set = if parameters.is_empty() {
+                    quote! { self.#name }
+                } else {
+                    quote! { self.#name.is_some() }
+                };
+
+                quote! {
+                    if #is_set {
+                        names.push(#spirv_name);
+                    }
+                }
+            },
+        );
+        let disassemble_params_items = members
+            .iter()
+            .filter(|KindEnumMember { parameters, .. }| !parameters.is_empty())
+            .map(|KindEnumMember { name, .. }| {
+                quote! {
+                    if let Some(params) = &self.#name {
+                        Operand::disassemble(params, out);
+                    }
+                }
+            });
+
         quote! {
             #[derive(Clone, Debug, PartialEq)]
             #[allow(non_camel_case_types)]
@@ -405,6 +557,29 @@ fn bit_enum_output(enums: &[(Ident, Vec<KindEnumMember>)]) -> TokenStream {
                     })
                 }
             }
+
+            impl Operand for #name {
+                fn encode(&self, writer: &mut InstructionWriter) {
+                    let mut bits = 0u32;
+                    #(#bits_items)*
+                    writer.push_u32(bits);
+                    #(#params_items)*
+                }
+
+                fn disassemble(&self, out: &mut String) {
+                    let mut names: Vec<&str> = Vec::new();
+                    #(#disassemble_items)*
+
+                    if names.is_empty() {
+                        out.push_str(" None");
+                    } else {
+                        out.push(' ');
+                        out.push_str(&names.join("|"));
+                    }
+
+                    #(#disassemble_params_items)*
+                }
+            }
         }
     });
 
@@ -421,6 +596,7 @@ fn bit_enum_members(grammar: &SpirvGrammar) -> Vec<(Ident, Vec<KindEnumMember>)>
         .iter()
         .filter(|operand_kind| operand_kind.category == "BitEnum")
         .map(|operand_kind| {
+            let mut values = HashSet::new();
             let members = operand_kind
                 .enumerants
                 .iter()
@@ -433,7 +609,9 @@ fn bit_enum_members(grammar: &SpirvGrammar) -> Vec<(Ident, Vec<KindEnumMember>)>
                         .unwrap();
                     let value = u32::from_str_radix(value, 16).unwrap();
 
-                    if value == 0 {
+                    // Skip aliases, otherwise the parameters of a bit would be read and written
+                    // once for each of its names.
+                    if value == 0 || !values.insert(value) {
                         return None;
                     }
 
@@ -459,6 +637,7 @@ fn bit_enum_members(grammar: &SpirvGrammar) -> Vec<(Ident, Vec<KindEnumMember>)>
 
                     Some(KindEnumMember {
                         name,
+                        spirv_name: enumerant.enumerant.clone(),
                         value,
                         parameters,
                     })
@@ -519,6 +698,64 @@ fn value_enum_output(enums: &[(Ident, Vec<KindEnumMember>)]) -> TokenStream {
                 }
             },
         );
+        let encode_items = members.iter().map(
+            |KindEnumMember {
+                 name,
+                 value,
+                 parameters,
+                 ..
+             }| {
+                if parameters.is_empty() {
+                    quote! {
+                        Self::#name => writer.push_u32(#value),
+                    }
+                } else {
+                    let names = parameters.iter().map(|OperandMember { name, .. }| name);
+                    let encodes = parameters.iter().map(|OperandMember { name, .. }| {
+                        quote! {
+                            Operand::encode(#name, writer);
+                        }
+                    });
+
+                    quote! {
+                        Self::#name { #(#names),* } => {
+                            writer.push_u32(#value);
+                            #(#encodes)*
+                        }
+                    }
+                }
+            },
+        );
+        let disassemble_items = members.iter().map(
+            |KindEnumMember {
+                 name,
+                 spirv_name,
+                 parameters,
+                 ..
+             }| {
+                let spirv_name = format!(" {}", spirv_name);
+
+                if parameters.is_empty() {
+                    quote! {
+                        Self::#name => out.push_str(#spirv_name),
+                    }
+                } else {
+                    let names = parameters.iter().map(|OperandMember { name, .. }| name);
+                    let disassembles = parameters.iter().map(|OperandMember { name, .. }| {
+                        quote! {
+                            Operand::disassemble(#name, out);
+                        }
+                    });
+
+                    quote! {
+                        Self::#name { #(#names),* } => {
+                            out.push_str(#spirv_name);
+                            #(#disassembles)*
+                        }
+                    }
+                }
+            },
+        );
         let name_string = name.to_string();
 
         quote! {
@@ -536,6 +773,20 @@ fn value_enum_output(enums: &[(Ident, Vec<KindEnumMember>)]) -> TokenStream {
                     })
                 }
             }
+
+            impl Operand for #name {
+                fn encode(&self, writer: &mut InstructionWriter) {
+                    match self {
+                        #(#encode_items)*
+                    }
+                }
+
+                fn disassemble(&self, out: &mut String) {
+                    match self {
+                        #(#disassemble_items)*
+                    }
+                }
+            }
         }
     });
 
@@ -578,6 +829,7 @@ fn value_enum_members(grammar: &SpirvGrammar) -> Vec<(Ident, Vec<KindEnumMember>
 
                     KindEnumMember {
                         name,
+                        spirv_name: enumerant.enumerant.clone(),
                         value: enumerant.value.as_u64().unwrap() as u32,
                         parameters,
                     }
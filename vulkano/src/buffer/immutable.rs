@@ -451,6 +451,20 @@ pub struct ImmutableBufferInitialization<
     used: Arc<AtomicBool>,
 }
 
+impl<T: ?Sized, A> ImmutableBufferInitialization<T, A> {
+    /// Returns a second initialization access to the same buffer, for the command buffer that
+    /// acquires the ownership of the buffer from the queue family that performs the upload.
+    ///
+    /// The returned access can't be locked on its own, and must be used by a command buffer that
+    /// is submitted after the one that uses `self`, through the future of that command buffer.
+    pub(crate) fn ownership_acquire(&self) -> ImmutableBufferInitialization<T, A> {
+        ImmutableBufferInitialization {
+            buffer: self.buffer.clone(),
+            used: Arc::new(AtomicBool::new(true)),
+        }
+    }
+}
+
 unsafe impl<T, A> BufferAccess for ImmutableBufferInitialization<T, A>
 where
     T: Send + Sync + ?Sized,
@@ -33,6 +33,9 @@
 //!   and writes.
 //! - A [`CpuBufferPool`](cpu_pool/struct.CpuBufferPool.html) is a ring buffer that can be used to
 //!   transfer data between the CPU and the GPU at a high rate.
+//! - An [`UploadBelt`](upload_belt/struct.UploadBelt.html) is similar to a `CpuBufferPool`, but
+//!   can hold values of different types and alignments, which is convenient for the data that
+//!   changes every frame.
 //! - A [`CpuAccessibleBuffer`](cpu_access/struct.CpuAccessibleBuffer.html) is a simple buffer that
 //!   can be used to prototype. It may be removed from vulkano in the far future.
 //!
@@ -86,6 +89,8 @@ pub use self::sys::BufferCreationError;
 pub use self::traits::BufferAccess;
 pub use self::traits::BufferInner;
 pub use self::traits::TypedBufferAccess;
+pub use self::upload_belt::UploadBelt;
+pub use self::upload_belt::UploadBeltStats;
 pub use self::usage::BufferUsage;
 pub use self::view::BufferView;
 pub use self::view::BufferViewRef;
@@ -95,6 +100,7 @@ pub mod cpu_pool;
 pub mod device_local;
 pub mod immutable;
 pub mod sys;
+pub mod upload_belt;
 pub mod view;
 
 mod slice;
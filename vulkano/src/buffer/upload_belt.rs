@@ -0,0 +1,713 @@
+// Copyright (c) 2021 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::buffer::sys::BufferCreationError;
+use crate::buffer::sys::UnsafeBuffer;
+use crate::buffer::traits::BufferAccess;
+use crate::buffer::traits::BufferInner;
+use crate::buffer::traits::TypedBufferAccess;
+use crate::buffer::BufferUsage;
+use crate::device::Device;
+use crate::device::DeviceOwned;
+use crate::device::Queue;
+use crate::memory::pool::AllocFromRequirementsFilter;
+use crate::memory::pool::AllocLayout;
+use crate::memory::pool::MappingRequirement;
+use crate::memory::pool::MemoryPool;
+use crate::memory::pool::MemoryPoolAlloc;
+use crate::memory::pool::PotentialDedicatedAllocation;
+use crate::memory::pool::StdMemoryPool;
+use crate::memory::DedicatedAlloc;
+use crate::memory::DeviceMemoryAllocError;
+use crate::sync::AccessError;
+use crate::sync::Sharing;
+use crate::DeviceSize;
+use std::cmp;
+use std::collections::VecDeque;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::iter;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+/// Ring of host-visible buffers from which slices of arbitrary types and alignments can be
+/// allocated.
+///
+/// This is meant for the data that changes every frame, like uniforms, vertex streams or the
+/// content of images to upload. Contrary to a `CpuBufferPool`, a single `UploadBelt` can hold
+/// values of different types.
+///
+/// # Usage
+///
+/// The belt is made of chunks of `chunk_size` bytes. Slices are allocated one after the other
+/// from the current chunk, and when a slice doesn't fit in it, the belt moves on to another chunk.
+///
+/// A chunk is recycled once all the slices allocated from it have been dropped. Since the command
+/// buffers that use a slice keep it alive until the `GpuFuture` of their submission is cleaned up
+/// (usually when you call `cleanup_finished()` on a future, or when you drop that future), this
+/// means that the chunks used by a frame become available again as soon as the GPU has finished
+/// executing that frame.
+///
+/// Slices that are larger than `chunk_size` get a buffer of their own, which is freed instead of
+/// recycled.
+///
+/// # Example
+///
+/// ```
+/// use vulkano::buffer::BufferUsage;
+/// use vulkano::buffer::UploadBelt;
+/// # let device: std::sync::Arc<vulkano::device::Device> = return;
+///
+/// let usage = BufferUsage {
+///     transfer_source: true,
+///     uniform_buffer: true,
+///     index_buffer: true,
+///     ..BufferUsage::none()
+/// };
+/// let belt = UploadBelt::new(device.clone(), usage, 1 << 16);
+///
+/// // Values of different types can be allocated from the same belt.
+/// let uniforms = belt.next([1.0f32, 0.5, 0.0, 1.0]).unwrap();
+/// let indices = belt.chunk(vec![0u16, 1, 2]).unwrap();
+/// let texels = belt.bytes(&[0xff; 256], 4).unwrap();
+/// ```
+pub struct UploadBelt<A = Arc<StdMemoryPool>>
+where
+    A: MemoryPool,
+{
+    // The device of the belt.
+    device: Arc<Device>,
+
+    // The memory pool to use for allocations.
+    pool: A,
+
+    // Usage of the buffers of the chunks.
+    usage: BufferUsage,
+
+    // Size in bytes of each chunk.
+    chunk_size: DeviceSize,
+
+    // Chunks of the belt.
+    state: Mutex<UploadBeltState<A>>,
+}
+
+struct UploadBeltState<A>
+where
+    A: MemoryPool,
+{
+    // Chunk from which slices are currently allocated, and offset of the first free byte in it.
+    current: Option<(Arc<UploadBeltChunk<A>>, DeviceSize)>,
+
+    // Chunks that have been filled and whose slices may still be in use, from oldest to newest.
+    in_flight: VecDeque<Arc<UploadBeltChunk<A>>>,
+
+    // Chunks that can be used again.
+    free: Vec<Arc<UploadBeltChunk<A>>>,
+
+    // Number of bytes allocated since the creation of the belt.
+    total_allocated: DeviceSize,
+}
+
+// One chunk of the belt.
+struct UploadBeltChunk<A>
+where
+    A: MemoryPool,
+{
+    // Inner content.
+    inner: UnsafeBuffer,
+
+    // The memory held by the buffer.
+    memory: PotentialDedicatedAllocation<A::Alloc>,
+
+    // Size of the buffer in bytes.
+    size: DeviceSize,
+}
+
+/// A slice allocated from an `UploadBelt`.
+///
+/// The chunk that holds the slice can't be recycled by the belt as long as this object is alive.
+pub struct UploadBeltSlice<T: ?Sized, A = Arc<StdMemoryPool>>
+where
+    A: MemoryPool,
+{
+    chunk: Arc<UploadBeltChunk<A>>,
+
+    // Offset of the slice within the buffer of `chunk`, in bytes.
+    offset: DeviceSize,
+
+    // Size of the slice in bytes.
+    size: DeviceSize,
+
+    // Number of GPU accesses to the slice, and whether these accesses are exclusive.
+    gpu_accesses: Mutex<(usize, bool)>,
+
+    // Necessary to make it compile.
+    marker: PhantomData<Box<T>>,
+}
+
+/// Statistics about the capacity of an `UploadBelt`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct UploadBeltStats {
+    /// Number of chunks of `chunk_size` bytes owned by the belt.
+    pub num_chunks: usize,
+    /// Number of chunks that are waiting for their slices to be dropped before they can be used
+    /// again. This includes the buffers of slices larger than `chunk_size`.
+    pub chunks_in_flight: usize,
+    /// Number of chunks that can be used again right away.
+    pub free_chunks: usize,
+    /// Total size in bytes of the chunks owned by the belt.
+    pub capacity: DeviceSize,
+    /// Number of bytes used in the current chunk, including the padding for alignment.
+    pub current_chunk_used: DeviceSize,
+    /// Number of bytes allocated since the creation of the belt, excluding the padding for
+    /// alignment.
+    pub total_allocated: DeviceSize,
+}
+
+impl UploadBelt {
+    /// Builds an `UploadBelt` whose chunks are `chunk_size` bytes large.
+    ///
+    /// No memory is allocated until the first slice is allocated.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `chunk_size` is 0.
+    #[inline]
+    pub fn new(device: Arc<Device>, usage: BufferUsage, chunk_size: DeviceSize) -> UploadBelt {
+        assert!(chunk_size != 0);
+
+        let pool = Device::standard_pool(&device);
+
+        UploadBelt {
+            device,
+            pool,
+            usage,
+            chunk_size,
+            state: Mutex::new(UploadBeltState {
+                current: None,
+                in_flight: VecDeque::new(),
+                free: Vec::new(),
+                total_allocated: 0,
+            }),
+        }
+    }
+}
+
+impl<A> UploadBelt<A>
+where
+    A: MemoryPool,
+{
+    /// Returns the size in bytes of each chunk of the belt.
+    #[inline]
+    pub fn chunk_size(&self) -> DeviceSize {
+        self.chunk_size
+    }
+
+    /// Returns statistics about the capacity of the belt.
+    ///
+    /// The chunks whose slices have all been dropped are recycled before the statistics are
+    /// computed.
+    pub fn stats(&self) -> UploadBeltStats {
+        let mut state = self.state.lock().unwrap();
+        self.recycle(&mut state);
+
+        let current_chunk_used = state.current.as_ref().map_or(0, |&(_, offset)| offset);
+        let num_chunks = state.free.len()
+            + state.current.iter().count()
+            + state
+                .in_flight
+                .iter()
+                .filter(|chunk| chunk.size == self.chunk_size)
+                .count();
+        let oversized: DeviceSize = state
+            .in_flight
+            .iter()
+            .filter(|chunk| chunk.size != self.chunk_size)
+            .map(|chunk| chunk.size)
+            .sum();
+
+        UploadBeltStats {
+            num_chunks,
+            chunks_in_flight: state.in_flight.len(),
+            free_chunks: state.free.len(),
+            capacity: num_chunks as DeviceSize * self.chunk_size + oversized,
+            current_chunk_used,
+            total_allocated: state.total_allocated,
+        }
+    }
+
+    /// Allocates a new slice and puts `data` in it.
+    ///
+    /// The slice is aligned to the alignment of `T`, and to the offset alignments that the usage
+    /// of the belt requires.
+    #[inline]
+    pub fn next<T>(&self, data: T) -> Result<UploadBeltSlice<T, A>, DeviceMemoryAllocError> {
+        let size = mem::size_of::<T>() as DeviceSize;
+        let (chunk, offset) = self.allocate(size, mem::align_of::<T>() as DeviceSize)?;
+
+        unsafe {
+            let mem_off = chunk.memory.offset() + offset;
+            let mut mapping = chunk
+                .memory
+                .mapped_memory()
+                .unwrap()
+                .read_write::<T>(mem_off..mem_off + size);
+            ptr::write(&mut *mapping, data);
+        }
+
+        Ok(UploadBeltSlice::new(chunk, offset, size))
+    }
+
+    /// Allocates a new slice and puts the elements of `data` in it.
+    ///
+    /// The slice is aligned to the alignment of `T`, and to the offset alignments that the usage
+    /// of the belt requires.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the length of the iterator didn't match the actual number of elements.
+    #[inline]
+    pub fn chunk<T, I>(&self, data: I) -> Result<UploadBeltSlice<[T], A>, DeviceMemoryAllocError>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.chunk_with_alignment(data, mem::align_of::<T>() as DeviceSize)
+    }
+
+    /// Allocates a new slice aligned to `alignment` bytes and copies `data` in it.
+    ///
+    /// When the slice is the source of a copy to an image, the alignment must be a multiple of the
+    /// size of a texel block of the format of the image, and of 4.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `alignment` is not a power of two.
+    #[inline]
+    pub fn bytes(
+        &self,
+        data: &[u8],
+        alignment: DeviceSize,
+    ) -> Result<UploadBeltSlice<[u8], A>, DeviceMemoryAllocError> {
+        self.chunk_with_alignment(data.iter().cloned(), alignment)
+    }
+
+    /// Same as `chunk`, but the slice is aligned to `alignment` bytes instead of the alignment of
+    /// `T`.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `alignment` is not a power of two.
+    /// - Panics if `alignment` is less than the alignment of `T`.
+    /// - Panics if the length of the iterator didn't match the actual number of elements.
+    pub fn chunk_with_alignment<T, I>(
+        &self,
+        data: I,
+        alignment: DeviceSize,
+    ) -> Result<UploadBeltSlice<[T], A>, DeviceMemoryAllocError>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        assert!(alignment >= mem::align_of::<T>() as DeviceSize);
+
+        let data = data.into_iter();
+        let len = data.len() as DeviceSize;
+        let size = len * mem::size_of::<T>() as DeviceSize;
+        let (chunk, offset) = self.allocate(size, alignment)?;
+
+        unsafe {
+            let mem_off = chunk.memory.offset() + offset;
+            let mut mapping = chunk
+                .memory
+                .mapped_memory()
+                .unwrap()
+                .read_write::<[T]>(mem_off..mem_off + size);
+
+            let mut written = 0;
+            for (o, i) in mapping.iter_mut().zip(data) {
+                ptr::write(o, i);
+                written += 1;
+            }
+            assert_eq!(
+                written, len,
+                "Iterator passed to UploadBelt::chunk has a mismatch between reported \
+                        length and actual number of elements"
+            );
+        }
+
+        Ok(UploadBeltSlice::new(chunk, offset, size))
+    }
+
+    // Reserves `size` bytes aligned to `alignment` and returns the chunk and the offset of the
+    // bytes within it.
+    fn allocate(
+        &self,
+        size: DeviceSize,
+        alignment: DeviceSize,
+    ) -> Result<(Arc<UploadBeltChunk<A>>, DeviceSize), DeviceMemoryAllocError> {
+        assert!(alignment.is_power_of_two());
+
+        let alignment = cmp::max(alignment, self.min_alignment());
+        // Empty slices still occupy a byte, so that they don't have the same conflict key as the
+        // slice that follows them.
+        let occupied = cmp::max(size, 1);
+
+        let mut state = self.state.lock().unwrap();
+
+        // Slices larger than a chunk get a buffer of their own.
+        if occupied > self.chunk_size {
+            let chunk = Arc::new(self.create_chunk(occupied)?);
+            state.in_flight.push_back(chunk.clone());
+            state.total_allocated += size;
+            return Ok((chunk, 0));
+        }
+
+        if let Some((chunk, offset)) = state.current.as_mut() {
+            let start = align_up(*offset, alignment);
+
+            if start + occupied <= chunk.size {
+                let chunk = chunk.clone();
+                *offset = start + occupied;
+                state.total_allocated += size;
+                return Ok((chunk, start));
+            }
+        }
+
+        // The current chunk is full. If all of its slices have already been dropped, it can be
+        // reused right away.
+        match state.current.take() {
+            Some((chunk, _)) if Arc::strong_count(&chunk) == 1 => state.free.push(chunk),
+            Some((chunk, _)) => state.in_flight.push_back(chunk),
+            None => (),
+        }
+
+        self.recycle(&mut state);
+
+        let chunk = match state.free.pop() {
+            Some(chunk) => chunk,
+            None => Arc::new(self.create_chunk(self.chunk_size)?),
+        };
+        state.current = Some((chunk.clone(), occupied));
+        state.total_allocated += size;
+
+        Ok((chunk, 0))
+    }
+
+    // Moves the chunks whose slices have all been dropped to the list of free chunks. The buffers
+    // of the slices larger than a chunk are freed.
+    //
+    // `state` must be an active lock of `self.state`.
+    fn recycle(&self, state: &mut UploadBeltState<A>) {
+        let chunk_size = self.chunk_size;
+        let free = &mut state.free;
+
+        // Slices are only created while `state` is locked, so the strong count of a chunk can't
+        // increase concurrently.
+        state.in_flight.retain(|chunk| {
+            if Arc::strong_count(chunk) != 1 {
+                return true;
+            }
+
+            if chunk.size == chunk_size {
+                free.push(chunk.clone());
+            }
+
+            false
+        });
+    }
+
+    // Returns the alignment in bytes that the usage of the belt requires for the offsets of the
+    // slices.
+    fn min_alignment(&self) -> DeviceSize {
+        let properties = self.device.physical_device().properties();
+        let mut alignment = 1;
+
+        if self.usage.uniform_buffer {
+            alignment = cmp::max(alignment, properties.min_uniform_buffer_offset_alignment);
+        }
+
+        if self.usage.storage_buffer {
+            alignment = cmp::max(alignment, properties.min_storage_buffer_offset_alignment);
+        }
+
+        if self.usage.uniform_texel_buffer || self.usage.storage_texel_buffer {
+            alignment = cmp::max(alignment, properties.min_texel_buffer_offset_alignment);
+        }
+
+        alignment
+    }
+
+    // Creates a new chunk of `size` bytes.
+    fn create_chunk(&self, size: DeviceSize) -> Result<UploadBeltChunk<A>, DeviceMemoryAllocError> {
+        unsafe {
+            let (buffer, mem_reqs) = match UnsafeBuffer::new(
+                self.device.clone(),
+                size,
+                self.usage,
+                Sharing::Exclusive::<iter::Empty<_>>,
+                None,
+            ) {
+                Ok(b) => b,
+                Err(BufferCreationError::AllocError(err)) => return Err(err),
+                Err(_) => unreachable!(), // We don't use sparse binding, therefore the other
+                                          // errors can't happen
+            };
+
+            let mem = MemoryPool::alloc_from_requirements(
+                &self.pool,
+                &mem_reqs,
+                AllocLayout::Linear,
+                MappingRequirement::Map,
+                DedicatedAlloc::Buffer(&buffer),
+                |_| AllocFromRequirementsFilter::Allowed,
+            )?;
+            debug_assert!((mem.offset() % mem_reqs.alignment) == 0);
+            debug_assert!(mem.mapped_memory().is_some());
+            buffer.bind_memory(mem.memory(), mem.offset())?;
+
+            Ok(UploadBeltChunk {
+                inner: buffer,
+                memory: mem,
+                size,
+            })
+        }
+    }
+}
+
+unsafe impl<A> DeviceOwned for UploadBelt<A>
+where
+    A: MemoryPool,
+{
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+// Rounds `offset` up to a multiple of `alignment`, which must be a power of two.
+#[inline]
+fn align_up(offset: DeviceSize, alignment: DeviceSize) -> DeviceSize {
+    (offset + alignment - 1) & !(alignment - 1)
+}
+
+impl<T: ?Sized, A> UploadBeltSlice<T, A>
+where
+    A: MemoryPool,
+{
+    #[inline]
+    fn new(chunk: Arc<UploadBeltChunk<A>>, offset: DeviceSize, size: DeviceSize) -> Self {
+        UploadBeltSlice {
+            chunk,
+            offset,
+            size,
+            gpu_accesses: Mutex::new((0, false)),
+            marker: PhantomData,
+        }
+    }
+}
+
+unsafe impl<T: ?Sized, A> BufferAccess for UploadBeltSlice<T, A>
+where
+    T: Send + Sync,
+    A: MemoryPool,
+    <A as MemoryPool>::Alloc: Send + Sync,
+{
+    #[inline]
+    fn inner(&self) -> BufferInner {
+        BufferInner {
+            buffer: &self.chunk.inner,
+            offset: self.offset,
+        }
+    }
+
+    #[inline]
+    fn size(&self) -> DeviceSize {
+        self.size
+    }
+
+    #[inline]
+    fn conflict_key(&self) -> (u64, u64) {
+        (self.chunk.inner.key(), self.offset)
+    }
+
+    #[inline]
+    fn try_gpu_lock(&self, exclusive_access: bool, _: &Queue) -> Result<(), AccessError> {
+        let mut gpu_accesses = self.gpu_accesses.lock().unwrap();
+        let (ref mut num, ref mut exclusive) = *gpu_accesses;
+
+        if *num != 0 && (*exclusive || exclusive_access) {
+            return Err(AccessError::AlreadyInUse);
+        }
+
+        *num += 1;
+        *exclusive = exclusive_access;
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn increase_gpu_lock(&self) {
+        let mut gpu_accesses = self.gpu_accesses.lock().unwrap();
+        debug_assert!(gpu_accesses.0 >= 1);
+        gpu_accesses.0 = gpu_accesses
+            .0
+            .checked_add(1)
+            .expect("Overflow in GPU usages");
+    }
+
+    #[inline]
+    unsafe fn unlock(&self) {
+        let mut gpu_accesses = self.gpu_accesses.lock().unwrap();
+        debug_assert!(gpu_accesses.0 >= 1);
+        gpu_accesses.0 -= 1;
+
+        if gpu_accesses.0 == 0 {
+            gpu_accesses.1 = false;
+        }
+    }
+}
+
+unsafe impl<T: ?Sized, A> TypedBufferAccess for UploadBeltSlice<T, A>
+where
+    T: Send + Sync,
+    A: MemoryPool,
+    <A as MemoryPool>::Alloc: Send + Sync,
+{
+    type Content = T;
+}
+
+unsafe impl<T: ?Sized, A> DeviceOwned for UploadBeltSlice<T, A>
+where
+    A: MemoryPool,
+{
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        self.chunk.inner.device()
+    }
+}
+
+impl<T: ?Sized, A> PartialEq for UploadBeltSlice<T, A>
+where
+    T: Send + Sync,
+    A: MemoryPool,
+    <A as MemoryPool>::Alloc: Send + Sync,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.inner() == other.inner() && self.size() == other.size()
+    }
+}
+
+impl<T: ?Sized, A> Eq for UploadBeltSlice<T, A>
+where
+    T: Send + Sync,
+    A: MemoryPool,
+    <A as MemoryPool>::Alloc: Send + Sync,
+{
+}
+
+impl<T: ?Sized, A> Hash for UploadBeltSlice<T, A>
+where
+    T: Send + Sync,
+    A: MemoryPool,
+    <A as MemoryPool>::Alloc: Send + Sync,
+{
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.inner().hash(state);
+        self.size().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::BufferAccess;
+    use crate::buffer::BufferUsage;
+    use crate::buffer::UploadBelt;
+
+    #[test]
+    fn basic_create() {
+        let (device, _) = gfx_dev_and_queue!();
+        let belt = UploadBelt::new(device, BufferUsage::transfer_source(), 1024);
+        assert_eq!(belt.stats().capacity, 0);
+    }
+
+    #[test]
+    fn mixed_types_and_alignment() {
+        let (device, _) = gfx_dev_and_queue!();
+        let belt = UploadBelt::new(device, BufferUsage::transfer_source(), 1024);
+
+        let a = belt.next(7u8).unwrap();
+        let b = belt.next(1.0f32).unwrap();
+        let c = belt.bytes(&[1, 2, 3], 64).unwrap();
+        let d = belt.chunk(vec![0u16, 1, 2]).unwrap();
+
+        assert_eq!(a.inner().offset, 0);
+        assert_eq!(b.inner().offset, 4);
+        assert_eq!(c.inner().offset, 64);
+        assert_eq!(c.size(), 3);
+        assert_eq!(d.inner().offset, 68);
+        assert_eq!(d.size(), 6);
+
+        let stats = belt.stats();
+        assert_eq!(stats.num_chunks, 1);
+        assert_eq!(stats.current_chunk_used, 74);
+        assert_eq!(stats.total_allocated, 14);
+    }
+
+    #[test]
+    fn recycle_chunks() {
+        let (device, _) = gfx_dev_and_queue!();
+        let belt = UploadBelt::new(device, BufferUsage::transfer_source(), 16);
+
+        for _ in 0..64 {
+            let slices = (0..5).map(|n| belt.next([n as u32; 2]).unwrap());
+            drop(slices.collect::<Vec<_>>());
+        }
+
+        // At most 3 chunks are needed at the same time for 5 slices of 8 bytes.
+        assert!(belt.stats().num_chunks <= 3);
+    }
+
+    #[test]
+    fn chunks_in_use_arent_recycled() {
+        let (device, _) = gfx_dev_and_queue!();
+        let belt = UploadBelt::new(device, BufferUsage::transfer_source(), 16);
+
+        let kept = (0..4)
+            .map(|n| belt.next([n as u32; 4]).unwrap())
+            .collect::<Vec<_>>();
+        let stats = belt.stats();
+        assert_eq!(stats.num_chunks, 4);
+        assert_eq!(stats.chunks_in_flight, 3);
+        assert_eq!(stats.free_chunks, 0);
+
+        drop(kept);
+        let stats = belt.stats();
+        assert_eq!(stats.num_chunks, 4);
+        assert_eq!(stats.chunks_in_flight, 0);
+        assert_eq!(stats.free_chunks, 3);
+    }
+
+    #[test]
+    fn oversized_slice() {
+        let (device, _) = gfx_dev_and_queue!();
+        let belt = UploadBelt::new(device, BufferUsage::transfer_source(), 16);
+
+        let big = belt.bytes(&[0; 100], 1).unwrap();
+        assert_eq!(big.size(), 100);
+        assert_eq!(belt.stats().capacity, 100);
+
+        drop(big);
+        assert_eq!(belt.stats().capacity, 0);
+    }
+}
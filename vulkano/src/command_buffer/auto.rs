@@ -22,6 +22,7 @@ use crate::command_buffer::sys::UnsafeCommandBufferBuilderColorImageClear;
 use crate::command_buffer::sys::UnsafeCommandBufferBuilderImageBlit;
 use crate::command_buffer::sys::UnsafeCommandBufferBuilderImageCopy;
 use crate::command_buffer::validity::*;
+use crate::command_buffer::BufferImageCopy;
 use crate::command_buffer::CommandBufferExecError;
 use crate::command_buffer::CommandBufferInheritance;
 use crate::command_buffer::CommandBufferInheritanceRenderPass;
@@ -30,6 +31,8 @@ use crate::command_buffer::CommandBufferUsage;
 use crate::command_buffer::DispatchIndirectCommand;
 use crate::command_buffer::DrawIndexedIndirectCommand;
 use crate::command_buffer::DrawIndirectCommand;
+use crate::command_buffer::ImageBlit;
+use crate::command_buffer::ImageCopy;
 use crate::command_buffer::ImageUninitializedSafe;
 use crate::command_buffer::PrimaryCommandBuffer;
 use crate::command_buffer::SecondaryCommandBuffer;
@@ -50,6 +53,7 @@ use crate::pipeline::depth_stencil::StencilFaces;
 use crate::pipeline::input_assembly::Index;
 use crate::pipeline::input_assembly::IndexType;
 use crate::pipeline::layout::PipelineLayout;
+use crate::pipeline::layout::PushConstantsLayout;
 use crate::pipeline::shader::ShaderStages;
 use crate::pipeline::vertex::VertexBuffersCollection;
 use crate::pipeline::viewport::Scissor;
@@ -826,6 +830,54 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     where
         S: ImageAccess + Send + Sync + 'static,
         D: ImageAccess + Send + Sync + 'static,
+    {
+        let source_aspects = source.format().aspects();
+        let destination_aspects = destination.format().aspects();
+        let region = ImageBlit {
+            // If color is included, neither depth nor stencil may.
+            aspects: ImageAspects {
+                color: source_aspects.color,
+                depth: !source_aspects.color && source_aspects.depth && destination_aspects.depth,
+                stencil: !source_aspects.color
+                    && source_aspects.stencil
+                    && destination_aspects.stencil,
+                ..ImageAspects::none()
+            },
+            source_mip_level,
+            source_base_array_layer,
+            source_top_left,
+            source_bottom_right,
+            destination_mip_level,
+            destination_base_array_layer,
+            destination_top_left,
+            destination_bottom_right,
+            layer_count,
+        };
+
+        self.blit_image_regions(source, destination, iter::once(region), filter)
+    }
+
+    /// Adds a command that blits several regions of an image to another.
+    ///
+    /// This is the same as `blit_image`, except that each region chooses its own aspects, mipmap
+    /// levels, array layers and coordinates. All the regions are checked together and recorded as
+    /// a single command. There must be at least one region.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the source or the destination was not created with `device`.
+    ///
+    pub fn blit_image_regions<S, D, R>(
+        &mut self,
+        source: S,
+        destination: D,
+        regions: R,
+        filter: Filter,
+    ) -> Result<&mut Self, BlitImageError>
+    where
+        S: ImageAccess + Send + Sync + 'static,
+        D: ImageAccess + Send + Sync + 'static,
+        R: IntoIterator<Item = ImageBlit>,
     {
         unsafe {
             if !self.queue_family().supports_graphics() {
@@ -834,49 +886,18 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
 
             self.ensure_outside_render_pass()?;
 
-            check_blit_image(
-                self.device(),
-                &source,
-                source_top_left,
-                source_bottom_right,
-                source_base_array_layer,
-                source_mip_level,
-                &destination,
-                destination_top_left,
-                destination_bottom_right,
-                destination_base_array_layer,
-                destination_mip_level,
-                layer_count,
-                filter,
-            )?;
+            let regions: Vec<_> = regions.into_iter().collect();
+            check_blit_image_regions(self.device(), &source, &destination, &regions, filter)?;
 
-            let blit = UnsafeCommandBufferBuilderImageBlit {
-                // TODO:
-                aspects: if source.format().aspects().color {
-                    ImageAspects {
-                        color: true,
-                        ..ImageAspects::none()
-                    }
-                } else {
-                    unimplemented!()
-                },
-                source_mip_level,
-                destination_mip_level,
-                source_base_array_layer,
-                destination_base_array_layer,
-                layer_count,
-                source_top_left,
-                source_bottom_right,
-                destination_top_left,
-                destination_bottom_right,
-            };
+            let regions: Vec<UnsafeCommandBufferBuilderImageBlit> =
+                regions.into_iter().map(Into::into).collect();
 
             self.inner.blit_image(
                 source,
                 ImageLayout::TransferSrcOptimal,
                 destination, // TODO: let choose layout
                 ImageLayout::TransferDstOptimal,
-                iter::once(blit),
+                regions,
                 filter,
             )?;
             Ok(self)
@@ -1052,43 +1073,67 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         S: TypedBufferAccess<Content = [Px]> + Send + Sync + 'static,
         D: ImageAccess + Send + Sync + 'static,
         Px: Pixel,
+    {
+        let destination_aspects = destination.format().aspects();
+        let region = BufferImageCopy {
+            buffer_offset: 0,
+            buffer_row_length: 0,
+            buffer_image_height: 0,
+            // TODO: Allow the user to choose aspect
+            image_aspect: if destination_aspects.color {
+                ImageAspect::Color
+            } else if destination_aspects.depth {
+                ImageAspect::Depth
+            } else {
+                ImageAspect::Stencil
+            },
+            image_mip_level: mipmap,
+            image_base_array_layer: first_layer,
+            image_layer_count: num_layers,
+            image_offset: offset,
+            image_extent: size,
+        };
+
+        self.copy_buffer_to_image_regions(source, destination, iter::once(region))
+    }
+
+    /// Adds a command that copies from a buffer to several regions of an image.
+    ///
+    /// Each region chooses its own offset and layout in the buffer, and its own aspect, mipmap
+    /// level, array layers and coordinates in the image. All the regions are checked together and
+    /// recorded as a single command. There must be at least one region.
+    pub fn copy_buffer_to_image_regions<S, D, Px, R>(
+        &mut self,
+        source: S,
+        destination: D,
+        regions: R,
+    ) -> Result<&mut Self, CopyBufferImageError>
+    where
+        S: TypedBufferAccess<Content = [Px]> + Send + Sync + 'static,
+        D: ImageAccess + Send + Sync + 'static,
+        Px: Pixel,
+        R: IntoIterator<Item = BufferImageCopy>,
     {
         unsafe {
             self.ensure_outside_render_pass()?;
 
-            check_copy_buffer_image(
+            let regions: Vec<_> = regions.into_iter().collect();
+            check_copy_buffer_image_regions(
                 self.device(),
                 &source,
                 &destination,
                 CheckCopyBufferImageTy::BufferToImage,
-                offset,
-                size,
-                first_layer,
-                num_layers,
-                mipmap,
+                &regions,
             )?;
 
-            let copy = UnsafeCommandBufferBuilderBufferImageCopy {
-                buffer_offset: 0,
-                buffer_row_length: 0,
-                buffer_image_height: 0,
-                image_aspect: if destination.format().aspects().color {
-                    ImageAspect::Color
-                } else {
-                    unimplemented!()
-                },
-                image_mip_level: mipmap,
-                image_base_array_layer: first_layer,
-                image_layer_count: num_layers,
-                image_offset: [offset[0] as i32, offset[1] as i32, offset[2] as i32],
-                image_extent: size,
-            };
+            let regions: Vec<UnsafeCommandBufferBuilderBufferImageCopy> =
+                regions.into_iter().map(Into::into).collect();
 
             self.inner.copy_buffer_to_image(
                 source,
                 destination,
                 ImageLayout::TransferDstOptimal, // TODO: let choose layout
-                iter::once(copy),
+                regions,
             )?;
             Ok(self)
         }
@@ -1132,48 +1177,62 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
     where
         S: ImageAccess + Send + Sync + 'static,
         D: ImageAccess + Send + Sync + 'static,
+    {
+        let source_aspects = source.format().aspects();
+        let destination_aspects = destination.format().aspects();
+        let region = ImageCopy {
+            // TODO: Allowing choosing a subset of the image aspects, but note that if color
+            // is included, neither depth nor stencil may.
+            aspects: ImageAspects {
+                color: source_aspects.color,
+                depth: !source_aspects.color && source_aspects.depth && destination_aspects.depth,
+                stencil: !source_aspects.color
+                    && source_aspects.stencil
+                    && destination_aspects.stencil,
+                ..ImageAspects::none()
+            },
+            source_mip_level,
+            source_base_array_layer,
+            source_offset,
+            destination_mip_level,
+            destination_base_array_layer,
+            destination_offset,
+            extent,
+            layer_count,
+        };
+
+        self.copy_image_regions(source, destination, iter::once(region))
+    }
+
+    /// Adds a command that copies several regions of an image to another.
+    ///
+    /// This is the same as `copy_image`, except that each region chooses its own aspects, mipmap
+    /// levels, array layers and coordinates. All the regions are checked together and recorded as
+    /// a single command. There must be at least one region.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the source or the destination was not created with `device`.
+    ///
+    pub fn copy_image_regions<S, D, R>(
+        &mut self,
+        source: S,
+        destination: D,
+        regions: R,
+    ) -> Result<&mut Self, CopyImageError>
+    where
+        S: ImageAccess + Send + Sync + 'static,
+        D: ImageAccess + Send + Sync + 'static,
+        R: IntoIterator<Item = ImageCopy>,
     {
         unsafe {
             self.ensure_outside_render_pass()?;
 
-            check_copy_image(
-                self.device(),
-                &source,
-                source_offset,
-                source_base_array_layer,
-                source_mip_level,
-                &destination,
-                destination_offset,
-                destination_base_array_layer,
-                destination_mip_level,
-                extent,
-                layer_count,
-            )?;
+            let regions: Vec<_> = regions.into_iter().collect();
+            check_copy_image_regions(self.device(), &source, &destination, &regions)?;
 
-            let source_aspects = source.format().aspects();
-            let destination_aspects = destination.format().aspects();
-            let copy = UnsafeCommandBufferBuilderImageCopy {
-                // TODO: Allowing choosing a subset of the image aspects, but note that if color
-                // is included, neither depth nor stencil may.
-                aspects: ImageAspects {
-                    color: source_aspects.color,
-                    depth: !source_aspects.color
-                        && source_aspects.depth
-                        && destination_aspects.depth,
-                    stencil: !source_aspects.color
-                        && source_aspects.stencil
-                        && destination_aspects.stencil,
-                    ..ImageAspects::none()
-                },
-                source_mip_level,
-                destination_mip_level,
-                source_base_array_layer,
-                destination_base_array_layer,
-                layer_count,
-                source_offset,
-                destination_offset,
-                extent,
-            };
+            let regions: Vec<UnsafeCommandBufferBuilderImageCopy> =
+                regions.into_iter().map(Into::into).collect();
 
             // TODO: Allow choosing layouts, but note that only Transfer*Optimal and General are
             // valid.
@@ -1182,7 +1241,7 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
                 ImageLayout::TransferSrcOptimal,
                 destination,
                 ImageLayout::TransferDstOptimal,
-                iter::once(copy),
+                regions,
             )?;
             Ok(self)
         }
@@ -1222,49 +1281,67 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         S: ImageAccess + Send + Sync + 'static,
         D: TypedBufferAccess<Content = [Px]> + Send + Sync + 'static,
         Px: Pixel,
+    {
+        let source_aspects = source.format().aspects();
+        let region = BufferImageCopy {
+            buffer_offset: 0,
+            buffer_row_length: 0,
+            buffer_image_height: 0,
+            // TODO: Allow the user to choose aspect
+            image_aspect: if source_aspects.color {
+                ImageAspect::Color
+            } else if source_aspects.depth {
+                ImageAspect::Depth
+            } else {
+                ImageAspect::Stencil
+            },
+            image_mip_level: mipmap,
+            image_base_array_layer: first_layer,
+            image_layer_count: num_layers,
+            image_offset: offset,
+            image_extent: size,
+        };
+
+        self.copy_image_to_buffer_regions(source, destination, iter::once(region))
+    }
+
+    /// Adds a command that copies several regions of an image to a buffer.
+    ///
+    /// Each region chooses its own aspect, mipmap level, array layers and coordinates in the image,
+    /// and its own offset and layout in the buffer. All the regions are checked together and
+    /// recorded as a single command. There must be at least one region.
+    pub fn copy_image_to_buffer_regions<S, D, Px, R>(
+        &mut self,
+        source: S,
+        destination: D,
+        regions: R,
+    ) -> Result<&mut Self, CopyBufferImageError>
+    where
+        S: ImageAccess + Send + Sync + 'static,
+        D: TypedBufferAccess<Content = [Px]> + Send + Sync + 'static,
+        Px: Pixel,
+        R: IntoIterator<Item = BufferImageCopy>,
     {
         unsafe {
             self.ensure_outside_render_pass()?;
 
-            check_copy_buffer_image(
+            let regions: Vec<_> = regions.into_iter().collect();
+            check_copy_buffer_image_regions(
                 self.device(),
                 &destination,
                 &source,
                 CheckCopyBufferImageTy::ImageToBuffer,
-                offset,
-                size,
-                first_layer,
-                num_layers,
-                mipmap,
+                &regions,
             )?;
 
-            let source_aspects = source.format().aspects();
-            let copy = UnsafeCommandBufferBuilderBufferImageCopy {
-                buffer_offset: 0,
-                buffer_row_length: 0,
-                buffer_image_height: 0,
-                // TODO: Allow the user to choose aspect
-                image_aspect: if source_aspects.color {
-                    ImageAspect::Color
-                } else if source_aspects.depth {
-                    ImageAspect::Depth
-                } else if source_aspects.stencil {
-                    ImageAspect::Stencil
-                } else {
-                    unimplemented!()
-                },
-                image_mip_level: mipmap,
-                image_base_array_layer: first_layer,
-                image_layer_count: num_layers,
-                image_offset: [offset[0] as i32, offset[1] as i32, offset[2] as i32],
-                image_extent: size,
-            };
+            let regions: Vec<UnsafeCommandBufferBuilderBufferImageCopy> =
+                regions.into_iter().map(Into::into).collect();
 
             self.inner.copy_image_to_buffer(
                 source,
                 ImageLayout::TransferSrcOptimal,
                 destination, // TODO: let choose layout
-                iter::once(copy),
+                regions,
             )?;
             Ok(self)
         }
@@ -1695,6 +1772,41 @@ impl<L, P> AutoCommandBufferBuilder<L, P> {
         self
     }
 
+    /// Sets push constants for future dispatch or draw calls, from a type that describes a push
+    /// constant block, such as the `PushConstants` types generated by `vulkano-shaders`.
+    ///
+    /// Unlike `push_constants`, only the range of the block is written, at its offset. An error is
+    /// returned if this range isn't covered by the push constant ranges of the pipeline layout,
+    /// or if the layout records that the shaders of a stage that can access the range expect
+    /// another type.
+    pub fn push_constants_typed<Pc>(
+        &mut self,
+        pipeline_layout: Arc<PipelineLayout>,
+        push_constants: Pc,
+    ) -> Result<&mut Self, PushConstantsError>
+    where
+        Pc: PushConstantsLayout,
+    {
+        let shader_stages = check_push_constants_type::<Pc>(&pipeline_layout)?;
+
+        unsafe {
+            let data = slice::from_raw_parts(
+                (&push_constants as *const Pc as *const u8).offset(Pc::OFFSET as isize),
+                Pc::SIZE as usize,
+            );
+
+            self.inner.push_constants::<[u8]>(
+                pipeline_layout,
+                shader_stages,
+                Pc::OFFSET,
+                Pc::SIZE,
+                data,
+            );
+        }
+
+        Ok(self)
+    }
+
     /// Sets the dynamic blend constants for future draw calls.
     ///
     /// # Panics
@@ -2520,6 +2632,175 @@ where
             Ok(self)
         }
     }
+
+    /// Adds a command that releases the ownership of a buffer from the queue family of this
+    /// command buffer, so that it can be acquired by `destination_family`.
+    ///
+    /// This is only needed for buffers that were created with exclusive sharing, when their
+    /// content must be preserved while moving from one queue family to another.
+    ///
+    /// # Safety
+    ///
+    /// - The buffer must not be used by this queue family after this command, until its
+    ///   ownership is transferred back.
+    /// - `acquire_buffer_ownership` must be called with the same buffer in a command buffer of
+    ///   `destination_family`, which must execute after this one, before using the buffer there.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `destination_family` is the queue family of this command buffer.
+    /// - Panics if the buffer doesn't belong to the same device as this command buffer.
+    #[inline]
+    pub unsafe fn release_buffer_ownership<B>(
+        &mut self,
+        buffer: B,
+        destination_family: QueueFamily,
+    ) -> Result<&mut Self, AutoCommandBufferBuilderContextError>
+    where
+        B: BufferAccess + 'static,
+    {
+        assert_eq!(
+            buffer.device().internal_object(),
+            self.device().internal_object()
+        );
+        assert_ne!(destination_family.id(), self.queue_family_id);
+
+        self.ensure_outside_render_pass()?;
+        self.inner
+            .release_buffer_ownership(buffer, self.queue_family_id, destination_family.id());
+        Ok(self)
+    }
+
+    /// Adds a command that acquires the ownership of a buffer for the queue family of this
+    /// command buffer, after it has been released by `source_family`.
+    ///
+    /// # Safety
+    ///
+    /// - `release_buffer_ownership` must have been called with the same buffer in a command
+    ///   buffer of `source_family`, which must execute before this one.
+    /// - This must be the first command that uses the buffer in this command buffer.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `source_family` is the queue family of this command buffer.
+    /// - Panics if the buffer doesn't belong to the same device as this command buffer.
+    #[inline]
+    pub unsafe fn acquire_buffer_ownership<B>(
+        &mut self,
+        buffer: B,
+        source_family: QueueFamily,
+    ) -> Result<&mut Self, AutoCommandBufferBuilderContextError>
+    where
+        B: BufferAccess + 'static,
+    {
+        assert_eq!(
+            buffer.device().internal_object(),
+            self.device().internal_object()
+        );
+        assert_ne!(source_family.id(), self.queue_family_id);
+
+        self.ensure_outside_render_pass()?;
+        self.inner
+            .acquire_buffer_ownership(buffer, source_family.id(), self.queue_family_id);
+        Ok(self)
+    }
+
+    /// Adds a command that releases the ownership of an image from the queue family of this
+    /// command buffer, so that it can be acquired by `destination_family`.
+    ///
+    /// The image is transitioned from `old_layout`, which must be the layout it is in at this
+    /// point of the command buffer, to `new_layout` as part of the transfer.
+    ///
+    /// # Safety
+    ///
+    /// - The image must not be used by this queue family after this command, until its ownership
+    ///   is transferred back.
+    /// - `acquire_image_ownership` must be called with the same image and layouts in a command
+    ///   buffer of `destination_family`, which must execute after this one, before using the
+    ///   image there.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `destination_family` is the queue family of this command buffer.
+    /// - Panics if the image doesn't belong to the same device as this command buffer.
+    /// - Panics if `new_layout` is `Undefined` or `Preinitialized`.
+    #[inline]
+    pub unsafe fn release_image_ownership<I>(
+        &mut self,
+        image: I,
+        destination_family: QueueFamily,
+        old_layout: ImageLayout,
+        new_layout: ImageLayout,
+    ) -> Result<&mut Self, AutoCommandBufferBuilderContextError>
+    where
+        I: ImageAccess + Send + Sync + 'static,
+    {
+        assert_eq!(
+            image.inner().image.device().internal_object(),
+            self.device().internal_object()
+        );
+        assert_ne!(destination_family.id(), self.queue_family_id);
+        assert!(!matches!(
+            new_layout,
+            ImageLayout::Undefined | ImageLayout::Preinitialized
+        ));
+
+        self.ensure_outside_render_pass()?;
+        self.inner.release_image_ownership(
+            image,
+            self.queue_family_id,
+            destination_family.id(),
+            old_layout,
+            new_layout,
+        );
+        Ok(self)
+    }
+
+    /// Adds a command that acquires the ownership of an image for the queue family of this
+    /// command buffer, after it has been released by `source_family`.
+    ///
+    /// # Safety
+    ///
+    /// - `release_image_ownership` must have been called with the same image and layouts in a
+    ///   command buffer of `source_family`, which must execute before this one.
+    /// - This must be the first command that uses the image in this command buffer.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `source_family` is the queue family of this command buffer.
+    /// - Panics if the image doesn't belong to the same device as this command buffer.
+    /// - Panics if `new_layout` is `Undefined` or `Preinitialized`.
+    #[inline]
+    pub unsafe fn acquire_image_ownership<I>(
+        &mut self,
+        image: I,
+        source_family: QueueFamily,
+        old_layout: ImageLayout,
+        new_layout: ImageLayout,
+    ) -> Result<&mut Self, AutoCommandBufferBuilderContextError>
+    where
+        I: ImageAccess + Send + Sync + 'static,
+    {
+        assert_eq!(
+            image.inner().image.device().internal_object(),
+            self.device().internal_object()
+        );
+        assert_ne!(source_family.id(), self.queue_family_id);
+        assert!(!matches!(
+            new_layout,
+            ImageLayout::Undefined | ImageLayout::Preinitialized
+        ));
+
+        self.ensure_outside_render_pass()?;
+        self.inner.acquire_image_ownership(
+            image,
+            source_family.id(),
+            self.queue_family_id,
+            old_layout,
+            new_layout,
+        );
+        Ok(self)
+    }
 }
 
 impl<P> AutoCommandBufferBuilder<SecondaryAutoCommandBuffer<P::Alloc>, P> where
@@ -2749,6 +3030,11 @@ unsafe impl<P> SecondaryCommandBuffer for SecondaryAutoCommandBuffer<P> {
     )> {
         self.inner.image(index)
     }
+
+    #[inline]
+    fn image_subresources(&self, index: usize) -> Option<(Range<u32>, Range<u32>)> {
+        self.inner.image_subresources(index)
+    }
 }
 
 // Whether the command buffer can be submitted.
@@ -2955,6 +3241,10 @@ err_gen!(WriteTimestampError {
     CheckWriteTimestampError,
 });
 
+err_gen!(PushConstantsError {
+    CheckPushConstantsTypeError,
+});
+
 err_gen!(ResetQueryPoolError {
     AutoCommandBufferBuilderContextError,
     CheckResetQueryPoolError,
@@ -63,6 +63,12 @@
 //! let _future = cb.execute(queue.clone());
 //! ```
 //!
+//! # Uploading resources on a transfer queue
+//!
+//! An [`UploadBatch`](struct.UploadBatch.html) records the initial uploads of many immutable
+//! buffers and images, submits them at once on a transfer queue, and transfers the ownership of
+//! the resources to the queue family that is going to use them.
+//!
 //! # Internal architecture of vulkano
 //!
 //! The `commands_raw` and `commands_extra` modules contain structs that correspond to various
@@ -99,6 +105,7 @@ pub use self::auto::EndQueryError;
 pub use self::auto::ExecuteCommandsError;
 pub use self::auto::FillBufferError;
 pub use self::auto::PrimaryAutoCommandBuffer;
+pub use self::auto::PushConstantsError;
 pub use self::auto::ResetQueryPoolError;
 pub use self::auto::SecondaryAutoCommandBuffer;
 pub use self::auto::UpdateBufferError;
@@ -107,9 +114,15 @@ pub use self::traits::CommandBufferExecError;
 pub use self::traits::CommandBufferExecFuture;
 pub use self::traits::PrimaryCommandBuffer;
 pub use self::traits::SecondaryCommandBuffer;
+pub use self::upload::UploadBatch;
+pub use self::upload::UploadBatchError;
+pub use self::upload::UploadBatchFuture;
+use crate::image::ImageAspect;
+use crate::image::ImageAspects;
 use crate::query::QueryControlFlags;
 use crate::query::QueryPipelineStatisticFlags;
 use crate::render_pass::{Framebuffer, Subpass};
+use crate::DeviceSize;
 use std::sync::Arc;
 
 mod auto;
@@ -118,6 +131,7 @@ pub mod submit;
 pub mod synced;
 pub mod sys;
 mod traits;
+mod upload;
 pub mod validity;
 
 #[derive(Debug, Clone, Copy)]
@@ -162,6 +176,59 @@ pub struct DispatchIndirectCommand {
     pub z: u32,
 }
 
+/// A region to copy between a buffer and an image, with `copy_buffer_to_image_regions` or
+/// `copy_image_to_buffer_regions`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BufferImageCopy {
+    /// Offset in bytes from the start of the buffer.
+    pub buffer_offset: DeviceSize,
+    /// Number of texels in a row of the data in the buffer, or 0 if the rows are tightly packed.
+    pub buffer_row_length: u32,
+    /// Number of rows in a 2D image of the data in the buffer, or 0 if the images are tightly
+    /// packed.
+    pub buffer_image_height: u32,
+    /// The aspect of the image to copy. Depth/stencil images must copy each aspect separately.
+    pub image_aspect: ImageAspect,
+    pub image_mip_level: u32,
+    pub image_base_array_layer: u32,
+    pub image_layer_count: u32,
+    pub image_offset: [u32; 3],
+    pub image_extent: [u32; 3],
+}
+
+/// A region to copy between two images, with `copy_image_regions`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ImageCopy {
+    /// The aspects to copy. If `color` is included, neither `depth` nor `stencil` may be.
+    pub aspects: ImageAspects,
+    pub source_mip_level: u32,
+    pub source_base_array_layer: u32,
+    pub source_offset: [i32; 3],
+    pub destination_mip_level: u32,
+    pub destination_base_array_layer: u32,
+    pub destination_offset: [i32; 3],
+    pub extent: [u32; 3],
+    /// The number of array layers to copy, from both images.
+    pub layer_count: u32,
+}
+
+/// A region to blit between two images, with `blit_image_regions`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ImageBlit {
+    /// The aspects to blit. If `color` is included, neither `depth` nor `stencil` may be.
+    pub aspects: ImageAspects,
+    pub source_mip_level: u32,
+    pub source_base_array_layer: u32,
+    pub source_top_left: [i32; 3],
+    pub source_bottom_right: [i32; 3],
+    pub destination_mip_level: u32,
+    pub destination_base_array_layer: u32,
+    pub destination_top_left: [i32; 3],
+    pub destination_bottom_right: [i32; 3],
+    /// The number of array layers to blit, from both images.
+    pub layer_count: u32,
+}
+
 /// Describes what a subpass in a command buffer will contain.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(i32)]
@@ -14,6 +14,7 @@ use super::Command;
 use super::ResourceFinalState;
 use super::ResourceKey;
 use super::ResourceLocation;
+use super::SubresourceFinalState;
 use super::SyncCommandBuffer;
 use crate::command_buffer::pool::UnsafeCommandPoolAlloc;
 use crate::command_buffer::sys::UnsafeCommandBufferBuilder;
@@ -33,10 +34,12 @@ use crate::OomError;
 use commands::CurrentState;
 pub use commands::StencilState;
 use fnv::FnvHashMap;
+use smallvec::SmallVec;
 use std::borrow::Cow;
 use std::collections::hash_map::Entry;
 use std::error;
 use std::fmt;
+use std::ops::Range;
 use std::sync::Arc;
 
 #[path = "commands.rs"]
@@ -238,181 +241,281 @@ impl SyncCommandBufferBuilder {
                 debug_assert!(resource_ty != KeyTy::Buffer || end_layout == ImageLayout::Undefined);
                 debug_assert_ne!(end_layout, ImageLayout::Preinitialized);
 
-                let (resource_key, resource_index) = match resource_ty {
+                let (resource_key, resource_index, mip_levels, array_layers) = match resource_ty {
                     KeyTy::Buffer => {
                         let buffer = self.commands[latest_command_id].buffer(last_cmd_buffer);
-                        (ResourceKey::from(buffer), last_cmd_buffer)
+                        (ResourceKey::from(buffer), last_cmd_buffer, 0..1, 0..1)
                     }
                     KeyTy::Image => {
-                        let image = self.commands[latest_command_id].image(last_cmd_image);
-                        (ResourceKey::from(image), last_cmd_image)
+                        let command = &self.commands[latest_command_id];
+                        let image = command.image(last_cmd_image);
+                        let (mip_levels, array_layers) = command.image_subresources(last_cmd_image);
+                        (
+                            ResourceKey::from(image),
+                            last_cmd_image,
+                            mip_levels,
+                            array_layers,
+                        )
                     }
                 };
 
-                match self.resources.entry(resource_key) {
+                let state = match self.resources.entry(resource_key) {
                     // Situation where this resource was used before in this command buffer.
-                    Entry::Occupied(mut entry) => {
-                        // `collision_cmd_ids` contains the IDs of the commands that we are potentially
-                        // colliding with.
-                        let collision_cmd_ids = &entry.get().command_ids;
-                        debug_assert!(collision_cmd_ids.iter().all(|id| *id <= latest_command_id));
-
-                        let entry_key_resource_index = entry.get().resource_index;
-
-                        // Find out if we have a collision with the pending commands.
-                        if memory.exclusive
-                            || entry.get().memory.exclusive
-                            || entry.get().current_layout != start_layout
-                        {
-                            // Collision found between `latest_command_id` and `collision_cmd_id`.
-
-                            // We now want to modify the current pipeline barrier in order to handle the
-                            // collision. But since the pipeline barrier is going to be submitted before
-                            // the flushed commands, it would be a mistake if `collision_cmd_id` hasn't
-                            // been flushed yet.
-                            let first_unflushed_cmd_id = self.first_unflushed;
-
-                            if collision_cmd_ids
+                    Entry::Occupied(entry) => entry.into_mut(),
+
+                    // Situation where this is the first time we use this resource in this command
+                    // buffer. The whole resource starts out as a single range that isn't used yet.
+                    Entry::Vacant(entry) => {
+                        let (layout_initialized, num_mipmap_levels, num_array_layers) =
+                            match resource_ty {
+                                KeyTy::Buffer => (true, 1, 1),
+                                KeyTy::Image => {
+                                    let img =
+                                        self.commands[latest_command_id].image(resource_index);
+                                    (
+                                        img.is_layout_initialized(),
+                                        img.mipmap_levels(),
+                                        img.dimensions().array_layers(),
+                                    )
+                                }
+                            };
+
+                        entry.insert(ResourceState {
+                            command_ids: Vec::new(),
+                            resource_index,
+                            layout_initialized,
+                            initial_layout: ImageLayout::Undefined,
+                            subresources: vec![SubresourceState {
+                                mip_levels: 0..num_mipmap_levels,
+                                array_layers: 0..num_array_layers,
+                                usage: None,
+                            }],
+                            image_uninitialized_safe,
+                        })
+                    }
+                };
+
+                // We need to perform some tweaks if the initial layout requirement of the image
+                // is different from the first layout usage.
+                let needs_initial_transition = !self.is_secondary
+                    && resource_ty == KeyTy::Image
+                    && start_layout != ImageLayout::Undefined
+                    && start_layout != ImageLayout::Preinitialized;
+                let actual_start_layout = if needs_initial_transition {
+                    self.commands[latest_command_id]
+                        .image(resource_index)
+                        .initial_layout_requirement()
+                } else {
+                    start_layout
+                };
+
+                if state.command_ids.is_empty() {
+                    state.initial_layout = actual_start_layout;
+                }
+
+                let entry_key_resource_index = state.resource_index;
+
+                let inside = state.split_subresources(&mip_levels, &array_layers);
+
+                // The image is marked as initialized as a whole the first time it's transitioned,
+                // so the ranges that this command doesn't use are moved out of their undefined
+                // layout at the same time, to the layout that the next users expect.
+                let initializes_layout = needs_initial_transition && !state.layout_initialized;
+
+                if initializes_layout
+                    && actual_start_layout != ImageLayout::Undefined
+                    && actual_start_layout != ImageLayout::Preinitialized
+                {
+                    unsafe {
+                        let img = self.commands[latest_command_id].image(resource_index);
+                        let from_layout = if img.preinitialized_layout() {
+                            ImageLayout::Preinitialized
+                        } else {
+                            ImageLayout::Undefined
+                        };
+
+                        for (index, subresource) in state.subresources.iter().enumerate() {
+                            if subresource.usage.is_some() || inside.contains(&index) {
+                                continue;
+                            }
+
+                            self.pending_barrier.add_image_memory_barrier(
+                                img,
+                                subresource.mip_levels.clone(),
+                                subresource.array_layers.clone(),
+                                PipelineStages {
+                                    bottom_of_pipe: true,
+                                    ..PipelineStages::none()
+                                },
+                                AccessFlags::none(),
+                                memory.stages,
+                                memory.access,
+                                true,
+                                None,
+                                from_layout,
+                                actual_start_layout,
+                            );
+                        }
+                    }
+                }
+
+                for subresource_index in inside {
+                    let subresource = &mut state.subresources[subresource_index];
+
+                    match &mut subresource.usage {
+                        // Situation where this range was used before in this command buffer.
+                        Some(usage) => {
+                            // `collision_cmd_ids` contains the IDs of the commands that we are potentially
+                            // colliding with.
+                            let collision_cmd_ids = &usage.command_ids;
+                            debug_assert!(collision_cmd_ids
                                 .iter()
-                                .any(|command_id| *command_id >= first_unflushed_cmd_id)
-                                || entry.get().current_layout != start_layout
+                                .all(|id| *id <= latest_command_id));
+
+                            // Find out if we have a collision with the pending commands.
+                            if memory.exclusive
+                                || usage.memory.exclusive
+                                || usage.current_layout != start_layout
                             {
-                                unsafe {
-                                    // Flush the pending barrier.
-                                    self.inner.pipeline_barrier(&self.pending_barrier);
-                                    self.pending_barrier =
-                                        UnsafeCommandBufferBuilderPipelineBarrier::new();
-
-                                    // Flush the commands if possible, or return an error if not possible.
-                                    {
-                                        let start = self.first_unflushed;
-                                        self.barriers.push(start); // Track inserted barriers
-
-                                        if let Some(collision_cmd_id) = collision_cmd_ids
-                                            .iter()
-                                            .find(|command_id| **command_id >= end)
+                                // Collision found between `latest_command_id` and `collision_cmd_id`.
+
+                                // We now want to modify the current pipeline barrier in order to handle the
+                                // collision. But since the pipeline barrier is going to be submitted before
+                                // the flushed commands, it would be a mistake if `collision_cmd_id` hasn't
+                                // been flushed yet.
+                                let first_unflushed_cmd_id = self.first_unflushed;
+
+                                if collision_cmd_ids
+                                    .iter()
+                                    .any(|command_id| *command_id >= first_unflushed_cmd_id)
+                                    || usage.current_layout != start_layout
+                                {
+                                    unsafe {
+                                        // Flush the pending barrier.
+                                        self.inner.pipeline_barrier(&self.pending_barrier);
+                                        self.pending_barrier =
+                                            UnsafeCommandBufferBuilderPipelineBarrier::new();
+
+                                        // Flush the commands if possible, or return an error if not possible.
                                         {
-                                            // TODO: see comment for the `is_poisoned` member in the struct
-                                            self.is_poisoned = true;
-
-                                            let cmd1 = &self.commands[*collision_cmd_id];
-                                            let cmd2 = &self.commands[latest_command_id];
-
-                                            return Err(SyncCommandBufferBuilderError::Conflict {
-                                                command1_name: cmd1.name(),
-                                                command1_param: match resource_ty {
-                                                    KeyTy::Buffer => {
-                                                        cmd1.buffer_name(entry_key_resource_index)
-                                                    }
-                                                    KeyTy::Image => {
-                                                        cmd1.image_name(entry_key_resource_index)
-                                                    }
-                                                },
-                                                command1_offset: *collision_cmd_id,
-
-                                                command2_name: cmd2.name(),
-                                                command2_param: match resource_ty {
-                                                    KeyTy::Buffer => {
-                                                        cmd2.buffer_name(resource_index)
-                                                    }
-                                                    KeyTy::Image => cmd2.image_name(resource_index),
-                                                },
-                                                command2_offset: latest_command_id,
-                                            });
-                                        }
-                                        for command in &mut self.commands[start..end] {
-                                            command.send(&mut self.inner);
+                                            let start = self.first_unflushed;
+                                            self.barriers.push(start); // Track inserted barriers
+
+                                            if let Some(collision_cmd_id) = collision_cmd_ids
+                                                .iter()
+                                                .find(|command_id| **command_id >= end)
+                                            {
+                                                // TODO: see comment for the `is_poisoned` member in the struct
+                                                self.is_poisoned = true;
+
+                                                let cmd1 = &self.commands[*collision_cmd_id];
+                                                let cmd2 = &self.commands[latest_command_id];
+
+                                                return Err(
+                                                    SyncCommandBufferBuilderError::Conflict {
+                                                        command1_name: cmd1.name(),
+                                                        command1_param: match resource_ty {
+                                                            KeyTy::Buffer => cmd1.buffer_name(
+                                                                entry_key_resource_index,
+                                                            ),
+                                                            KeyTy::Image => cmd1.image_name(
+                                                                entry_key_resource_index,
+                                                            ),
+                                                        },
+                                                        command1_offset: *collision_cmd_id,
+
+                                                        command2_name: cmd2.name(),
+                                                        command2_param: match resource_ty {
+                                                            KeyTy::Buffer => {
+                                                                cmd2.buffer_name(resource_index)
+                                                            }
+                                                            KeyTy::Image => {
+                                                                cmd2.image_name(resource_index)
+                                                            }
+                                                        },
+                                                        command2_offset: latest_command_id,
+                                                    },
+                                                );
+                                            }
+                                            for command in &mut self.commands[start..end] {
+                                                command.send(&mut self.inner);
+                                            }
+                                            self.first_unflushed = end;
                                         }
-                                        self.first_unflushed = end;
                                     }
                                 }
-                            }
 
-                            entry.get_mut().command_ids.push(latest_command_id);
-                            let entry = entry.into_mut();
-
-                            // Modify the pipeline barrier to handle the collision.
-                            unsafe {
-                                match resource_ty {
-                                    KeyTy::Buffer => {
-                                        let buf =
-                                            self.commands[latest_command_id].buffer(resource_index);
-
-                                        let b = &mut self.pending_barrier;
-                                        b.add_buffer_memory_barrier(
-                                            buf,
-                                            entry.memory.stages,
-                                            entry.memory.access,
-                                            memory.stages,
-                                            memory.access,
-                                            true,
-                                            None,
-                                            0,
-                                            buf.size(),
-                                        );
-                                    }
+                                usage.command_ids.push(latest_command_id);
 
-                                    KeyTy::Image => {
-                                        let img =
-                                            self.commands[latest_command_id].image(resource_index);
-
-                                        let b = &mut self.pending_barrier;
-                                        b.add_image_memory_barrier(
-                                            img,
-                                            img.current_miplevels_access(),
-                                            img.current_layer_levels_access(),
-                                            entry.memory.stages,
-                                            entry.memory.access,
-                                            memory.stages,
-                                            memory.access,
-                                            true,
-                                            None,
-                                            entry.current_layout,
-                                            start_layout,
-                                        );
-                                    }
-                                };
-                            }
+                                // Modify the pipeline barrier to handle the collision.
+                                unsafe {
+                                    match resource_ty {
+                                        KeyTy::Buffer => {
+                                            let buf = self.commands[latest_command_id]
+                                                .buffer(resource_index);
+
+                                            let b = &mut self.pending_barrier;
+                                            b.add_buffer_memory_barrier(
+                                                buf,
+                                                usage.memory.stages,
+                                                usage.memory.access,
+                                                memory.stages,
+                                                memory.access,
+                                                true,
+                                                None,
+                                                0,
+                                                buf.size(),
+                                            );
+                                        }
 
-                            // Update state.
-                            entry.memory = memory;
-                            entry.exclusive_any = true;
-                            if memory.exclusive || end_layout != ImageLayout::Undefined {
-                                // Only modify the layout in case of a write, because buffer operations
-                                // pass `Undefined` for the layout. While a buffer write *must* set the
-                                // layout to `Undefined`, a buffer read must not touch it.
-                                entry.current_layout = end_layout;
+                                        KeyTy::Image => {
+                                            let img = self.commands[latest_command_id]
+                                                .image(resource_index);
+
+                                            let b = &mut self.pending_barrier;
+                                            b.add_image_memory_barrier(
+                                                img,
+                                                subresource.mip_levels.clone(),
+                                                subresource.array_layers.clone(),
+                                                usage.memory.stages,
+                                                usage.memory.access,
+                                                memory.stages,
+                                                memory.access,
+                                                true,
+                                                None,
+                                                usage.current_layout,
+                                                start_layout,
+                                            );
+                                        }
+                                    };
+                                }
+
+                                // Update state.
+                                usage.memory = memory;
+                                usage.exclusive_any = true;
+                                if memory.exclusive || end_layout != ImageLayout::Undefined {
+                                    // Only modify the layout in case of a write, because buffer operations
+                                    // pass `Undefined` for the layout. While a buffer write *must* set the
+                                    // layout to `Undefined`, a buffer read must not touch it.
+                                    usage.current_layout = end_layout;
+                                }
+                            } else {
+                                // There is no collision. Simply merge the stages and accesses.
+                                // TODO: what about simplifying the newly-constructed stages/accesses?
+                                //       this would simplify the job of the driver, but is it worth it?
+                                usage.memory.stages |= memory.stages;
+                                usage.memory.access |= memory.access;
                             }
-                        } else {
-                            // There is no collision. Simply merge the stages and accesses.
-                            // TODO: what about simplifying the newly-constructed stages/accesses?
-                            //       this would simplify the job of the driver, but is it worth it?
-                            let entry = entry.into_mut();
-                            entry.memory.stages |= memory.stages;
-                            entry.memory.access |= memory.access;
                         }
-                    }
 
-                    // Situation where this is the first time we use this resource in this command buffer.
-                    Entry::Vacant(entry) => {
-                        // We need to perform some tweaks if the initial layout requirement of the image
-                        // is different from the first layout usage.
-                        let mut actually_exclusive = memory.exclusive;
-                        let mut actual_start_layout = start_layout;
-
-                        if !self.is_secondary
-                            && resource_ty == KeyTy::Image
-                            && start_layout != ImageLayout::Undefined
-                            && start_layout != ImageLayout::Preinitialized
-                        {
-                            let img = self.commands[latest_command_id].image(resource_index);
-                            let initial_layout_requirement = img.initial_layout_requirement();
-
-                            // Checks if the image is initialized and transitions it
-                            // if it isn't
-                            let is_layout_initialized = img.is_layout_initialized();
-
-                            if initial_layout_requirement != start_layout || !is_layout_initialized
+                        // Situation where this is the first time we use this range in this command
+                        // buffer.
+                        None => {
+                            let mut actually_exclusive = memory.exclusive;
+
+                            if needs_initial_transition
+                                && (actual_start_layout != start_layout
+                                    || !state.layout_initialized)
                             {
                                 // Note that we transition from `bottom_of_pipe`, which means that we
                                 // wait for all the previous commands to be entirely finished. This is
@@ -425,9 +528,11 @@ impl SyncCommandBufferBuilder {
                                 //   suboptimal in some cases, in the general situation it will be ok.
                                 //
                                 unsafe {
-                                    let from_layout = if is_layout_initialized {
+                                    let img =
+                                        self.commands[latest_command_id].image(resource_index);
+                                    let from_layout = if state.layout_initialized {
                                         actually_exclusive = true;
-                                        initial_layout_requirement
+                                        actual_start_layout
                                     } else {
                                         if img.preinitialized_layout() {
                                             ImageLayout::Preinitialized
@@ -435,14 +540,11 @@ impl SyncCommandBufferBuilder {
                                             ImageLayout::Undefined
                                         }
                                     };
-                                    if initial_layout_requirement != start_layout {
-                                        actual_start_layout = initial_layout_requirement;
-                                    }
                                     let b = &mut self.pending_barrier;
                                     b.add_image_memory_barrier(
                                         img,
-                                        img.current_miplevels_access(),
-                                        img.current_layer_levels_access(),
+                                        subresource.mip_levels.clone(),
+                                        subresource.array_layers.clone(),
                                         PipelineStages {
                                             bottom_of_pipe: true,
                                             ..PipelineStages::none()
@@ -458,23 +560,27 @@ impl SyncCommandBufferBuilder {
                                     img.layout_initialized();
                                 }
                             }
+
+                            subresource.usage = Some(SubresourceUsage {
+                                command_ids: vec![latest_command_id],
+                                memory: PipelineMemoryAccess {
+                                    stages: memory.stages,
+                                    access: memory.access,
+                                    exclusive: actually_exclusive,
+                                },
+                                exclusive_any: actually_exclusive,
+                                current_layout: end_layout, // TODO: what if we reach the end with Undefined? that's not correct?
+                            });
                         }
+                    }
+                }
 
-                        entry.insert(ResourceState {
-                            command_ids: vec![latest_command_id],
-                            resource_index,
+                if initializes_layout {
+                    state.layout_initialized = true;
+                }
 
-                            memory: PipelineMemoryAccess {
-                                stages: memory.stages,
-                                access: memory.access,
-                                exclusive: actually_exclusive,
-                            },
-                            exclusive_any: actually_exclusive,
-                            initial_layout: actual_start_layout,
-                            current_layout: end_layout, // TODO: what if we reach the end with Undefined? that's not correct?
-                            image_uninitialized_safe,
-                        });
-                    }
+                if state.command_ids.last() != Some(&latest_command_id) {
+                    state.command_ids.push(latest_command_id);
                 }
 
                 // Add the resources to the lists
@@ -551,29 +657,37 @@ impl SyncCommandBufferBuilder {
                 {
                     let img = self.commands[state.command_ids[0]].image(state.resource_index);
                     let requested_layout = img.final_layout_requirement();
-                    if requested_layout == state.current_layout {
-                        continue;
-                    }
 
-                    barrier.add_image_memory_barrier(
-                        img,
-                        img.current_miplevels_access(),
-                        img.current_layer_levels_access(),
-                        state.memory.stages,
-                        state.memory.access,
-                        PipelineStages {
-                            top_of_pipe: true,
-                            ..PipelineStages::none()
-                        },
-                        AccessFlags::none(),
-                        true,
-                        None, // TODO: queue transfers?
-                        state.current_layout,
-                        requested_layout,
-                    );
-
-                    state.exclusive_any = true;
-                    state.current_layout = requested_layout;
+                    for subresource in &mut state.subresources {
+                        let usage = match &mut subresource.usage {
+                            Some(usage) => usage,
+                            None => continue,
+                        };
+
+                        if requested_layout == usage.current_layout {
+                            continue;
+                        }
+
+                        barrier.add_image_memory_barrier(
+                            img,
+                            subresource.mip_levels.clone(),
+                            subresource.array_layers.clone(),
+                            usage.memory.stages,
+                            usage.memory.access,
+                            PipelineStages {
+                                top_of_pipe: true,
+                                ..PipelineStages::none()
+                            },
+                            AccessFlags::none(),
+                            true,
+                            None, // TODO: queue transfers?
+                            usage.current_layout,
+                            requested_layout,
+                        );
+
+                        usage.exclusive_any = true;
+                        usage.current_layout = requested_layout;
+                    }
                 }
 
                 self.inner.pipeline_barrier(&barrier);
@@ -585,14 +699,45 @@ impl SyncCommandBufferBuilder {
             self.resources
                 .into_iter()
                 .map(|(resource, state)| {
+                    let subresources: Vec<_> = state
+                        .subresources
+                        .into_iter()
+                        .filter_map(|subresource| {
+                            let SubresourceState {
+                                mip_levels,
+                                array_layers,
+                                usage,
+                            } = subresource;
+                            usage.map(|usage| SubresourceFinalState {
+                                mip_levels,
+                                array_layers,
+                                final_stages: usage.memory.stages,
+                                final_access: usage.memory.access,
+                                exclusive: usage.exclusive_any,
+                                final_layout: usage.current_layout,
+                            })
+                        })
+                        .collect();
+
+                    let mut final_stages = PipelineStages::none();
+                    let mut final_access = AccessFlags::none();
+
+                    for subresource in &subresources {
+                        final_stages |= subresource.final_stages;
+                        final_access |= subresource.final_access;
+                    }
+
                     let final_state = ResourceFinalState {
                         command_ids: state.command_ids,
                         resource_index: state.resource_index,
-                        final_stages: state.memory.stages,
-                        final_access: state.memory.access,
-                        exclusive: state.exclusive_any,
+                        final_stages,
+                        final_access,
+                        exclusive: subresources.iter().any(|subresource| subresource.exclusive),
                         initial_layout: state.initial_layout,
-                        final_layout: state.current_layout,
+                        final_layout: subresources
+                            .first()
+                            .map_or(state.initial_layout, |subresource| subresource.final_layout),
+                        subresources,
                         image_uninitialized_safe: state.image_uninitialized_safe,
                     };
                     (resource, final_state)
@@ -677,20 +822,248 @@ struct ResourceState {
     // Index of the resource within the first command in `command_ids`.
     resource_index: usize,
 
-    // Memory access of the command that last used this resource.
-    memory: PipelineMemoryAccess,
-
-    // True if the resource was used in exclusive mode at any point during the building of the
-    // command buffer. Also true if an image layout transition or queue transfer has been performed.
-    exclusive_any: bool,
+    // True if the layout of the whole image is initialized, either before the command buffer
+    // first used it or by the first transition of the image in the command buffer.
+    layout_initialized: bool,
 
     // Layout at the first use of the resource by the command buffer. Can be `Undefined` if we
     // don't care.
     initial_layout: ImageLayout,
 
-    // Current layout at this stage of the building.
-    current_layout: ImageLayout,
+    // State of the ranges of mipmap levels and array layers of the resource. The ranges don't
+    // overlap and together cover the whole resource. Buffers always have a single range.
+    subresources: Vec<SubresourceState>,
 
     // Extra context of how the image will be used
     image_uninitialized_safe: ImageUninitializedSafe,
 }
+
+impl ResourceState {
+    // Splits the ranges of the resource so that each of them is either entirely inside or
+    // entirely outside of `mip_levels` and `array_layers`, and returns the indices of the ranges
+    // that are inside.
+    fn split_subresources(
+        &mut self,
+        mip_levels: &Range<u32>,
+        array_layers: &Range<u32>,
+    ) -> SmallVec<[usize; 4]> {
+        let mut inside = SmallVec::new();
+
+        for index in 0..self.subresources.len() {
+            let subresource = &self.subresources[index];
+            let overlap_mip_levels = subresource.mip_levels.start.max(mip_levels.start)
+                ..subresource.mip_levels.end.min(mip_levels.end);
+            let overlap_array_layers = subresource.array_layers.start.max(array_layers.start)
+                ..subresource.array_layers.end.min(array_layers.end);
+
+            if overlap_mip_levels.is_empty() || overlap_array_layers.is_empty() {
+                continue;
+            }
+
+            // Cut off the parts that are outside, first along the mipmap levels and then along
+            // the array layers of the overlapping mipmap levels.
+            let mut outside: SmallVec<[(Range<u32>, Range<u32>); 4]> = SmallVec::new();
+
+            if subresource.mip_levels.start < overlap_mip_levels.start {
+                outside.push((
+                    subresource.mip_levels.start..overlap_mip_levels.start,
+                    subresource.array_layers.clone(),
+                ));
+            }
+
+            if overlap_mip_levels.end < subresource.mip_levels.end {
+                outside.push((
+                    overlap_mip_levels.end..subresource.mip_levels.end,
+                    subresource.array_layers.clone(),
+                ));
+            }
+
+            if subresource.array_layers.start < overlap_array_layers.start {
+                outside.push((
+                    overlap_mip_levels.clone(),
+                    subresource.array_layers.start..overlap_array_layers.start,
+                ));
+            }
+
+            if overlap_array_layers.end < subresource.array_layers.end {
+                outside.push((
+                    overlap_mip_levels.clone(),
+                    overlap_array_layers.end..subresource.array_layers.end,
+                ));
+            }
+
+            let usage = subresource.usage.clone();
+            self.subresources
+                .extend(
+                    outside
+                        .into_iter()
+                        .map(|(mip_levels, array_layers)| SubresourceState {
+                            mip_levels,
+                            array_layers,
+                            usage: usage.clone(),
+                        }),
+                );
+
+            let subresource = &mut self.subresources[index];
+            subresource.mip_levels = overlap_mip_levels;
+            subresource.array_layers = overlap_array_layers;
+            inside.push(index);
+        }
+
+        inside
+    }
+}
+
+// State of a range of mipmap levels and array layers of a resource during the building of the
+// command buffer.
+#[derive(Debug, Clone)]
+struct SubresourceState {
+    mip_levels: Range<u32>,
+    array_layers: Range<u32>,
+
+    // How the range is used by the command buffer, or `None` if it isn't used yet.
+    usage: Option<SubresourceUsage>,
+}
+
+// Usage of a range of mipmap levels and array layers of a resource by the command buffer.
+#[derive(Debug, Clone)]
+struct SubresourceUsage {
+    // Indices of the commands that use the range.
+    command_ids: Vec<usize>,
+
+    // Memory access of the command that last used this range.
+    memory: PipelineMemoryAccess,
+
+    // True if the range was used in exclusive mode at any point during the building of the
+    // command buffer. Also true if an image layout transition or queue transfer has been performed.
+    exclusive_any: bool,
+
+    // Current layout at this stage of the building.
+    current_layout: ImageLayout,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SyncCommandBufferBuilder;
+    use crate::buffer::BufferUsage;
+    use crate::buffer::CpuAccessibleBuffer;
+    use crate::command_buffer::pool::CommandPool;
+    use crate::command_buffer::pool::CommandPoolBuilderAlloc;
+    use crate::command_buffer::sys::UnsafeCommandBufferBuilderBufferImageCopy;
+    use crate::command_buffer::CommandBufferLevel;
+    use crate::command_buffer::CommandBufferUsage;
+    use crate::device::Device;
+    use crate::format::Format;
+    use crate::image::DeviceLocalImage;
+    use crate::image::ImageAccess;
+    use crate::image::ImageAspect;
+    use crate::image::ImageDimensions;
+    use crate::image::ImageLayout;
+    use crate::image::ImageUsage;
+    use crate::image::MipmapsCount;
+    use std::iter;
+
+    #[test]
+    fn uninitialized_image_used_by_two_command_buffers() {
+        unsafe {
+            let (device, queue) = gfx_dev_and_queue!();
+            let pool = Device::standard_command_pool(&device, queue.family());
+
+            let image = DeviceLocalImage::start(
+                device.clone(),
+                ImageDimensions::Dim2d {
+                    width: 4,
+                    height: 4,
+                    array_layers: 1,
+                },
+                Format::R8G8B8A8_UNORM,
+                ImageUsage {
+                    transfer_destination: true,
+                    sampled: true,
+                    ..ImageUsage::none()
+                },
+            )
+            .with_mipmaps(MipmapsCount::Log2)
+            .build()
+            .unwrap();
+            let source = CpuAccessibleBuffer::from_iter(
+                device,
+                BufferUsage::transfer_source(),
+                false,
+                iter::repeat(0u8).take(64),
+            )
+            .unwrap();
+
+            let region = |mip_level| UnsafeCommandBufferBuilderBufferImageCopy {
+                buffer_offset: 0,
+                buffer_row_length: 0,
+                buffer_image_height: 0,
+                image_aspect: ImageAspect::Color,
+                image_mip_level: mip_level,
+                image_base_array_layer: 0,
+                image_layer_count: 1,
+                image_offset: [0, 0, 0],
+                image_extent: [1, 1, 1],
+            };
+
+            // Returns the old layout of the barrier of `barriers` that covers `mip_level`.
+            let old_layout = |barriers: &[ash::vk::ImageMemoryBarrier], mip_level| {
+                barriers
+                    .iter()
+                    .find(|barrier| {
+                        let range = barrier.subresource_range;
+                        range.base_mip_level <= mip_level
+                            && mip_level < range.base_mip_level + range.level_count
+                    })
+                    .map(|barrier| barrier.old_layout)
+            };
+
+            // The first command buffer only writes the first mipmap level, but moves all the
+            // levels out of the undefined layout.
+            let pool_builder_alloc = pool.alloc(false, 1).unwrap().next().unwrap();
+            let mut sync = SyncCommandBufferBuilder::new(
+                &pool_builder_alloc.inner(),
+                CommandBufferLevel::primary(),
+                CommandBufferUsage::MultipleSubmit,
+            )
+            .unwrap();
+            sync.copy_buffer_to_image(
+                source.clone(),
+                image.clone(),
+                ImageLayout::TransferDstOptimal,
+                iter::once(region(0)),
+            )
+            .unwrap();
+
+            for mip_level in 0..image.mipmap_levels() {
+                assert_eq!(
+                    old_layout(sync.pending_barrier.image_barriers(), mip_level),
+                    Some(ash::vk::ImageLayout::UNDEFINED)
+                );
+            }
+            sync.build().unwrap();
+
+            // The second command buffer then finds the second level in the layout that the image
+            // is expected to be in.
+            let pool_builder_alloc = pool.alloc(false, 1).unwrap().next().unwrap();
+            let mut sync = SyncCommandBufferBuilder::new(
+                &pool_builder_alloc.inner(),
+                CommandBufferLevel::primary(),
+                CommandBufferUsage::MultipleSubmit,
+            )
+            .unwrap();
+            sync.copy_buffer_to_image(
+                source,
+                image.clone(),
+                ImageLayout::TransferDstOptimal,
+                iter::once(region(1)),
+            )
+            .unwrap();
+
+            assert_eq!(
+                old_layout(sync.pending_barrier.image_barriers(), 1),
+                Some(image.initial_layout_requirement().into())
+            );
+        }
+    }
+}
@@ -20,6 +20,7 @@ use crate::command_buffer::sys::UnsafeCommandBufferBuilderColorImageClear;
 use crate::command_buffer::sys::UnsafeCommandBufferBuilderExecuteCommands;
 use crate::command_buffer::sys::UnsafeCommandBufferBuilderImageBlit;
 use crate::command_buffer::sys::UnsafeCommandBufferBuilderImageCopy;
+use crate::command_buffer::sys::UnsafeCommandBufferBuilderPipelineBarrier;
 use crate::command_buffer::CommandBufferExecError;
 use crate::command_buffer::ImageUninitializedSafe;
 use crate::command_buffer::SecondaryCommandBuffer;
@@ -106,6 +107,48 @@ pub struct StencilState {
 }
 
 impl SyncCommandBufferBuilder {
+    /// Calls `vkCmdPipelineBarrier` on the builder, with a buffer memory barrier that acquires
+    /// the ownership of `buffer` for `destination_family`, after it has been released by
+    /// `source_family`.
+    #[inline]
+    pub unsafe fn acquire_buffer_ownership<B>(
+        &mut self,
+        buffer: B,
+        source_family: u32,
+        destination_family: u32,
+    ) where
+        B: BufferAccess + 'static,
+    {
+        self.buffer_ownership_transfer(buffer, (source_family, destination_family), false);
+    }
+
+    /// Calls `vkCmdPipelineBarrier` on the builder, with an image memory barrier that acquires
+    /// the ownership of `image` for `destination_family`, after it has been released by
+    /// `source_family`.
+    ///
+    /// The layouts must be the same as the ones of the release. Since the previous content of
+    /// the image is provided by the release, the image is treated as if it started out in the
+    /// `Undefined` layout in this command buffer.
+    #[inline]
+    pub unsafe fn acquire_image_ownership<I>(
+        &mut self,
+        image: I,
+        source_family: u32,
+        destination_family: u32,
+        old_layout: ImageLayout,
+        new_layout: ImageLayout,
+    ) where
+        I: ImageAccess + Send + Sync + 'static,
+    {
+        self.image_ownership_transfer(
+            image,
+            (source_family, destination_family),
+            old_layout,
+            new_layout,
+            false,
+        );
+    }
+
     /// Calls `vkCmdBeginQuery` on the builder.
     #[inline]
     pub unsafe fn begin_query(
@@ -183,6 +226,11 @@ impl SyncCommandBufferBuilder {
                 self.framebuffer.attached_image_view(num).unwrap().image()
             }
 
+            fn image_subresources(&self, num: usize) -> (Range<u32>, Range<u32>) {
+                let view = self.framebuffer.attached_image_view(num).unwrap();
+                (view.mipmap_levels(), view.array_layers())
+            }
+
             fn image_name(&self, num: usize) -> Cow<'static, str> {
                 format!("attachment {}", num).into()
             }
@@ -425,19 +473,18 @@ impl SyncCommandBufferBuilder {
         D: ImageAccess + Send + Sync + 'static,
         R: IntoIterator<Item = UnsafeCommandBufferBuilderImageCopy> + Send + Sync + 'static,
     {
-        struct Cmd<S, D, R> {
+        struct Cmd<S, D> {
             source: S,
             source_layout: ImageLayout,
             destination: D,
             destination_layout: ImageLayout,
-            regions: Mutex<Option<R>>,
+            regions: SmallVec<[UnsafeCommandBufferBuilderImageCopy; 1]>,
         }
 
-        impl<S, D, R> Command for Cmd<S, D, R>
+        impl<S, D> Command for Cmd<S, D>
         where
             S: ImageAccess + Send + Sync + 'static,
             D: ImageAccess + Send + Sync + 'static,
-            R: IntoIterator<Item = UnsafeCommandBufferBuilderImageCopy>,
         {
             fn name(&self) -> &'static str {
                 "vkCmdCopyImage"
@@ -449,7 +496,7 @@ impl SyncCommandBufferBuilder {
                     self.source_layout,
                     &self.destination,
                     self.destination_layout,
-                    self.regions.lock().unwrap().take().unwrap(),
+                    self.regions.iter().copied(),
                 );
             }
 
@@ -463,6 +510,34 @@ impl SyncCommandBufferBuilder {
                 }
             }
 
+            fn image_subresources(&self, num: usize) -> (Range<u32>, Range<u32>) {
+                if num == 0 {
+                    subresources_union(
+                        &self.source,
+                        self.regions.iter().map(|region| {
+                            (
+                                region.source_mip_level..region.source_mip_level + 1,
+                                region.source_base_array_layer
+                                    ..region.source_base_array_layer + region.layer_count,
+                            )
+                        }),
+                    )
+                } else if num == 1 {
+                    subresources_union(
+                        &self.destination,
+                        self.regions.iter().map(|region| {
+                            (
+                                region.destination_mip_level..region.destination_mip_level + 1,
+                                region.destination_base_array_layer
+                                    ..region.destination_base_array_layer + region.layer_count,
+                            )
+                        }),
+                    )
+                } else {
+                    panic!()
+                }
+            }
+
             fn image_name(&self, num: usize) -> Cow<'static, str> {
                 if num == 0 {
                     "source".into()
@@ -480,7 +555,7 @@ impl SyncCommandBufferBuilder {
                 source_layout,
                 destination,
                 destination_layout,
-                regions: Mutex::new(Some(regions)),
+                regions: regions.into_iter().collect(),
             },
             &[
                 (
@@ -546,20 +621,19 @@ impl SyncCommandBufferBuilder {
         D: ImageAccess + Send + Sync + 'static,
         R: IntoIterator<Item = UnsafeCommandBufferBuilderImageBlit> + Send + Sync + 'static,
     {
-        struct Cmd<S, D, R> {
+        struct Cmd<S, D> {
             source: S,
             source_layout: ImageLayout,
             destination: D,
             destination_layout: ImageLayout,
-            regions: Mutex<Option<R>>,
+            regions: SmallVec<[UnsafeCommandBufferBuilderImageBlit; 1]>,
             filter: Filter,
         }
 
-        impl<S, D, R> Command for Cmd<S, D, R>
+        impl<S, D> Command for Cmd<S, D>
         where
             S: ImageAccess + Send + Sync + 'static,
             D: ImageAccess + Send + Sync + 'static,
-            R: IntoIterator<Item = UnsafeCommandBufferBuilderImageBlit>,
         {
             fn name(&self) -> &'static str {
                 "vkCmdBlitImage"
@@ -571,7 +645,7 @@ impl SyncCommandBufferBuilder {
                     self.source_layout,
                     &self.destination,
                     self.destination_layout,
-                    self.regions.lock().unwrap().take().unwrap(),
+                    self.regions.iter().copied(),
                     self.filter,
                 );
             }
@@ -586,6 +660,34 @@ impl SyncCommandBufferBuilder {
                 }
             }
 
+            fn image_subresources(&self, num: usize) -> (Range<u32>, Range<u32>) {
+                if num == 0 {
+                    subresources_union(
+                        &self.source,
+                        self.regions.iter().map(|region| {
+                            (
+                                region.source_mip_level..region.source_mip_level + 1,
+                                region.source_base_array_layer
+                                    ..region.source_base_array_layer + region.layer_count,
+                            )
+                        }),
+                    )
+                } else if num == 1 {
+                    subresources_union(
+                        &self.destination,
+                        self.regions.iter().map(|region| {
+                            (
+                                region.destination_mip_level..region.destination_mip_level + 1,
+                                region.destination_base_array_layer
+                                    ..region.destination_base_array_layer + region.layer_count,
+                            )
+                        }),
+                    )
+                } else {
+                    panic!()
+                }
+            }
+
             fn image_name(&self, num: usize) -> Cow<'static, str> {
                 if num == 0 {
                     "source".into()
@@ -603,7 +705,7 @@ impl SyncCommandBufferBuilder {
                 source_layout,
                 destination,
                 destination_layout,
-                regions: Mutex::new(Some(regions)),
+                regions: regions.into_iter().collect(),
                 filter,
             },
             &[
@@ -666,20 +768,16 @@ impl SyncCommandBufferBuilder {
         I: ImageAccess + Send + Sync + 'static,
         R: IntoIterator<Item = UnsafeCommandBufferBuilderColorImageClear> + Send + Sync + 'static,
     {
-        struct Cmd<I, R> {
+        struct Cmd<I> {
             image: I,
             layout: ImageLayout,
             color: ClearValue,
-            regions: Mutex<Option<R>>,
+            regions: SmallVec<[UnsafeCommandBufferBuilderColorImageClear; 1]>,
         }
 
-        impl<I, R> Command for Cmd<I, R>
+        impl<I> Command for Cmd<I>
         where
             I: ImageAccess + Send + Sync + 'static,
-            R: IntoIterator<Item = UnsafeCommandBufferBuilderColorImageClear>
-                + Send
-                + Sync
-                + 'static,
         {
             fn name(&self) -> &'static str {
                 "vkCmdClearColorImage"
@@ -690,7 +788,7 @@ impl SyncCommandBufferBuilder {
                     &self.image,
                     self.layout,
                     self.color,
-                    self.regions.lock().unwrap().take().unwrap(),
+                    self.regions.iter().copied(),
                 );
             }
 
@@ -699,6 +797,19 @@ impl SyncCommandBufferBuilder {
                 &self.image
             }
 
+            fn image_subresources(&self, num: usize) -> (Range<u32>, Range<u32>) {
+                assert_eq!(num, 0);
+                subresources_union(
+                    &self.image,
+                    self.regions.iter().map(|region| {
+                        (
+                            region.base_mip_level..region.base_mip_level + region.level_count,
+                            region.base_array_layer..region.base_array_layer + region.layer_count,
+                        )
+                    }),
+                )
+            }
+
             fn image_name(&self, num: usize) -> Cow<'static, str> {
                 assert_eq!(num, 0);
                 "target".into()
@@ -710,7 +821,7 @@ impl SyncCommandBufferBuilder {
                 image,
                 layout,
                 color,
-                regions: Mutex::new(Some(regions)),
+                regions: regions.into_iter().collect(),
             },
             &[(
                 KeyTy::Image,
@@ -861,18 +972,17 @@ impl SyncCommandBufferBuilder {
         D: ImageAccess + Send + Sync + 'static,
         R: IntoIterator<Item = UnsafeCommandBufferBuilderBufferImageCopy> + Send + Sync + 'static,
     {
-        struct Cmd<S, D, R> {
+        struct Cmd<S, D> {
             source: S,
             destination: D,
             destination_layout: ImageLayout,
-            regions: Mutex<Option<R>>,
+            regions: SmallVec<[UnsafeCommandBufferBuilderBufferImageCopy; 1]>,
         }
 
-        impl<S, D, R> Command for Cmd<S, D, R>
+        impl<S, D> Command for Cmd<S, D>
         where
             S: BufferAccess + 'static,
             D: ImageAccess + Send + Sync + 'static,
-            R: IntoIterator<Item = UnsafeCommandBufferBuilderBufferImageCopy>,
         {
             fn name(&self) -> &'static str {
                 "vkCmdCopyBufferToImage"
@@ -883,7 +993,7 @@ impl SyncCommandBufferBuilder {
                     &self.source,
                     &self.destination,
                     self.destination_layout,
-                    self.regions.lock().unwrap().take().unwrap(),
+                    self.regions.iter().copied(),
                 );
             }
 
@@ -902,6 +1012,14 @@ impl SyncCommandBufferBuilder {
                 &self.destination
             }
 
+            fn image_subresources(&self, num: usize) -> (Range<u32>, Range<u32>) {
+                assert_eq!(num, 0);
+                subresources_union(
+                    &self.destination,
+                    self.regions.iter().map(buffer_image_copy_subresources),
+                )
+            }
+
             fn image_name(&self, num: usize) -> Cow<'static, str> {
                 assert_eq!(num, 0);
                 "destination".into()
@@ -913,7 +1031,7 @@ impl SyncCommandBufferBuilder {
                 source,
                 destination,
                 destination_layout,
-                regions: Mutex::new(Some(regions)),
+                regions: regions.into_iter().collect(),
             },
             &[
                 (
@@ -977,18 +1095,17 @@ impl SyncCommandBufferBuilder {
         D: BufferAccess + 'static,
         R: IntoIterator<Item = UnsafeCommandBufferBuilderBufferImageCopy> + Send + Sync + 'static,
     {
-        struct Cmd<S, D, R> {
+        struct Cmd<S, D> {
             source: S,
             source_layout: ImageLayout,
             destination: D,
-            regions: Mutex<Option<R>>,
+            regions: SmallVec<[UnsafeCommandBufferBuilderBufferImageCopy; 1]>,
         }
 
-        impl<S, D, R> Command for Cmd<S, D, R>
+        impl<S, D> Command for Cmd<S, D>
         where
             S: ImageAccess + Send + Sync + 'static,
             D: BufferAccess + 'static,
-            R: IntoIterator<Item = UnsafeCommandBufferBuilderBufferImageCopy>,
         {
             fn name(&self) -> &'static str {
                 "vkCmdCopyImageToBuffer"
@@ -999,7 +1116,7 @@ impl SyncCommandBufferBuilder {
                     &self.source,
                     self.source_layout,
                     &self.destination,
-                    self.regions.lock().unwrap().take().unwrap(),
+                    self.regions.iter().copied(),
                 );
             }
 
@@ -1018,6 +1135,14 @@ impl SyncCommandBufferBuilder {
                 &self.source
             }
 
+            fn image_subresources(&self, num: usize) -> (Range<u32>, Range<u32>) {
+                assert_eq!(num, 0);
+                subresources_union(
+                    &self.source,
+                    self.regions.iter().map(buffer_image_copy_subresources),
+                )
+            }
+
             fn image_name(&self, num: usize) -> Cow<'static, str> {
                 assert_eq!(num, 0);
                 "source".into()
@@ -1029,7 +1154,7 @@ impl SyncCommandBufferBuilder {
                 source,
                 destination,
                 source_layout,
-                regions: Mutex::new(Some(regions)),
+                regions: regions.into_iter().collect(),
             },
             &[
                 (
@@ -1298,6 +1423,21 @@ impl SyncCommandBufferBuilder {
                 panic!()
             }
 
+            fn image_subresources(&self, mut num: usize) -> (Range<u32>, Range<u32>) {
+                for set in self
+                    .descriptor_sets
+                    .iter()
+                    .enumerate()
+                    .map(|(set_num, cmd)| cmd.bound_descriptor_set(set_num as u32).0)
+                {
+                    if let Some(img) = set.image(num) {
+                        return (img.0.mipmap_levels(), img.0.array_layers());
+                    }
+                    num -= set.num_images();
+                }
+                panic!()
+            }
+
             fn image_name(&self, mut num: usize) -> Cow<'static, str> {
                 for (set_num, set) in self
                     .descriptor_sets
@@ -1417,6 +1557,21 @@ impl SyncCommandBufferBuilder {
                 panic!()
             }
 
+            fn image_subresources(&self, mut num: usize) -> (Range<u32>, Range<u32>) {
+                for set in self
+                    .descriptor_sets
+                    .iter()
+                    .enumerate()
+                    .map(|(set_num, cmd)| cmd.bound_descriptor_set(set_num as u32).0)
+                {
+                    if let Some(img) = set.image(num) {
+                        return (img.0.mipmap_levels(), img.0.array_layers());
+                    }
+                    num -= set.num_images();
+                }
+                panic!()
+            }
+
             fn image_name(&self, mut num: usize) -> Cow<'static, str> {
                 for (set_num, set) in self
                     .descriptor_sets
@@ -1562,6 +1717,21 @@ impl SyncCommandBufferBuilder {
                 panic!()
             }
 
+            fn image_subresources(&self, mut num: usize) -> (Range<u32>, Range<u32>) {
+                for set in self
+                    .descriptor_sets
+                    .iter()
+                    .enumerate()
+                    .map(|(set_num, cmd)| cmd.bound_descriptor_set(set_num as u32).0)
+                {
+                    if let Some(img) = set.image(num) {
+                        return (img.0.mipmap_levels(), img.0.array_layers());
+                    }
+                    num -= set.num_images();
+                }
+                panic!()
+            }
+
             fn image_name(&self, mut num: usize) -> Cow<'static, str> {
                 for (set_num, set) in self
                     .descriptor_sets
@@ -1723,6 +1893,21 @@ impl SyncCommandBufferBuilder {
                 panic!()
             }
 
+            fn image_subresources(&self, mut num: usize) -> (Range<u32>, Range<u32>) {
+                for set in self
+                    .descriptor_sets
+                    .iter()
+                    .enumerate()
+                    .map(|(set_num, cmd)| cmd.bound_descriptor_set(set_num as u32).0)
+                {
+                    if let Some(img) = set.image(num) {
+                        return (img.0.mipmap_levels(), img.0.array_layers());
+                    }
+                    num -= set.num_images();
+                }
+                panic!()
+            }
+
             fn image_name(&self, mut num: usize) -> Cow<'static, str> {
                 for (set_num, set) in self
                     .descriptor_sets
@@ -1882,6 +2067,21 @@ impl SyncCommandBufferBuilder {
                 panic!()
             }
 
+            fn image_subresources(&self, mut num: usize) -> (Range<u32>, Range<u32>) {
+                for set in self
+                    .descriptor_sets
+                    .iter()
+                    .enumerate()
+                    .map(|(set_num, cmd)| cmd.bound_descriptor_set(set_num as u32).0)
+                {
+                    if let Some(img) = set.image(num) {
+                        return (img.0.mipmap_levels(), img.0.array_layers());
+                    }
+                    num -= set.num_images();
+                }
+                panic!()
+            }
+
             fn image_name(&self, mut num: usize) -> Cow<'static, str> {
                 for (set_num, set) in self
                     .descriptor_sets
@@ -2044,6 +2244,21 @@ impl SyncCommandBufferBuilder {
                 panic!()
             }
 
+            fn image_subresources(&self, mut num: usize) -> (Range<u32>, Range<u32>) {
+                for set in self
+                    .descriptor_sets
+                    .iter()
+                    .enumerate()
+                    .map(|(set_num, cmd)| cmd.bound_descriptor_set(set_num as u32).0)
+                {
+                    if let Some(img) = set.image(num) {
+                        return (img.0.mipmap_levels(), img.0.array_layers());
+                    }
+                    num -= set.num_images();
+                }
+                panic!()
+            }
+
             fn image_name(&self, mut num: usize) -> Cow<'static, str> {
                 for (set_num, set) in self
                     .descriptor_sets
@@ -2300,6 +2515,201 @@ impl SyncCommandBufferBuilder {
             .map(|state| &state.pipeline_layout)
     }
 
+    /// Calls `vkCmdPipelineBarrier` on the builder, with a buffer memory barrier that releases
+    /// the ownership of `buffer` from `source_family`, so that it can be acquired by
+    /// `destination_family`.
+    #[inline]
+    pub unsafe fn release_buffer_ownership<B>(
+        &mut self,
+        buffer: B,
+        source_family: u32,
+        destination_family: u32,
+    ) where
+        B: BufferAccess + 'static,
+    {
+        self.buffer_ownership_transfer(buffer, (source_family, destination_family), true);
+    }
+
+    /// Calls `vkCmdPipelineBarrier` on the builder, with an image memory barrier that releases
+    /// the ownership of `image` from `source_family`, so that it can be acquired by
+    /// `destination_family`. The image is transitioned from `old_layout` to `new_layout` as part
+    /// of the transfer.
+    #[inline]
+    pub unsafe fn release_image_ownership<I>(
+        &mut self,
+        image: I,
+        source_family: u32,
+        destination_family: u32,
+        old_layout: ImageLayout,
+        new_layout: ImageLayout,
+    ) where
+        I: ImageAccess + Send + Sync + 'static,
+    {
+        self.image_ownership_transfer(
+            image,
+            (source_family, destination_family),
+            old_layout,
+            new_layout,
+            true,
+        );
+    }
+
+    // Adds the release or the acquire half of a queue family ownership transfer of a buffer.
+    unsafe fn buffer_ownership_transfer<B>(
+        &mut self,
+        buffer: B,
+        queue_transfer: (u32, u32),
+        release: bool,
+    ) where
+        B: BufferAccess + 'static,
+    {
+        struct Cmd<B> {
+            buffer: B,
+            queue_transfer: (u32, u32),
+            release: bool,
+        }
+
+        impl<B> Command for Cmd<B>
+        where
+            B: BufferAccess + 'static,
+        {
+            fn name(&self) -> &'static str {
+                "vkCmdPipelineBarrier"
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                let ((source_stage, source_access), (destination_stage, destination_access)) =
+                    ownership_transfer_accesses(self.release);
+                let mut barrier = UnsafeCommandBufferBuilderPipelineBarrier::new();
+                barrier.add_buffer_memory_barrier(
+                    &self.buffer,
+                    source_stage,
+                    source_access,
+                    destination_stage,
+                    destination_access,
+                    false,
+                    Some(self.queue_transfer),
+                    0,
+                    self.buffer.size(),
+                );
+                out.pipeline_barrier(&barrier);
+            }
+
+            fn buffer(&self, num: usize) -> &dyn BufferAccess {
+                assert_eq!(num, 0);
+                &self.buffer
+            }
+
+            fn buffer_name(&self, _: usize) -> Cow<'static, str> {
+                "buffer".into()
+            }
+        }
+
+        self.append_command(
+            Cmd {
+                buffer,
+                queue_transfer,
+                release,
+            },
+            &[(
+                KeyTy::Buffer,
+                Some((
+                    ownership_transfer_memory(),
+                    ImageLayout::Undefined,
+                    ImageLayout::Undefined,
+                    ImageUninitializedSafe::Unsafe,
+                )),
+            )],
+        )
+        .unwrap();
+    }
+
+    // Adds the release or the acquire half of a queue family ownership transfer of an image.
+    unsafe fn image_ownership_transfer<I>(
+        &mut self,
+        image: I,
+        queue_transfer: (u32, u32),
+        old_layout: ImageLayout,
+        new_layout: ImageLayout,
+        release: bool,
+    ) where
+        I: ImageAccess + Send + Sync + 'static,
+    {
+        struct Cmd<I> {
+            image: I,
+            queue_transfer: (u32, u32),
+            old_layout: ImageLayout,
+            new_layout: ImageLayout,
+            release: bool,
+        }
+
+        impl<I> Command for Cmd<I>
+        where
+            I: ImageAccess + Send + Sync + 'static,
+        {
+            fn name(&self) -> &'static str {
+                "vkCmdPipelineBarrier"
+            }
+
+            unsafe fn send(&self, out: &mut UnsafeCommandBufferBuilder) {
+                let ((source_stage, source_access), (destination_stage, destination_access)) =
+                    ownership_transfer_accesses(self.release);
+                let mut barrier = UnsafeCommandBufferBuilderPipelineBarrier::new();
+                barrier.add_image_memory_barrier(
+                    &self.image,
+                    self.image.current_miplevels_access(),
+                    self.image.current_layer_levels_access(),
+                    source_stage,
+                    source_access,
+                    destination_stage,
+                    destination_access,
+                    false,
+                    Some(self.queue_transfer),
+                    self.old_layout,
+                    self.new_layout,
+                );
+                out.pipeline_barrier(&barrier);
+            }
+
+            fn image(&self, num: usize) -> &dyn ImageAccess {
+                assert_eq!(num, 0);
+                &self.image
+            }
+
+            fn image_name(&self, _: usize) -> Cow<'static, str> {
+                "image".into()
+            }
+        }
+
+        // The acquire doesn't need the image to be in any particular layout in this command
+        // buffer, as its content comes from the queue family that released it.
+        let start_layout = if release {
+            old_layout
+        } else {
+            ImageLayout::Undefined
+        };
+
+        self.append_command(
+            Cmd {
+                image,
+                queue_transfer,
+                old_layout,
+                new_layout,
+                release,
+            },
+            &[(
+                KeyTy::Image,
+                Some((
+                    ownership_transfer_memory(),
+                    start_layout,
+                    new_layout,
+                    ImageUninitializedSafe::Unsafe,
+                )),
+            )],
+        )
+        .unwrap();
+    }
+
     /// Calls `vkCmdResetEvent` on the builder.
     #[inline]
     pub unsafe fn reset_event(&mut self, event: Arc<Event>, stages: PipelineStages) {
@@ -3290,6 +3700,16 @@ impl<'a> SyncCommandBufferBuilderExecuteCommands<'a> {
                 panic!()
             }
 
+            fn image_subresources(&self, mut num: usize) -> (Range<u32>, Range<u32>) {
+                for cbuf in self.0.iter() {
+                    if let Some(subresources) = cbuf.image_subresources(num) {
+                        return subresources;
+                    }
+                    num -= cbuf.num_images();
+                }
+                panic!()
+            }
+
             fn image_name(&self, mut num: usize) -> Cow<'static, str> {
                 for (cbuf_num, cbuf) in self.0.iter().enumerate() {
                     if let Some(img) = cbuf.image(num) {
@@ -3343,3 +3763,101 @@ impl<'a> SyncCommandBufferBuilderExecuteCommands<'a> {
         Ok(())
     }
 }
+
+// Returns the smallest ranges of mipmap levels and array layers that contain all the given
+// ranges. Falls back to the ranges that `image` currently gives access to if there are none.
+fn subresources_union<I>(image: &dyn ImageAccess, subresources: I) -> (Range<u32>, Range<u32>)
+where
+    I: IntoIterator<Item = (Range<u32>, Range<u32>)>,
+{
+    subresources
+        .into_iter()
+        .fold(
+            None,
+            |union: Option<(Range<u32>, Range<u32>)>, (mip_levels, array_layers)| {
+                Some(match union {
+                    Some((union_mip_levels, union_array_layers)) => (
+                        union_mip_levels.start.min(mip_levels.start)
+                            ..union_mip_levels.end.max(mip_levels.end),
+                        union_array_layers.start.min(array_layers.start)
+                            ..union_array_layers.end.max(array_layers.end),
+                    ),
+                    None => (mip_levels, array_layers),
+                })
+            },
+        )
+        .unwrap_or_else(|| {
+            (
+                image.current_miplevels_access(),
+                image.current_layer_levels_access(),
+            )
+        })
+}
+
+// Returns the range of mipmap levels and the range of array layers of the image that a
+// buffer-image copy region accesses.
+fn buffer_image_copy_subresources(
+    region: &UnsafeCommandBufferBuilderBufferImageCopy,
+) -> (Range<u32>, Range<u32>) {
+    (
+        region.image_mip_level..region.image_mip_level + 1,
+        region.image_base_array_layer..region.image_base_array_layer + region.image_layer_count,
+    )
+}
+
+// Returns how the half of a queue family ownership transfer that is recorded in the command
+// buffer accesses the resource. This is as broad as possible, since the commands that use the
+// resource on the other side of the transfer aren't known.
+fn ownership_transfer_memory() -> PipelineMemoryAccess {
+    PipelineMemoryAccess {
+        stages: PipelineStages {
+            all_commands: true,
+            ..PipelineStages::none()
+        },
+        access: AccessFlags {
+            memory_read: true,
+            memory_write: true,
+            ..AccessFlags::none()
+        },
+        exclusive: true,
+    }
+}
+
+// Returns the source and destination stages and accesses of the pipeline barrier of a queue family
+// ownership transfer. The destination of a release and the source of an acquire are ignored by
+// Vulkan, and are only required to be valid.
+fn ownership_transfer_accesses(
+    release: bool,
+) -> ((PipelineStages, AccessFlags), (PipelineStages, AccessFlags)) {
+    let memory = ownership_transfer_memory();
+
+    if release {
+        (
+            (
+                memory.stages,
+                AccessFlags {
+                    memory_write: true,
+                    ..AccessFlags::none()
+                },
+            ),
+            (
+                PipelineStages {
+                    bottom_of_pipe: true,
+                    ..PipelineStages::none()
+                },
+                AccessFlags::none(),
+            ),
+        )
+    } else {
+        (
+            (
+                PipelineStages {
+                    top_of_pipe: true,
+                    ..PipelineStages::none()
+                },
+                AccessFlags::none(),
+            ),
+            (memory.stages, memory.access),
+        )
+    }
+}
@@ -320,20 +320,37 @@ impl SyncCommandBuffer {
     ) -> Result<Option<(PipelineStages, AccessFlags)>, AccessCheckError> {
         // TODO: check the queue family
         if let Some(value) = self.resources.get(&image.into()) {
-            if layout != ImageLayout::Undefined && value.final_layout != layout {
-                return Err(AccessCheckError::Denied(
-                    AccessError::UnexpectedImageLayout {
-                        allowed: value.final_layout,
-                        requested: layout,
-                    },
-                ));
-            }
+            let mip_levels = image.current_miplevels_access();
+            let array_layers = image.current_layer_levels_access();
+            let mut result: Option<(PipelineStages, AccessFlags)> = None;
+
+            for subresource in value
+                .subresources
+                .iter()
+                .filter(|subresource| subresource.overlaps(&mip_levels, &array_layers))
+            {
+                if layout != ImageLayout::Undefined && subresource.final_layout != layout {
+                    return Err(AccessCheckError::Denied(
+                        AccessError::UnexpectedImageLayout {
+                            allowed: subresource.final_layout,
+                            requested: layout,
+                        },
+                    ));
+                }
 
-            if !value.exclusive && exclusive {
-                return Err(AccessCheckError::Unknown);
+                if !subresource.exclusive && exclusive {
+                    return Err(AccessCheckError::Unknown);
+                }
+
+                let (stages, access) =
+                    result.get_or_insert((PipelineStages::none(), AccessFlags::none()));
+                *stages |= subresource.final_stages;
+                *access |= subresource.final_access;
             }
 
-            return Ok(Some((value.final_stages, value.final_access)));
+            if let Some(result) = result {
+                return Ok(Some(result));
+            }
         }
 
         Err(AccessCheckError::Unknown)
@@ -381,6 +398,16 @@ impl SyncCommandBuffer {
             },
         )
     }
+
+    /// Returns the range of mipmap levels and the range of array layers of the `index`th image
+    /// that are accessed by the command buffer.
+    #[inline]
+    pub fn image_subresources(&self, index: usize) -> Option<(Range<u32>, Range<u32>)> {
+        self.images.get(index).map(|(location, ..)| {
+            let cmd = &self.commands[location.command_id];
+            cmd.image_subresources(location.resource_index)
+        })
+    }
 }
 
 impl AsRef<UnsafeCommandBuffer> for SyncCommandBuffer {
@@ -398,11 +425,12 @@ unsafe impl DeviceOwned for SyncCommandBuffer {
 }
 
 // Key that identifies a resource. Implements `PartialEq`, `Eq` and `Hash` so that two resources
-// that conflict with each other compare equal.
+// that conflict with each other compare equal. The mipmap levels and array layers of an image
+// all share the same key, and are tracked separately within the state of the resource.
 #[derive(Debug, PartialEq, Eq, Hash)]
 enum ResourceKey {
     Buffer((u64, u64)),
-    Image(u64, Range<u32>, Range<u32>),
+    Image(u64),
 }
 
 impl From<&dyn BufferAccess> for ResourceKey {
@@ -415,11 +443,7 @@ impl From<&dyn BufferAccess> for ResourceKey {
 impl From<&dyn ImageAccess> for ResourceKey {
     #[inline]
     fn from(image: &dyn ImageAccess) -> Self {
-        Self::Image(
-            image.conflict_key(),
-            image.current_miplevels_access(),
-            image.current_layer_levels_access(),
-        )
+        Self::Image(image.conflict_key())
     }
 }
 
@@ -432,9 +456,13 @@ struct ResourceFinalState {
     // Index of the resource within the first command in `command_ids`.
     resource_index: usize,
 
-    // Stages of the last command that uses the resource.
+    // Final state of the ranges of mipmap levels and array layers that are used by the command
+    // buffer. Buffers always have a single range.
+    subresources: Vec<SubresourceFinalState>,
+
+    // Union of the stages of the last commands that use each range of the resource.
     final_stages: PipelineStages,
-    // Access for the last command that uses the resource.
+    // Union of the accesses of the last commands that use each range of the resource.
     final_access: AccessFlags,
 
     // True if the resource is used in exclusive mode.
@@ -450,6 +478,34 @@ struct ResourceFinalState {
     image_uninitialized_safe: ImageUninitializedSafe,
 }
 
+// Usage of a range of mipmap levels and array layers of a resource in a finished command buffer.
+#[derive(Debug, Clone)]
+struct SubresourceFinalState {
+    mip_levels: Range<u32>,
+    array_layers: Range<u32>,
+
+    // Stages of the last command that uses the range.
+    final_stages: PipelineStages,
+    // Access for the last command that uses the range.
+    final_access: AccessFlags,
+
+    // True if the range is used in exclusive mode.
+    exclusive: bool,
+
+    // Layout the range will be in at the end of the command buffer.
+    final_layout: ImageLayout,
+}
+
+impl SubresourceFinalState {
+    #[inline]
+    fn overlaps(&self, mip_levels: &Range<u32>, array_layers: &Range<u32>) -> bool {
+        self.mip_levels.start < mip_levels.end
+            && mip_levels.start < self.mip_levels.end
+            && self.array_layers.start < array_layers.end
+            && array_layers.start < self.array_layers.end
+    }
+}
+
 // Identifies a resource within the list of commands.
 #[derive(Clone, Copy, Debug)]
 struct ResourceLocation {
@@ -478,6 +534,16 @@ trait Command {
         panic!()
     }
 
+    // Returns the range of mipmap levels and the range of array layers of the `num`th image that
+    // are accessed by the command. Only these parts of the image are synchronized.
+    fn image_subresources(&self, num: usize) -> (Range<u32>, Range<u32>) {
+        let image = self.image(num);
+        (
+            image.current_miplevels_access(),
+            image.current_layer_levels_access(),
+        )
+    }
+
     // Returns a user-friendly name for the `num`th buffer used by the command, for error
     // reporting purposes.
     fn buffer_name(&self, _num: usize) -> Cow<'static, str> {
@@ -526,6 +592,7 @@ mod tests {
     use crate::buffer::ImmutableBuffer;
     use crate::command_buffer::pool::CommandPool;
     use crate::command_buffer::pool::CommandPoolBuilderAlloc;
+    use crate::command_buffer::sys::UnsafeCommandBufferBuilderImageBlit;
     use crate::command_buffer::AutoCommandBufferBuilder;
     use crate::command_buffer::CommandBufferLevel;
     use crate::command_buffer::CommandBufferUsage;
@@ -534,9 +601,17 @@ mod tests {
     use crate::descriptor_set::layout::DescriptorSetLayout;
     use crate::descriptor_set::PersistentDescriptorSet;
     use crate::device::Device;
+    use crate::format::Format;
+    use crate::image::DeviceLocalImage;
+    use crate::image::ImageAspects;
+    use crate::image::ImageDimensions;
+    use crate::image::ImageLayout;
+    use crate::image::ImageUsage;
+    use crate::image::MipmapsCount;
     use crate::pipeline::layout::PipelineLayout;
     use crate::pipeline::shader::ShaderStages;
     use crate::pipeline::PipelineBindPoint;
+    use crate::sampler::Filter;
     use crate::sampler::Sampler;
     use crate::sync::GpuFuture;
     use std::sync::Arc;
@@ -582,6 +657,92 @@ mod tests {
         }
     }
 
+    #[test]
+    fn blit_between_mipmap_levels() {
+        unsafe {
+            let (device, queue) = gfx_dev_and_queue!();
+
+            let pool = Device::standard_command_pool(&device, queue.family());
+            let pool_builder_alloc = pool.alloc(false, 1).unwrap().next().unwrap();
+            let mut sync = SyncCommandBufferBuilder::new(
+                &pool_builder_alloc.inner(),
+                CommandBufferLevel::primary(),
+                CommandBufferUsage::MultipleSubmit,
+            )
+            .unwrap();
+            let image = DeviceLocalImage::start(
+                device,
+                ImageDimensions::Dim2d {
+                    width: 4,
+                    height: 4,
+                    array_layers: 1,
+                },
+                Format::R8G8B8A8_UNORM,
+                ImageUsage {
+                    transfer_source: true,
+                    transfer_destination: true,
+                    ..ImageUsage::none()
+                },
+            )
+            .with_mipmaps(MipmapsCount::Log2)
+            .build()
+            .unwrap();
+
+            let region =
+                |source_mip_level, destination_mip_level| UnsafeCommandBufferBuilderImageBlit {
+                    aspects: ImageAspects {
+                        color: true,
+                        ..ImageAspects::none()
+                    },
+                    source_mip_level,
+                    destination_mip_level,
+                    source_base_array_layer: 0,
+                    destination_base_array_layer: 0,
+                    layer_count: 1,
+                    source_top_left: [0, 0, 0],
+                    source_bottom_right: [1, 1, 1],
+                    destination_top_left: [0, 0, 0],
+                    destination_bottom_right: [1, 1, 1],
+                };
+
+            // Reading and writing different mipmap levels of the same image doesn't conflict.
+            assert!(sync
+                .blit_image(
+                    image.clone(),
+                    ImageLayout::TransferSrcOptimal,
+                    image.clone(),
+                    ImageLayout::TransferDstOptimal,
+                    std::iter::once(region(0, 1)),
+                    Filter::Nearest,
+                )
+                .is_ok());
+
+            // The level that was just written can be read by the next blit.
+            assert!(sync
+                .blit_image(
+                    image.clone(),
+                    ImageLayout::TransferSrcOptimal,
+                    image.clone(),
+                    ImageLayout::TransferDstOptimal,
+                    std::iter::once(region(1, 2)),
+                    Filter::Nearest,
+                )
+                .is_ok());
+
+            assert!(matches!(
+                sync.blit_image(
+                    image.clone(),
+                    ImageLayout::TransferSrcOptimal,
+                    image.clone(),
+                    ImageLayout::TransferDstOptimal,
+                    std::iter::once(region(2, 2)),
+                    Filter::Nearest,
+                ),
+                Err(SyncCommandBufferBuilderError::Conflict { .. })
+            ));
+        }
+    }
+
     #[test]
     fn secondary_conflicting_writes() {
         unsafe {
@@ -719,6 +880,7 @@ mod tests {
                         stages: ShaderStages::all(),
                         mutable: false,
                         variable_count: false,
+                        name: None,
                     })],
                 )
                 .unwrap(),
@@ -12,9 +12,12 @@ use crate::buffer::BufferInner;
 use crate::buffer::TypedBufferAccess;
 use crate::check_errors;
 use crate::command_buffer::pool::UnsafeCommandPoolAlloc;
+use crate::command_buffer::BufferImageCopy;
 use crate::command_buffer::CommandBufferInheritance;
 use crate::command_buffer::CommandBufferLevel;
 use crate::command_buffer::CommandBufferUsage;
+use crate::command_buffer::ImageBlit;
+use crate::command_buffer::ImageCopy;
 use crate::command_buffer::SecondaryCommandBuffer;
 use crate::command_buffer::SubpassContents;
 use crate::descriptor_set::sys::UnsafeDescriptorSet;
@@ -1634,6 +1637,25 @@ pub struct UnsafeCommandBufferBuilderBufferImageCopy {
     pub image_extent: [u32; 3],
 }
 
+impl From<BufferImageCopy> for UnsafeCommandBufferBuilderBufferImageCopy {
+    #[inline]
+    fn from(region: BufferImageCopy) -> Self {
+        let [x, y, z] = region.image_offset;
+
+        UnsafeCommandBufferBuilderBufferImageCopy {
+            buffer_offset: region.buffer_offset,
+            buffer_row_length: region.buffer_row_length,
+            buffer_image_height: region.buffer_image_height,
+            image_aspect: region.image_aspect,
+            image_mip_level: region.image_mip_level,
+            image_base_array_layer: region.image_base_array_layer,
+            image_layer_count: region.image_layer_count,
+            image_offset: [x as i32, y as i32, z as i32],
+            image_extent: region.image_extent,
+        }
+    }
+}
+
 // TODO: move somewhere else?
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct UnsafeCommandBufferBuilderImageCopy {
@@ -1648,6 +1670,23 @@ pub struct UnsafeCommandBufferBuilderImageCopy {
     pub extent: [u32; 3],
 }
 
+impl From<ImageCopy> for UnsafeCommandBufferBuilderImageCopy {
+    #[inline]
+    fn from(region: ImageCopy) -> Self {
+        UnsafeCommandBufferBuilderImageCopy {
+            aspects: region.aspects,
+            source_mip_level: region.source_mip_level,
+            destination_mip_level: region.destination_mip_level,
+            source_base_array_layer: region.source_base_array_layer,
+            destination_base_array_layer: region.destination_base_array_layer,
+            layer_count: region.layer_count,
+            source_offset: region.source_offset,
+            destination_offset: region.destination_offset,
+            extent: region.extent,
+        }
+    }
+}
+
 // TODO: move somewhere else?
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct UnsafeCommandBufferBuilderImageBlit {
@@ -1663,6 +1702,24 @@ pub struct UnsafeCommandBufferBuilderImageBlit {
     pub destination_bottom_right: [i32; 3],
 }
 
+impl From<ImageBlit> for UnsafeCommandBufferBuilderImageBlit {
+    #[inline]
+    fn from(region: ImageBlit) -> Self {
+        UnsafeCommandBufferBuilderImageBlit {
+            aspects: region.aspects,
+            source_mip_level: region.source_mip_level,
+            destination_mip_level: region.destination_mip_level,
+            source_base_array_layer: region.source_base_array_layer,
+            destination_base_array_layer: region.destination_base_array_layer,
+            layer_count: region.layer_count,
+            source_top_left: region.source_top_left,
+            source_bottom_right: region.source_bottom_right,
+            destination_top_left: region.destination_top_left,
+            destination_bottom_right: region.destination_bottom_right,
+        }
+    }
+}
+
 /// Command that adds a pipeline barrier to a command buffer builder.
 ///
 /// A pipeline barrier is a low-level system-ish command that is often necessary for safety. By
@@ -1705,6 +1762,12 @@ impl UnsafeCommandBufferBuilderPipelineBarrier {
         self.src_stage_mask.is_empty() || self.dst_stage_mask.is_empty()
     }
 
+    // Returns the image memory barriers that have been added so far.
+    #[cfg(test)]
+    pub(crate) fn image_barriers(&self) -> &[ash::vk::ImageMemoryBarrier] {
+        &self.image_barriers
+    }
+
     /// Merges another pipeline builder into this one.
     #[inline]
     pub fn merge(&mut self, other: UnsafeCommandBufferBuilderPipelineBarrier) {
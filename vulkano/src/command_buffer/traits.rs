@@ -33,6 +33,7 @@ use crate::VulkanObject;
 use std::borrow::Cow;
 use std::error;
 use std::fmt;
+use std::ops::Range;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
@@ -248,6 +249,20 @@ pub unsafe trait SecondaryCommandBuffer: DeviceOwned {
         ImageLayout,
         ImageUninitializedSafe,
     )>;
+
+    /// Returns the range of mipmap levels and the range of array layers of the `index`th image
+    /// that are accessed by this command buffer, or `None` if out of range.
+    ///
+    /// The default implementation returns the ranges that the image itself gives access to.
+    #[inline]
+    fn image_subresources(&self, index: usize) -> Option<(Range<u32>, Range<u32>)> {
+        self.image(index).map(|(image, ..)| {
+            (
+                image.current_miplevels_access(),
+                image.current_layer_levels_access(),
+            )
+        })
+    }
 }
 
 unsafe impl<T> SecondaryCommandBuffer for T
@@ -303,6 +318,11 @@ where
     )> {
         (**self).image(index)
     }
+
+    #[inline]
+    fn image_subresources(&self, index: usize) -> Option<(Range<u32>, Range<u32>)> {
+        (**self).image_subresources(index)
+    }
 }
 
 /// Represents a command buffer being executed by the GPU and the moment when the execution
@@ -0,0 +1,581 @@
+// Copyright (c) 2021 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use crate::buffer::BufferAccess;
+use crate::buffer::BufferUsage;
+use crate::buffer::CpuAccessibleBuffer;
+use crate::buffer::ImmutableBuffer;
+use crate::command_buffer::AutoCommandBufferBuilder;
+use crate::command_buffer::AutoCommandBufferBuilderContextError;
+use crate::command_buffer::BuildError;
+use crate::command_buffer::CommandBufferExecError;
+use crate::command_buffer::CommandBufferExecFuture;
+use crate::command_buffer::CommandBufferUsage;
+use crate::command_buffer::CopyBufferError;
+use crate::command_buffer::CopyBufferImageError;
+use crate::command_buffer::PrimaryAutoCommandBuffer;
+use crate::command_buffer::PrimaryCommandBuffer;
+use crate::device::Queue;
+use crate::format::Format;
+use crate::format::Pixel;
+use crate::image::ImageCreateFlags;
+use crate::image::ImageCreationError;
+use crate::image::ImageDimensions;
+use crate::image::ImageLayout;
+use crate::image::ImageUsage;
+use crate::image::ImmutableImage;
+use crate::image::MipmapsCount;
+use crate::memory::Content;
+use crate::memory::DeviceMemoryAllocError;
+use crate::sync::GpuFuture;
+use crate::sync::NowFuture;
+use crate::sync::SemaphoreSignalFuture;
+use crate::OomError;
+use std::error;
+use std::fmt;
+use std::iter;
+use std::sync::Arc;
+
+/// Future returned by `UploadBatch::execute`. Represents the moment when the uploads are finished
+/// and the resources are owned by the queue family of the destination queue.
+pub type UploadBatchFuture = CommandBufferExecFuture<
+    SemaphoreSignalFuture<CommandBufferExecFuture<NowFuture, PrimaryAutoCommandBuffer>>,
+    PrimaryAutoCommandBuffer,
+>;
+
+/// Records the uploads of many immutable buffers and images into a single command buffer of a
+/// transfer queue.
+///
+/// Contrary to `ImmutableBuffer::from_iter` and `ImmutableImage::from_iter`, which each submit
+/// their own command buffer on the queue that will use the resource, the copies of all the
+/// resources of the batch are submitted at once on `transfer_queue`, which is usually a queue
+/// dedicated to transfers that can run alongside the rendering.
+///
+/// The resources are created with exclusive sharing for the queue family of `destination_queue`.
+/// If the two queues belong to different families, the ownership of each resource is released
+/// from the transfer queue family at the end of the transfer command buffer, and acquired by the
+/// destination queue family in a second command buffer that is executed on `destination_queue`.
+///
+/// The resources can be used on `destination_queue` once the `UploadBatchFuture` returned by
+/// `execute` has been joined with the future of the commands that use them.
+///
+/// # Example
+///
+/// ```
+/// use vulkano::buffer::BufferUsage;
+/// use vulkano::command_buffer::UploadBatch;
+/// use vulkano::format::Format;
+/// use vulkano::image::ImageDimensions;
+/// use vulkano::sync::GpuFuture;
+/// # let transfer_queue: std::sync::Arc<vulkano::device::Queue> = return;
+/// # let graphics_queue: std::sync::Arc<vulkano::device::Queue> = return;
+///
+/// let mut batch = UploadBatch::new(transfer_queue.clone(), graphics_queue.clone()).unwrap();
+/// let vertices = batch
+///     .buffer_from_iter([0.0f32, 1.0, 2.0].iter().cloned(), BufferUsage::vertex_buffer())
+///     .unwrap();
+/// let texture = batch
+///     .image_from_iter(
+///         [[255u8; 4]; 16].iter().cloned(),
+///         ImageDimensions::Dim2d { width: 4, height: 4, array_layers: 1 },
+///         Format::R8G8B8A8_UNORM,
+///     )
+///     .unwrap();
+/// let upload_future = batch.execute().unwrap();
+///
+/// // The future can then be joined with the future of the frame that uses the resources.
+/// let frame_future = vulkano::sync::now(graphics_queue.device().clone()).join(upload_future);
+/// ```
+pub struct UploadBatch {
+    // Queue on which the copies are executed.
+    transfer_queue: Arc<Queue>,
+
+    // Queue on which the resources are going to be used.
+    destination_queue: Arc<Queue>,
+
+    // Copies of the resources, followed by the releases of their ownership if needed.
+    transfer_builder: AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+
+    // Acquisitions of the ownership of the resources. Stays empty if the two queues belong to the
+    // same family.
+    acquire_builder: AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>,
+}
+
+impl UploadBatch {
+    /// Starts a new batch of uploads, which will be executed on `transfer_queue` and whose
+    /// resources will be used on `destination_queue`.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the two queues don't belong to the same device.
+    pub fn new(
+        transfer_queue: Arc<Queue>,
+        destination_queue: Arc<Queue>,
+    ) -> Result<UploadBatch, OomError> {
+        assert_eq!(transfer_queue.device(), destination_queue.device());
+
+        let transfer_builder = AutoCommandBufferBuilder::primary(
+            transfer_queue.device().clone(),
+            transfer_queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )?;
+        let acquire_builder = AutoCommandBufferBuilder::primary(
+            destination_queue.device().clone(),
+            destination_queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )?;
+
+        Ok(UploadBatch {
+            transfer_queue,
+            destination_queue,
+            transfer_builder,
+            acquire_builder,
+        })
+    }
+
+    /// Adds the upload of a buffer that contains `data` to the batch.
+    ///
+    /// The buffer can't be used before the future returned by `execute` is reached.
+    pub fn buffer_from_data<T>(
+        &mut self,
+        data: T,
+        usage: BufferUsage,
+    ) -> Result<Arc<ImmutableBuffer<T>>, UploadBatchError>
+    where
+        T: Content + Copy + Send + Sync + 'static,
+    {
+        let source = CpuAccessibleBuffer::from_data(
+            self.transfer_queue.device().clone(),
+            BufferUsage::transfer_source(),
+            false,
+            data,
+        )?;
+
+        unsafe {
+            let (buffer, init) = ImmutableBuffer::raw(
+                self.transfer_queue.device().clone(),
+                source.size(),
+                BufferUsage {
+                    transfer_destination: true,
+                    ..usage
+                },
+                iter::once(self.destination_queue.family()),
+            )?;
+
+            self.transfer_builder.copy_buffer(source, init.clone())?;
+            if self.needs_ownership_transfer() {
+                // The initialization access of the transfer command buffer can only be locked
+                // once, so the acquire gets its own.
+                self.acquire_builder.acquire_buffer_ownership(
+                    init.ownership_acquire(),
+                    self.transfer_queue.family(),
+                )?;
+                self.transfer_builder
+                    .release_buffer_ownership(init, self.destination_queue.family())?;
+            }
+
+            Ok(buffer)
+        }
+    }
+
+    /// Adds the upload of a buffer that contains the elements of `data` to the batch.
+    ///
+    /// The buffer can't be used before the future returned by `execute` is reached.
+    pub fn buffer_from_iter<T, D>(
+        &mut self,
+        data: D,
+        usage: BufferUsage,
+    ) -> Result<Arc<ImmutableBuffer<[T]>>, UploadBatchError>
+    where
+        D: ExactSizeIterator<Item = T>,
+        T: Send + Sync + 'static,
+    {
+        let source = CpuAccessibleBuffer::from_iter(
+            self.transfer_queue.device().clone(),
+            BufferUsage::transfer_source(),
+            false,
+            data,
+        )?;
+
+        unsafe {
+            let (buffer, init) = ImmutableBuffer::raw(
+                self.transfer_queue.device().clone(),
+                source.size(),
+                BufferUsage {
+                    transfer_destination: true,
+                    ..usage
+                },
+                iter::once(self.destination_queue.family()),
+            )?;
+
+            self.transfer_builder.copy_buffer(source, init.clone())?;
+            if self.needs_ownership_transfer() {
+                // The initialization access of the transfer command buffer can only be locked
+                // once, so the acquire gets its own.
+                self.acquire_builder.acquire_buffer_ownership(
+                    init.ownership_acquire(),
+                    self.transfer_queue.family(),
+                )?;
+                self.transfer_builder
+                    .release_buffer_ownership(init, self.destination_queue.family())?;
+            }
+
+            Ok(buffer)
+        }
+    }
+
+    /// Adds the upload of an image that contains the texels of `iter` to the batch. The image has
+    /// a single mipmap level, since the transfer queue can't generate the others.
+    ///
+    /// The image can't be used before the future returned by `execute` is reached.
+    pub fn image_from_iter<Px, I>(
+        &mut self,
+        iter: I,
+        dimensions: ImageDimensions,
+        format: Format,
+    ) -> Result<Arc<ImmutableImage>, UploadBatchError>
+    where
+        Px: Pixel + Send + Sync + Clone + 'static,
+        I: ExactSizeIterator<Item = Px>,
+    {
+        let source = CpuAccessibleBuffer::from_iter(
+            self.transfer_queue.device().clone(),
+            BufferUsage::transfer_source(),
+            false,
+            iter,
+        )?;
+
+        let usage = ImageUsage {
+            transfer_destination: true,
+            sampled: true,
+            ..ImageUsage::none()
+        };
+        let layout = ImageLayout::ShaderReadOnlyOptimal;
+
+        let (image, init) = ImmutableImage::uninitialized(
+            self.transfer_queue.device().clone(),
+            dimensions,
+            format,
+            MipmapsCount::One,
+            usage,
+            ImageCreateFlags::none(),
+            layout,
+            iter::once(self.destination_queue.family()),
+        )?;
+        // The initialization access of the transfer command buffer can only be locked once, so
+        // the acquire gets its own.
+        let acquire_init = init.ownership_acquire();
+        let init = Arc::new(init);
+
+        self.transfer_builder
+            .copy_buffer_to_image(source, init.clone())?;
+
+        if self.needs_ownership_transfer() {
+            // The copy leaves the image in the `TransferDstOptimal` layout, and the transfer moves
+            // it to its final layout.
+            unsafe {
+                self.transfer_builder.release_image_ownership(
+                    init,
+                    self.destination_queue.family(),
+                    ImageLayout::TransferDstOptimal,
+                    layout,
+                )?;
+                self.acquire_builder.acquire_image_ownership(
+                    acquire_init,
+                    self.transfer_queue.family(),
+                    ImageLayout::TransferDstOptimal,
+                    layout,
+                )?;
+            }
+        }
+
+        Ok(image)
+    }
+
+    /// Builds the command buffers of the batch and submits them. Returns a future that represents
+    /// the moment when the resources of the batch can be used on the destination queue.
+    pub fn execute(self) -> Result<UploadBatchFuture, UploadBatchError> {
+        let transfer_cb = self.transfer_builder.build()?;
+        let acquire_cb = self.acquire_builder.build()?;
+
+        Ok(transfer_cb
+            .execute(self.transfer_queue)?
+            .then_signal_semaphore()
+            .then_execute(self.destination_queue, acquire_cb)?)
+    }
+
+    // Returns true if the resources must change of queue family between the copy and their use.
+    #[inline]
+    fn needs_ownership_transfer(&self) -> bool {
+        self.transfer_queue.family().id() != self.destination_queue.family().id()
+    }
+}
+
+/// Error that can happen when recording or executing an `UploadBatch`.
+#[derive(Debug, Clone)]
+pub enum UploadBatchError {
+    /// Allocating memory for a resource or its staging buffer failed.
+    AllocError(DeviceMemoryAllocError),
+    /// Creating an image failed.
+    ImageCreationError(ImageCreationError),
+    /// Recording the copy to a buffer failed.
+    CopyBufferError(CopyBufferError),
+    /// Recording the copy to an image failed.
+    CopyBufferImageError(CopyBufferImageError),
+    /// Recording a queue family ownership transfer failed.
+    OwnershipTransferError(AutoCommandBufferBuilderContextError),
+    /// Building the command buffers failed.
+    BuildError(BuildError),
+    /// Submitting the command buffers failed.
+    ExecError(CommandBufferExecError),
+}
+
+impl error::Error for UploadBatchError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            UploadBatchError::AllocError(ref err) => Some(err),
+            UploadBatchError::ImageCreationError(ref err) => Some(err),
+            UploadBatchError::CopyBufferError(ref err) => Some(err),
+            UploadBatchError::CopyBufferImageError(ref err) => Some(err),
+            UploadBatchError::OwnershipTransferError(ref err) => Some(err),
+            UploadBatchError::BuildError(ref err) => Some(err),
+            UploadBatchError::ExecError(ref err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for UploadBatchError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(
+            fmt,
+            "{}",
+            match *self {
+                UploadBatchError::AllocError(_) => "allocating memory failed",
+                UploadBatchError::ImageCreationError(_) => "creating an image failed",
+                UploadBatchError::CopyBufferError(_) => "recording the copy to a buffer failed",
+                UploadBatchError::CopyBufferImageError(_) => {
+                    "recording the copy to an image failed"
+                }
+                UploadBatchError::OwnershipTransferError(_) => {
+                    "recording a queue family ownership transfer failed"
+                }
+                UploadBatchError::BuildError(_) => "building the command buffers failed",
+                UploadBatchError::ExecError(_) => "submitting the command buffers failed",
+            }
+        )
+    }
+}
+
+impl From<DeviceMemoryAllocError> for UploadBatchError {
+    #[inline]
+    fn from(err: DeviceMemoryAllocError) -> UploadBatchError {
+        UploadBatchError::AllocError(err)
+    }
+}
+
+impl From<ImageCreationError> for UploadBatchError {
+    #[inline]
+    fn from(err: ImageCreationError) -> UploadBatchError {
+        UploadBatchError::ImageCreationError(err)
+    }
+}
+
+impl From<CopyBufferError> for UploadBatchError {
+    #[inline]
+    fn from(err: CopyBufferError) -> UploadBatchError {
+        UploadBatchError::CopyBufferError(err)
+    }
+}
+
+impl From<CopyBufferImageError> for UploadBatchError {
+    #[inline]
+    fn from(err: CopyBufferImageError) -> UploadBatchError {
+        UploadBatchError::CopyBufferImageError(err)
+    }
+}
+
+impl From<AutoCommandBufferBuilderContextError> for UploadBatchError {
+    #[inline]
+    fn from(err: AutoCommandBufferBuilderContextError) -> UploadBatchError {
+        UploadBatchError::OwnershipTransferError(err)
+    }
+}
+
+impl From<BuildError> for UploadBatchError {
+    #[inline]
+    fn from(err: BuildError) -> UploadBatchError {
+        UploadBatchError::BuildError(err)
+    }
+}
+
+impl From<CommandBufferExecError> for UploadBatchError {
+    #[inline]
+    fn from(err: CommandBufferExecError) -> UploadBatchError {
+        UploadBatchError::ExecError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::BufferUsage;
+    use crate::buffer::CpuAccessibleBuffer;
+    use crate::command_buffer::AutoCommandBufferBuilder;
+    use crate::command_buffer::CommandBufferUsage;
+    use crate::command_buffer::UploadBatch;
+    use crate::device::physical::PhysicalDevice;
+    use crate::device::Device;
+    use crate::device::DeviceExtensions;
+    use crate::device::Features;
+    use crate::format::Format;
+    use crate::image::ImageDimensions;
+    use crate::sync::GpuFuture;
+
+    #[test]
+    fn buffers_same_queue() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let mut batch = UploadBatch::new(queue.clone(), queue.clone()).unwrap();
+        let data = batch.buffer_from_data(12u32, BufferUsage::all()).unwrap();
+        let array = batch
+            .buffer_from_iter((0..4u32).map(|n| n * 2), BufferUsage::all())
+            .unwrap();
+        let upload_future = batch.execute().unwrap();
+
+        let data_destination =
+            CpuAccessibleBuffer::from_data(device.clone(), BufferUsage::all(), false, 0u32)
+                .unwrap();
+        let array_destination =
+            CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::all(), false, 0..4u32)
+                .unwrap();
+
+        let mut cbb = AutoCommandBufferBuilder::primary(
+            device.clone(),
+            queue.family(),
+            CommandBufferUsage::MultipleSubmit,
+        )
+        .unwrap();
+        cbb.copy_buffer(data, data_destination.clone()).unwrap();
+        cbb.copy_buffer(array, array_destination.clone()).unwrap();
+        upload_future
+            .then_execute(queue.clone(), cbb.build().unwrap())
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        assert_eq!(*data_destination.read().unwrap(), 12);
+        assert_eq!(&*array_destination.read().unwrap(), &[0, 2, 4, 6]);
+    }
+
+    #[test]
+    fn image_same_queue() {
+        let (_, queue) = gfx_dev_and_queue!();
+
+        let mut batch = UploadBatch::new(queue.clone(), queue.clone()).unwrap();
+        let _image = batch
+            .image_from_iter(
+                [[0u8; 4]; 16].iter().cloned(),
+                ImageDimensions::Dim2d {
+                    width: 4,
+                    height: 4,
+                    array_layers: 1,
+                },
+                Format::R8G8B8A8_UNORM,
+            )
+            .unwrap();
+        batch
+            .execute()
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+    }
+
+    #[test]
+    fn other_queue_family() {
+        let instance = instance!();
+
+        let physical = match PhysicalDevice::enumerate(&instance).next() {
+            Some(p) => p,
+            None => return,
+        };
+
+        let graphics_family = match physical.queue_families().find(|q| q.supports_graphics()) {
+            Some(q) => q,
+            None => return,
+        };
+
+        // Queue families that support graphics or compute operations also support transfers.
+        let transfer_family = match physical.queue_families().find(|q| {
+            q.id() != graphics_family.id()
+                && (q.supports_graphics()
+                    || q.supports_compute()
+                    || q.explicitly_supports_transfers())
+        }) {
+            Some(q) => q,
+            None => return,
+        };
+
+        let (device, mut queues) = match Device::new(
+            physical,
+            &Features::none(),
+            &DeviceExtensions::none(),
+            [(graphics_family, 0.5), (transfer_family, 0.5)]
+                .iter()
+                .cloned(),
+        ) {
+            Ok(r) => r,
+            Err(_) => return,
+        };
+        let graphics_queue = queues.next().unwrap();
+        let transfer_queue = queues.next().unwrap();
+
+        let mut batch = UploadBatch::new(transfer_queue, graphics_queue.clone()).unwrap();
+        let array = batch
+            .buffer_from_iter((0..4u32).map(|n| n * 3), BufferUsage::all())
+            .unwrap();
+        let _image = batch
+            .image_from_iter(
+                [[0u8; 4]; 16].iter().cloned(),
+                ImageDimensions::Dim2d {
+                    width: 4,
+                    height: 4,
+                    array_layers: 1,
+                },
+                Format::R8G8B8A8_UNORM,
+            )
+            .unwrap();
+        let upload_future = batch.execute().unwrap();
+
+        let array_destination =
+            CpuAccessibleBuffer::from_iter(device.clone(), BufferUsage::all(), false, 0..4u32)
+                .unwrap();
+
+        let mut cbb = AutoCommandBufferBuilder::primary(
+            device.clone(),
+            graphics_queue.family(),
+            CommandBufferUsage::MultipleSubmit,
+        )
+        .unwrap();
+        cbb.copy_buffer(array, array_destination.clone()).unwrap();
+        upload_future
+            .then_execute(graphics_queue.clone(), cbb.build().unwrap())
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        assert_eq!(&*array_destination.read().unwrap(), &[0, 3, 6, 9]);
+    }
+}
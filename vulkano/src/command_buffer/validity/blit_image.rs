@@ -7,6 +7,8 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use crate::command_buffer::validity::copy_image::{aspects_allowed, default_aspects};
+use crate::command_buffer::ImageBlit;
 use crate::device::Device;
 use crate::format::NumericType;
 use crate::image::ImageAccess;
@@ -40,6 +42,41 @@ pub fn check_blit_image<S, D>(
     layer_count: u32,
     filter: Filter,
 ) -> Result<(), CheckBlitImageError>
+where
+    S: ?Sized + ImageAccess,
+    D: ?Sized + ImageAccess,
+{
+    let region = ImageBlit {
+        aspects: default_aspects(source.format().aspects(), destination.format().aspects()),
+        source_mip_level,
+        source_base_array_layer,
+        source_top_left,
+        source_bottom_right,
+        destination_mip_level,
+        destination_base_array_layer,
+        destination_top_left,
+        destination_bottom_right,
+        layer_count,
+    };
+
+    check_blit_image_regions(device, source, destination, &[region], filter)
+}
+
+/// Checks whether a blit image command with multiple regions is valid.
+///
+/// Note that this doesn't check whether `layer_count` is equal to 0. TODO: change that?
+///
+/// # Panic
+///
+/// - Panics if the source or the destination was not created with `device`.
+///
+pub fn check_blit_image_regions<S, D>(
+    device: &Device,
+    source: &S,
+    destination: &D,
+    regions: &[ImageBlit],
+    filter: Filter,
+) -> Result<(), CheckBlitImageError>
 where
     S: ?Sized + ImageAccess,
     D: ?Sized + ImageAccess,
@@ -56,6 +93,10 @@ where
         device.internal_object()
     );
 
+    if regions.is_empty() {
+        return Err(CheckBlitImageError::NoRegions);
+    }
+
     if !source_inner.image.usage().transfer_source {
         return Err(CheckBlitImageError::MissingTransferSourceUsage);
     }
@@ -100,6 +141,44 @@ where
         }
     }
 
+    for region in regions {
+        if !aspects_allowed(
+            region.aspects,
+            source.format().aspects(),
+            destination.format().aspects(),
+        ) {
+            return Err(CheckBlitImageError::AspectsNotAllowed);
+        }
+
+        check_region(source, destination, region)?;
+    }
+
+    Ok(())
+}
+
+// Checks the coordinates of a single region of a blit image command.
+fn check_region<S, D>(
+    source: &S,
+    destination: &D,
+    region: &ImageBlit,
+) -> Result<(), CheckBlitImageError>
+where
+    S: ?Sized + ImageAccess,
+    D: ?Sized + ImageAccess,
+{
+    let &ImageBlit {
+        source_mip_level,
+        source_base_array_layer,
+        source_top_left,
+        source_bottom_right,
+        destination_mip_level,
+        destination_base_array_layer,
+        destination_top_left,
+        destination_bottom_right,
+        layer_count,
+        ..
+    } = region;
+
     let source_dimensions = match source.dimensions().mipmap_dimensions(source_mip_level) {
         Some(d) => d,
         None => return Err(CheckBlitImageError::SourceCoordinatesOutOfRange),
@@ -221,6 +300,8 @@ where
 /// Error that can happen from `check_clear_color_image`.
 #[derive(Debug, Copy, Clone)]
 pub enum CheckBlitImageError {
+    /// No region was given.
+    NoRegions,
     /// The source is missing the transfer source usage.
     MissingTransferSourceUsage,
     /// The destination is missing the transfer destination usage.
@@ -246,6 +327,9 @@ pub enum CheckBlitImageError {
     DestinationCoordinatesOutOfRange,
     /// The top-left and/or bottom-right coordinates are incompatible with the image type.
     IncompatibleRangeForImageType,
+    /// The aspects of a region are empty, combine color with depth or stencil, or are missing
+    /// from the source or destination image.
+    AspectsNotAllowed,
 }
 
 impl error::Error for CheckBlitImageError {}
@@ -257,6 +341,7 @@ impl fmt::Display for CheckBlitImageError {
             fmt,
             "{}",
             match *self {
+                CheckBlitImageError::NoRegions => "no region was given",
                 CheckBlitImageError::MissingTransferSourceUsage => {
                     "the source is missing the transfer source usage"
                 }
@@ -293,7 +378,42 @@ impl fmt::Display for CheckBlitImageError {
                 CheckBlitImageError::IncompatibleRangeForImageType => {
                     "the top-left and/or bottom-right coordinates are incompatible with the image type"
                 }
+                CheckBlitImageError::AspectsNotAllowed => {
+                    "the aspects of a region are not allowed for the source and destination images"
+                }
             }
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::command_buffer::validity::blit_image::check_blit_image_regions;
+    use crate::command_buffer::validity::blit_image::CheckBlitImageError;
+    use crate::format::Format;
+    use crate::image::AttachmentImage;
+    use crate::image::ImageUsage;
+    use crate::sampler::Filter;
+
+    #[test]
+    fn no_regions() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let image = AttachmentImage::with_usage(
+            device.clone(),
+            [4, 4],
+            Format::R8G8B8A8_UNORM,
+            ImageUsage {
+                transfer_source: true,
+                transfer_destination: true,
+                ..ImageUsage::none()
+            },
+        )
+        .unwrap();
+
+        assert!(matches!(
+            check_blit_image_regions(&device, &image, &image, &[], Filter::Nearest),
+            Err(CheckBlitImageError::NoRegions)
+        ));
+    }
+}
@@ -7,9 +7,11 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use crate::command_buffer::ImageCopy;
 use crate::device::Device;
 use crate::format::NumericType;
 use crate::image::ImageAccess;
+use crate::image::ImageAspects;
 use crate::image::ImageDimensions;
 use crate::VulkanObject;
 use std::error;
@@ -36,6 +38,39 @@ pub fn check_copy_image<S, D>(
     extent: [u32; 3],
     layer_count: u32,
 ) -> Result<(), CheckCopyImageError>
+where
+    S: ?Sized + ImageAccess,
+    D: ?Sized + ImageAccess,
+{
+    let region = ImageCopy {
+        aspects: default_aspects(source.format().aspects(), destination.format().aspects()),
+        source_mip_level,
+        source_base_array_layer,
+        source_offset,
+        destination_mip_level,
+        destination_base_array_layer,
+        destination_offset,
+        extent,
+        layer_count,
+    };
+
+    check_copy_image_regions(device, source, destination, &[region])
+}
+
+/// Checks whether a copy image command with multiple regions is valid.
+///
+/// Note that this doesn't check whether `layer_count` is equal to 0. TODO: change that?
+///
+/// # Panic
+///
+/// - Panics if the source or the destination was not created with `device`.
+///
+pub fn check_copy_image_regions<S, D>(
+    device: &Device,
+    source: &S,
+    destination: &D,
+    regions: &[ImageCopy],
+) -> Result<(), CheckCopyImageError>
 where
     S: ?Sized + ImageAccess,
     D: ?Sized + ImageAccess,
@@ -52,6 +87,10 @@ where
         device.internal_object()
     );
 
+    if regions.is_empty() {
+        return Err(CheckCopyImageError::NoRegions);
+    }
+
     if !source_inner.image.usage().transfer_source {
         return Err(CheckCopyImageError::MissingTransferSourceUsage);
     }
@@ -86,6 +125,43 @@ where
         }
     }
 
+    for region in regions {
+        if !aspects_allowed(
+            region.aspects,
+            source.format().aspects(),
+            destination.format().aspects(),
+        ) {
+            return Err(CheckCopyImageError::AspectsNotAllowed);
+        }
+
+        check_region(source, destination, region)?;
+    }
+
+    Ok(())
+}
+
+// Checks the coordinates of a single region of a copy image command.
+fn check_region<S, D>(
+    source: &S,
+    destination: &D,
+    region: &ImageCopy,
+) -> Result<(), CheckCopyImageError>
+where
+    S: ?Sized + ImageAccess,
+    D: ?Sized + ImageAccess,
+{
+    let &ImageCopy {
+        source_mip_level,
+        source_base_array_layer,
+        source_offset,
+        destination_mip_level,
+        destination_base_array_layer,
+        destination_offset,
+        extent,
+        layer_count,
+        ..
+    } = region;
+
     let source_dimensions = match source.dimensions().mipmap_dimensions(source_mip_level) {
         Some(d) => d,
         None => return Err(CheckCopyImageError::SourceCoordinatesOutOfRange),
@@ -174,9 +250,50 @@ where
     Ok(())
 }
 
+// Returns the aspects that are copied or blitted by default between images of two formats.
+pub(super) fn default_aspects(source: ImageAspects, destination: ImageAspects) -> ImageAspects {
+    // If color is included, neither depth nor stencil may.
+    ImageAspects {
+        color: source.color,
+        depth: !source.color && source.depth && destination.depth,
+        stencil: !source.color && source.stencil && destination.stencil,
+        ..ImageAspects::none()
+    }
+}
+
+// Returns whether `aspects` can be copied or blitted between images with the aspects `source` and
+// `destination`.
+pub(super) fn aspects_allowed(
+    aspects: ImageAspects,
+    source: ImageAspects,
+    destination: ImageAspects,
+) -> bool {
+    let ImageAspects {
+        color,
+        depth,
+        stencil,
+        ..
+    } = aspects;
+
+    aspects
+        == ImageAspects {
+            color,
+            depth,
+            stencil,
+            ..ImageAspects::none()
+        }
+        && (color || depth || stencil)
+        && !(color && (depth || stencil))
+        && (!color || source.color && destination.color)
+        && (!depth || source.depth && destination.depth)
+        && (!stencil || source.stencil && destination.stencil)
+}
+
 /// Error that can happen from `check_copy_image`.
 #[derive(Debug, Copy, Clone)]
 pub enum CheckCopyImageError {
+    /// No region was given.
+    NoRegions,
     /// The source is missing the transfer source usage.
     MissingTransferSourceUsage,
     /// The destination is missing the transfer destination usage.
@@ -196,6 +313,9 @@ pub enum CheckCopyImageError {
     DestinationCoordinatesOutOfRange,
     /// The offsets or extent are incompatible with the image type.
     IncompatibleRangeForImageType,
+    /// The aspects of a region are empty, combine color with depth or stencil, or are missing
+    /// from the source or destination image.
+    AspectsNotAllowed,
 }
 
 impl error::Error for CheckCopyImageError {}
@@ -207,6 +327,7 @@ impl fmt::Display for CheckCopyImageError {
             fmt,
             "{}",
             match *self {
+                CheckCopyImageError::NoRegions => "no region was given",
                 CheckCopyImageError::MissingTransferSourceUsage => {
                     "the source is missing the transfer source usage"
                 }
@@ -234,7 +355,76 @@ impl fmt::Display for CheckCopyImageError {
                 CheckCopyImageError::IncompatibleRangeForImageType => {
                     "the offsets or extent are incompatible with the image type"
                 }
+                CheckCopyImageError::AspectsNotAllowed => {
+                    "the aspects of a region are not allowed for the source and destination images"
+                }
             }
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::command_buffer::validity::copy_image::aspects_allowed;
+    use crate::command_buffer::validity::copy_image::check_copy_image_regions;
+    use crate::command_buffer::validity::copy_image::CheckCopyImageError;
+    use crate::format::Format;
+    use crate::image::AttachmentImage;
+    use crate::image::ImageAspects;
+    use crate::image::ImageUsage;
+
+    #[test]
+    fn allowed_aspects() {
+        let color = ImageAspects {
+            color: true,
+            ..ImageAspects::none()
+        };
+        let depth_stencil = ImageAspects {
+            depth: true,
+            stencil: true,
+            ..ImageAspects::none()
+        };
+        let depth = ImageAspects {
+            depth: true,
+            ..ImageAspects::none()
+        };
+
+        assert!(aspects_allowed(color, color, color));
+        assert!(aspects_allowed(depth, depth_stencil, depth_stencil));
+        assert!(aspects_allowed(depth_stencil, depth_stencil, depth_stencil));
+        assert!(!aspects_allowed(depth_stencil, depth, depth));
+        assert!(!aspects_allowed(color, depth, depth));
+        assert!(!aspects_allowed(color | depth, color, color));
+        assert!(!aspects_allowed(ImageAspects::none(), color, color));
+        assert!(!aspects_allowed(
+            ImageAspects {
+                plane0: true,
+                ..ImageAspects::none()
+            },
+            color,
+            color
+        ));
+    }
+
+    #[test]
+    fn no_regions() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let image = AttachmentImage::with_usage(
+            device.clone(),
+            [4, 4],
+            Format::R8G8B8A8_UNORM,
+            ImageUsage {
+                transfer_source: true,
+                transfer_destination: true,
+                ..ImageUsage::none()
+            },
+        )
+        .unwrap();
+
+        assert!(matches!(
+            check_copy_image_regions(&device, &image, &image, &[]),
+            Err(CheckCopyImageError::NoRegions)
+        ));
+    }
+}
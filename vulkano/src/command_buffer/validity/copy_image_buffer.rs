@@ -8,18 +8,21 @@
 // according to those terms.
 
 use crate::buffer::TypedBufferAccess;
+use crate::command_buffer::BufferImageCopy;
 use crate::device::Device;
 use crate::device::DeviceOwned;
 use crate::format::Format;
 use crate::format::IncompatiblePixelsType;
 use crate::format::Pixel;
 use crate::image::ImageAccess;
+use crate::image::ImageAspect;
 use crate::image::ImageDimensions;
 use crate::image::SampleCount;
 use crate::DeviceSize;
 use crate::VulkanObject;
 use std::error;
 use std::fmt;
+use std::mem;
 
 /// Type of operation to check.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -46,6 +49,47 @@ pub fn check_copy_buffer_image<B, I, Px>(
     image_num_layers: u32,
     image_mipmap: u32,
 ) -> Result<(), CheckCopyBufferImageError>
+where
+    I: ?Sized + ImageAccess,
+    B: ?Sized + TypedBufferAccess<Content = [Px]>,
+    Px: Pixel, // TODO: use a trait on the image itself instead
+{
+    let aspects = image.format().aspects();
+    let region = BufferImageCopy {
+        buffer_offset: 0,
+        buffer_row_length: 0,
+        buffer_image_height: 0,
+        image_aspect: if aspects.color {
+            ImageAspect::Color
+        } else if aspects.depth {
+            ImageAspect::Depth
+        } else {
+            ImageAspect::Stencil
+        },
+        image_mip_level: image_mipmap,
+        image_base_array_layer: image_first_layer,
+        image_layer_count: image_num_layers,
+        image_offset,
+        image_extent: image_size,
+    };
+
+    check_copy_buffer_image_regions(device, buffer, image, ty, &[region])
+}
+
+/// Checks whether a copy buffer-image command with multiple regions is valid. Can check both
+/// buffer-to-image copies and image-to-buffer copies.
+///
+/// # Panic
+///
+/// - Panics if the buffer and image were not created with `device`.
+///
+pub fn check_copy_buffer_image_regions<B, I, Px>(
+    device: &Device,
+    buffer: &B,
+    image: &I,
+    ty: CheckCopyBufferImageTy,
+    regions: &[BufferImageCopy],
+) -> Result<(), CheckCopyBufferImageError>
 where
     I: ?Sized + ImageAccess,
     B: ?Sized + TypedBufferAccess<Content = [Px]>,
@@ -63,6 +107,10 @@ where
         device.internal_object()
     );
 
+    if regions.is_empty() {
+        return Err(CheckCopyBufferImageError::NoRegions);
+    }
+
     match ty {
         CheckCopyBufferImageTy::BufferToImage => {
             if !buffer_inner.buffer.usage().transfer_source {
@@ -86,24 +134,71 @@ where
         return Err(CheckCopyBufferImageError::UnexpectedMultisampled);
     }
 
-    let image_dimensions = match image.dimensions().mipmap_dimensions(image_mipmap) {
+    for region in regions {
+        check_region::<_, Px>(buffer_inner.offset, buffer.len(), image, region)?;
+    }
+
+    // TODO: check memory overlap?
+
+    Ok(())
+}
+
+// Checks a single region of a copy buffer-image command.
+fn check_region<I, Px>(
+    buffer_offset: DeviceSize,
+    buffer_len: DeviceSize,
+    image: &I,
+    region: &BufferImageCopy,
+) -> Result<(), CheckCopyBufferImageError>
+where
+    I: ?Sized + ImageAccess,
+    Px: Pixel,
+{
+    let format = image.format();
+    let aspect = region.image_aspect;
+    let image_offset = region.image_offset;
+    let image_size = region.image_extent;
+
+    // VUID-VkBufferImageCopy-aspectMask-00211
+    let block_size = match format.aspect_block_size(aspect) {
+        Some(x) => x,
+        None => return Err(CheckCopyBufferImageError::AspectNotAllowed),
+    };
+
+    if aspect == ImageAspect::Color {
+        Px::ensure_accepts(format)?;
+    } else if block_size % mem::size_of::<Px>() as DeviceSize != 0 {
+        return Err(IncompatiblePixelsType.into());
+    }
+
+    let image_dimensions = match image.dimensions().mipmap_dimensions(region.image_mip_level) {
         Some(d) => d,
         None => return Err(CheckCopyBufferImageError::ImageCoordinatesOutOfRange),
     };
 
-    if image_first_layer + image_num_layers > image_dimensions.array_layers() {
+    if region.image_base_array_layer + region.image_layer_count > image_dimensions.array_layers() {
         return Err(CheckCopyBufferImageError::ImageCoordinatesOutOfRange);
     }
 
-    if image_offset[0] + image_size[0] > image_dimensions.width() {
+    // The chroma planes of subsampled formats are smaller than the image.
+    let [width, height, depth] = match aspect {
+        ImageAspect::Plane1 | ImageAspect::Plane2 => {
+            let [block_width, block_height] = format.block_dimensions();
+            let [width, height, depth] = image_dimensions.width_height_depth();
+            [width / block_width, height / block_height, depth]
+        }
+        _ => image_dimensions.width_height_depth(),
+    };
+
+    if image_offset[0] + image_size[0] > width {
         return Err(CheckCopyBufferImageError::ImageCoordinatesOutOfRange);
     }
 
-    if image_offset[1] + image_size[1] > image_dimensions.height() {
+    if image_offset[1] + image_size[1] > height {
         return Err(CheckCopyBufferImageError::ImageCoordinatesOutOfRange);
     }
 
-    if image_offset[2] + image_size[2] > image_dimensions.depth() {
+    if image_offset[2] + image_size[2] > depth {
         return Err(CheckCopyBufferImageError::ImageCoordinatesOutOfRange);
     }
 
@@ -127,101 +222,271 @@ where
         }
         ImageDimensions::Dim3d { .. } => {
             // VUID-vkCmdCopyBufferToImage-baseArrayLayer-00213
-            if image_first_layer != 0 || image_num_layers != 1 {
+            if region.image_base_array_layer != 0 || region.image_layer_count != 1 {
                 return Err(CheckCopyBufferImageError::ImageCoordinatesOutOfRange);
             }
         }
     }
 
-    Px::ensure_accepts(image.format())?;
+    // VUID-vkCmdCopyBufferToImage-bufferOffset-01558
+    // VUID-vkCmdCopyBufferToImage-bufferOffset-00193
+    // VUID-VkBufferImageCopy-bufferOffset-00194
+    {
+        let alignment = if block_size % 4 == 0 {
+            block_size
+        } else if block_size % 2 == 0 {
+            block_size * 2
+        } else {
+            block_size * 4
+        };
+        let offset = buffer_offset + region.buffer_offset;
+
+        if offset % alignment != 0 {
+            return Err(CheckCopyBufferImageError::BufferOffsetNotAligned { offset, alignment });
+        }
+    }
+
+    let block_extent = if aspect == ImageAspect::Color {
+        format.block_extent()
+    } else {
+        [1, 1, 1]
+    };
+
+    // VUID-VkBufferImageCopy-imageOffset-00205
+    // VUID-VkBufferImageCopy-imageExtent-00207
+    // VUID-VkBufferImageCopy-imageExtent-00208
+    {
+        let [block_width, block_height, _] = block_extent;
+
+        if image_offset[0] % block_width != 0 || image_offset[1] % block_height != 0 {
+            return Err(CheckCopyBufferImageError::ImageCoordinatesNotAligned { block_extent });
+        }
+
+        // The extent can end at the edge of the image without being a whole number of blocks.
+        if (image_size[0] % block_width != 0 && image_offset[0] + image_size[0] != width)
+            || (image_size[1] % block_height != 0 && image_offset[1] + image_size[1] != height)
+        {
+            return Err(CheckCopyBufferImageError::ImageCoordinatesNotAligned { block_extent });
+        }
+    }
+
+    // VUID-VkBufferImageCopy-bufferRowLength-00195
+    // VUID-VkBufferImageCopy-bufferImageHeight-00196
+    // VUID-VkBufferImageCopy-bufferRowLength-00203
+    // VUID-VkBufferImageCopy-bufferImageHeight-00204
+    {
+        let [block_width, block_height, _] = block_extent;
+        let row_length = region.buffer_row_length;
+        let image_height = region.buffer_image_height;
+
+        if row_length != 0 && (row_length < image_size[0] || row_length % block_width != 0) {
+            return Err(CheckCopyBufferImageError::InvalidBufferLayout);
+        }
+
+        if image_height != 0 && (image_height < image_size[1] || image_height % block_height != 0) {
+            return Err(CheckCopyBufferImageError::InvalidBufferLayout);
+        }
+    }
 
     {
-        let required_len =
-            required_len_for_format::<Px>(image.format(), image_size, image_num_layers);
-        if required_len > buffer.len() {
+        let required_len = required_len_for_region::<Px>(format, region);
+        if required_len > buffer_len {
             return Err(CheckCopyBufferImageError::BufferTooSmall {
                 required_len,
-                actual_len: buffer.len(),
+                actual_len: buffer_len,
             });
         }
     }
 
-    // TODO: check memory overlap?
-
     Ok(())
 }
 
-/// Computes the minimum required len in elements for buffer with image data in specified
-/// format of specified size.
-fn required_len_for_format<Px>(
-    format: Format,
-    image_size: [u32; 3],
-    image_num_layers: u32,
-) -> DeviceSize
+/// Computes the minimum required len in elements for buffer with the image data of a region in
+/// the specified format.
+fn required_len_for_region<Px>(format: Format, region: &BufferImageCopy) -> DeviceSize
 where
     Px: Pixel,
 {
-    let [block_width, block_height] = format.block_dimensions();
-    let num_blocks = (image_size[0] + block_width - 1) / block_width
-        * ((image_size[1] + block_height - 1) / block_height)
-        * image_size[2]
-        * image_num_layers;
-    let required_len = num_blocks as DeviceSize * Px::rate(format) as DeviceSize;
-
-    return required_len;
+    let [width, height, depth] = region.image_extent;
+    let required_size = region.buffer_offset
+        + format
+            .aspect_required_buffer_size(
+                region.image_aspect,
+                [width, height, depth * region.image_layer_count],
+                region.buffer_row_length,
+                region.buffer_image_height,
+            )
+            .expect("this format cannot accept pixels");
+    let pixel_size = mem::size_of::<Px>() as DeviceSize;
+
+    (required_size + pixel_size - 1) / pixel_size
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::command_buffer::validity::copy_image_buffer::required_len_for_format;
+    use crate::buffer::BufferUsage;
+    use crate::buffer::CpuAccessibleBuffer;
+    use crate::command_buffer::validity::copy_image_buffer::check_copy_buffer_image_regions;
+    use crate::command_buffer::validity::copy_image_buffer::required_len_for_region;
+    use crate::command_buffer::validity::copy_image_buffer::CheckCopyBufferImageError;
+    use crate::command_buffer::validity::copy_image_buffer::CheckCopyBufferImageTy;
+    use crate::command_buffer::BufferImageCopy;
     use crate::format::Format;
+    use crate::image::AttachmentImage;
+    use crate::image::ImageAspect;
+    use crate::image::ImageUsage;
+    use std::iter;
 
     #[test]
     fn test_required_len_for_format() {
         // issue #1292
+        let region = BufferImageCopy {
+            buffer_offset: 0,
+            buffer_row_length: 0,
+            buffer_image_height: 0,
+            image_aspect: ImageAspect::Color,
+            image_mip_level: 0,
+            image_base_array_layer: 0,
+            image_layer_count: 1,
+            image_offset: [0, 0, 0],
+            image_extent: [2048, 2048, 1],
+        };
         assert_eq!(
-            required_len_for_format::<u8>(Format::BC1_RGB_UNORM_BLOCK, [2048, 2048, 1], 1),
+            required_len_for_region::<u8>(Format::BC1_RGB_UNORM_BLOCK, &region),
             2097152
         );
         // other test cases
         assert_eq!(
-            required_len_for_format::<u8>(Format::R8G8B8A8_UNORM, [2048, 2048, 1], 1),
+            required_len_for_region::<u8>(Format::R8G8B8A8_UNORM, &region),
             16777216
         );
+
+        let region = BufferImageCopy {
+            image_extent: [512, 512, 1],
+            ..region
+        };
         assert_eq!(
-            required_len_for_format::<u8>(Format::R4G4_UNORM_PACK8, [512, 512, 1], 1),
+            required_len_for_region::<u8>(Format::R4G4_UNORM_PACK8, &region),
             262144
         );
         assert_eq!(
-            required_len_for_format::<u8>(Format::R8G8B8_USCALED, [512, 512, 1], 1),
+            required_len_for_region::<u8>(Format::R8G8B8_USCALED, &region),
             786432
         );
         assert_eq!(
-            required_len_for_format::<u8>(Format::R32G32_UINT, [512, 512, 1], 1),
+            required_len_for_region::<u8>(Format::R32G32_UINT, &region),
             2097152
         );
         assert_eq!(
-            required_len_for_format::<u32>(Format::R32G32_UINT, [512, 512, 1], 1),
+            required_len_for_region::<u32>(Format::R32G32_UINT, &region),
             524288
         );
         assert_eq!(
-            required_len_for_format::<[u32; 2]>(Format::R32G32_UINT, [512, 512, 1], 1),
+            required_len_for_region::<[u32; 2]>(Format::R32G32_UINT, &region),
             262144
         );
         assert_eq!(
-            required_len_for_format::<u8>(Format::ASTC_8x8_UNORM_BLOCK, [512, 512, 1], 1),
+            required_len_for_region::<u8>(Format::ASTC_8x8_UNORM_BLOCK, &region),
             65536
         );
         assert_eq!(
-            required_len_for_format::<u8>(Format::ASTC_12x12_SRGB_BLOCK, [512, 512, 1], 1),
+            required_len_for_region::<u8>(Format::ASTC_12x12_SRGB_BLOCK, &region),
             29584
         );
+
+        let region = BufferImageCopy {
+            image_layer_count: 3,
+            image_extent: [6, 6, 1],
+            ..region
+        };
+        assert_eq!(
+            required_len_for_region::<u8>(Format::BC1_RGB_UNORM_BLOCK, &region),
+            96
+        );
+
+        let region = BufferImageCopy {
+            image_layer_count: 1,
+            image_extent: [3, 1, 1],
+            ..region
+        };
+        assert_eq!(
+            required_len_for_region::<[u32; 2]>(Format::R8G8B8A8_UNORM, &region),
+            2
+        );
+    }
+
+    #[test]
+    fn test_required_len_for_region() {
+        let region = BufferImageCopy {
+            buffer_offset: 64,
+            buffer_row_length: 32,
+            buffer_image_height: 0,
+            image_aspect: ImageAspect::Color,
+            image_mip_level: 0,
+            image_base_array_layer: 0,
+            image_layer_count: 1,
+            image_offset: [0, 0, 0],
+            image_extent: [16, 16, 1],
+        };
+        assert_eq!(
+            required_len_for_region::<u8>(Format::R8G8B8A8_UNORM, &region),
+            64 + (15 * 32 + 16) * 4
+        );
+
+        let region = BufferImageCopy {
+            buffer_offset: 0,
+            buffer_row_length: 0,
+            image_aspect: ImageAspect::Depth,
+            image_layer_count: 2,
+            image_extent: [4, 4, 1],
+            ..region
+        };
+        assert_eq!(
+            required_len_for_region::<f32>(Format::D32_SFLOAT_S8_UINT, &region),
+            32
+        );
+    }
+
+    #[test]
+    fn no_regions() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let image = AttachmentImage::with_usage(
+            device.clone(),
+            [4, 4],
+            Format::R8G8B8A8_UNORM,
+            ImageUsage {
+                transfer_source: true,
+                transfer_destination: true,
+                ..ImageUsage::none()
+            },
+        )
+        .unwrap();
+        let buffer = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::all(),
+            false,
+            iter::repeat(0u8).take(64),
+        )
+        .unwrap();
+
+        for &ty in &[
+            CheckCopyBufferImageTy::BufferToImage,
+            CheckCopyBufferImageTy::ImageToBuffer,
+        ] {
+            assert!(matches!(
+                check_copy_buffer_image_regions(&device, &buffer, &image, ty, &[]),
+                Err(CheckCopyBufferImageError::NoRegions)
+            ));
+        }
     }
 }
 
 /// Error that can happen from `check_copy_buffer_image`.
 #[derive(Debug, Copy, Clone)]
 pub enum CheckCopyBufferImageError {
+    /// No region was given.
+    NoRegions,
     /// The source buffer or image is missing the transfer source usage.
     SourceMissingTransferUsage,
     /// The destination buffer or image is missing the transfer destination usage.
@@ -232,6 +497,24 @@ pub enum CheckCopyBufferImageError {
     UnexpectedMultisampled,
     /// The image coordinates are out of range.
     ImageCoordinatesOutOfRange,
+    /// The image offset or extent is not a multiple of the texel block extent of the format. The
+    /// extent can only end elsewhere than on a block boundary at the edge of the image.
+    ImageCoordinatesNotAligned {
+        /// The texel block extent of the format of the image.
+        block_extent: [u32; 3],
+    },
+    /// The offset of the buffer is not a multiple of both the texel block size of the format and 4.
+    BufferOffsetNotAligned {
+        /// The offset of the buffer.
+        offset: DeviceSize,
+        /// The required alignment of the offset.
+        alignment: DeviceSize,
+    },
+    /// The image aspect of a region can't be copied for the image format.
+    AspectNotAllowed,
+    /// The row length or image height of the data in the buffer is smaller than the extent of a
+    /// region, or isn't a multiple of the texel block extent of the format.
+    InvalidBufferLayout,
     /// The type of pixels in the buffer isn't compatible with the image format.
     WrongPixelType(IncompatiblePixelsType),
     /// The buffer is too small for the copy operation.
@@ -259,6 +542,7 @@ impl fmt::Display for CheckCopyBufferImageError {
             fmt,
             "{}",
             match *self {
+                CheckCopyBufferImageError::NoRegions => "no region was given",
                 CheckCopyBufferImageError::SourceMissingTransferUsage => {
                     "the source buffer is missing the transfer source usage"
                 }
@@ -274,6 +558,19 @@ impl fmt::Display for CheckCopyBufferImageError {
                 CheckCopyBufferImageError::ImageCoordinatesOutOfRange => {
                     "the image coordinates are out of range"
                 }
+                CheckCopyBufferImageError::ImageCoordinatesNotAligned { .. } => {
+                    "the image coordinates are not aligned to the texel blocks of the format"
+                }
+                CheckCopyBufferImageError::BufferOffsetNotAligned { .. } => {
+                    "the offset of the buffer is not aligned to the texel block size of the format"
+                }
+                CheckCopyBufferImageError::AspectNotAllowed => {
+                    "the image aspect of a region can't be copied for the image format"
+                }
+                CheckCopyBufferImageError::InvalidBufferLayout => {
+                    "the row length or image height of the data in the buffer is invalid for a \
+                     region"
+                }
                 CheckCopyBufferImageError::WrongPixelType(_) => {
                     "the type of pixels in the buffer isn't compatible with the image format"
                 }
@@ -9,12 +9,13 @@
 
 //! Functions that check the validity of commands.
 
-pub use self::blit_image::{check_blit_image, CheckBlitImageError};
+pub use self::blit_image::{check_blit_image, check_blit_image_regions, CheckBlitImageError};
 pub use self::clear_color_image::{check_clear_color_image, CheckClearColorImageError};
 pub use self::copy_buffer::{check_copy_buffer, CheckCopyBuffer, CheckCopyBufferError};
-pub use self::copy_image::{check_copy_image, CheckCopyImageError};
+pub use self::copy_image::{check_copy_image, check_copy_image_regions, CheckCopyImageError};
 pub use self::copy_image_buffer::{
-    check_copy_buffer_image, CheckCopyBufferImageError, CheckCopyBufferImageTy,
+    check_copy_buffer_image, check_copy_buffer_image_regions, CheckCopyBufferImageError,
+    CheckCopyBufferImageTy,
 };
 pub use self::debug_marker::{check_debug_marker_color, CheckColorError};
 pub use self::descriptor_sets::CheckDescriptorSetsValidityError;
@@ -24,7 +25,9 @@ pub use self::fill_buffer::{check_fill_buffer, CheckFillBufferError};
 pub use self::index_buffer::CheckIndexBufferError;
 pub use self::indirect_buffer::{check_indirect_buffer, CheckIndirectBufferError};
 pub use self::pipeline::CheckPipelineError;
-pub use self::push_constants::CheckPushConstantsValidityError;
+pub use self::push_constants::{
+    check_push_constants_type, CheckPushConstantsTypeError, CheckPushConstantsValidityError,
+};
 pub use self::query::{
     check_begin_query, check_copy_query_pool_results, check_end_query, check_reset_query_pool,
     check_write_timestamp, CheckBeginQueryError, CheckCopyQueryPoolResultsError,
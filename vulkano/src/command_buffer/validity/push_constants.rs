@@ -9,7 +9,10 @@
 
 use crate::command_buffer::synced::SyncCommandBufferBuilder;
 use crate::pipeline::layout::PipelineLayout;
+use crate::pipeline::layout::PushConstantsLayout;
+use crate::pipeline::shader::ShaderStages;
 use crate::VulkanObject;
+use std::cmp;
 use std::error;
 use std::fmt;
 
@@ -73,3 +76,75 @@ impl fmt::Display for CheckPushConstantsValidityError {
         }
     }
 }
+
+/// Checks whether push constants of type `Pc` can be set with the pipeline layout. Returns the
+/// shader stages that can access them.
+pub fn check_push_constants_type<Pc>(
+    pipeline_layout: &PipelineLayout,
+) -> Result<ShaderStages, CheckPushConstantsTypeError>
+where
+    Pc: PushConstantsLayout,
+{
+    let start = Pc::OFFSET;
+    let end = Pc::OFFSET + Pc::SIZE;
+
+    // The ranges are sorted by offset, so the bytes of `Pc` are covered if each overlapping range
+    // starts before the end of the previous ones.
+    let mut stages = ShaderStages::none();
+    let mut covered_end = start;
+    for range in pipeline_layout
+        .push_constant_ranges()
+        .iter()
+        .filter(|range| range.offset < end && start < range.offset + range.size)
+    {
+        if range.offset > covered_end {
+            return Err(CheckPushConstantsTypeError::OutOfRange);
+        }
+        stages = stages.union(&range.stages);
+        covered_end = cmp::max(covered_end, range.offset + range.size);
+    }
+
+    if stages == ShaderStages::none() || covered_end < end {
+        return Err(CheckPushConstantsTypeError::OutOfRange);
+    }
+
+    for &(hash_stages, hash) in pipeline_layout.push_constants_layout_hashes() {
+        if hash_stages.intersects(&stages) && hash != Pc::LAYOUT_HASH {
+            return Err(CheckPushConstantsTypeError::TypeMismatch {
+                stages: hash_stages,
+            });
+        }
+    }
+
+    Ok(stages)
+}
+
+/// Error that can happen when checking the type of push constants.
+#[derive(Debug, Copy, Clone)]
+pub enum CheckPushConstantsTypeError {
+    /// Some bytes of the push constants are not in any push constant range of the pipeline
+    /// layout.
+    OutOfRange,
+    /// The shaders of these stages expect push constants of another type.
+    TypeMismatch { stages: ShaderStages },
+}
+
+impl error::Error for CheckPushConstantsTypeError {}
+
+impl fmt::Display for CheckPushConstantsTypeError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            CheckPushConstantsTypeError::OutOfRange => write!(
+                fmt,
+                "not all bytes of the push constants fall within the pipeline layout's push \
+                constant ranges"
+            ),
+            CheckPushConstantsTypeError::TypeMismatch { stages } => write!(
+                fmt,
+                "the shaders of stages {:?} expect push constants of another type",
+                stages
+            ),
+        }
+    }
+}
@@ -48,6 +48,7 @@ use crate::sampler::Sampler;
 use crate::sync::AccessFlags;
 use crate::sync::PipelineStages;
 use smallvec::SmallVec;
+use std::borrow::Cow;
 use std::cmp;
 use std::error;
 use std::fmt;
@@ -93,6 +94,19 @@ impl DescriptorSetDesc {
         self.descriptors.get(num as usize).and_then(|b| b.as_ref())
     }
 
+    /// Returns the binding number of the descriptor with the given name in the shader, or `None`
+    /// if there is no such descriptor.
+    #[inline]
+    pub fn binding_by_name(&self, name: &str) -> Option<u32> {
+        self.descriptors
+            .iter()
+            .position(|desc| {
+                desc.as_ref()
+                    .map_or(false, |desc| desc.name.as_deref() == Some(name))
+            })
+            .map(|num| num as u32)
+    }
+
     /// Builds the union of this layout description and another.
     #[inline]
     pub fn union(
@@ -354,6 +368,11 @@ pub struct DescriptorDesc {
 
     /// True if the attachment can be written by the shader.
     pub mutable: bool,
+
+    /// Name of the descriptor in the shader, or `None` if the name is unknown.
+    ///
+    /// The name is only informative, and is ignored when checking compatibility.
+    pub name: Option<Cow<'static, str>>,
 }
 
 impl DescriptorDesc {
@@ -475,7 +494,7 @@ impl DescriptorDesc {
     ///  geometry: true,
     ///  fragment: false,
     ///  compute: true
-    ///}, mutable: true, variable_count: false };
+    ///}, mutable: true, variable_count: false, name: None };
     ///
     ///let desc_part2 = DescriptorDesc{ ty: Sampler { immutable_samplers: vec![] }, descriptor_count: 1, stages: ShaderStages{
     ///  vertex: true,
@@ -484,7 +503,7 @@ impl DescriptorDesc {
     ///  geometry: false,
     ///  fragment: true,
     ///  compute: true
-    ///}, mutable: false, variable_count: false };
+    ///}, mutable: false, variable_count: false, name: None };
     ///
     ///let desc_union = DescriptorDesc{ ty: Sampler { immutable_samplers: vec![] }, descriptor_count: 2, stages: ShaderStages{
     ///  vertex: true,
@@ -493,7 +512,7 @@ impl DescriptorDesc {
     ///  geometry: true,
     ///  fragment: true,
     ///  compute: true
-    ///}, mutable: true, variable_count: false };
+    ///}, mutable: true, variable_count: false, name: None };
     ///
     ///assert_eq!(DescriptorDesc::union(Some(&desc_part1), Some(&desc_part2)), Ok(Some(desc_union)));
     ///```
@@ -513,6 +532,7 @@ impl DescriptorDesc {
                 stages: first.stages | second.stages,
                 mutable: first.mutable || second.mutable,
                 variable_count: first.variable_count && second.variable_count, // TODO: What is the correct behavior here?
+                name: first.name.clone().or_else(|| second.name.clone()),
             }))
         } else {
             Ok(first.or(second).cloned())
@@ -911,3 +931,50 @@ impl fmt::Display for DescriptorCompatibilityError {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::descriptor_set::layout::DescriptorDesc;
+    use crate::descriptor_set::layout::DescriptorDescTy;
+    use crate::descriptor_set::layout::DescriptorSetDesc;
+    use crate::pipeline::shader::ShaderStages;
+    use std::borrow::Cow;
+
+    fn uniform_buffer(name: Option<&'static str>) -> DescriptorDesc {
+        DescriptorDesc {
+            ty: DescriptorDescTy::UniformBuffer,
+            descriptor_count: 1,
+            stages: ShaderStages::all_graphics(),
+            variable_count: false,
+            mutable: false,
+            name: name.map(Cow::Borrowed),
+        }
+    }
+
+    #[test]
+    fn binding_by_name() {
+        let desc = DescriptorSetDesc::new([
+            Some(uniform_buffer(Some("u_camera"))),
+            None,
+            Some(uniform_buffer(None)),
+            Some(uniform_buffer(Some("u_albedo"))),
+        ]);
+
+        assert_eq!(desc.binding_by_name("u_camera"), Some(0));
+        assert_eq!(desc.binding_by_name("u_albedo"), Some(3));
+        assert_eq!(desc.binding_by_name("u_normal"), None);
+    }
+
+    #[test]
+    fn union_keeps_name() {
+        let named = uniform_buffer(Some("u_camera"));
+        let unnamed = uniform_buffer(None);
+
+        let union = DescriptorDesc::union(Some(&unnamed), Some(&named))
+            .unwrap()
+            .unwrap();
+        assert_eq!(union.name.as_deref(), Some("u_camera"));
+        // Names don't affect compatibility.
+        assert!(named.is_compatible_with(&unnamed));
+    }
+}
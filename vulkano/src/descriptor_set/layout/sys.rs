@@ -390,6 +390,7 @@ mod tests {
             stages: ShaderStages::all_graphics(),
             mutable: false,
             variable_count: false,
+            name: None,
         };
 
         let sl = DescriptorSetLayout::new(
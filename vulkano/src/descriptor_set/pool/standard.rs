@@ -200,6 +200,7 @@ mod tests {
             stages: ShaderStages::all(),
             mutable: true,
             variable_count: false,
+            name: None,
         };
         let layout = DescriptorSetLayout::new(
             device.clone(),
@@ -445,6 +445,7 @@ mod tests {
             stages: ShaderStages::all_graphics(),
             mutable: false,
             variable_count: false,
+            name: None,
         };
 
         let set_layout = DescriptorSetLayout::new(
@@ -476,6 +477,7 @@ mod tests {
             stages: ShaderStages::all_graphics(),
             mutable: false,
             variable_count: false,
+            name: None,
         };
 
         let set_layout =
@@ -93,7 +93,9 @@
 //! behaves as a regular image, and even has its own format, which can be queried with the `plane`
 //! method on a format.
 
+use self::texel::TexelLayout;
 use crate::device::physical::PhysicalDevice;
+use crate::image::ImageAspect;
 use crate::image::ImageAspects;
 use crate::DeviceSize;
 use crate::VulkanObject;
@@ -103,6 +105,8 @@ use std::mem::MaybeUninit;
 use std::vec::IntoIter as VecIntoIter;
 use std::{error, fmt, mem};
 
+pub mod texel;
+
 // Generated by build.rs
 include!(concat!(env!("OUT_DIR"), "/formats.rs"));
 
@@ -119,6 +123,112 @@ impl Format {
         )
     }
 
+    /// Returns the extent in texels of a single texel block of this format, in the order width,
+    /// height, depth. This is `block_dimensions` with a depth of 1, which is what the extent of a
+    /// buffer-image copy is compared to.
+    #[inline]
+    pub fn block_extent(&self) -> [u32; 3] {
+        let [width, height] = self.block_dimensions();
+        [width, height, 1]
+    }
+
+    /// Returns the size in bytes of a single texel block of this format, or `None` if it isn't
+    /// well-defined. This is the same value as `size`, and is the unit in which the data of an image
+    /// of this format is laid out in a buffer.
+    #[inline]
+    pub fn block_size(&self) -> Option<DeviceSize> {
+        self.size()
+    }
+
+    /// Returns the size in bytes of a texel block of one aspect of this format, as it is laid out
+    /// in a buffer when copying that aspect to or from an image. Returns `None` if the format
+    /// doesn't have the aspect.
+    ///
+    /// Unlike `block_size`, this is defined for depth/stencil formats, whose aspects are copied
+    /// separately, and for multi-planar formats, whose planes are copied separately.
+    pub fn aspect_block_size(&self, aspect: ImageAspect) -> Option<DeviceSize> {
+        let aspects = self.aspects();
+
+        match aspect {
+            ImageAspect::Color if aspects.color && self.planes().is_empty() => self.size(),
+            ImageAspect::Depth if aspects.depth => match *self {
+                Format::D16_UNORM | Format::D16_UNORM_S8_UINT => Some(2),
+                _ => Some(4),
+            },
+            ImageAspect::Stencil if aspects.stencil => Some(1),
+            ImageAspect::Plane0 => self.planes().get(0).and_then(|plane| plane.size()),
+            ImageAspect::Plane1 => self.planes().get(1).and_then(|plane| plane.size()),
+            ImageAspect::Plane2 => self.planes().get(2).and_then(|plane| plane.size()),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of bytes that a buffer must contain to copy a region of `extent` texels
+    /// between the buffer and an image of this format. `row_length` and `image_height` are the
+    /// size in texels of the rows and of the 2D images of the data in the buffer, as in a
+    /// buffer-image copy; 0 means that they are the same as the extent of the region.
+    ///
+    /// The array layers of a region are laid out one after the other like the depth slices of a
+    /// 3D image, so the depth of `extent` must be multiplied by the number of layers. Returns
+    /// `None` if the size of a texel block of the format isn't well-defined.
+    #[inline]
+    pub fn required_buffer_size(
+        &self,
+        extent: [u32; 3],
+        row_length: u32,
+        image_height: u32,
+    ) -> Option<DeviceSize> {
+        self.aspect_required_buffer_size(ImageAspect::Color, extent, row_length, image_height)
+    }
+
+    /// Same as `required_buffer_size`, but for copying a single aspect of an image of this format.
+    /// Returns `None` if the format doesn't have the aspect, or if the size of a texel block of the
+    /// aspect isn't well-defined.
+    ///
+    /// For multi-planar formats, `extent` is in texels of the plane, which may be smaller than the
+    /// image itself.
+    pub fn aspect_required_buffer_size(
+        &self,
+        aspect: ImageAspect,
+        extent: [u32; 3],
+        row_length: u32,
+        image_height: u32,
+    ) -> Option<DeviceSize> {
+        let block_size = self.aspect_block_size(aspect)?;
+
+        if extent.contains(&0) {
+            return Some(0);
+        }
+
+        // Only the color aspect can have texel blocks of more than one texel.
+        let [block_width, block_height, _] = if aspect == ImageAspect::Color {
+            self.block_extent()
+        } else {
+            [1, 1, 1]
+        };
+        let blocks = |texels: u32, block: u32| ((texels + block - 1) / block) as DeviceSize;
+
+        let row_length = if row_length == 0 {
+            extent[0]
+        } else {
+            row_length
+        };
+        let image_height = if image_height == 0 {
+            extent[1]
+        } else {
+            image_height
+        };
+        let row_blocks = blocks(row_length, block_width);
+        let image_blocks = blocks(image_height, block_height) * row_blocks;
+
+        // The last row of the last image doesn't need to be complete.
+        let num_blocks = (extent[2] - 1) as DeviceSize * image_blocks
+            + (blocks(extent[1], block_height) - 1) * row_blocks
+            + blocks(extent[0], block_width);
+
+        Some(num_blocks * block_size)
+    }
+
     /// Retrieves the properties of a format when used by a certain device.
     #[inline]
     pub fn properties(&self, physical_device: PhysicalDevice) -> FormatProperties {
@@ -150,6 +260,22 @@ impl Format {
             assert!(matches!(value, ClearValue::Depth(_)));
         } else if aspects.stencil {
             assert!(matches!(value, ClearValue::Stencil(_)));
+        } else if self.texel_layout().is_some() {
+            // Checks that the value can be encoded in this format.
+            let mut texel = [0; 32];
+            let texel = &mut texel[..self.size().unwrap() as usize];
+            let result = match value {
+                ClearValue::Float(value) => texel::encode_f32(*self, value, texel),
+                ClearValue::Int(value) => texel::encode_i32(*self, value, texel),
+                ClearValue::Uint(value) => texel::encode_u32(*self, value, texel),
+                _ => Err(texel::TexelError::NumericTypeMismatch {
+                    numeric_type: self.type_color().unwrap(),
+                }),
+            };
+
+            if let Err(err) = result {
+                panic!("invalid clear value {:?} for {:?}: {}", value, self, err);
+            }
         } else if let Some(numeric_type) = self.type_color() {
             match numeric_type {
                 NumericType::SFLOAT
@@ -583,3 +709,73 @@ impl From<ash::vk::FormatFeatureFlags> for FormatFeatures {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::format::Format;
+    use crate::image::ImageAspect;
+
+    #[test]
+    fn block_metadata() {
+        assert_eq!(Format::R8G8B8A8_UNORM.block_extent(), [1, 1, 1]);
+        assert_eq!(Format::R8G8B8A8_UNORM.block_size(), Some(4));
+        assert_eq!(Format::BC1_RGB_UNORM_BLOCK.block_extent(), [4, 4, 1]);
+        assert_eq!(Format::BC1_RGB_UNORM_BLOCK.block_size(), Some(8));
+        assert_eq!(Format::ASTC_10x6_UNORM_BLOCK.block_extent(), [10, 6, 1]);
+        assert_eq!(Format::D24_UNORM_S8_UINT.block_size(), None);
+        assert_eq!(Format::G8_B8R8_2PLANE_420_UNORM.planes().len(), 2);
+    }
+
+    #[test]
+    fn aspect_block_size() {
+        let format = Format::D16_UNORM_S8_UINT;
+        assert_eq!(format.aspect_block_size(ImageAspect::Depth), Some(2));
+        assert_eq!(format.aspect_block_size(ImageAspect::Stencil), Some(1));
+        assert_eq!(format.aspect_block_size(ImageAspect::Color), None);
+
+        let format = Format::G8_B8R8_2PLANE_420_UNORM;
+        assert_eq!(format.aspect_block_size(ImageAspect::Color), None);
+        assert_eq!(format.aspect_block_size(ImageAspect::Plane0), Some(1));
+        assert_eq!(format.aspect_block_size(ImageAspect::Plane1), Some(2));
+        assert_eq!(format.aspect_block_size(ImageAspect::Plane2), None);
+    }
+
+    #[test]
+    fn required_buffer_size() {
+        let format = Format::R8G8B8A8_UNORM;
+        assert_eq!(format.required_buffer_size([16, 16, 1], 0, 0), Some(1024));
+        assert_eq!(format.required_buffer_size([16, 16, 2], 0, 0), Some(2048));
+        // The last row only contains the texels of the region.
+        assert_eq!(
+            format.required_buffer_size([16, 16, 1], 32, 0),
+            Some((15 * 32 + 16) * 4)
+        );
+        assert_eq!(
+            format.required_buffer_size([16, 16, 2], 0, 20),
+            Some((20 * 16 + 15 * 16 + 16) * 4)
+        );
+
+        // A 6x6 region of a BC1 image is 2x2 blocks.
+        let format = Format::BC1_RGB_UNORM_BLOCK;
+        assert_eq!(format.required_buffer_size([6, 6, 1], 0, 0), Some(32));
+        assert_eq!(format.required_buffer_size([0, 6, 1], 0, 0), Some(0));
+        assert_eq!(
+            Format::D32_SFLOAT.required_buffer_size([4, 4, 1], 0, 0),
+            None
+        );
+
+        let format = Format::D24_UNORM_S8_UINT;
+        assert_eq!(
+            format.aspect_required_buffer_size(ImageAspect::Depth, [4, 4, 1], 0, 0),
+            Some(64)
+        );
+        assert_eq!(
+            format.aspect_required_buffer_size(ImageAspect::Stencil, [4, 4, 1], 8, 0),
+            Some(3 * 8 + 4)
+        );
+        assert_eq!(
+            format.aspect_required_buffer_size(ImageAspect::Color, [4, 4, 1], 0, 0),
+            None
+        );
+    }
+}
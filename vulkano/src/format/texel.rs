@@ -0,0 +1,682 @@
+// Copyright (c) 2021 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Conversion of individual texels between their memory representation and numeric values.
+//!
+//! The functions in this module decode the bytes of a single texel into four components (R, G,
+//! B, A), or encode four components into the bytes of a texel. They can be used to fill or read
+//! back the contents of a buffer that is copied to or from an image, without the GPU being
+//! involved.
+//!
+//! Only formats whose texels can be accessed individually are supported. This excludes
+//! depth/stencil formats, whose representation is opaque, block-compressed formats, multi-planar
+//! formats and formats with chroma subsampling.
+//!
+//! The numeric type of the format determines which functions can be used:
+//! - `UNORM`, `SNORM`, `USCALED`, `SSCALED`, `UFLOAT`, `SFLOAT` and `SRGB` formats are converted
+//!   from and to `f32` values.
+//! - `UINT` formats are converted from and to `u32` values.
+//! - `SINT` formats are converted from and to `i32` values.
+//!
+//! When decoding, components that are absent from the format are given the value that a shader
+//! would read when sampling the image: 0 for R, G and B, and 1 for A. When encoding, components
+//! that are absent are ignored. Values that are out of range for a component are clamped to the
+//! nearest representable value.
+
+use crate::format::Format;
+use crate::format::NumericType;
+use half::f16;
+use smallvec::SmallVec;
+use std::error;
+use std::fmt;
+
+/// Decodes a texel of a floating-point or normalized format.
+///
+/// `texel` must contain exactly the bytes of one texel of `format`. For `SRGB` formats, the R, G
+/// and B components are converted to linear values.
+pub fn decode_f32(format: Format, texel: &[u8]) -> Result<[f32; 4], TexelError> {
+    let (layout, numeric_type) = check(format, texel.len(), ValueKind::Float)?;
+    let mut value = [0.0, 0.0, 0.0, 1.0];
+
+    if layout.is_shared_exponent() {
+        let components = components(&layout);
+        let exponent = read_bits(texel, &components[0]) as i32;
+
+        for component in &components[1..] {
+            let mantissa = read_bits(texel, component) as f32;
+            value[component.index.unwrap()] =
+                mantissa * 2f32.powi(exponent - SHARED_EXPONENT_BIAS - SHARED_MANTISSA_BITS);
+        }
+
+        return Ok(value);
+    }
+
+    for component in components(&layout) {
+        let index = match component.index {
+            Some(x) => x,
+            None => continue,
+        };
+        let raw = read_bits(texel, &component);
+        let bits = component.bits;
+
+        value[index] = match numeric_type {
+            NumericType::UNORM => raw as f32 / unsigned_max(bits) as f32,
+            NumericType::SNORM => {
+                (sign_extend(raw, bits) as f32 / signed_max(bits) as f32).max(-1.0)
+            }
+            NumericType::USCALED => raw as f32,
+            NumericType::SSCALED => sign_extend(raw, bits) as f32,
+            NumericType::SRGB => {
+                let unorm = raw as f32 / unsigned_max(bits) as f32;
+
+                if index == 3 {
+                    unorm
+                } else {
+                    srgb_to_linear(unorm)
+                }
+            }
+            NumericType::SFLOAT => match bits {
+                16 => f16::from_bits(raw as u16).to_f32(),
+                32 => f32::from_bits(raw as u32),
+                64 => f64::from_bits(raw) as f32,
+                _ => unreachable!(),
+            },
+            NumericType::UFLOAT => {
+                // Unsigned 10- and 11-bit floats have the same exponent as a 16-bit float, and a
+                // shorter mantissa.
+                f16::from_bits((raw as u16) << (16 - 1 - bits)).to_f32()
+            }
+            NumericType::UINT | NumericType::SINT => unreachable!(),
+        };
+    }
+
+    Ok(value)
+}
+
+/// Decodes a texel of a `UINT` format.
+///
+/// `texel` must contain exactly the bytes of one texel of `format`. Components with more than 32
+/// bits are clamped to `u32::MAX`.
+pub fn decode_u32(format: Format, texel: &[u8]) -> Result<[u32; 4], TexelError> {
+    let (layout, _) = check(format, texel.len(), ValueKind::Uint)?;
+    let mut value = [0, 0, 0, 1];
+
+    for component in components(&layout) {
+        if let Some(index) = component.index {
+            let raw = read_bits(texel, &component);
+            value[index] = raw.min(u32::MAX as u64) as u32;
+        }
+    }
+
+    Ok(value)
+}
+
+/// Decodes a texel of a `SINT` format.
+///
+/// `texel` must contain exactly the bytes of one texel of `format`. Components with more than 32
+/// bits are clamped to the range of `i32`.
+pub fn decode_i32(format: Format, texel: &[u8]) -> Result<[i32; 4], TexelError> {
+    let (layout, _) = check(format, texel.len(), ValueKind::Int)?;
+    let mut value = [0, 0, 0, 1];
+
+    for component in components(&layout) {
+        if let Some(index) = component.index {
+            let raw = sign_extend(read_bits(texel, &component), component.bits);
+            value[index] = raw.max(i32::MIN as i64).min(i32::MAX as i64) as i32;
+        }
+    }
+
+    Ok(value)
+}
+
+/// Encodes a texel of a floating-point or normalized format.
+///
+/// `texel` must be exactly the size of one texel of `format`. For `SRGB` formats, the R, G and B
+/// components are expected to be linear values, and are converted to sRGB.
+pub fn encode_f32(format: Format, value: [f32; 4], texel: &mut [u8]) -> Result<(), TexelError> {
+    let (layout, numeric_type) = check(format, texel.len(), ValueKind::Float)?;
+    clear(texel);
+
+    if layout.is_shared_exponent() {
+        let components = components(&layout);
+        let (exponent, mantissas) = encode_shared_exponent(value);
+        write_bits(texel, &components[0], exponent as u64);
+
+        for component in &components[1..] {
+            write_bits(texel, component, mantissas[component.index.unwrap()] as u64);
+        }
+
+        return Ok(());
+    }
+
+    for component in components(&layout) {
+        let index = match component.index {
+            Some(x) => x,
+            None => continue,
+        };
+        let value = value[index];
+        let bits = component.bits;
+
+        let raw = match numeric_type {
+            NumericType::UNORM => encode_unorm(value, bits),
+            NumericType::SNORM => {
+                let max = signed_max(bits) as f64;
+                (value.max(-1.0).min(1.0) as f64 * max).round() as i64 as u64
+            }
+            NumericType::USCALED => (value as f64)
+                .round()
+                .max(0.0)
+                .min(unsigned_max(bits) as f64) as u64,
+            NumericType::SSCALED => {
+                let max = signed_max(bits) as f64;
+                (value as f64).round().max(-max - 1.0).min(max) as i64 as u64
+            }
+            NumericType::SRGB => {
+                if index == 3 {
+                    encode_unorm(value, bits)
+                } else {
+                    encode_unorm(linear_to_srgb(value), bits)
+                }
+            }
+            NumericType::SFLOAT => match bits {
+                16 => f16::from_f32(value).to_bits() as u64,
+                32 => value.to_bits() as u64,
+                64 => (value as f64).to_bits(),
+                _ => unreachable!(),
+            },
+            NumericType::UFLOAT => encode_small_ufloat(value, bits),
+            NumericType::UINT | NumericType::SINT => unreachable!(),
+        };
+
+        write_bits(texel, &component, raw);
+    }
+
+    Ok(())
+}
+
+/// Encodes a texel of a `UINT` format.
+///
+/// `texel` must be exactly the size of one texel of `format`.
+pub fn encode_u32(format: Format, value: [u32; 4], texel: &mut [u8]) -> Result<(), TexelError> {
+    let (layout, _) = check(format, texel.len(), ValueKind::Uint)?;
+    clear(texel);
+
+    for component in components(&layout) {
+        if let Some(index) = component.index {
+            let raw = (value[index] as u64).min(unsigned_max(component.bits));
+            write_bits(texel, &component, raw);
+        }
+    }
+
+    Ok(())
+}
+
+/// Encodes a texel of a `SINT` format.
+///
+/// `texel` must be exactly the size of one texel of `format`.
+pub fn encode_i32(format: Format, value: [i32; 4], texel: &mut [u8]) -> Result<(), TexelError> {
+    let (layout, _) = check(format, texel.len(), ValueKind::Int)?;
+    clear(texel);
+
+    for component in components(&layout) {
+        if let Some(index) = component.index {
+            let max = signed_max(component.bits);
+            let raw = (value[index] as i64).max(-max - 1).min(max);
+            write_bits(texel, &component, raw as u64);
+        }
+    }
+
+    Ok(())
+}
+
+/// Converts a value encoded with the sRGB transfer function to a linear value.
+#[inline]
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a linear value to a value encoded with the sRGB transfer function.
+#[inline]
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// How the components of a single texel of a format are laid out in memory.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TexelLayout {
+    /// If `Some`, the components are packed into words of this many bits, with the first
+    /// component in the most significant bits of the first word. If `None`, each component is
+    /// stored in its own bytes.
+    pub(crate) word_bits: Option<u8>,
+    /// The letter and number of bits of each component, in the order they appear in the name of
+    /// the format. `X` denotes unused bits, `E` a shared exponent.
+    pub(crate) components: &'static [(char, u8)],
+}
+
+impl TexelLayout {
+    #[inline]
+    fn is_shared_exponent(&self) -> bool {
+        self.components[0].0 == 'E'
+    }
+}
+
+/// Error that can happen when encoding or decoding a texel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TexelError {
+    /// The texels of the format cannot be accessed individually.
+    FormatNotSupported,
+    /// The type of the values doesn't match the numeric type of the format.
+    NumericTypeMismatch { numeric_type: NumericType },
+    /// The number of bytes doesn't match the size of a texel of the format.
+    SizeMismatch { expected: usize, obtained: usize },
+}
+
+impl error::Error for TexelError {}
+
+impl fmt::Display for TexelError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            TexelError::FormatNotSupported => write!(
+                fmt,
+                "the texels of the format cannot be accessed individually"
+            ),
+            TexelError::NumericTypeMismatch { numeric_type } => write!(
+                fmt,
+                "the type of the values doesn't match the numeric type {:?} of the format",
+                numeric_type
+            ),
+            TexelError::SizeMismatch { expected, obtained } => write!(
+                fmt,
+                "the texel is {} bytes long, but the format requires {} bytes",
+                obtained, expected
+            ),
+        }
+    }
+}
+
+const SHARED_EXPONENT_BIAS: i32 = 15;
+const SHARED_MANTISSA_BITS: i32 = 9;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ValueKind {
+    Float,
+    Uint,
+    Int,
+}
+
+// Checks that values of type `kind` can be converted for `format`, and that the texel has the
+// right size.
+fn check(
+    format: Format,
+    len: usize,
+    kind: ValueKind,
+) -> Result<(TexelLayout, NumericType), TexelError> {
+    let layout = format
+        .texel_layout()
+        .ok_or(TexelError::FormatNotSupported)?;
+    let numeric_type = format.type_color().unwrap();
+
+    let expected_kind = match numeric_type {
+        NumericType::UINT => ValueKind::Uint,
+        NumericType::SINT => ValueKind::Int,
+        _ => ValueKind::Float,
+    };
+
+    if kind != expected_kind {
+        return Err(TexelError::NumericTypeMismatch { numeric_type });
+    }
+
+    let expected = format.size().unwrap() as usize;
+
+    if len != expected {
+        return Err(TexelError::SizeMismatch {
+            expected,
+            obtained: len,
+        });
+    }
+
+    Ok((layout, numeric_type))
+}
+
+// Location of a component inside a texel.
+#[derive(Clone, Copy, Debug)]
+struct Component {
+    // Index of the component in the RGBA value, or `None` if it isn't a color component.
+    index: Option<usize>,
+    // Offset in bytes of the word containing the component.
+    offset: usize,
+    // Size in bytes of the word containing the component.
+    word_bytes: usize,
+    // Position of the least significant bit of the component in the word.
+    shift: u32,
+    bits: u32,
+}
+
+fn components(layout: &TexelLayout) -> SmallVec<[Component; 8]> {
+    let mut components = SmallVec::new();
+    let mut offset = 0;
+    let mut used_bits = 0;
+
+    for &(letter, bits) in layout.components {
+        let bits = bits as u32;
+        let index = match letter {
+            'R' => Some(0),
+            'G' => Some(1),
+            'B' => Some(2),
+            'A' => Some(3),
+            _ => None,
+        };
+
+        match layout.word_bits {
+            Some(word_bits) => {
+                let word_bits = word_bits as u32;
+                used_bits += bits;
+                components.push(Component {
+                    index,
+                    offset,
+                    word_bytes: word_bits as usize / 8,
+                    shift: word_bits - used_bits,
+                    bits,
+                });
+
+                if used_bits == word_bits {
+                    offset += word_bits as usize / 8;
+                    used_bits = 0;
+                }
+            }
+            None => {
+                components.push(Component {
+                    index,
+                    offset,
+                    word_bytes: bits as usize / 8,
+                    shift: 0,
+                    bits,
+                });
+                offset += bits as usize / 8;
+            }
+        }
+    }
+
+    components
+}
+
+fn read_bits(texel: &[u8], component: &Component) -> u64 {
+    let word = texel[component.offset..component.offset + component.word_bytes]
+        .iter()
+        .rev()
+        .fold(0u64, |word, &byte| word << 8 | byte as u64);
+
+    (word >> component.shift) & unsigned_max(component.bits)
+}
+
+fn write_bits(texel: &mut [u8], component: &Component, value: u64) {
+    let bytes = &mut texel[component.offset..component.offset + component.word_bytes];
+    let word = (value & unsigned_max(component.bits)) << component.shift;
+
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte |= (word >> (i * 8)) as u8;
+    }
+}
+
+fn clear(texel: &mut [u8]) {
+    for byte in texel {
+        *byte = 0;
+    }
+}
+
+#[inline]
+fn unsigned_max(bits: u32) -> u64 {
+    u64::MAX >> (64 - bits)
+}
+
+#[inline]
+fn signed_max(bits: u32) -> i64 {
+    (unsigned_max(bits) >> 1) as i64
+}
+
+#[inline]
+fn sign_extend(value: u64, bits: u32) -> i64 {
+    ((value << (64 - bits)) as i64) >> (64 - bits)
+}
+
+#[inline]
+fn encode_unorm(value: f32, bits: u32) -> u64 {
+    let max = unsigned_max(bits) as f64;
+    (value.max(0.0).min(1.0) as f64 * max).round() as u64
+}
+
+// Encodes an unsigned 10- or 11-bit float, by rounding the mantissa of a 16-bit float.
+fn encode_small_ufloat(value: f32, bits: u32) -> u64 {
+    let shift = 16 - 1 - bits;
+    let half = f16::from_f32(value).to_bits();
+
+    if value.is_nan() {
+        // All exponent bits set, and a non-zero mantissa.
+        unsigned_max(bits)
+    } else if half & 0x8000 != 0 {
+        0
+    } else if half & 0x7c00 == 0x7c00 {
+        // Infinity
+        (half >> shift) as u64
+    } else {
+        ((half as u64 + (1 << (shift - 1))) >> shift).min(0x1f << (bits - 5))
+    }
+}
+
+// Encodes a value as a shared exponent and three mantissas, following the algorithm in the
+// "Shared Exponent" section of the Vulkan specification.
+fn encode_shared_exponent(value: [f32; 4]) -> (u32, [u32; 3]) {
+    let max_mantissa = ((1 << SHARED_MANTISSA_BITS) - 1) as f32;
+    let shared_max = max_mantissa / (1 << SHARED_MANTISSA_BITS) as f32
+        * 2f32.powi((1 << 5) - 1 - SHARED_EXPONENT_BIAS);
+
+    let mut clamped = [0.0; 3];
+
+    for (clamped, &value) in clamped.iter_mut().zip(&value[0..3]) {
+        // `max` and `min` return the other operand if one is NaN.
+        *clamped = value.max(0.0).min(shared_max);
+    }
+
+    let max_value = clamped[0].max(clamped[1]).max(clamped[2]);
+    let max_exponent = if max_value > 0.0 {
+        max_value.log2().floor() as i32
+    } else {
+        0
+    };
+    let exponent_prime = max_exponent.max(-SHARED_EXPONENT_BIAS - 1) + 1 + SHARED_EXPONENT_BIAS;
+
+    let scale = |exponent: i32| 2f32.powi(exponent - SHARED_EXPONENT_BIAS - SHARED_MANTISSA_BITS);
+    let max_shared = (max_value / scale(exponent_prime) + 0.5).floor();
+    let exponent = if max_shared > max_mantissa {
+        exponent_prime + 1
+    } else {
+        exponent_prime
+    };
+
+    let mut mantissas = [0; 3];
+
+    for (mantissa, &value) in mantissas.iter_mut().zip(&clamped) {
+        *mantissa = ((value / scale(exponent) + 0.5).floor() as u32).min(max_mantissa as u32);
+    }
+
+    (exponent as u32, mantissas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unorm_srgb() {
+        let texel = [0x00, 0x80, 0xff, 0x40];
+        let value = decode_f32(Format::R8G8B8A8_UNORM, &texel).unwrap();
+        assert_eq!(value, [0.0, 128.0 / 255.0, 1.0, 64.0 / 255.0]);
+
+        let mut encoded = [0; 4];
+        encode_f32(Format::R8G8B8A8_UNORM, value, &mut encoded).unwrap();
+        assert_eq!(encoded, texel);
+
+        // B and R are swapped, alpha is linear.
+        let value = decode_f32(Format::B8G8R8A8_SRGB, &[0xff, 0xbc, 0x00, 0x80]).unwrap();
+        assert_eq!(value[0], 0.0);
+        assert!((value[1] - 0.5029).abs() < 1e-3);
+        assert_eq!(value[2], 1.0);
+        assert_eq!(value[3], 128.0 / 255.0);
+
+        encode_f32(Format::B8G8R8A8_SRGB, [0.0, 0.5, 1.0, 0.5], &mut encoded).unwrap();
+        assert_eq!(encoded, [0xff, 0xbc, 0x00, 0x80]);
+
+        // Missing components
+        let value = decode_f32(Format::R8G8_UNORM, &[0xff, 0x00]).unwrap();
+        assert_eq!(value, [1.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn snorm_scaled() {
+        let value = decode_f32(Format::R8G8_SNORM, &[0x81, 0x80]).unwrap();
+        assert_eq!(value, [-1.0, -1.0, 0.0, 1.0]);
+
+        let mut encoded = [0; 2];
+        encode_f32(Format::R8G8_SNORM, [-1.0, 0.5, 0.0, 0.0], &mut encoded).unwrap();
+        assert_eq!(encoded, [0x81, 0x40]);
+
+        let value = decode_f32(Format::R16_SSCALED, &[0x18, 0xfc]).unwrap();
+        assert_eq!(value, [-1000.0, 0.0, 0.0, 1.0]);
+
+        encode_f32(Format::R8G8_USCALED, [300.0, 12.4, 0.0, 0.0], &mut encoded).unwrap();
+        assert_eq!(encoded, [0xff, 12]);
+    }
+
+    #[test]
+    fn packed() {
+        // A is in the 2 most significant bits, R in the 10 least significant bits.
+        let texel = 0xc00ffc01u32.to_le_bytes();
+        let value = decode_u32(Format::A2B10G10R10_UINT_PACK32, &texel).unwrap();
+        assert_eq!(value, [1, 0x3ff, 0, 3]);
+
+        let mut encoded = [0; 4];
+        encode_u32(Format::A2B10G10R10_UINT_PACK32, value, &mut encoded).unwrap();
+        assert_eq!(encoded, texel);
+
+        let texel = 0xf800u16.to_le_bytes();
+        let value = decode_f32(Format::R5G6B5_UNORM_PACK16, &texel).unwrap();
+        assert_eq!(value, [1.0, 0.0, 0.0, 1.0]);
+
+        let mut encoded = [0; 2];
+        encode_f32(
+            Format::R4G4B4A4_UNORM_PACK16,
+            [1.0, 0.0, 1.0, 0.0],
+            &mut encoded,
+        )
+        .unwrap();
+        assert_eq!(u16::from_le_bytes(encoded), 0xf0f0);
+
+        // The 6 unused bits are the least significant ones.
+        let value = decode_f32(Format::R10X6_UNORM_PACK16, &0xffc0u16.to_le_bytes()).unwrap();
+        assert_eq!(value, [1.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn integer() {
+        let value = decode_i32(Format::R16G16_SINT, &[0xff, 0xff, 0x00, 0x80]).unwrap();
+        assert_eq!(value, [-1, -32768, 0, 1]);
+
+        let mut encoded = [0; 2];
+        encode_i32(Format::R8G8_SINT, [-200, 100, 0, 0], &mut encoded).unwrap();
+        assert_eq!(encoded, [0x80, 100]);
+
+        let mut encoded = [0; 8];
+        encode_u32(Format::R64_UINT, [u32::MAX, 0, 0, 0], &mut encoded).unwrap();
+        assert_eq!(encoded, [0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn floats() {
+        let mut texel = [0; 8];
+        texel[0..2].copy_from_slice(&0x3c00u16.to_le_bytes());
+        texel[2..4].copy_from_slice(&0xc000u16.to_le_bytes());
+        texel[4..6].copy_from_slice(&0x3800u16.to_le_bytes());
+        let value = decode_f32(Format::R16G16B16A16_SFLOAT, &texel).unwrap();
+        assert_eq!(value, [1.0, -2.0, 0.5, 0.0]);
+
+        let mut encoded = [0; 8];
+        encode_f32(Format::R16G16B16A16_SFLOAT, value, &mut encoded).unwrap();
+        assert_eq!(encoded, texel);
+
+        let value = decode_f32(Format::R32_SFLOAT, &1.5f32.to_le_bytes()).unwrap();
+        assert_eq!(value, [1.5, 0.0, 0.0, 1.0]);
+
+        // B in the 10 most significant bits, R in the 11 least significant bits.
+        let texel = (0x200u32 << 22 | 0x3c0 << 11 | 0x380).to_le_bytes();
+        let value = decode_f32(Format::B10G11R11_UFLOAT_PACK32, &texel).unwrap();
+        assert_eq!(value, [0.5, 1.0, 2.0, 1.0]);
+
+        let mut encoded = [0; 4];
+        encode_f32(Format::B10G11R11_UFLOAT_PACK32, value, &mut encoded).unwrap();
+        assert_eq!(encoded, texel);
+
+        encode_f32(
+            Format::B10G11R11_UFLOAT_PACK32,
+            [-1.0, 0.0, 0.0, 0.0],
+            &mut encoded,
+        )
+        .unwrap();
+        assert_eq!(encoded, [0; 4]);
+    }
+
+    #[test]
+    fn shared_exponent() {
+        // 1.0 = 256 * 2^(16 - 15 - 9)
+        let texel = (16u32 << 27 | 128 << 9 | 256).to_le_bytes();
+        let value = decode_f32(Format::E5B9G9R9_UFLOAT_PACK32, &texel).unwrap();
+        assert_eq!(value, [1.0, 0.5, 0.0, 1.0]);
+
+        let mut encoded = [0; 4];
+        encode_f32(Format::E5B9G9R9_UFLOAT_PACK32, value, &mut encoded).unwrap();
+        assert_eq!(encoded, texel);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            decode_f32(Format::BC1_RGB_UNORM_BLOCK, &[0; 8]),
+            Err(TexelError::FormatNotSupported)
+        );
+        assert_eq!(
+            decode_f32(Format::D32_SFLOAT, &[0; 4]),
+            Err(TexelError::FormatNotSupported)
+        );
+        assert_eq!(
+            decode_f32(Format::G8B8G8R8_422_UNORM, &[0; 4]),
+            Err(TexelError::FormatNotSupported)
+        );
+        assert_eq!(
+            decode_u32(Format::R8_UNORM, &[0]),
+            Err(TexelError::NumericTypeMismatch {
+                numeric_type: NumericType::UNORM
+            })
+        );
+        assert_eq!(
+            decode_f32(Format::R8_UNORM, &[0, 0]),
+            Err(TexelError::SizeMismatch {
+                expected: 1,
+                obtained: 2
+            })
+        );
+    }
+}
@@ -0,0 +1,827 @@
+// Copyright (c) 2021 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Image whose content is accessible to the CPU.
+//!
+//! The `CpuAccessibleImage` uses linear tiling and is allocated in host-visible memory, so that
+//! its texels can be read and written directly by the CPU without going through a staging
+//! buffer. The rows of a linear image can be padded, so the content is accessed one subresource
+//! at a time, through locks that iterate over the rows of texels of the subresource.
+//!
+//! Each access from the CPU or from the GPU locks the whole image for either reading or writing,
+//! even if it only concerns one subresource.
+//! You can read the image multiple times simultaneously. Trying to read and write simultaneously,
+//! or write and write simultaneously will return an error.
+
+use crate::device::physical::QueueFamily;
+use crate::device::Device;
+use crate::format::ClearValue;
+use crate::format::Format;
+use crate::image::device_local::check_image_support;
+use crate::image::sys::ImageCreationError;
+use crate::image::sys::LinearLayout;
+use crate::image::sys::UnsafeImage;
+use crate::image::traits::ImageAccess;
+use crate::image::traits::ImageClearValue;
+use crate::image::traits::ImageContent;
+use crate::image::ImageCreateFlags;
+use crate::image::ImageDescriptorLayouts;
+use crate::image::ImageDimensions;
+use crate::image::ImageInner;
+use crate::image::ImageLayout;
+use crate::image::ImageTiling;
+use crate::image::ImageUsage;
+use crate::image::MipmapsCount;
+use crate::image::SampleCount;
+use crate::memory::pool::AllocFromRequirementsFilter;
+use crate::memory::pool::AllocLayout;
+use crate::memory::pool::MappingRequirement;
+use crate::memory::pool::MemoryPool;
+use crate::memory::pool::MemoryPoolAlloc;
+use crate::memory::pool::PotentialDedicatedAllocation;
+use crate::memory::pool::StdMemoryPoolAlloc;
+use crate::memory::CpuAccess as MemCpuAccess;
+use crate::memory::DedicatedAlloc;
+use crate::sync::AccessError;
+use crate::sync::Sharing;
+use crate::DeviceSize;
+use parking_lot::RwLock;
+use parking_lot::RwLockReadGuard;
+use parking_lot::RwLockWriteGuard;
+use smallvec::SmallVec;
+use std::error;
+use std::fmt;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::ops::Deref;
+use std::ops::DerefMut;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+/// Image with linear tiling whose content is accessible by the CPU.
+///
+/// The image is created in the `Preinitialized` layout, so that the content written by the CPU
+/// before its first use by the GPU is preserved, and is kept in the `General` layout between the
+/// commands that use it afterwards.
+///
+/// Setting `host_cached` to `true` when creating the image will make it so the
+/// `CpuAccessibleImage` prefers to allocate from host cached memory, which can be more performant
+/// when the CPU needs to read data coming off the GPU.
+#[derive(Debug)]
+pub struct CpuAccessibleImage<A = PotentialDedicatedAllocation<StdMemoryPoolAlloc>> {
+    // Inner implementation.
+    image: UnsafeImage,
+
+    // Memory used to back the image.
+    memory: A,
+
+    // Access pattern of the image.
+    // Every time the user tries to read or write the image from the CPU, this `RwLock` is kept
+    // locked and its content is checked to verify that we are allowed access. Every time the user
+    // tries to submit this image for the GPU, this `RwLock` is briefly locked and modified.
+    access: RwLock<CurrentGpuAccess>,
+
+    // Queue families allowed to access this image.
+    queue_families: SmallVec<[u32; 4]>,
+
+    // Whether the image has been transitioned out of the `Preinitialized` layout.
+    initialized: AtomicBool,
+}
+
+#[derive(Debug)]
+enum CurrentGpuAccess {
+    NonExclusive {
+        // Number of non-exclusive GPU accesses. Can be 0.
+        num: AtomicUsize,
+    },
+    Exclusive {
+        // Number of exclusive locks. Cannot be 0. If 0 is reached, we must jump to `NonExclusive`.
+        num: usize,
+    },
+}
+
+impl CpuAccessibleImage {
+    /// Builds a new image with linear tiling in host-visible memory.
+    ///
+    /// Only color formats that aren't multi-planar are supported. The format, usage, dimensions
+    /// and number of mipmaps are checked against what the physical device supports for linear
+    /// tiling before the image is created, which is often much less than for optimal tiling.
+    pub fn new<'a, M, I>(
+        device: Arc<Device>,
+        dimensions: ImageDimensions,
+        format: Format,
+        mipmaps: M,
+        usage: ImageUsage,
+        host_cached: bool,
+        queue_families: I,
+    ) -> Result<Arc<CpuAccessibleImage>, ImageCreationError>
+    where
+        M: Into<MipmapsCount>,
+        I: IntoIterator<Item = QueueFamily<'a>>,
+    {
+        let aspects = format.aspects();
+
+        if !aspects.color || !format.planes().is_empty() || format.block_size().is_none() {
+            return Err(ImageCreationError::FormatNotSupported);
+        }
+
+        let flags = ImageCreateFlags::none();
+        let mipmaps = check_image_support(
+            &device,
+            dimensions,
+            format,
+            usage,
+            flags,
+            ImageTiling::Linear,
+            mipmaps.into(),
+            SampleCount::Sample1,
+        )?;
+
+        let queue_families = queue_families
+            .into_iter()
+            .map(|f| f.id())
+            .collect::<SmallVec<[u32; 4]>>();
+
+        let (image, mem_reqs) = unsafe {
+            let sharing = if queue_families.len() >= 2 {
+                Sharing::Concurrent(queue_families.iter().cloned())
+            } else {
+                Sharing::Exclusive
+            };
+
+            UnsafeImage::new(
+                device.clone(),
+                usage,
+                format,
+                flags,
+                dimensions,
+                SampleCount::Sample1,
+                mipmaps,
+                sharing,
+                true,
+                true,
+            )?
+        };
+
+        let memory = MemoryPool::alloc_from_requirements(
+            &Device::standard_pool(&device),
+            &mem_reqs,
+            AllocLayout::Linear,
+            MappingRequirement::Map,
+            DedicatedAlloc::Image(&image),
+            |m| {
+                if m.is_host_cached() {
+                    if host_cached {
+                        AllocFromRequirementsFilter::Preferred
+                    } else {
+                        AllocFromRequirementsFilter::Allowed
+                    }
+                } else {
+                    if host_cached {
+                        AllocFromRequirementsFilter::Allowed
+                    } else {
+                        AllocFromRequirementsFilter::Preferred
+                    }
+                }
+            },
+        )?;
+        debug_assert!((memory.offset() % mem_reqs.alignment) == 0);
+        debug_assert!(memory.mapped_memory().is_some());
+        unsafe {
+            image.bind_memory(memory.memory(), memory.offset())?;
+        }
+
+        Ok(Arc::new(CpuAccessibleImage {
+            image,
+            memory,
+            access: RwLock::new(CurrentGpuAccess::NonExclusive {
+                num: AtomicUsize::new(0),
+            }),
+            queue_families,
+            initialized: AtomicBool::new(false),
+        }))
+    }
+}
+
+impl<A> CpuAccessibleImage<A> {
+    /// Returns the queue families this image can be used on.
+    // TODO: use a custom iterator
+    #[inline]
+    pub fn queue_families(&self) -> Vec<QueueFamily> {
+        self.queue_families
+            .iter()
+            .map(|&num| {
+                self.image
+                    .device()
+                    .physical_device()
+                    .queue_family_by_id(num)
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    /// Returns the layout in memory of a mipmap level of the image. The layout of the array layer
+    /// `n` of the level starts `n * array_pitch` bytes after the one of the first array layer.
+    #[inline]
+    pub fn linear_layout(&self, mip_level: u32) -> LinearLayout {
+        unsafe { self.image.color_linear_layout(mip_level) }
+    }
+
+    // Returns the range of the memory and the shape of the rows of a subresource.
+    fn subresource(&self, mip_level: u32, array_layer: u32) -> (DeviceSize, RowsShape) {
+        assert!(array_layer < self.image.dimensions().array_layers());
+
+        let layout = self.linear_layout(mip_level);
+        let format = self.image.format();
+        let [block_width, block_height] = format.block_dimensions();
+        let [width, height, depth] = self
+            .image
+            .dimensions()
+            .mipmap_dimensions(mip_level)
+            .unwrap()
+            .width_height_depth();
+
+        let row_size =
+            ((width + block_width - 1) / block_width) as DeviceSize * format.block_size().unwrap();
+        let num_rows = (height + block_height - 1) / block_height;
+
+        // The pitches are undefined when there is only one row or one depth slice, in which
+        // case they are replaced with the size of the subresource.
+        let row_pitch = if num_rows > 1 {
+            layout.row_pitch
+        } else {
+            layout.size
+        };
+        let depth_pitch = if depth > 1 {
+            layout.depth_pitch
+        } else {
+            layout.size
+        };
+
+        let offset = layout.offset + array_layer as DeviceSize * layout.array_pitch;
+        let shape = RowsShape {
+            size: layout.size as usize,
+            row_size: row_size as usize,
+            num_rows: num_rows as usize,
+            row_pitch: row_pitch as usize,
+            depth: depth as usize,
+            depth_pitch: depth_pitch as usize,
+        };
+
+        (offset, shape)
+    }
+}
+
+impl<A> CpuAccessibleImage<A>
+where
+    A: MemoryPoolAlloc,
+{
+    /// Locks the image in order to read the content of one of its subresources from the CPU.
+    ///
+    /// The lock covers the whole image, not only the subresource that is read: while it is held,
+    /// no subresource of the image can be written, whether by `write()` or by the GPU.
+    ///
+    /// If the image is currently used in exclusive mode by the GPU, this function will return
+    /// an error. Similarly if you called `write()` on the image and haven't dropped the lock,
+    /// this function will return an error as well.
+    ///
+    /// After this function successfully locks the image, any attempt to submit a command buffer
+    /// that uses it in exclusive mode will fail. You can still submit this image for non-exclusive
+    /// accesses (ie. reads).
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `mip_level` or `array_layer` is out of range.
+    #[inline]
+    pub fn read(&self, mip_level: u32, array_layer: u32) -> Result<ReadLock, ReadLockError> {
+        let (offset, shape) = self.subresource(mip_level, array_layer);
+
+        let lock = match self.access.try_read() {
+            Some(l) => l,
+            None => return Err(ReadLockError::CpuWriteLocked),
+        };
+
+        if let CurrentGpuAccess::Exclusive { .. } = *lock {
+            return Err(ReadLockError::GpuWriteLocked);
+        }
+
+        let offset = self.memory.offset() + offset;
+        let range = offset..offset + shape.size as DeviceSize;
+
+        Ok(ReadLock {
+            inner: unsafe { self.memory.mapped_memory().unwrap().read_write(range) },
+            lock,
+            shape,
+        })
+    }
+
+    /// Locks the image in order to write the content of one of its subresources from the CPU.
+    ///
+    /// The lock covers the whole image, not only the subresource that is written: while it is
+    /// held, no subresource of the image can be read or written, whether by the CPU or by the GPU.
+    ///
+    /// If the image is currently in use by the GPU, this function will return an error. Similarly
+    /// if you called `read()` on the image and haven't dropped the lock, this function will
+    /// return an error as well.
+    ///
+    /// After this function successfully locks the image, any attempt to submit a command buffer
+    /// that uses it and any attempt to call `read()` will return an error.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `mip_level` or `array_layer` is out of range.
+    #[inline]
+    pub fn write(&self, mip_level: u32, array_layer: u32) -> Result<WriteLock, WriteLockError> {
+        let (offset, shape) = self.subresource(mip_level, array_layer);
+
+        let lock = match self.access.try_write() {
+            Some(l) => l,
+            None => return Err(WriteLockError::CpuLocked),
+        };
+
+        match *lock {
+            CurrentGpuAccess::NonExclusive { ref num } if num.load(Ordering::SeqCst) == 0 => (),
+            _ => return Err(WriteLockError::GpuLocked),
+        }
+
+        let offset = self.memory.offset() + offset;
+        let range = offset..offset + shape.size as DeviceSize;
+
+        Ok(WriteLock {
+            inner: unsafe { self.memory.mapped_memory().unwrap().read_write(range) },
+            lock,
+            shape,
+        })
+    }
+}
+
+unsafe impl<A> ImageAccess for CpuAccessibleImage<A> {
+    #[inline]
+    fn inner(&self) -> ImageInner {
+        ImageInner {
+            image: &self.image,
+            first_layer: 0,
+            num_layers: self.image.dimensions().array_layers() as usize,
+            first_mipmap_level: 0,
+            num_mipmap_levels: self.image.mipmap_levels() as usize,
+        }
+    }
+
+    #[inline]
+    fn initial_layout_requirement(&self) -> ImageLayout {
+        ImageLayout::General
+    }
+
+    #[inline]
+    fn final_layout_requirement(&self) -> ImageLayout {
+        ImageLayout::General
+    }
+
+    #[inline]
+    fn descriptor_layouts(&self) -> Option<ImageDescriptorLayouts> {
+        Some(ImageDescriptorLayouts {
+            storage_image: ImageLayout::General,
+            combined_image_sampler: ImageLayout::General,
+            sampled_image: ImageLayout::General,
+            input_attachment: ImageLayout::General,
+        })
+    }
+
+    #[inline]
+    fn conflict_key(&self) -> u64 {
+        self.image.key()
+    }
+
+    #[inline]
+    fn try_gpu_lock(
+        &self,
+        exclusive_access: bool,
+        _: bool,
+        expected_layout: ImageLayout,
+    ) -> Result<(), AccessError> {
+        if expected_layout != ImageLayout::General && expected_layout != ImageLayout::Undefined {
+            return Err(AccessError::UnexpectedImageLayout {
+                requested: expected_layout,
+                allowed: ImageLayout::General,
+            });
+        }
+
+        if exclusive_access {
+            let mut lock = match self.access.try_write() {
+                Some(lock) => lock,
+                None => return Err(AccessError::AlreadyInUse),
+            };
+
+            match *lock {
+                CurrentGpuAccess::NonExclusive { ref num } if num.load(Ordering::SeqCst) == 0 => (),
+                _ => return Err(AccessError::AlreadyInUse),
+            };
+
+            *lock = CurrentGpuAccess::Exclusive { num: 1 };
+            Ok(())
+        } else {
+            let lock = match self.access.try_read() {
+                Some(lock) => lock,
+                None => return Err(AccessError::AlreadyInUse),
+            };
+
+            match *lock {
+                CurrentGpuAccess::Exclusive { .. } => return Err(AccessError::AlreadyInUse),
+                CurrentGpuAccess::NonExclusive { ref num } => num.fetch_add(1, Ordering::SeqCst),
+            };
+
+            Ok(())
+        }
+    }
+
+    #[inline]
+    unsafe fn increase_gpu_lock(&self) {
+        {
+            let read_lock = self.access.read();
+            if let CurrentGpuAccess::NonExclusive { ref num } = *read_lock {
+                let prev = num.fetch_add(1, Ordering::SeqCst);
+                debug_assert!(prev >= 1);
+                return;
+            }
+        }
+
+        {
+            let mut write_lock = self.access.write();
+            if let CurrentGpuAccess::Exclusive { ref mut num } = *write_lock {
+                *num += 1;
+            } else {
+                unreachable!()
+            }
+        }
+    }
+
+    #[inline]
+    unsafe fn unlock(&self, new_layout: Option<ImageLayout>) {
+        if let Some(new_layout) = new_layout {
+            debug_assert_eq!(new_layout, ImageLayout::General);
+            self.initialized.store(true, Ordering::SeqCst);
+        }
+
+        {
+            let read_lock = self.access.read();
+            if let CurrentGpuAccess::NonExclusive { ref num } = *read_lock {
+                let prev = num.fetch_sub(1, Ordering::SeqCst);
+                debug_assert!(prev >= 1);
+                return;
+            }
+        }
+
+        {
+            let mut write_lock = self.access.write();
+            if let CurrentGpuAccess::Exclusive { ref mut num } = *write_lock {
+                if *num != 1 {
+                    *num -= 1;
+                    return;
+                }
+            } else {
+                // Can happen if we lock in exclusive mode N times, and unlock N+1 times with the
+                // last two unlocks happen simultaneously.
+                panic!()
+            }
+
+            *write_lock = CurrentGpuAccess::NonExclusive {
+                num: AtomicUsize::new(0),
+            };
+        }
+    }
+
+    #[inline]
+    unsafe fn layout_initialized(&self) {
+        self.initialized.store(true, Ordering::SeqCst);
+    }
+
+    #[inline]
+    fn is_layout_initialized(&self) -> bool {
+        self.initialized.load(Ordering::SeqCst)
+    }
+
+    #[inline]
+    fn current_miplevels_access(&self) -> std::ops::Range<u32> {
+        0..self.mipmap_levels()
+    }
+
+    #[inline]
+    fn current_layer_levels_access(&self) -> std::ops::Range<u32> {
+        0..self.dimensions().array_layers()
+    }
+}
+
+unsafe impl<A> ImageClearValue<ClearValue> for CpuAccessibleImage<A> {
+    #[inline]
+    fn decode(&self, value: ClearValue) -> Option<ClearValue> {
+        Some(self.format().decode_clear_value(value))
+    }
+}
+
+unsafe impl<P, A> ImageContent<P> for CpuAccessibleImage<A> {
+    #[inline]
+    fn matches_format(&self) -> bool {
+        true // FIXME:
+    }
+}
+
+impl<A> PartialEq for CpuAccessibleImage<A> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        ImageAccess::inner(self) == ImageAccess::inner(other)
+    }
+}
+
+impl<A> Eq for CpuAccessibleImage<A> {}
+
+impl<A> Hash for CpuAccessibleImage<A> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        ImageAccess::inner(self).hash(state);
+    }
+}
+
+// How the rows of texels, or of blocks for compressed formats, of a subresource are laid out in
+// its memory.
+#[derive(Debug, Copy, Clone)]
+struct RowsShape {
+    // Size of the memory of the subresource.
+    size: usize,
+    // Number of bytes of texel data in a row.
+    row_size: usize,
+    // Number of rows in a depth slice.
+    num_rows: usize,
+    // Number of bytes between the starts of two rows.
+    row_pitch: usize,
+    // Number of depth slices.
+    depth: usize,
+    // Number of bytes between the starts of two depth slices.
+    depth_pitch: usize,
+}
+
+/// Object that can be used to read the content of a subresource of a `CpuAccessibleImage`.
+///
+/// Dereferences to the memory of the subresource, including the padding between rows. Use
+/// `rows()` to access the texels without the padding.
+///
+/// Note that this object holds a rwlock read guard on the image. If another thread tries to access
+/// this image's content or tries to submit a GPU command that uses this image, it will fail.
+pub struct ReadLock<'a> {
+    inner: MemCpuAccess<'a, [u8]>,
+    lock: RwLockReadGuard<'a, CurrentGpuAccess>,
+    shape: RowsShape,
+}
+
+impl<'a> ReadLock<'a> {
+    /// Returns the number of bytes between the starts of two rows of the subresource.
+    #[inline]
+    pub fn row_pitch(&self) -> usize {
+        self.shape.row_pitch
+    }
+
+    /// Returns an iterator over the rows of the subresource, from top to bottom and then from
+    /// front to back for 3D images. Each row contains the texels, or the blocks for compressed
+    /// formats, of the row without any padding.
+    #[inline]
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        let shape = self.shape;
+        let data: &[u8] = &self.inner;
+
+        (0..shape.depth).flat_map(move |z| {
+            (0..shape.num_rows).map(move |y| {
+                let start = z * shape.depth_pitch + y * shape.row_pitch;
+                &data[start..start + shape.row_size]
+            })
+        })
+    }
+}
+
+impl<'a> Deref for ReadLock<'a> {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.inner.deref()
+    }
+}
+
+/// Error when attempting to CPU-read an image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReadLockError {
+    /// The image is already locked for write mode by the CPU.
+    CpuWriteLocked,
+    /// The image is already locked for write mode by the GPU.
+    GpuWriteLocked,
+}
+
+impl error::Error for ReadLockError {}
+
+impl fmt::Display for ReadLockError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(
+            fmt,
+            "{}",
+            match *self {
+                ReadLockError::CpuWriteLocked => {
+                    "the image is already locked for write mode by the CPU"
+                }
+                ReadLockError::GpuWriteLocked => {
+                    "the image is already locked for write mode by the GPU"
+                }
+            }
+        )
+    }
+}
+
+/// Object that can be used to read or write the content of a subresource of a
+/// `CpuAccessibleImage`.
+///
+/// Dereferences to the memory of the subresource, including the padding between rows. Use
+/// `rows()` or `rows_mut()` to access the texels without the padding.
+///
+/// Note that this object holds a rwlock write guard on the image. If another thread tries to
+/// access this image's content or tries to submit a GPU command that uses this image, it will
+/// fail.
+pub struct WriteLock<'a> {
+    inner: MemCpuAccess<'a, [u8]>,
+    lock: RwLockWriteGuard<'a, CurrentGpuAccess>,
+    shape: RowsShape,
+}
+
+impl<'a> WriteLock<'a> {
+    /// Returns the number of bytes between the starts of two rows of the subresource.
+    #[inline]
+    pub fn row_pitch(&self) -> usize {
+        self.shape.row_pitch
+    }
+
+    /// Returns an iterator over the rows of the subresource, from top to bottom and then from
+    /// front to back for 3D images. Each row contains the texels, or the blocks for compressed
+    /// formats, of the row without any padding.
+    #[inline]
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        let shape = self.shape;
+        let data: &[u8] = &self.inner;
+
+        (0..shape.depth).flat_map(move |z| {
+            (0..shape.num_rows).map(move |y| {
+                let start = z * shape.depth_pitch + y * shape.row_pitch;
+                &data[start..start + shape.row_size]
+            })
+        })
+    }
+
+    /// Same as `rows()`, but the rows can be modified.
+    #[inline]
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [u8]> + '_ {
+        let shape = self.shape;
+        let data: &mut [u8] = &mut self.inner;
+
+        data.chunks_mut(shape.depth_pitch)
+            .take(shape.depth)
+            .flat_map(move |slice| {
+                slice
+                    .chunks_mut(shape.row_pitch)
+                    .take(shape.num_rows)
+                    .map(move |row| &mut row[..shape.row_size])
+            })
+    }
+}
+
+impl<'a> Deref for WriteLock<'a> {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.inner.deref()
+    }
+}
+
+impl<'a> DerefMut for WriteLock<'a> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.inner.deref_mut()
+    }
+}
+
+/// Error when attempting to CPU-write an image.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WriteLockError {
+    /// The image is already locked by the CPU.
+    CpuLocked,
+    /// The image is already locked by the GPU.
+    GpuLocked,
+}
+
+impl error::Error for WriteLockError {}
+
+impl fmt::Display for WriteLockError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(
+            fmt,
+            "{}",
+            match *self {
+                WriteLockError::CpuLocked => "the image is already locked by the CPU",
+                WriteLockError::GpuLocked => "the image is already locked by the GPU",
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CpuAccessibleImage;
+    use super::WriteLockError;
+    use crate::format::Format;
+    use crate::image::ImageCreationError;
+    use crate::image::ImageDimensions;
+    use crate::image::ImageUsage;
+    use crate::image::MipmapsCount;
+
+    #[test]
+    fn write_then_read_rows() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let image = match CpuAccessibleImage::new(
+            device,
+            ImageDimensions::Dim2d {
+                width: 13,
+                height: 7,
+                array_layers: 1,
+            },
+            Format::R8G8B8A8_UNORM,
+            MipmapsCount::One,
+            ImageUsage {
+                transfer_source: true,
+                ..ImageUsage::none()
+            },
+            false,
+            Some(queue.family()),
+        ) {
+            Ok(image) => image,
+            Err(ImageCreationError::FormatNotSupported)
+            | Err(ImageCreationError::UnsupportedUsage) => return,
+            Err(err) => panic!("{:?}", err),
+        };
+
+        {
+            let mut lock = image.write(0, 0).unwrap();
+            assert!(lock.row_pitch() >= 13 * 4);
+            assert_eq!(lock.rows_mut().count(), 7);
+
+            for (y, row) in lock.rows_mut().enumerate() {
+                assert_eq!(row.len(), 13 * 4);
+                for texel in row.iter_mut() {
+                    *texel = y as u8;
+                }
+            }
+
+            assert_eq!(image.write(0, 0).err(), Some(WriteLockError::CpuLocked));
+        }
+
+        let lock = image.read(0, 0).unwrap();
+        for (y, row) in lock.rows().enumerate() {
+            assert!(row.iter().all(|&texel| texel == y as u8));
+        }
+    }
+
+    #[test]
+    fn block_compressed_rows() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let image = match CpuAccessibleImage::new(
+            device,
+            ImageDimensions::Dim2d {
+                width: 10,
+                height: 6,
+                array_layers: 1,
+            },
+            Format::BC1_RGB_UNORM_BLOCK,
+            MipmapsCount::One,
+            ImageUsage {
+                transfer_source: true,
+                ..ImageUsage::none()
+            },
+            false,
+            Some(queue.family()),
+        ) {
+            Ok(image) => image,
+            Err(ImageCreationError::FormatNotSupported)
+            | Err(ImageCreationError::UnsupportedUsage) => return,
+            Err(err) => panic!("{:?}", err),
+        };
+
+        // 3 blocks of 8 bytes per row, and 2 rows of blocks.
+        let lock = image.read(0, 0).unwrap();
+        assert_eq!(lock.rows().count(), 2);
+        assert!(lock.rows().all(|row| row.len() == 3 * 8));
+    }
+}
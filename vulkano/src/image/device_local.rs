@@ -0,0 +1,814 @@
+// Copyright (c) 2021 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Image in device memory whose every creation parameter can be chosen.
+//!
+//! Unlike the other image types of this module, which each make some choices on behalf of the
+//! user, a `DeviceLocalImage` is configured through an `ImageBuilder` that covers all the
+//! parameters of an image: dimensions and array layers, mipmaps, samples, usage, creation flags,
+//! tiling, sharing between queue families and the list of formats that views of the image can
+//! have. The parameters are checked against what the physical device supports for this
+//! combination before the image is created.
+
+use crate::device::physical::QueueFamily;
+use crate::device::Device;
+use crate::format::ClearValue;
+use crate::format::Format;
+use crate::image::sys::ImageCreationError;
+use crate::image::sys::UnsafeImage;
+use crate::image::traits::ImageAccess;
+use crate::image::traits::ImageClearValue;
+use crate::image::traits::ImageContent;
+use crate::image::Extent;
+use crate::image::ImageCreateFlags;
+use crate::image::ImageDescriptorLayouts;
+use crate::image::ImageDimensions;
+use crate::image::ImageInner;
+use crate::image::ImageLayout;
+use crate::image::ImageTiling;
+use crate::image::ImageType;
+use crate::image::ImageUsage;
+use crate::image::MipmapsCount;
+use crate::image::SampleCount;
+use crate::memory::pool::AllocFromRequirementsFilter;
+use crate::memory::pool::AllocLayout;
+use crate::memory::pool::MappingRequirement;
+use crate::memory::pool::MemoryPool;
+use crate::memory::pool::MemoryPoolAlloc;
+use crate::memory::pool::PotentialDedicatedAllocation;
+use crate::memory::pool::StdMemoryPoolAlloc;
+use crate::memory::DedicatedAlloc;
+use crate::sync::AccessError;
+use crate::sync::Sharing;
+use parking_lot::RwLock;
+use smallvec::SmallVec;
+use std::cmp;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+/// General-purpose image in device memory, created with an `ImageBuilder`.
+///
+/// The image is kept in a single layout, chosen when building it, between the commands that use
+/// it. By default this is `General`, which allows the image to be used for any purpose.
+#[derive(Debug)]
+pub struct DeviceLocalImage<A = PotentialDedicatedAllocation<StdMemoryPoolAlloc>> {
+    // Inner implementation.
+    image: UnsafeImage,
+
+    // Memory used to back the image.
+    memory: A,
+
+    // Layout that the image has between the commands that use it.
+    layout: ImageLayout,
+
+    // Queue families allowed to access this image.
+    queue_families: SmallVec<[u32; 4]>,
+
+    // Formats that image views of this image can have, if they were specified.
+    view_formats: SmallVec<[Format; 4]>,
+
+    // Whether the image has been transitioned out of the `Undefined` layout.
+    initialized: AtomicBool,
+
+    // Access pattern of the image.
+    // Every time the user tries to submit this image for the GPU, this `RwLock` is briefly locked
+    // and modified.
+    access: RwLock<CurrentGpuAccess>,
+}
+
+#[derive(Debug)]
+enum CurrentGpuAccess {
+    NonExclusive {
+        // Number of non-exclusive GPU accesses. Can be 0.
+        num: AtomicUsize,
+    },
+    Exclusive {
+        // Number of exclusive locks. Cannot be 0. If 0 is reached, we must jump to `NonExclusive`.
+        num: usize,
+    },
+}
+
+impl DeviceLocalImage {
+    /// Creates a new image with the given dimensions, format and usage, and default values for
+    /// all the other parameters. Equivalent to
+    /// `DeviceLocalImage::start(device, dimensions, format, usage).build()`.
+    #[inline]
+    pub fn new(
+        device: Arc<Device>,
+        dimensions: ImageDimensions,
+        format: Format,
+        usage: ImageUsage,
+    ) -> Result<Arc<DeviceLocalImage>, ImageCreationError> {
+        DeviceLocalImage::start(device, dimensions, format, usage).build()
+    }
+
+    /// Begins building a `DeviceLocalImage`.
+    ///
+    /// The number of array layers is part of `dimensions`.
+    #[inline]
+    pub fn start(
+        device: Arc<Device>,
+        dimensions: ImageDimensions,
+        format: Format,
+        usage: ImageUsage,
+    ) -> ImageBuilder {
+        ImageBuilder {
+            device,
+            dimensions,
+            format,
+            usage,
+
+            flags: ImageCreateFlags::none(),
+            layout: ImageLayout::General,
+            mipmaps: MipmapsCount::One,
+            queue_families: SmallVec::new(),
+            samples: SampleCount::Sample1,
+            tiling: ImageTiling::Optimal,
+            view_formats: SmallVec::new(),
+        }
+    }
+}
+
+impl<A> DeviceLocalImage<A> {
+    /// Returns the dimensions of the image.
+    #[inline]
+    pub fn dimensions(&self) -> ImageDimensions {
+        self.image.dimensions()
+    }
+
+    /// Returns the flags the image was created with.
+    #[inline]
+    pub fn flags(&self) -> ImageCreateFlags {
+        self.image.flags()
+    }
+
+    /// Returns the usage the image was created with.
+    #[inline]
+    pub fn usage(&self) -> ImageUsage {
+        self.image.usage()
+    }
+
+    /// Returns the layout that the image has between the commands that use it.
+    #[inline]
+    pub fn layout(&self) -> ImageLayout {
+        self.layout
+    }
+
+    /// Returns the formats that image views of this image can have, or an empty list if they
+    /// weren't specified when building the image.
+    #[inline]
+    pub fn view_formats(&self) -> &[Format] {
+        &self.view_formats
+    }
+
+    /// Returns the list of queue families allowed to access this image, or an empty list if the
+    /// image is exclusive to a single queue family.
+    #[inline]
+    pub fn queue_families(&self) -> Vec<QueueFamily> {
+        self.queue_families
+            .iter()
+            .map(|&num| {
+                self.image
+                    .device()
+                    .physical_device()
+                    .queue_family_by_id(num)
+                    .unwrap()
+            })
+            .collect()
+    }
+}
+
+/// Builder for a `DeviceLocalImage`. Obtained with `DeviceLocalImage::start`.
+#[derive(Debug)]
+pub struct ImageBuilder {
+    device: Arc<Device>,
+    dimensions: ImageDimensions,
+    format: Format,
+    usage: ImageUsage,
+
+    flags: ImageCreateFlags,
+    layout: ImageLayout,
+    mipmaps: MipmapsCount,
+    queue_families: SmallVec<[u32; 4]>,
+    samples: SampleCount,
+    tiling: ImageTiling,
+    view_formats: SmallVec<[Format; 4]>,
+}
+
+impl ImageBuilder {
+    /// Sets the creation flags of the image.
+    ///
+    /// By default, no flag is set. The sparse flags are not supported.
+    #[inline]
+    pub fn with_flags(mut self, flags: ImageCreateFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Sets the layout that the image has between the commands that use it.
+    ///
+    /// By default, this is `General`. The layout must be allowed by the usage of the image.
+    #[inline]
+    pub fn with_layout(mut self, layout: ImageLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Sets the number of mipmap levels of the image.
+    ///
+    /// By default, the image has only one mipmap level.
+    #[inline]
+    pub fn with_mipmaps<M>(mut self, mipmaps: M) -> Self
+    where
+        M: Into<MipmapsCount>,
+    {
+        self.mipmaps = mipmaps.into();
+        self
+    }
+
+    /// Sets the queue families that can access the image.
+    ///
+    /// By default, or if fewer than two queue families are given, the image is exclusive to a
+    /// single queue family. Otherwise it can be accessed concurrently by all of them.
+    #[inline]
+    pub fn with_queue_families<'a, I>(mut self, queue_families: I) -> Self
+    where
+        I: IntoIterator<Item = QueueFamily<'a>>,
+    {
+        self.queue_families = queue_families.into_iter().map(|f| f.id()).collect();
+        self
+    }
+
+    /// Sets the number of samples per texel of the image.
+    ///
+    /// By default, this is `Sample1`.
+    #[inline]
+    pub fn with_samples(mut self, samples: SampleCount) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// Sets the tiling of the image.
+    ///
+    /// By default, this is `Optimal`.
+    #[inline]
+    pub fn with_tiling(mut self, tiling: ImageTiling) -> Self {
+        self.tiling = tiling;
+        self
+    }
+
+    /// Sets the list of formats that image views of this image can have.
+    ///
+    /// By default, the list is empty and isn't passed to Vulkan. A non-empty list requires
+    /// Vulkan 1.2 or the `khr_image_format_list` extension. Unless the `mutable_format` flag is
+    /// set, the list can only contain the format of the image.
+    #[inline]
+    pub fn with_view_formats<I>(mut self, view_formats: I) -> Self
+    where
+        I: IntoIterator<Item = Format>,
+    {
+        self.view_formats = view_formats.into_iter().collect();
+        self
+    }
+
+    /// Builds the `DeviceLocalImage`.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if the layout is `Undefined` or `Preinitialized`.
+    ///
+    pub fn build(self) -> Result<Arc<DeviceLocalImage>, ImageCreationError> {
+        assert!(!matches!(
+            self.layout,
+            ImageLayout::Undefined | ImageLayout::Preinitialized
+        ));
+
+        let mipmaps = check_image_support(
+            &self.device,
+            self.dimensions,
+            self.format,
+            self.usage,
+            self.flags,
+            self.tiling,
+            self.mipmaps,
+            self.samples,
+        )?;
+
+        let (image, mem_reqs) = unsafe {
+            let sharing = if self.queue_families.len() >= 2 {
+                Sharing::Concurrent(self.queue_families.iter().cloned())
+            } else {
+                Sharing::Exclusive
+            };
+
+            UnsafeImage::with_view_formats(
+                self.device.clone(),
+                self.usage,
+                self.format,
+                self.flags,
+                self.dimensions,
+                self.samples,
+                mipmaps,
+                sharing,
+                self.tiling == ImageTiling::Linear,
+                false,
+                &self.view_formats,
+            )?
+        };
+
+        let memory = MemoryPool::alloc_from_requirements(
+            &Device::standard_pool(&self.device),
+            &mem_reqs,
+            match self.tiling {
+                ImageTiling::Optimal => AllocLayout::Optimal,
+                ImageTiling::Linear => AllocLayout::Linear,
+            },
+            MappingRequirement::DoNotMap,
+            DedicatedAlloc::Image(&image),
+            |t| {
+                if t.is_device_local() {
+                    AllocFromRequirementsFilter::Preferred
+                } else {
+                    AllocFromRequirementsFilter::Allowed
+                }
+            },
+        )?;
+        debug_assert!((memory.offset() % mem_reqs.alignment) == 0);
+        unsafe {
+            image.bind_memory(memory.memory(), memory.offset())?;
+        }
+
+        Ok(Arc::new(DeviceLocalImage {
+            image,
+            memory,
+            layout: self.layout,
+            queue_families: self.queue_families,
+            view_formats: self.view_formats,
+            initialized: AtomicBool::new(false),
+            access: RwLock::new(CurrentGpuAccess::NonExclusive {
+                num: AtomicUsize::new(0),
+            }),
+        }))
+    }
+}
+
+unsafe impl<A> ImageAccess for DeviceLocalImage<A> {
+    #[inline]
+    fn inner(&self) -> ImageInner {
+        ImageInner {
+            image: &self.image,
+            first_layer: 0,
+            num_layers: self.image.dimensions().array_layers() as usize,
+            first_mipmap_level: 0,
+            num_mipmap_levels: self.image.mipmap_levels() as usize,
+        }
+    }
+
+    #[inline]
+    fn initial_layout_requirement(&self) -> ImageLayout {
+        self.layout
+    }
+
+    #[inline]
+    fn final_layout_requirement(&self) -> ImageLayout {
+        self.layout
+    }
+
+    #[inline]
+    fn descriptor_layouts(&self) -> Option<ImageDescriptorLayouts> {
+        if self.layout == ImageLayout::General {
+            Some(ImageDescriptorLayouts {
+                storage_image: ImageLayout::General,
+                combined_image_sampler: ImageLayout::General,
+                sampled_image: ImageLayout::General,
+                input_attachment: ImageLayout::General,
+            })
+        } else {
+            Some(ImageDescriptorLayouts {
+                storage_image: ImageLayout::General,
+                combined_image_sampler: ImageLayout::ShaderReadOnlyOptimal,
+                sampled_image: ImageLayout::ShaderReadOnlyOptimal,
+                input_attachment: ImageLayout::ShaderReadOnlyOptimal,
+            })
+        }
+    }
+
+    #[inline]
+    fn conflict_key(&self) -> u64 {
+        self.image.key()
+    }
+
+    #[inline]
+    fn try_gpu_lock(
+        &self,
+        exclusive_access: bool,
+        uninitialized_safe: bool,
+        expected_layout: ImageLayout,
+    ) -> Result<(), AccessError> {
+        if expected_layout != self.layout && expected_layout != ImageLayout::Undefined {
+            if self.initialized.load(Ordering::SeqCst) {
+                return Err(AccessError::UnexpectedImageLayout {
+                    requested: expected_layout,
+                    allowed: self.layout,
+                });
+            } else {
+                return Err(AccessError::UnexpectedImageLayout {
+                    requested: expected_layout,
+                    allowed: ImageLayout::Undefined,
+                });
+            }
+        }
+
+        if !uninitialized_safe && expected_layout != ImageLayout::Undefined {
+            if !self.initialized.load(Ordering::SeqCst) {
+                return Err(AccessError::ImageNotInitialized {
+                    requested: expected_layout,
+                });
+            }
+        }
+
+        if exclusive_access {
+            let mut lock = match self.access.try_write() {
+                Some(lock) => lock,
+                None => return Err(AccessError::AlreadyInUse),
+            };
+
+            match *lock {
+                CurrentGpuAccess::NonExclusive { ref num } if num.load(Ordering::SeqCst) == 0 => (),
+                _ => return Err(AccessError::AlreadyInUse),
+            };
+
+            *lock = CurrentGpuAccess::Exclusive { num: 1 };
+            Ok(())
+        } else {
+            let lock = match self.access.try_read() {
+                Some(lock) => lock,
+                None => return Err(AccessError::AlreadyInUse),
+            };
+
+            match *lock {
+                CurrentGpuAccess::Exclusive { .. } => return Err(AccessError::AlreadyInUse),
+                CurrentGpuAccess::NonExclusive { ref num } => num.fetch_add(1, Ordering::SeqCst),
+            };
+
+            Ok(())
+        }
+    }
+
+    #[inline]
+    unsafe fn increase_gpu_lock(&self) {
+        {
+            let read_lock = self.access.read();
+            if let CurrentGpuAccess::NonExclusive { ref num } = *read_lock {
+                let prev = num.fetch_add(1, Ordering::SeqCst);
+                debug_assert!(prev >= 1);
+                return;
+            }
+        }
+
+        {
+            let mut write_lock = self.access.write();
+            if let CurrentGpuAccess::Exclusive { ref mut num } = *write_lock {
+                *num += 1;
+            } else {
+                unreachable!()
+            }
+        }
+    }
+
+    #[inline]
+    unsafe fn unlock(&self, new_layout: Option<ImageLayout>) {
+        if let Some(new_layout) = new_layout {
+            debug_assert_eq!(new_layout, self.layout);
+            self.initialized.store(true, Ordering::SeqCst);
+        }
+
+        {
+            let read_lock = self.access.read();
+            if let CurrentGpuAccess::NonExclusive { ref num } = *read_lock {
+                let prev = num.fetch_sub(1, Ordering::SeqCst);
+                debug_assert!(prev >= 1);
+                return;
+            }
+        }
+
+        {
+            let mut write_lock = self.access.write();
+            if let CurrentGpuAccess::Exclusive { ref mut num } = *write_lock {
+                if *num != 1 {
+                    *num -= 1;
+                    return;
+                }
+            } else {
+                // Can happen if we lock in exclusive mode N times, and unlock N+1 times with the
+                // last two unlocks happen simultaneously.
+                panic!()
+            }
+
+            *write_lock = CurrentGpuAccess::NonExclusive {
+                num: AtomicUsize::new(0),
+            };
+        }
+    }
+
+    #[inline]
+    unsafe fn layout_initialized(&self) {
+        self.initialized.store(true, Ordering::SeqCst);
+    }
+
+    #[inline]
+    fn is_layout_initialized(&self) -> bool {
+        self.initialized.load(Ordering::SeqCst)
+    }
+
+    #[inline]
+    fn current_miplevels_access(&self) -> std::ops::Range<u32> {
+        0..self.mipmap_levels()
+    }
+
+    #[inline]
+    fn current_layer_levels_access(&self) -> std::ops::Range<u32> {
+        0..self.dimensions().array_layers()
+    }
+}
+
+unsafe impl<A> ImageClearValue<ClearValue> for DeviceLocalImage<A> {
+    #[inline]
+    fn decode(&self, value: ClearValue) -> Option<ClearValue> {
+        Some(self.format().decode_clear_value(value))
+    }
+}
+
+unsafe impl<P, A> ImageContent<P> for DeviceLocalImage<A> {
+    #[inline]
+    fn matches_format(&self) -> bool {
+        true // FIXME:
+    }
+}
+
+impl<A> PartialEq for DeviceLocalImage<A> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        ImageAccess::inner(self) == ImageAccess::inner(other)
+    }
+}
+
+impl<A> Eq for DeviceLocalImage<A> {}
+
+impl<A> Hash for DeviceLocalImage<A> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        ImageAccess::inner(self).hash(state);
+    }
+}
+
+// Checks the parameters of an image against what the physical device supports for this
+// combination of format, type, tiling, usage and flags. Returns the number of mipmap levels that
+// `mipmaps` stands for.
+pub(super) fn check_image_support(
+    device: &Device,
+    dimensions: ImageDimensions,
+    format: Format,
+    usage: ImageUsage,
+    flags: ImageCreateFlags,
+    tiling: ImageTiling,
+    mipmaps: MipmapsCount,
+    samples: SampleCount,
+) -> Result<u32, ImageCreationError> {
+    let ty = match dimensions {
+        ImageDimensions::Dim1d { .. } => ImageType::Dim1d,
+        ImageDimensions::Dim2d { .. } => ImageType::Dim2d,
+        ImageDimensions::Dim3d { .. } => ImageType::Dim3d,
+    };
+
+    let properties = device
+        .image_format_properties(format, ty, tiling, usage, flags)
+        .map_err(|_| ImageCreationError::FormatNotSupported)?;
+
+    let max_extent = match properties.max_extent {
+        Extent::E3D(extent) => extent,
+        _ => unreachable!(),
+    };
+    let max_mipmaps = match properties.max_mip_levels {
+        MipmapsCount::Specific(num) => num,
+        _ => unreachable!(),
+    };
+
+    if dimensions.width() > max_extent[0]
+        || dimensions.height() > max_extent[1]
+        || dimensions.depth() > max_extent[2]
+        || dimensions.array_layers() > properties.max_array_layers
+    {
+        return Err(ImageCreationError::UnsupportedDimensions { dimensions });
+    }
+
+    let mipmaps = match mipmaps {
+        MipmapsCount::Specific(num) => num,
+        MipmapsCount::Log2 => dimensions.max_mipmaps(),
+        MipmapsCount::One => 1,
+    };
+    let valid_mipmaps = 1..cmp::min(dimensions.max_mipmaps(), max_mipmaps) + 1;
+
+    if !valid_mipmaps.contains(&mipmaps) {
+        return Err(ImageCreationError::InvalidMipmapsCount {
+            obtained: mipmaps,
+            valid_range: valid_mipmaps,
+        });
+    }
+
+    if (ash::vk::SampleCountFlags::from(samples)
+        & ash::vk::SampleCountFlags::from(properties.sample_counts))
+    .is_empty()
+    {
+        return Err(ImageCreationError::UnsupportedSamplesCount { obtained: samples });
+    }
+
+    Ok(mipmaps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DeviceLocalImage;
+    use crate::format::Format;
+    use crate::image::view::ImageView;
+    use crate::image::view::ImageViewType;
+    use crate::image::ImageAccess;
+    use crate::image::ImageCreateFlags;
+    use crate::image::ImageCreationError;
+    use crate::image::ImageDimensions;
+    use crate::image::ImageLayout;
+    use crate::image::ImageUsage;
+    use crate::image::MipmapsCount;
+
+    #[test]
+    fn create() {
+        let (device, queue) = gfx_dev_and_queue!();
+        let image = DeviceLocalImage::start(
+            device,
+            ImageDimensions::Dim2d {
+                width: 32,
+                height: 32,
+                array_layers: 1,
+            },
+            Format::R8G8B8A8_UNORM,
+            ImageUsage {
+                sampled: true,
+                transfer_destination: true,
+                ..ImageUsage::none()
+            },
+        )
+        .with_mipmaps(MipmapsCount::Log2)
+        .with_queue_families(Some(queue.family()))
+        .build()
+        .unwrap();
+
+        assert_eq!(image.inner().num_mipmap_levels, 6);
+    }
+
+    #[test]
+    fn cube_compatible() {
+        let (device, _) = gfx_dev_and_queue!();
+        let image = DeviceLocalImage::start(
+            device,
+            ImageDimensions::Dim2d {
+                width: 32,
+                height: 32,
+                array_layers: 6,
+            },
+            Format::R8G8B8A8_UNORM,
+            ImageUsage {
+                sampled: true,
+                ..ImageUsage::none()
+            },
+        )
+        .with_flags(ImageCreateFlags {
+            cube_compatible: true,
+            ..ImageCreateFlags::none()
+        })
+        .build()
+        .unwrap();
+
+        ImageView::start(image)
+            .with_type(ImageViewType::Cube)
+            .build()
+            .unwrap();
+    }
+
+    #[test]
+    fn too_many_mipmaps() {
+        let (device, _) = gfx_dev_and_queue!();
+        let res = DeviceLocalImage::start(
+            device,
+            ImageDimensions::Dim2d {
+                width: 32,
+                height: 32,
+                array_layers: 1,
+            },
+            Format::R8G8B8A8_UNORM,
+            ImageUsage {
+                sampled: true,
+                ..ImageUsage::none()
+            },
+        )
+        .with_mipmaps(7)
+        .build();
+
+        match res {
+            Err(ImageCreationError::InvalidMipmapsCount {
+                obtained: 7,
+                valid_range,
+            }) => assert_eq!(valid_range, 1..7),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn view_formats_need_mutable_format() {
+        let (device, _) = gfx_dev_and_queue!();
+        let res = DeviceLocalImage::start(
+            device,
+            ImageDimensions::Dim2d {
+                width: 32,
+                height: 32,
+                array_layers: 1,
+            },
+            Format::R8G8B8A8_UNORM,
+            ImageUsage {
+                sampled: true,
+                ..ImageUsage::none()
+            },
+        )
+        .with_view_formats(vec![Format::R8G8B8A8_UNORM, Format::R8G8B8A8_SRGB])
+        .build();
+
+        match res {
+            Err(ImageCreationError::IncompatibleViewFormat { .. }) => (),
+            // The device doesn't support lists of view formats at all.
+            Err(ImageCreationError::ImageFormatListExtensionNotEnabled) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn gpu_lock_shared_and_exclusive() {
+        let (device, _) = gfx_dev_and_queue!();
+        let image = DeviceLocalImage::new(
+            device,
+            ImageDimensions::Dim2d {
+                width: 32,
+                height: 32,
+                array_layers: 1,
+            },
+            Format::R8G8B8A8_UNORM,
+            ImageUsage {
+                sampled: true,
+                ..ImageUsage::none()
+            },
+        )
+        .unwrap();
+
+        // Several shared accesses can coexist, but exclude exclusive ones.
+        image
+            .try_gpu_lock(false, true, ImageLayout::Undefined)
+            .unwrap();
+        image
+            .try_gpu_lock(false, true, ImageLayout::Undefined)
+            .unwrap();
+        assert!(image
+            .try_gpu_lock(true, true, ImageLayout::Undefined)
+            .is_err());
+
+        unsafe {
+            image.unlock(None);
+            image.unlock(None);
+        }
+
+        // An exclusive access excludes every other access.
+        image
+            .try_gpu_lock(true, true, ImageLayout::Undefined)
+            .unwrap();
+        assert!(image
+            .try_gpu_lock(false, true, ImageLayout::Undefined)
+            .is_err());
+        assert!(image
+            .try_gpu_lock(true, true, ImageLayout::Undefined)
+            .is_err());
+
+        unsafe {
+            image.unlock(None);
+        }
+        image
+            .try_gpu_lock(false, true, ImageLayout::Undefined)
+            .unwrap();
+    }
+}
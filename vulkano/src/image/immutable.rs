@@ -12,6 +12,7 @@ use crate::buffer::BufferUsage;
 use crate::buffer::CpuAccessibleBuffer;
 use crate::buffer::TypedBufferAccess;
 use crate::command_buffer::AutoCommandBufferBuilder;
+use crate::command_buffer::BufferImageCopy;
 use crate::command_buffer::CommandBufferExecFuture;
 use crate::command_buffer::CommandBufferUsage;
 use crate::command_buffer::PrimaryAutoCommandBuffer;
@@ -23,8 +24,13 @@ use crate::format::Format;
 use crate::format::Pixel;
 use crate::image::sys::ImageCreationError;
 use crate::image::sys::UnsafeImage;
+use crate::image::texture::TextureData;
 use crate::image::traits::ImageAccess;
 use crate::image::traits::ImageContent;
+use crate::image::view::ImageView;
+use crate::image::view::ImageViewCreationError;
+use crate::image::view::ImageViewType;
+use crate::image::ImageAspect;
 use crate::image::ImageCreateFlags;
 use crate::image::ImageDescriptorLayouts;
 use crate::image::ImageDimensions;
@@ -45,9 +51,11 @@ use crate::sampler::Filter;
 use crate::sync::AccessError;
 use crate::sync::NowFuture;
 use crate::sync::Sharing;
+use crate::DeviceSize;
 use smallvec::SmallVec;
 use std::hash::Hash;
 use std::hash::Hasher;
+use std::mem;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
@@ -168,7 +176,7 @@ fn generate_mipmaps<L, Img>(
             [xd as i32, yd as i32, dd as i32], //destination_bottom_right
             0,                                 //destination_base_array_layer
             level,                             //destination_mip_level
-            1,                                 //layer_count
+            dimensions.array_layers(),         //layer_count
             Filter::Linear,                    //filter
         )
         .expect("failed to blit a mip map to image!");
@@ -426,6 +434,326 @@ impl ImmutableImage {
 
         Ok((image, future))
     }
+
+    /// Construct an ImmutableImage containing a texture loaded from a KTX2 or DDS file.
+    ///
+    /// Every mip level, array layer and cube face of the texture is uploaded, so that this also
+    /// works for block-compressed formats, whose mip levels can't be generated with blits. Returns
+    /// `FormatNotSupported` if the device can't sample images of the format of the texture.
+    pub fn from_texture(
+        texture: &TextureData,
+        queue: Arc<Queue>,
+    ) -> Result<
+        (
+            Arc<Self>,
+            CommandBufferExecFuture<NowFuture, PrimaryAutoCommandBuffer>,
+        ),
+        ImageCreationError,
+    > {
+        let device = queue.device().clone();
+        let format = texture.format();
+
+        if !format
+            .properties(device.physical_device())
+            .optimal_tiling_features
+            .sampled_image
+        {
+            return Err(ImageCreationError::FormatNotSupported);
+        }
+
+        let usage = ImageUsage {
+            transfer_destination: true,
+            sampled: true,
+            ..ImageUsage::none()
+        };
+        let flags = ImageCreateFlags {
+            cube_compatible: texture.is_cube(),
+            ..ImageCreateFlags::none()
+        };
+        let layout = ImageLayout::ShaderReadOnlyOptimal;
+        let dimensions = texture.dimensions();
+        let array_layers = dimensions.array_layers();
+
+        let (image, initializer) = ImmutableImage::uninitialized(
+            device.clone(),
+            dimensions,
+            format,
+            MipmapsCount::Specific(texture.mip_levels()),
+            usage,
+            flags,
+            layout,
+            device.active_queue_families(),
+        )?;
+        let initializer = Arc::new(initializer);
+
+        let source = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::transfer_source(),
+            false,
+            texture.data().iter().copied(),
+        )?;
+
+        let mut cbb = AutoCommandBufferBuilder::primary(
+            device,
+            queue.family(),
+            CommandBufferUsage::MultipleSubmit,
+        )?;
+
+        for level in 0..texture.mip_levels() {
+            let range = texture.level_range(level);
+            let level_source = source
+                .clone()
+                .into_buffer_slice()
+                .slice(range.start as DeviceSize..range.end as DeviceSize)
+                .unwrap();
+            let init = SubImage::new(initializer.clone(), level, 1, 0, array_layers, layout);
+
+            cbb.copy_buffer_to_image_dimensions(
+                level_source,
+                init,
+                [0, 0, 0],
+                dimensions
+                    .mipmap_dimensions(level)
+                    .unwrap()
+                    .width_height_depth(),
+                0,
+                array_layers,
+                level,
+            )
+            .unwrap();
+        }
+
+        let cb = cbb.build().unwrap();
+
+        let future = match cb.execute(queue) {
+            Ok(f) => f,
+            Err(e) => unreachable!("{:?}", e),
+        };
+
+        image.initialized.store(true, Ordering::Relaxed);
+
+        Ok((image, future))
+    }
+
+    /// Construct a cube-compatible ImmutableImage from its six faces, and return a cube view of
+    /// it that is ready to be sampled.
+    ///
+    /// The faces are given in the order +X, -X, +Y, -Y, +Z, -Z, and each of them contains the
+    /// first mipmap level of a `size` by `size` square. The other mipmap levels, if any, are
+    /// generated with blits.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if there aren't exactly six faces.
+    /// - Panics if the data of a face doesn't have the size of a face in `format`.
+    pub fn cubemap_from_faces<Px, F, I>(
+        faces: F,
+        size: u32,
+        mipmaps: MipmapsCount,
+        format: Format,
+        queue: Arc<Queue>,
+    ) -> Result<
+        (
+            Arc<ImageView<Arc<Self>>>,
+            CommandBufferExecFuture<NowFuture, PrimaryAutoCommandBuffer>,
+        ),
+        ImageCreationError,
+    >
+    where
+        Px: Pixel + Send + Sync + Clone + 'static,
+        F: IntoIterator<Item = I>,
+        I: IntoIterator<Item = Px>,
+    {
+        let dimensions = ImageDimensions::Dim2d {
+            width: size,
+            height: size,
+            array_layers: 6,
+        };
+        let flags = ImageCreateFlags {
+            cube_compatible: true,
+            ..ImageCreateFlags::none()
+        };
+
+        ImmutableImage::from_layers(
+            faces,
+            dimensions,
+            mipmaps,
+            format,
+            flags,
+            ImageViewType::Cube,
+            queue,
+        )
+    }
+
+    /// Construct an array ImmutableImage whose layers each come from a separate source, and return
+    /// an array view of it that is ready to be sampled.
+    ///
+    /// `dimensions` must be one-dimensional or two-dimensional, and each layer contains the first
+    /// mipmap level of one of its array layers. The other mipmap levels, if any, are generated
+    /// with blits.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if `dimensions` is three-dimensional.
+    /// - Panics if the number of layers isn't the number of array layers of `dimensions`.
+    /// - Panics if the data of a layer doesn't have the size of a layer in `format`.
+    pub fn array_from_layers<Px, L, I>(
+        layers: L,
+        dimensions: ImageDimensions,
+        mipmaps: MipmapsCount,
+        format: Format,
+        queue: Arc<Queue>,
+    ) -> Result<
+        (
+            Arc<ImageView<Arc<Self>>>,
+            CommandBufferExecFuture<NowFuture, PrimaryAutoCommandBuffer>,
+        ),
+        ImageCreationError,
+    >
+    where
+        Px: Pixel + Send + Sync + Clone + 'static,
+        L: IntoIterator<Item = I>,
+        I: IntoIterator<Item = Px>,
+    {
+        let view_type = match dimensions {
+            ImageDimensions::Dim1d { .. } => ImageViewType::Dim1dArray,
+            ImageDimensions::Dim2d { .. } => ImageViewType::Dim2dArray,
+            ImageDimensions::Dim3d { .. } => panic!("3D images can't have array layers"),
+        };
+
+        ImmutableImage::from_layers(
+            layers,
+            dimensions,
+            mipmaps,
+            format,
+            ImageCreateFlags::none(),
+            view_type,
+            queue,
+        )
+    }
+
+    // Uploads each layer to its own array layer of a new image, with one region per layer.
+    fn from_layers<Px, L, I>(
+        layers: L,
+        dimensions: ImageDimensions,
+        mipmaps: MipmapsCount,
+        format: Format,
+        flags: ImageCreateFlags,
+        view_type: ImageViewType,
+        queue: Arc<Queue>,
+    ) -> Result<
+        (
+            Arc<ImageView<Arc<Self>>>,
+            CommandBufferExecFuture<NowFuture, PrimaryAutoCommandBuffer>,
+        ),
+        ImageCreationError,
+    >
+    where
+        Px: Pixel + Send + Sync + Clone + 'static,
+        L: IntoIterator<Item = I>,
+        I: IntoIterator<Item = Px>,
+    {
+        let device = queue.device().clone();
+        let extent = dimensions.width_height_depth();
+        let array_layers = dimensions.array_layers();
+        let layer_size = format
+            .required_buffer_size(extent, 0, 0)
+            .ok_or(ImageCreationError::FormatNotSupported)?;
+        let pixel_size = mem::size_of::<Px>() as DeviceSize;
+
+        let mut data = Vec::new();
+        let mut num_layers = 0;
+
+        for layer in layers {
+            let start = data.len();
+            data.extend(layer);
+            assert_eq!(
+                (data.len() - start) as DeviceSize * pixel_size,
+                layer_size,
+                "layer {} doesn't have the size of a layer of the image",
+                num_layers,
+            );
+            num_layers += 1;
+        }
+
+        assert_eq!(
+            num_layers, array_layers,
+            "the number of layers must be the number of array layers of the image"
+        );
+
+        let need_to_generate_mipmaps = has_mipmaps(mipmaps);
+        let usage = ImageUsage {
+            transfer_destination: true,
+            transfer_source: need_to_generate_mipmaps,
+            sampled: true,
+            ..ImageUsage::none()
+        };
+        let layout = ImageLayout::ShaderReadOnlyOptimal;
+
+        let (image, initializer) = ImmutableImage::uninitialized(
+            device.clone(),
+            dimensions,
+            format,
+            mipmaps,
+            usage,
+            flags,
+            layout,
+            device.active_queue_families(),
+        )?;
+
+        let source = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::transfer_source(),
+            false,
+            data.into_iter(),
+        )?;
+
+        let init = SubImage::new(Arc::new(initializer), 0, 1, 0, array_layers, layout);
+        let regions = (0..array_layers).map(|layer| BufferImageCopy {
+            buffer_offset: layer as DeviceSize * layer_size,
+            buffer_row_length: 0,
+            buffer_image_height: 0,
+            image_aspect: ImageAspect::Color,
+            image_mip_level: 0,
+            image_base_array_layer: layer,
+            image_layer_count: 1,
+            image_offset: [0, 0, 0],
+            image_extent: extent,
+        });
+
+        let mut cbb = AutoCommandBufferBuilder::primary(
+            device,
+            queue.family(),
+            CommandBufferUsage::MultipleSubmit,
+        )?;
+        cbb.copy_buffer_to_image_regions(source, init, regions)
+            .unwrap();
+
+        if need_to_generate_mipmaps {
+            generate_mipmaps(&mut cbb, image.clone(), dimensions, layout);
+        }
+
+        let cb = cbb.build().unwrap();
+
+        let future = match cb.execute(queue) {
+            Ok(f) => f,
+            Err(e) => unreachable!("{:?}", e),
+        };
+
+        image.initialized.store(true, Ordering::Relaxed);
+
+        // The image was created with the flags and usage that the view needs, so only running out
+        // of memory can make this fail.
+        let view = ImageView::start(image)
+            .with_type(view_type)
+            .build()
+            .map_err(|err| match err {
+                ImageViewCreationError::AllocError(err) => ImageCreationError::AllocError(err),
+                err => unreachable!("{:?}", err),
+            })?;
+
+        Ok((view, future))
+    }
 }
 
 impl<A> ImmutableImage<A> {
@@ -608,6 +936,22 @@ impl<A> Hash for ImmutableImage<A> {
     }
 }
 
+impl<A> ImmutableImageInitialization<A> {
+    /// Returns a second initialization access to the same image, for the command buffer that
+    /// acquires the ownership of the image from the queue family that performs the upload.
+    ///
+    /// The returned access can't be locked on its own, and must be used by a command buffer that
+    /// is submitted after the one that uses `self`, through the future of that command buffer.
+    pub(crate) fn ownership_acquire(&self) -> ImmutableImageInitialization<A> {
+        ImmutableImageInitialization {
+            image: self.image.clone(),
+            used: AtomicBool::new(true),
+            mip_levels_access: self.mip_levels_access.clone(),
+            layer_levels_access: self.layer_levels_access.clone(),
+        }
+    }
+}
+
 unsafe impl<A> ImageAccess for ImmutableImageInitialization<A> {
     #[inline]
     fn inner(&self) -> ImageInner {
@@ -701,3 +1045,133 @@ impl<A> Hash for ImmutableImageInitialization<A> {
         ImageAccess::inner(self).hash(state);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::buffer::BufferUsage;
+    use crate::buffer::CpuAccessibleBuffer;
+    use crate::command_buffer::AutoCommandBufferBuilder;
+    use crate::command_buffer::CommandBufferUsage;
+    use crate::format::Format;
+    use crate::image::ImageDimensions;
+    use crate::image::ImmutableImage;
+    use crate::image::MipmapsCount;
+    use crate::sync::GpuFuture;
+    use std::iter;
+
+    // Color of every texel of the first mipmap level of `layer`.
+    fn layer_color(layer: u32) -> [u8; 4] {
+        [layer as u8 * 40, 255 - layer as u8 * 40, 0, 255]
+    }
+
+    #[test]
+    fn cubemap_from_faces_mipmaps() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let faces = (0..6).map(|face| iter::repeat(layer_color(face)).take(16));
+        let (view, future) = ImmutableImage::cubemap_from_faces(
+            faces,
+            4,
+            MipmapsCount::Log2,
+            Format::R8G8B8A8_UNORM,
+            queue.clone(),
+        )
+        .unwrap();
+
+        // The last mipmap level of each face is generated from the first level of the same face.
+        let destination = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::all(),
+            false,
+            iter::repeat([0u8; 4]).take(6),
+        )
+        .unwrap();
+        let mut cbb = AutoCommandBufferBuilder::primary(
+            device,
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        cbb.copy_image_to_buffer_dimensions(
+            view.image().clone(),
+            destination.clone(),
+            [0, 0, 0],
+            [1, 1, 1],
+            0,
+            6,
+            2,
+        )
+        .unwrap();
+        future
+            .then_execute(queue, cbb.build().unwrap())
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        let texels = destination.read().unwrap();
+        for face in 0..6 {
+            assert_eq!(texels[face as usize], layer_color(face));
+        }
+    }
+
+    #[test]
+    fn array_from_layers_mipmaps() {
+        let (device, queue) = gfx_dev_and_queue!();
+
+        let dimensions = ImageDimensions::Dim2d {
+            width: 8,
+            height: 8,
+            array_layers: 3,
+        };
+        let layers = (0..3).map(|layer| iter::repeat(layer_color(layer)).take(64));
+        let (view, future) = ImmutableImage::array_from_layers(
+            layers,
+            dimensions,
+            MipmapsCount::Log2,
+            Format::R8G8B8A8_UNORM,
+            queue.clone(),
+        )
+        .unwrap();
+        assert_eq!(view.image().mipmap_levels(), 4);
+
+        // The last mipmap level of each layer is generated from the first level of the same
+        // layer.
+        let destination = CpuAccessibleBuffer::from_iter(
+            device.clone(),
+            BufferUsage::all(),
+            false,
+            iter::repeat([0u8; 4]).take(3),
+        )
+        .unwrap();
+        let mut cbb = AutoCommandBufferBuilder::primary(
+            device,
+            queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        cbb.copy_image_to_buffer_dimensions(
+            view.image().clone(),
+            destination.clone(),
+            [0, 0, 0],
+            [1, 1, 1],
+            0,
+            3,
+            3,
+        )
+        .unwrap();
+        future
+            .then_execute(queue, cbb.build().unwrap())
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        let texels = destination.read().unwrap();
+        for layer in 0..3 {
+            assert_eq!(texels[layer as usize], layer_color(layer));
+        }
+    }
+}
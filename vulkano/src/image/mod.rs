@@ -40,6 +40,10 @@
 //! - An `AttachmentImage` can be used when you want to draw to an image.
 //! - An `ImmutableImage` stores data which never need be changed after the initial upload,
 //!   like a texture.
+//! - A `DeviceLocalImage` is created with an `ImageBuilder`, which lets you choose every
+//!   parameter of the image, including its creation flags and the formats of its views.
+//! - A `CpuAccessibleImage` uses linear tiling and host-visible memory, so that its content can
+//!   be read and written directly by the CPU.
 //!
 //! # Low-level information
 //!
@@ -49,12 +53,17 @@
 pub use self::aspect::ImageAspect;
 pub use self::aspect::ImageAspects;
 pub use self::attachment::AttachmentImage;
+pub use self::cpu_access::CpuAccessibleImage;
+pub use self::device_local::DeviceLocalImage;
+pub use self::device_local::ImageBuilder;
 pub use self::immutable::ImmutableImage;
 pub use self::layout::ImageDescriptorLayouts;
 pub use self::layout::ImageLayout;
 pub use self::storage::StorageImage;
 pub use self::swapchain::SwapchainImage;
 pub use self::sys::ImageCreationError;
+pub use self::texture::TextureData;
+pub use self::texture::TextureError;
 pub use self::traits::ImageAccess;
 pub use self::traits::ImageInner;
 pub use self::usage::ImageUsage;
@@ -64,11 +73,14 @@ use std::convert::TryFrom;
 
 mod aspect;
 pub mod attachment; // TODO: make private
+pub mod cpu_access;
+mod device_local;
 pub mod immutable; // TODO: make private
 mod layout;
 mod storage;
 pub mod swapchain; // TODO: make private
 pub mod sys;
+mod texture;
 pub mod traits;
 mod usage;
 pub mod view;
@@ -119,6 +119,59 @@ impl UnsafeImage {
             sharing,
             linear_tiling,
             preinitialized_layout,
+            &[],
+        )
+    }
+
+    /// Same as `new`, but also specifies the list of formats that image views of this image can
+    /// have, through `VK_KHR_image_format_list`.
+    ///
+    /// If `view_formats` is empty, this is the same as `new`. Otherwise the device must support
+    /// Vulkan 1.2 or have the `khr_image_format_list` extension enabled, and every format in the
+    /// list must be compatible with `format`. Without the `mutable_format` flag, the list can
+    /// only contain `format` itself.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if one of the dimensions is 0.
+    /// - Panics if the number of mipmaps is 0.
+    /// - Panics if the number of samples is 0.
+    ///
+    #[inline]
+    pub unsafe fn with_view_formats<'a, Mi, I>(
+        device: Arc<Device>,
+        usage: ImageUsage,
+        format: Format,
+        flags: ImageCreateFlags,
+        dimensions: ImageDimensions,
+        num_samples: SampleCount,
+        mipmaps: Mi,
+        sharing: Sharing<I>,
+        linear_tiling: bool,
+        preinitialized_layout: bool,
+        view_formats: &[Format],
+    ) -> Result<(UnsafeImage, MemoryRequirements), ImageCreationError>
+    where
+        Mi: Into<MipmapsCount>,
+        I: Iterator<Item = u32>,
+    {
+        let sharing = match sharing {
+            Sharing::Exclusive => (ash::vk::SharingMode::EXCLUSIVE, SmallVec::<[u32; 8]>::new()),
+            Sharing::Concurrent(ids) => (ash::vk::SharingMode::CONCURRENT, ids.collect()),
+        };
+
+        UnsafeImage::new_impl(
+            device,
+            usage,
+            format,
+            flags,
+            dimensions,
+            num_samples,
+            mipmaps.into(),
+            sharing,
+            linear_tiling,
+            preinitialized_layout,
+            view_formats,
         )
     }
 
@@ -134,6 +187,7 @@ impl UnsafeImage {
         (sh_mode, sh_indices): (ash::vk::SharingMode, SmallVec<[u32; 8]>),
         linear_tiling: bool,
         preinitialized_layout: bool,
+        view_formats: &[Format],
     ) -> Result<(UnsafeImage, MemoryRequirements), ImageCreationError> {
         // TODO: doesn't check that the proper features are enabled
 
@@ -437,6 +491,27 @@ impl UnsafeImage {
             }
         }
 
+        // Checking the list of view formats.
+        if !view_formats.is_empty() {
+            if !(device.api_version() >= Version::V1_2
+                || device.enabled_extensions().khr_image_format_list)
+            {
+                return Err(ImageCreationError::ImageFormatListExtensionNotEnabled);
+            }
+
+            for &view_format in view_formats {
+                if !view_format_compatible(format, flags, view_format) {
+                    return Err(ImageCreationError::IncompatibleViewFormat {
+                        format: view_format,
+                    });
+                }
+            }
+
+            if !flags.mutable_format && view_formats.len() > 1 {
+                return Err(ImageCreationError::CreationFlagRequirementsNotMet);
+            }
+        }
+
         // Checking the dimensions against the limits.
         if array_layers > device.physical_device().properties().max_image_array_layers {
             let err = ImageCreationError::UnsupportedDimensions { dimensions };
@@ -497,7 +572,7 @@ impl UnsafeImage {
                 ty,
                 tiling,
                 usage_bits,
-                ash::vk::ImageCreateFlags::empty(), /* TODO */
+                flags.into(),
                 output.as_mut_ptr(),
             );
 
@@ -524,7 +599,23 @@ impl UnsafeImage {
 
         // Everything now ok. Creating the image.
         let image = {
+            let view_formats: SmallVec<[ash::vk::Format; 4]> =
+                view_formats.iter().map(|&f| f.into()).collect();
+            let format_list = if !view_formats.is_empty() {
+                Some(ash::vk::ImageFormatListCreateInfo {
+                    view_format_count: view_formats.len() as u32,
+                    p_view_formats: view_formats.as_ptr(),
+                    ..Default::default()
+                })
+            } else {
+                None
+            };
+
             let infos = ash::vk::ImageCreateInfo {
+                p_next: format_list
+                    .as_ref()
+                    .map(|l| l as *const _ as *const _)
+                    .unwrap_or(ptr::null()),
                 flags: flags.into(),
                 image_type: ty,
                 format: format.into(),
@@ -921,6 +1012,24 @@ impl Hash for UnsafeImage {
     }
 }
 
+// Returns whether an image view of an image with the given format and flags can have the format
+// `view_format`.
+fn view_format_compatible(format: Format, flags: ImageCreateFlags, view_format: Format) -> bool {
+    if view_format == format {
+        return true;
+    }
+
+    if !flags.mutable_format || !format.planes().is_empty() {
+        return false;
+    }
+
+    view_format.compatibility() == format.compatibility()
+        || (flags.block_texel_view_compatible
+            && format.compression().is_some()
+            && view_format.compression().is_none()
+            && view_format.size() == format.size())
+}
+
 /// Error that can happen when creating an instance.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ImageCreationError {
@@ -943,6 +1052,11 @@ pub enum ImageCreationError {
     UnsupportedUsage,
     /// The `shader_storage_image_multisample` feature must be enabled to create such an image.
     ShaderStorageImageMultisampleFeatureNotEnabled,
+    /// A list of view formats was provided, but the `khr_image_format_list` extension was not
+    /// enabled and the device doesn't support Vulkan 1.2.
+    ImageFormatListExtensionNotEnabled,
+    /// A format in the list of view formats is not compatible with the format of the image.
+    IncompatibleViewFormat { format: Format },
 }
 
 impl error::Error for ImageCreationError {
@@ -986,6 +1100,14 @@ impl fmt::Display for ImageCreationError {
                     "the `shader_storage_image_multisample` feature must be enabled to create such \
                  an image"
                 }
+                ImageCreationError::ImageFormatListExtensionNotEnabled => {
+                    "the `khr_image_format_list` extension must be enabled to provide a list of \
+                 view formats"
+                }
+                ImageCreationError::IncompatibleViewFormat { .. } => {
+                    "a format in the list of view formats is not compatible with the format of the \
+                 image"
+                }
             }
         )
     }
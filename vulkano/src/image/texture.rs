@@ -0,0 +1,778 @@
+// Copyright (c) 2021 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Loading of textures from KTX2 and DDS files.
+//!
+//! Texture files contain all the mip levels, array layers and cube faces of an image, usually in
+//! a block-compressed format whose mip levels can't be generated at runtime. A [`TextureData`]
+//! holds these contents in the order that they are copied to an image, and can be uploaded with
+//! [`ImmutableImage::from_texture`](crate::image::ImmutableImage::from_texture).
+
+use crate::format::Format;
+use crate::image::ImageDimensions;
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::ops::Range;
+
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+const DDS_MAGIC: [u8; 4] = *b"DDS ";
+
+/// The contents of a texture file, with all of its mip levels.
+///
+/// The data of each mip level contains all the array layers of the level one after the other,
+/// tightly packed, and starts at an offset that is a multiple of both the size of a texel block of
+/// the format and 4. The faces of a cubemap are array layers, in the order +X, -X, +Y, -Y, +Z, -Z.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextureData {
+    format: Format,
+    dimensions: ImageDimensions,
+    cube: bool,
+    levels: Vec<Range<usize>>,
+    data: Vec<u8>,
+}
+
+impl TextureData {
+    /// Loads a texture from the contents of a KTX2 or DDS file, depending on the identifier at the
+    /// start of `bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<TextureData, TextureError> {
+        if bytes.starts_with(&KTX2_IDENTIFIER) {
+            TextureData::from_ktx2(bytes)
+        } else if bytes.starts_with(&DDS_MAGIC) {
+            TextureData::from_dds(bytes)
+        } else {
+            Err(TextureError::UnknownContainer)
+        }
+    }
+
+    /// Loads a texture from the contents of a KTX2 file.
+    ///
+    /// The file must not use supercompression, and its `vkFormat` must be a format with a
+    /// well-defined texel block size. A file that asks for its mip levels to be generated has a
+    /// single mip level.
+    pub fn from_ktx2(bytes: &[u8]) -> Result<TextureData, TextureError> {
+        if !bytes.starts_with(&KTX2_IDENTIFIER) {
+            return Err(TextureError::UnknownContainer);
+        }
+
+        let vk_format = read_u32(bytes, 12)?;
+        let width = read_u32(bytes, 20)?;
+        let height = read_u32(bytes, 24)?;
+        let depth = read_u32(bytes, 28)?;
+        let layer_count = read_u32(bytes, 32)?;
+        let face_count = read_u32(bytes, 36)?;
+        let level_count = read_u32(bytes, 40)?;
+        let supercompression_scheme = read_u32(bytes, 44)?;
+
+        if supercompression_scheme != 0 {
+            return Err(TextureError::UnsupportedSupercompression {
+                scheme: supercompression_scheme,
+            });
+        }
+
+        let format = Format::try_from(ash::vk::Format::from_raw(vk_format as i32))
+            .map_err(|_| TextureError::UnsupportedFormat)?;
+        let cube = match face_count {
+            1 => false,
+            6 => true,
+            _ => return Err(TextureError::InvalidDimensions),
+        };
+        let array_layers = layer_count
+            .max(1)
+            .checked_mul(face_count)
+            .ok_or(TextureError::InvalidDimensions)?;
+        let dimensions = if depth != 0 {
+            // Arrays of 3D images don't exist in Vulkan.
+            if height == 0 || layer_count != 0 || cube {
+                return Err(TextureError::InvalidDimensions);
+            }
+
+            ImageDimensions::Dim3d {
+                width,
+                height,
+                depth,
+            }
+        } else if height != 0 {
+            ImageDimensions::Dim2d {
+                width,
+                height,
+                array_layers,
+            }
+        } else if !cube {
+            ImageDimensions::Dim1d {
+                width,
+                array_layers,
+            }
+        } else {
+            return Err(TextureError::InvalidDimensions);
+        };
+
+        let mut texture =
+            TextureData::new(format, dimensions, cube, level_count.max(1), bytes.len())?;
+
+        for level in 0..texture.mip_levels() {
+            let index = 80 + level as usize * 24;
+            let offset = read_u64(bytes, index)?;
+            let length = read_u64(bytes, index + 8)?;
+            let range = texture.levels[level as usize].clone();
+
+            if length != range.len() as u64 {
+                return Err(TextureError::InvalidHeader);
+            }
+
+            let source = usize::try_from(offset)
+                .ok()
+                .and_then(|offset| bytes.get(offset..offset.checked_add(range.len())?))
+                .ok_or(TextureError::Truncated)?;
+            texture.data[range].copy_from_slice(source);
+        }
+
+        Ok(texture)
+    }
+
+    /// Loads a texture from the contents of a DDS file.
+    ///
+    /// Files with a `DX10` header are supported for the DXGI formats that have an equivalent
+    /// Vulkan format with a well-defined texel block size. Older files are supported for the `DXT1`
+    /// to `DXT5`, `ATI1`, `ATI2`, `BC4U`, `BC4S`, `BC5U` and `BC5S` compressions and for 32-bit RGBA
+    /// and BGRA texels.
+    pub fn from_dds(bytes: &[u8]) -> Result<TextureData, TextureError> {
+        const DDSD_DEPTH: u32 = 0x800000;
+        const DDPF_ALPHAPIXELS: u32 = 0x1;
+        const DDPF_FOURCC: u32 = 0x4;
+        const DDPF_RGB: u32 = 0x40;
+        const DDSCAPS2_CUBEMAP: u32 = 0x200;
+        const DDSCAPS2_CUBEMAP_ALLFACES: u32 = 0xFC00;
+        const DDSCAPS2_VOLUME: u32 = 0x200000;
+        const D3D10_RESOURCE_DIMENSION_TEXTURE1D: u32 = 2;
+        const D3D10_RESOURCE_DIMENSION_TEXTURE2D: u32 = 3;
+        const D3D10_RESOURCE_DIMENSION_TEXTURE3D: u32 = 4;
+        const D3D10_RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+
+        if !bytes.starts_with(&DDS_MAGIC) {
+            return Err(TextureError::UnknownContainer);
+        }
+
+        if read_u32(bytes, 4)? != 124 || read_u32(bytes, 76)? != 32 {
+            return Err(TextureError::InvalidHeader);
+        }
+
+        let flags = read_u32(bytes, 8)?;
+        let height = read_u32(bytes, 12)?;
+        let width = read_u32(bytes, 16)?;
+        let depth = read_u32(bytes, 24)?;
+        let mip_levels = read_u32(bytes, 28)?.max(1);
+        let pixel_format_flags = read_u32(bytes, 80)?;
+        let four_cc = read_u32(bytes, 84)?;
+        let caps2 = read_u32(bytes, 112)?;
+
+        let (format, dimensions, cube, data_offset) =
+            if pixel_format_flags & DDPF_FOURCC != 0 && four_cc == u32::from_le_bytes(*b"DX10") {
+                let format = dxgi_format(read_u32(bytes, 128)?)?;
+                let resource_dimension = read_u32(bytes, 132)?;
+                let cube = read_u32(bytes, 136)? & D3D10_RESOURCE_MISC_TEXTURECUBE != 0;
+                let array_size = read_u32(bytes, 140)?.max(1);
+                let array_layers = if cube {
+                    array_size
+                        .checked_mul(6)
+                        .ok_or(TextureError::InvalidDimensions)?
+                } else {
+                    array_size
+                };
+
+                let dimensions = match resource_dimension {
+                    D3D10_RESOURCE_DIMENSION_TEXTURE1D if !cube => ImageDimensions::Dim1d {
+                        width,
+                        array_layers,
+                    },
+                    D3D10_RESOURCE_DIMENSION_TEXTURE2D => ImageDimensions::Dim2d {
+                        width,
+                        height,
+                        array_layers,
+                    },
+                    D3D10_RESOURCE_DIMENSION_TEXTURE3D if !cube && array_size == 1 => {
+                        ImageDimensions::Dim3d {
+                            width,
+                            height,
+                            depth,
+                        }
+                    }
+                    _ => return Err(TextureError::InvalidDimensions),
+                };
+
+                (format, dimensions, cube, 148)
+            } else {
+                let format = if pixel_format_flags & DDPF_FOURCC != 0 {
+                    match &four_cc.to_le_bytes() {
+                        b"DXT1" => Format::BC1_RGBA_UNORM_BLOCK,
+                        b"DXT2" | b"DXT3" => Format::BC2_UNORM_BLOCK,
+                        b"DXT4" | b"DXT5" => Format::BC3_UNORM_BLOCK,
+                        b"ATI1" | b"BC4U" => Format::BC4_UNORM_BLOCK,
+                        b"BC4S" => Format::BC4_SNORM_BLOCK,
+                        b"ATI2" | b"BC5U" => Format::BC5_UNORM_BLOCK,
+                        b"BC5S" => Format::BC5_SNORM_BLOCK,
+                        _ => return Err(TextureError::UnsupportedFormat),
+                    }
+                } else if pixel_format_flags & DDPF_RGB != 0 && read_u32(bytes, 88)? == 32 {
+                    let alpha = pixel_format_flags & DDPF_ALPHAPIXELS != 0;
+                    match (read_u32(bytes, 92)?, read_u32(bytes, 100)?) {
+                        (0x000000FF, 0x00FF0000) if alpha => Format::R8G8B8A8_UNORM,
+                        (0x00FF0000, 0x000000FF) if alpha => Format::B8G8R8A8_UNORM,
+                        _ => return Err(TextureError::UnsupportedFormat),
+                    }
+                } else {
+                    return Err(TextureError::UnsupportedFormat);
+                };
+
+                let cube = caps2 & DDSCAPS2_CUBEMAP != 0;
+                if cube && caps2 & DDSCAPS2_CUBEMAP_ALLFACES != DDSCAPS2_CUBEMAP_ALLFACES {
+                    return Err(TextureError::InvalidDimensions);
+                }
+
+                let dimensions = if flags & DDSD_DEPTH != 0 && caps2 & DDSCAPS2_VOLUME != 0 {
+                    ImageDimensions::Dim3d {
+                        width,
+                        height,
+                        depth,
+                    }
+                } else {
+                    ImageDimensions::Dim2d {
+                        width,
+                        height,
+                        array_layers: if cube { 6 } else { 1 },
+                    }
+                };
+
+                (format, dimensions, cube, 128)
+            };
+
+        if let ImageDimensions::Dim3d { .. } = dimensions {
+            if cube {
+                return Err(TextureError::InvalidDimensions);
+            }
+        }
+
+        let mut texture = TextureData::new(
+            format,
+            dimensions,
+            cube,
+            mip_levels,
+            bytes.len().saturating_sub(data_offset),
+        )?;
+
+        // DDS files store all the mip levels of an array layer before the next layer.
+        let array_layers = dimensions.array_layers() as usize;
+        let mut offset = data_offset;
+
+        for layer in 0..array_layers {
+            for range in &texture.levels {
+                let layer_size = range.len() / array_layers;
+                let destination = range.start + layer * layer_size;
+                let source = bytes
+                    .get(offset..offset + layer_size)
+                    .ok_or(TextureError::Truncated)?;
+                texture.data[destination..destination + layer_size].copy_from_slice(source);
+                offset += layer_size;
+            }
+        }
+
+        Ok(texture)
+    }
+
+    // Computes the layout of the mip levels and allocates zeroed data for them.
+    //
+    // The dimensions come from the header of the file, so nothing is allocated if the mip levels
+    // don't fit in the `payload_len` bytes of the file that follow the header.
+    fn new(
+        format: Format,
+        dimensions: ImageDimensions,
+        cube: bool,
+        mip_levels: u32,
+        payload_len: usize,
+    ) -> Result<TextureData, TextureError> {
+        let block_size = match format.size() {
+            Some(block_size) if format.planes().is_empty() => block_size as usize,
+            _ => return Err(TextureError::UnsupportedFormat),
+        };
+
+        let [width, height, depth] = dimensions.width_height_depth();
+        if width == 0 || height == 0 || depth == 0 || dimensions.array_layers() == 0 {
+            return Err(TextureError::InvalidDimensions);
+        }
+        if cube && (width != height || dimensions.array_layers() % 6 != 0) {
+            return Err(TextureError::InvalidDimensions);
+        }
+        if mip_levels > dimensions.max_mipmaps() {
+            return Err(TextureError::InvalidDimensions);
+        }
+
+        let alignment = lcm(block_size, 4);
+        let [block_width, block_height] = format.block_dimensions();
+        let mut levels = Vec::with_capacity(mip_levels as usize);
+        let mut end = 0;
+        let mut total_size: usize = 0;
+
+        for level in 0..mip_levels {
+            let level_dimensions = dimensions.mipmap_dimensions(level).unwrap();
+            let [width, height, depth] = level_dimensions.width_height_depth();
+            let size = [
+                ((width - 1) / block_width + 1) as usize,
+                ((height - 1) / block_height + 1) as usize,
+                depth as usize,
+                level_dimensions.array_layers() as usize,
+            ]
+            .iter()
+            .try_fold(block_size, |size, &count| size.checked_mul(count))
+            .ok_or(TextureError::InvalidDimensions)?;
+
+            // The levels are tightly packed or padded in the file, so their total size can't be
+            // larger than the payload.
+            total_size = total_size
+                .checked_add(size)
+                .filter(|&total_size| total_size <= payload_len)
+                .ok_or(TextureError::Truncated)?;
+
+            let start = (end + alignment - 1) / alignment * alignment;
+            end = start + size;
+            levels.push(start..end);
+        }
+
+        Ok(TextureData {
+            format,
+            dimensions,
+            cube,
+            levels,
+            data: vec![0; end],
+        })
+    }
+
+    /// Returns the format of the texture.
+    #[inline]
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Returns the dimensions of the first mip level of the texture. The faces of a cubemap are
+    /// counted as array layers.
+    #[inline]
+    pub fn dimensions(&self) -> ImageDimensions {
+        self.dimensions
+    }
+
+    /// Returns the number of mip levels of the texture.
+    #[inline]
+    pub fn mip_levels(&self) -> u32 {
+        self.levels.len() as u32
+    }
+
+    /// Returns whether the texture is a cubemap or an array of cubemaps.
+    #[inline]
+    pub fn is_cube(&self) -> bool {
+        self.cube
+    }
+
+    /// Returns the range of `data()` that contains the mip level `level`.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `level` is not less than `mip_levels()`.
+    ///
+    #[inline]
+    pub fn level_range(&self, level: u32) -> Range<usize> {
+        self.levels[level as usize].clone()
+    }
+
+    /// Returns the data of the mip level `level`, for all the array layers.
+    ///
+    /// # Panic
+    ///
+    /// - Panics if `level` is not less than `mip_levels()`.
+    ///
+    #[inline]
+    pub fn level(&self, level: u32) -> &[u8] {
+        &self.data[self.level_range(level)]
+    }
+
+    /// Returns the data of all the mip levels.
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+// Converts a `DXGI_FORMAT` to the equivalent format.
+fn dxgi_format(dxgi_format: u32) -> Result<Format, TextureError> {
+    Ok(match dxgi_format {
+        2 => Format::R32G32B32A32_SFLOAT,
+        3 => Format::R32G32B32A32_UINT,
+        4 => Format::R32G32B32A32_SINT,
+        6 => Format::R32G32B32_SFLOAT,
+        10 => Format::R16G16B16A16_SFLOAT,
+        11 => Format::R16G16B16A16_UNORM,
+        12 => Format::R16G16B16A16_UINT,
+        13 => Format::R16G16B16A16_SNORM,
+        14 => Format::R16G16B16A16_SINT,
+        16 => Format::R32G32_SFLOAT,
+        24 => Format::A2B10G10R10_UNORM_PACK32,
+        26 => Format::B10G11R11_UFLOAT_PACK32,
+        28 => Format::R8G8B8A8_UNORM,
+        29 => Format::R8G8B8A8_SRGB,
+        30 => Format::R8G8B8A8_UINT,
+        31 => Format::R8G8B8A8_SNORM,
+        32 => Format::R8G8B8A8_SINT,
+        34 => Format::R16G16_SFLOAT,
+        35 => Format::R16G16_UNORM,
+        41 => Format::R32_SFLOAT,
+        49 => Format::R8G8_UNORM,
+        54 => Format::R16_SFLOAT,
+        56 => Format::R16_UNORM,
+        61 => Format::R8_UNORM,
+        67 => Format::E5B9G9R9_UFLOAT_PACK32,
+        71 => Format::BC1_RGBA_UNORM_BLOCK,
+        72 => Format::BC1_RGBA_SRGB_BLOCK,
+        74 => Format::BC2_UNORM_BLOCK,
+        75 => Format::BC2_SRGB_BLOCK,
+        77 => Format::BC3_UNORM_BLOCK,
+        78 => Format::BC3_SRGB_BLOCK,
+        80 => Format::BC4_UNORM_BLOCK,
+        81 => Format::BC4_SNORM_BLOCK,
+        83 => Format::BC5_UNORM_BLOCK,
+        84 => Format::BC5_SNORM_BLOCK,
+        87 => Format::B8G8R8A8_UNORM,
+        91 => Format::B8G8R8A8_SRGB,
+        95 => Format::BC6H_UFLOAT_BLOCK,
+        96 => Format::BC6H_SFLOAT_BLOCK,
+        98 => Format::BC7_UNORM_BLOCK,
+        99 => Format::BC7_SRGB_BLOCK,
+        _ => return Err(TextureError::UnsupportedFormat),
+    })
+}
+
+#[inline]
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, TextureError> {
+    match bytes.get(offset..offset + 4) {
+        Some(b) => Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+        None => Err(TextureError::Truncated),
+    }
+}
+
+#[inline]
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64, TextureError> {
+    Ok(read_u32(bytes, offset)? as u64 | (read_u32(bytes, offset + 4)? as u64) << 32)
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        let r = x % y;
+        x = y;
+        y = r;
+    }
+    a / x * b
+}
+
+/// Error that can happen when loading a texture file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TextureError {
+    /// The data doesn't start with the identifier of a KTX2 or DDS file.
+    UnknownContainer,
+    /// The data ends before the end of the header or of the texture data.
+    Truncated,
+    /// The header contains invalid values.
+    InvalidHeader,
+    /// The dimensions of the texture are invalid, or describe a kind of image that can't be
+    /// created.
+    InvalidDimensions,
+    /// The texture uses a format that has no equivalent `Format`, or whose texel data has no
+    /// well-defined size.
+    UnsupportedFormat,
+    /// The KTX2 file uses supercompression.
+    UnsupportedSupercompression {
+        /// The `supercompressionScheme` of the file.
+        scheme: u32,
+    },
+}
+
+impl error::Error for TextureError {}
+
+impl fmt::Display for TextureError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(
+            fmt,
+            "{}",
+            match *self {
+                TextureError::UnknownContainer => "the data is not a KTX2 or DDS file",
+                TextureError::Truncated => "the texture file is truncated",
+                TextureError::InvalidHeader => "the header of the texture file is invalid",
+                TextureError::InvalidDimensions => "the dimensions of the texture are invalid",
+                TextureError::UnsupportedFormat => "the format of the texture is not supported",
+                TextureError::UnsupportedSupercompression { .. } => {
+                    "the texture file uses an unsupported supercompression scheme"
+                }
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TextureData;
+    use super::TextureError;
+    use super::KTX2_IDENTIFIER;
+    use crate::format::Format;
+    use crate::image::ImageDimensions;
+
+    fn ktx2(
+        vk_format: u32,
+        size: [u32; 3],
+        layers: u32,
+        faces: u32,
+        levels: &[Vec<u8>],
+    ) -> Vec<u8> {
+        let mut bytes = KTX2_IDENTIFIER.to_vec();
+        let header = [
+            vk_format,
+            1,
+            size[0],
+            size[1],
+            size[2],
+            layers,
+            faces,
+            levels.len() as u32,
+            0,
+        ];
+        for value in header.iter().chain(&[0; 4]) {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes.extend_from_slice(&[0; 16]);
+
+        // Like in real files, the smallest level comes first.
+        let mut offset = 80 + levels.len() * 24;
+        let mut index = Vec::new();
+        for level in levels.iter().rev() {
+            index.push((offset as u64, level.len() as u64));
+            offset += level.len();
+        }
+        for &(offset, length) in index.iter().rev() {
+            for value in &[offset, length, length] {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        for level in levels.iter().rev() {
+            bytes.extend_from_slice(level);
+        }
+
+        bytes
+    }
+
+    fn dds(pixel_format: [u32; 8], size: [u32; 3], mip_levels: u32, caps2: u32) -> Vec<u8> {
+        let mut header = [0u32; 32];
+        header[0] = u32::from_le_bytes(*b"DDS ");
+        header[1] = 124;
+        header[2] = 0x1 | 0x2 | 0x4 | 0x1000 | 0x20000 | if size[2] > 1 { 0x800000 } else { 0 };
+        header[3] = size[1];
+        header[4] = size[0];
+        header[6] = size[2];
+        header[7] = mip_levels;
+        header[19..27].copy_from_slice(&pixel_format);
+        header[28] = caps2;
+
+        header
+            .iter()
+            .flat_map(|value| value.to_le_bytes().to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn ktx2_mip_chain() {
+        let levels = vec![vec![1; 64], vec![2; 16], vec![3; 4]];
+        let bytes = ktx2(37, [4, 4, 0], 0, 1, &levels); // VK_FORMAT_R8G8B8A8_UNORM
+
+        let texture = TextureData::from_bytes(&bytes).unwrap();
+        assert_eq!(texture.format(), Format::R8G8B8A8_UNORM);
+        assert_eq!(
+            texture.dimensions(),
+            ImageDimensions::Dim2d {
+                width: 4,
+                height: 4,
+                array_layers: 1,
+            }
+        );
+        assert!(!texture.is_cube());
+        assert_eq!(texture.mip_levels(), 3);
+        for (level, data) in levels.iter().enumerate() {
+            assert_eq!(texture.level(level as u32), &data[..]);
+        }
+    }
+
+    #[test]
+    fn ktx2_level_alignment() {
+        // A 3x1 R8 texture has levels of 3 and 1 bytes.
+        let levels = vec![vec![1; 3], vec![2; 1]];
+        let bytes = ktx2(9, [3, 1, 0], 0, 1, &levels); // VK_FORMAT_R8_UNORM
+
+        let texture = TextureData::from_ktx2(&bytes).unwrap();
+        assert_eq!(texture.level_range(0), 0..3);
+        assert_eq!(texture.level_range(1), 4..5);
+    }
+
+    #[test]
+    fn ktx2_errors() {
+        let levels = vec![vec![0; 64]];
+
+        let mut bytes = ktx2(37, [4, 4, 0], 0, 1, &levels);
+        bytes[44] = 2;
+        assert_eq!(
+            TextureData::from_ktx2(&bytes),
+            Err(TextureError::UnsupportedSupercompression { scheme: 2 })
+        );
+
+        let bytes = ktx2(0, [4, 4, 0], 0, 1, &levels);
+        assert_eq!(
+            TextureData::from_ktx2(&bytes),
+            Err(TextureError::UnsupportedFormat)
+        );
+
+        let bytes = ktx2(37, [4, 4, 0], 0, 1, &levels);
+        assert_eq!(
+            TextureData::from_ktx2(&bytes[..bytes.len() - 1]),
+            Err(TextureError::Truncated)
+        );
+
+        let bytes = ktx2(37, [4, 4, 0], 0, 1, &[vec![0; 60]]);
+        assert_eq!(
+            TextureData::from_ktx2(&bytes),
+            Err(TextureError::InvalidHeader)
+        );
+
+        assert_eq!(
+            TextureData::from_bytes(b"not a texture"),
+            Err(TextureError::UnknownContainer)
+        );
+    }
+
+    #[test]
+    fn huge_dimensions() {
+        let is_rejected = |result: Result<TextureData, TextureError>| {
+            matches!(
+                result,
+                Err(TextureError::InvalidDimensions) | Err(TextureError::Truncated)
+            )
+        };
+        let sizes = [0x8000_0000, u32::MAX - 1, u32::MAX];
+
+        // The headers claim much more data than the few bytes that follow them, and nothing must
+        // overflow or be allocated for it.
+        for &width in &sizes {
+            for &height in &sizes {
+                for &layers in &[0, 0x3000_0000, u32::MAX] {
+                    for &faces in &[1, 6] {
+                        let bytes = ktx2(37, [width, height, 0], layers, faces, &[vec![0; 64]]);
+                        assert!(is_rejected(TextureData::from_ktx2(&bytes)));
+                    }
+                }
+
+                for &mip_levels in &[1, 32] {
+                    let bytes = dds(
+                        [32, 0x41, 0, 32, 0xFF, 0xFF00, 0xFF0000, 0xFF000000],
+                        [width, height, 1],
+                        mip_levels,
+                        0,
+                    );
+                    assert!(is_rejected(TextureData::from_dds(&bytes)));
+
+                    let mut bytes = dds(
+                        [32, 0x4, u32::from_le_bytes(*b"DX10"), 0, 0, 0, 0, 0],
+                        [width, height, 1],
+                        mip_levels,
+                        0,
+                    );
+                    // DXGI_FORMAT_BC1_UNORM, D3D10_RESOURCE_DIMENSION_TEXTURE2D, TEXTURECUBE.
+                    for value in &[71u32, 3, 0x4, u32::MAX, 0] {
+                        bytes.extend_from_slice(&value.to_le_bytes());
+                    }
+                    bytes.extend_from_slice(&[0; 48]);
+                    assert!(is_rejected(TextureData::from_dds(&bytes)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dds_dx10_cube() {
+        let mut bytes = dds(
+            [32, 0x4, u32::from_le_bytes(*b"DX10"), 0, 0, 0, 0, 0],
+            [8, 8, 1],
+            2,
+            0,
+        );
+        // DXGI_FORMAT_BC1_UNORM, D3D10_RESOURCE_DIMENSION_TEXTURE2D, TEXTURECUBE, 1 cube.
+        for value in &[71u32, 3, 0x4, 1, 0] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        // Each face has a level of 2x2 blocks and a level of 1 block.
+        for face in 0..6u8 {
+            bytes.extend_from_slice(&[face; 32]);
+            bytes.extend_from_slice(&[face + 10; 8]);
+        }
+
+        let texture = TextureData::from_bytes(&bytes).unwrap();
+        assert_eq!(texture.format(), Format::BC1_RGBA_UNORM_BLOCK);
+        assert!(texture.is_cube());
+        assert_eq!(texture.dimensions().array_layers(), 6);
+        assert_eq!(texture.mip_levels(), 2);
+        assert_eq!(texture.level(0).len(), 6 * 32);
+        assert_eq!(texture.level(1).len(), 6 * 8);
+        for face in 0..6u8 {
+            let face_usize = face as usize;
+            assert!(texture.level(0)[face_usize * 32..][..32]
+                .iter()
+                .all(|&b| b == face));
+            assert!(texture.level(1)[face_usize * 8..][..8]
+                .iter()
+                .all(|&b| b == face + 10));
+        }
+    }
+
+    #[test]
+    fn dds_legacy() {
+        let mut bytes = dds(
+            [32, 0x4, u32::from_le_bytes(*b"DXT5"), 0, 0, 0, 0, 0],
+            [8, 4, 1],
+            1,
+            0,
+        );
+        bytes.extend_from_slice(&[7; 32]);
+
+        let texture = TextureData::from_dds(&bytes).unwrap();
+        assert_eq!(texture.format(), Format::BC3_UNORM_BLOCK);
+        assert_eq!(texture.mip_levels(), 1);
+        assert_eq!(texture.data(), &[7; 32][..]);
+
+        let mut bytes = dds(
+            [32, 0x41, 0, 32, 0xFF0000, 0xFF00, 0xFF, 0xFF000000],
+            [1, 1, 1],
+            1,
+            0,
+        );
+        bytes.extend_from_slice(&[1, 2, 3, 4]);
+
+        let texture = TextureData::from_dds(&bytes).unwrap();
+        assert_eq!(texture.format(), Format::B8G8R8A8_UNORM);
+        assert_eq!(texture.data(), &[1, 2, 3, 4]);
+
+        assert_eq!(
+            TextureData::from_dds(&bytes[..130]),
+            Err(TextureError::Truncated)
+        );
+    }
+}
@@ -44,6 +44,7 @@ where
     array_layers: Range<u32>,
     component_mapping: ComponentMapping,
     format: Format,
+    mipmap_levels: Range<u32>,
     ty: ImageViewType,
 }
 
@@ -281,7 +282,7 @@ where
                 image_inner,
                 self.ty,
                 self.component_mapping,
-                self.mipmap_levels,
+                self.mipmap_levels.clone(),
                 self.array_layers.clone(),
             )?
         };
@@ -293,6 +294,7 @@ where
             array_layers: self.array_layers,
             component_mapping: self.component_mapping,
             format: self.format,
+            mipmap_levels: self.mipmap_levels,
             ty: self.ty,
         }))
     }
@@ -592,6 +594,14 @@ pub unsafe trait ImageViewAbstract {
     /// Returns the range of array layers of the wrapped image that this view exposes.
     fn array_layers(&self) -> Range<u32>;
 
+    /// Returns the range of mipmap levels of the wrapped image that this view exposes.
+    ///
+    /// The default implementation returns all the mipmap levels of the image.
+    #[inline]
+    fn mipmap_levels(&self) -> Range<u32> {
+        0..self.image().mipmap_levels()
+    }
+
     /// Returns the format of this view. This can be different from the parent's format.
     fn format(&self) -> Format;
 
@@ -630,6 +640,11 @@ where
         self.array_layers.clone()
     }
 
+    #[inline]
+    fn mipmap_levels(&self) -> Range<u32> {
+        self.mipmap_levels.clone()
+    }
+
     #[inline]
     fn format(&self) -> Format {
         // TODO: remove this default impl
@@ -667,6 +682,11 @@ where
         (**self).array_layers()
     }
 
+    #[inline]
+    fn mipmap_levels(&self) -> Range<u32> {
+        (**self).mipmap_levels()
+    }
+
     #[inline]
     fn format(&self) -> Format {
         (**self).format()
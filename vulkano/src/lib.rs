@@ -71,6 +71,10 @@ use std::sync::Arc;
 use std::sync::MutexGuard;
 pub use version::Version;
 
+// Allows the derive macros, which refer to `::vulkano`, to be used by the tests of this crate.
+#[cfg(test)]
+extern crate self as vulkano;
+
 #[macro_use]
 mod tests;
 #[macro_use]
@@ -22,9 +22,14 @@
 //! will take precedence if it is activated, otherwise the blending operation is applied.
 //!
 
+use crate::pipeline::hash_f32;
+use std::hash::Hash;
+use std::hash::Hasher;
+
 /// Describes how the color output of the fragment shader is written to the attachment. See the
 /// documentation of the `blend` module for more info.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Blend {
     pub logic_op: Option<LogicOp>,
 
@@ -59,8 +64,25 @@ impl Blend {
     }
 }
 
+impl Eq for Blend {}
+
+impl Hash for Blend {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.logic_op.hash(state);
+        self.attachments.hash(state);
+        self.blend_constants.is_some().hash(state);
+        if let Some(constants) = self.blend_constants {
+            for &c in constants.iter() {
+                hash_f32(c, state);
+            }
+        }
+    }
+}
+
 /// Describes how the blending system should behave.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AttachmentsBlend {
     /// All the framebuffer attachments will use the same blending.
     Collective(AttachmentBlend),
@@ -71,7 +93,8 @@ pub enum AttachmentsBlend {
 }
 
 /// Describes how the blending system should behave for an individual attachment.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AttachmentBlend {
     // TODO: could be automatically determined from the other params
     /// If false, blending is ignored and the output is directly written to the attachment.
@@ -191,7 +214,8 @@ impl From<AttachmentBlend> for ash::vk::PipelineColorBlendAttachmentState {
 /// Only relevant for integer or unsigned attachments.
 ///
 /// Also note that some implementations don't support logic operations.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(i32)]
 pub enum LogicOp {
     /// Returns `0`.
@@ -242,7 +266,8 @@ impl Default for LogicOp {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(i32)]
 pub enum BlendOp {
     Add = ash::vk::BlendOp::ADD.as_raw(),
@@ -259,7 +284,8 @@ impl From<BlendOp> for ash::vk::BlendOp {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(i32)]
 pub enum BlendFactor {
     Zero = ash::vk::BlendFactor::ZERO.as_raw(),
@@ -247,7 +247,6 @@ impl Drop for PipelineCache {
 mod tests {
     use crate::pipeline::cache::PipelineCache;
     use crate::pipeline::shader::ShaderModule;
-    use crate::pipeline::shader::SpecializationConstants;
     use crate::pipeline::ComputePipeline;
     use std::{ffi::CStr, sync::Arc};
 
@@ -287,12 +286,7 @@ mod tests {
 
         let shader = unsafe {
             static NAME: [u8; 5] = [109, 97, 105, 110, 0]; // "main"
-            module.compute_entry_point(
-                CStr::from_ptr(NAME.as_ptr() as *const _),
-                [],
-                None,
-                <()>::descriptors(),
-            )
+            module.compute_entry_point(CStr::from_ptr(NAME.as_ptr() as *const _), [], None, [])
         };
 
         let pipeline = Arc::new(
@@ -337,7 +331,7 @@ mod tests {
                 CStr::from_ptr(NAME.as_ptr() as *const _),
                 [],
                 None,
-                <()>::descriptors(),
+                [],
             )
         };
 
@@ -378,7 +372,7 @@ mod tests {
                 CStr::from_ptr(NAME.as_ptr() as *const _),
                 [],
                 None,
-                <()>::descriptors(),
+                [],
             )
         };
 
@@ -442,12 +436,7 @@ mod tests {
 
         let shader = unsafe {
             static NAME: [u8; 5] = [109, 97, 105, 110, 0]; // "main"
-            module.compute_entry_point(
-                CStr::from_ptr(NAME.as_ptr() as *const _),
-                [],
-                None,
-                <()>::descriptors(),
-            )
+            module.compute_entry_point(CStr::from_ptr(NAME.as_ptr() as *const _), [], None, [])
         };
 
         let pipeline = Arc::new(
@@ -17,14 +17,15 @@ use crate::pipeline::layout::PipelineLayout;
 use crate::pipeline::layout::PipelineLayoutCreationError;
 use crate::pipeline::layout::PipelineLayoutSupersetError;
 use crate::pipeline::shader::EntryPointAbstract;
-use crate::pipeline::shader::SpecializationConstants;
+use crate::pipeline::shader::LocalSizeValue;
+use crate::pipeline::shader::SpecializationData;
+use crate::pipeline::shader::SpecializationMismatchError;
 use crate::Error;
 use crate::OomError;
 use crate::VulkanObject;
 use std::error;
 use std::fmt;
 use std::marker::PhantomData;
-use std::mem;
 use std::mem::MaybeUninit;
 use std::ptr;
 use std::sync::Arc;
@@ -43,6 +44,7 @@ use std::sync::Arc;
 pub struct ComputePipeline {
     inner: Inner,
     pipeline_layout: Arc<PipelineLayout>,
+    local_size: Option<[u32; 3]>,
 }
 
 struct Inner {
@@ -56,6 +58,9 @@ impl ComputePipeline {
     /// `func` is a closure that is given a mutable reference to the inferred descriptor set
     /// definitions. This can be used to make changes to the layout before it's created, for example
     /// to add dynamic buffers or immutable samplers.
+    ///
+    /// `spec_constants` can be either a type generated by `vulkano-shaders`, or a
+    /// `SpecializationInfo` built at runtime.
     pub fn new<Cs, Css, F>(
         device: Arc<Device>,
         shader: &Cs,
@@ -65,9 +70,11 @@ impl ComputePipeline {
     ) -> Result<ComputePipeline, ComputePipelineCreationError>
     where
         Cs: EntryPointAbstract,
-        Css: SpecializationConstants,
+        Css: SpecializationData,
         F: FnOnce(&mut [DescriptorSetDesc]),
     {
+        spec_constants.check_requirements(shader.spec_constants())?;
+
         let mut descriptor_set_layout_descs = shader.descriptor_set_layout_descs().to_owned();
         func(&mut descriptor_set_layout_descs);
 
@@ -80,11 +87,19 @@ impl ComputePipeline {
                 )?))
             })
             .collect::<Result<Vec<_>, PipelineLayoutCreationError>>()?;
-        let pipeline_layout = Arc::new(PipelineLayout::new(
+        let mut pipeline_layout = PipelineLayout::new(
             device.clone(),
             descriptor_set_layouts,
             shader.push_constant_range().iter().cloned(),
-        )?);
+        )?;
+        if let (Some(range), Some(hash)) = (
+            shader.push_constant_range(),
+            shader.push_constants_layout_hash(),
+        ) {
+            pipeline_layout.set_push_constants_layout_hash(range.stages, hash);
+        }
+        pipeline_layout.add_push_constant_members(shader.push_constant_members());
+        let pipeline_layout = Arc::new(pipeline_layout);
 
         unsafe {
             ComputePipeline::with_unchecked_pipeline_layout(
@@ -110,29 +125,59 @@ impl ComputePipeline {
     ) -> Result<ComputePipeline, ComputePipelineCreationError>
     where
         Cs: EntryPointAbstract,
-        Css: SpecializationConstants,
+        Css: SpecializationData,
     {
-        if Css::descriptors() != shader.spec_constants() {
-            return Err(ComputePipelineCreationError::IncompatibleSpecializationConstants);
-        }
+        ComputePipeline::with_subgroup_size_control(
+            device,
+            shader,
+            spec_constants,
+            pipeline_layout,
+            SubgroupSizeControl::default(),
+            cache,
+        )
+    }
+
+    /// Builds a new `ComputePipeline` with a specific pipeline layout and control over the
+    /// subgroup size used by the shader.
+    ///
+    /// In addition to the checks performed by `with_pipeline_layout`, an error will be returned
+    /// if `subgroup_size` requires a feature that isn't enabled, or isn't supported by the device
+    /// for the given local size.
+    pub fn with_subgroup_size_control<Cs, Css>(
+        device: Arc<Device>,
+        shader: &Cs,
+        spec_constants: &Css,
+        pipeline_layout: Arc<PipelineLayout>,
+        subgroup_size: SubgroupSizeControl,
+        cache: Option<Arc<PipelineCache>>,
+    ) -> Result<ComputePipeline, ComputePipelineCreationError>
+    where
+        Cs: EntryPointAbstract,
+        Css: SpecializationData,
+    {
+        spec_constants.check_requirements(shader.spec_constants())?;
+        let local_size = specialized_local_size(shader, spec_constants);
+        subgroup_size.check(&device, local_size)?;
 
         unsafe {
             pipeline_layout.ensure_compatible_with_shader(
                 shader.descriptor_set_layout_descs(),
                 shader.push_constant_range(),
             )?;
-            ComputePipeline::with_unchecked_pipeline_layout(
+            ComputePipeline::create(
                 device,
                 shader,
                 spec_constants,
                 pipeline_layout,
+                &subgroup_size,
                 cache,
             )
         }
     }
 
     /// Same as `with_pipeline_layout`, but doesn't check whether the pipeline layout is a
-    /// superset of what the shader expects.
+    /// superset of what the shader expects, nor whether the specialization constants match those
+    /// of the shader.
     pub unsafe fn with_unchecked_pipeline_layout<Cs, Css>(
         device: Arc<Device>,
         shader: &Cs,
@@ -142,21 +187,63 @@ impl ComputePipeline {
     ) -> Result<ComputePipeline, ComputePipelineCreationError>
     where
         Cs: EntryPointAbstract,
-        Css: SpecializationConstants,
+        Css: SpecializationData,
+    {
+        ComputePipeline::create(
+            device,
+            shader,
+            spec_constants,
+            pipeline_layout,
+            &SubgroupSizeControl::default(),
+            cache,
+        )
+    }
+
+    unsafe fn create<Cs, Css>(
+        device: Arc<Device>,
+        shader: &Cs,
+        spec_constants: &Css,
+        pipeline_layout: Arc<PipelineLayout>,
+        subgroup_size: &SubgroupSizeControl,
+        cache: Option<Arc<PipelineCache>>,
+    ) -> Result<ComputePipeline, ComputePipelineCreationError>
+    where
+        Cs: EntryPointAbstract,
+        Css: SpecializationData,
     {
         let fns = device.fns();
+        let local_size = specialized_local_size(shader, spec_constants);
 
         let pipeline = {
-            let spec_descriptors = Css::descriptors();
+            let spec_descriptors = spec_constants.map_entries();
             let specialization = ash::vk::SpecializationInfo {
                 map_entry_count: spec_descriptors.len() as u32,
                 p_map_entries: spec_descriptors.as_ptr() as *const _,
-                data_size: mem::size_of_val(spec_constants),
-                p_data: spec_constants as *const Css as *const _,
+                data_size: spec_constants.data_size(),
+                p_data: spec_constants.data_ptr(),
             };
 
+            let mut flags = ash::vk::PipelineShaderStageCreateFlags::empty();
+            if subgroup_size.allow_varying_subgroup_size {
+                flags |= ash::vk::PipelineShaderStageCreateFlags::ALLOW_VARYING_SUBGROUP_SIZE_EXT;
+            }
+            if subgroup_size.require_full_subgroups {
+                flags |= ash::vk::PipelineShaderStageCreateFlags::REQUIRE_FULL_SUBGROUPS_EXT;
+            }
+
+            let required_subgroup_size = subgroup_size.required_subgroup_size.map(|size| {
+                ash::vk::PipelineShaderStageRequiredSubgroupSizeCreateInfoEXT {
+                    required_subgroup_size: size,
+                    ..Default::default()
+                }
+            });
+
             let stage = ash::vk::PipelineShaderStageCreateInfo {
-                flags: ash::vk::PipelineShaderStageCreateFlags::empty(),
+                p_next: match required_subgroup_size {
+                    Some(ref info) => info as *const _ as *const _,
+                    None => ptr::null(),
+                },
+                flags,
                 stage: ash::vk::ShaderStageFlags::COMPUTE,
                 module: shader.module().internal_object(),
                 p_name: shader.name().as_ptr(),
@@ -200,6 +287,7 @@ impl ComputePipeline {
                 pipeline: pipeline,
             },
             pipeline_layout: pipeline_layout,
+            local_size,
         })
     }
 
@@ -214,6 +302,138 @@ impl ComputePipeline {
     pub fn layout(&self) -> &Arc<PipelineLayout> {
         &self.pipeline_layout
     }
+
+    /// Returns the local workgroup size of the shader, with the specialization constants that
+    /// were provided at creation applied.
+    ///
+    /// Returns `None` if the entry point the pipeline was created from doesn't describe its local
+    /// size.
+    #[inline]
+    pub fn local_size(&self) -> Option<[u32; 3]> {
+        self.local_size
+    }
+}
+
+fn specialized_local_size<Cs, Css>(shader: &Cs, spec_constants: &Css) -> Option<[u32; 3]>
+where
+    Cs: EntryPointAbstract,
+    Css: SpecializationData,
+{
+    shader.local_size().map(|local_size| {
+        let specialize = |value: &LocalSizeValue| value.specialize(spec_constants);
+        [
+            specialize(&local_size[0]),
+            specialize(&local_size[1]),
+            specialize(&local_size[2]),
+        ]
+    })
+}
+
+/// Controls the size of the subgroups a compute shader is dispatched with.
+///
+/// All the options other than the default require the `ext_subgroup_size_control` extension
+/// and the corresponding features.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SubgroupSizeControl {
+    /// Forces the subgroup size to the given value. Requires the `subgroup_size_control` feature.
+    ///
+    /// The value must be a power of two between the `min_subgroup_size` and `max_subgroup_size`
+    /// properties of the device, and the compute stage must be part of the
+    /// `required_subgroup_size_stages` property.
+    pub required_subgroup_size: Option<u32>,
+
+    /// Allows the subgroup size to vary within the shader. Requires the `subgroup_size_control`
+    /// feature.
+    pub allow_varying_subgroup_size: bool,
+
+    /// Requires all the subgroups to be full. Requires the `compute_full_subgroups` feature.
+    ///
+    /// The first dimension of the local size must be a multiple of the subgroup size.
+    pub require_full_subgroups: bool,
+}
+
+impl SubgroupSizeControl {
+    fn check(
+        &self,
+        device: &Device,
+        local_size: Option<[u32; 3]>,
+    ) -> Result<(), ComputePipelineCreationError> {
+        let properties = device.physical_device().properties();
+
+        if self.required_subgroup_size.is_some() && self.allow_varying_subgroup_size {
+            return Err(ComputePipelineCreationError::RequiredSubgroupSizeWithVaryingSubgroupSize);
+        }
+
+        if self.required_subgroup_size.is_some() || self.allow_varying_subgroup_size {
+            if !device.enabled_features().subgroup_size_control {
+                return Err(ComputePipelineCreationError::SubgroupSizeControlFeatureNotEnabled);
+            }
+        }
+
+        if self.require_full_subgroups && !device.enabled_features().compute_full_subgroups {
+            return Err(ComputePipelineCreationError::ComputeFullSubgroupsFeatureNotEnabled);
+        }
+
+        if let Some(size) = self.required_subgroup_size {
+            let supported = properties
+                .required_subgroup_size_stages
+                .map_or(false, |stages| stages.compute);
+            if !supported {
+                return Err(ComputePipelineCreationError::RequiredSubgroupSizeNotSupported);
+            }
+
+            let min = properties.min_subgroup_size.unwrap_or(0);
+            let max = properties.max_subgroup_size.unwrap_or(0);
+            if !size.is_power_of_two() || size < min || size > max {
+                return Err(
+                    ComputePipelineCreationError::RequiredSubgroupSizeOutOfRange {
+                        requested: size,
+                        min,
+                        max,
+                    },
+                );
+            }
+        }
+
+        let local_size = match local_size {
+            Some(local_size) => local_size,
+            None => return Ok(()),
+        };
+
+        if let Some(size) = self.required_subgroup_size {
+            let invocations = local_size.iter().map(|&s| s as u64).product::<u64>();
+            let max = properties.max_compute_workgroup_subgroups.unwrap_or(0) as u64 * size as u64;
+            if invocations > max {
+                return Err(
+                    ComputePipelineCreationError::MaxComputeWorkgroupSubgroupsExceeded {
+                        invocations,
+                        max,
+                    },
+                );
+            }
+        }
+
+        if self.require_full_subgroups {
+            let subgroup_size = match self.required_subgroup_size {
+                Some(size) => Some(size),
+                None if self.allow_varying_subgroup_size => properties.max_subgroup_size,
+                None => properties.subgroup_size,
+            };
+
+            if let Some(subgroup_size) = subgroup_size {
+                if local_size[0] % subgroup_size != 0 {
+                    return Err(
+                        ComputePipelineCreationError::LocalSizeNotMultipleOfSubgroupSize {
+                            local_size_x: local_size[0],
+                            subgroup_size,
+                        },
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl fmt::Debug for ComputePipeline {
@@ -283,7 +503,41 @@ pub enum ComputePipelineCreationError {
     /// The pipeline layout is not compatible with what the shader expects.
     IncompatiblePipelineLayout(PipelineLayoutSupersetError),
     /// The provided specialization constants are not compatible with what the shader expects.
-    IncompatibleSpecializationConstants,
+    IncompatibleSpecializationConstants(SpecializationMismatchError),
+    /// The `subgroup_size_control` feature must be enabled in order to set the subgroup size.
+    SubgroupSizeControlFeatureNotEnabled,
+    /// The `compute_full_subgroups` feature must be enabled in order to require full subgroups.
+    ComputeFullSubgroupsFeatureNotEnabled,
+    /// The device doesn't support setting a required subgroup size for compute shaders.
+    RequiredSubgroupSizeNotSupported,
+    /// A required subgroup size was set while also allowing the subgroup size to vary.
+    RequiredSubgroupSizeWithVaryingSubgroupSize,
+    /// The required subgroup size is not a power of two within the range supported by the
+    /// device.
+    RequiredSubgroupSizeOutOfRange {
+        /// The requested subgroup size.
+        requested: u32,
+        /// The minimum subgroup size supported by the device.
+        min: u32,
+        /// The maximum subgroup size supported by the device.
+        max: u32,
+    },
+    /// The local size has more invocations than `max_compute_workgroup_subgroups` subgroups of
+    /// the required size can hold.
+    MaxComputeWorkgroupSubgroupsExceeded {
+        /// The number of invocations in a workgroup.
+        invocations: u64,
+        /// The maximum number of invocations.
+        max: u64,
+    },
+    /// Full subgroups were required, but the first dimension of the local size is not a multiple
+    /// of the subgroup size.
+    LocalSizeNotMultipleOfSubgroupSize {
+        /// The first dimension of the local size.
+        local_size_x: u32,
+        /// The subgroup size it must be a multiple of.
+        subgroup_size: u32,
+    },
 }
 
 impl error::Error for ComputePipelineCreationError {
@@ -293,7 +547,8 @@ impl error::Error for ComputePipelineCreationError {
             ComputePipelineCreationError::OomError(ref err) => Some(err),
             ComputePipelineCreationError::PipelineLayoutCreationError(ref err) => Some(err),
             ComputePipelineCreationError::IncompatiblePipelineLayout(ref err) => Some(err),
-            ComputePipelineCreationError::IncompatibleSpecializationConstants => None,
+            ComputePipelineCreationError::IncompatibleSpecializationConstants(ref err) => Some(err),
+            _ => None,
         }
     }
 }
@@ -312,9 +567,30 @@ impl fmt::Display for ComputePipelineCreationError {
                 ComputePipelineCreationError::IncompatiblePipelineLayout(_) => {
                     "the pipeline layout is not compatible with what the shader expects"
                 }
-                ComputePipelineCreationError::IncompatibleSpecializationConstants => {
+                ComputePipelineCreationError::IncompatibleSpecializationConstants(_) => {
                     "the provided specialization constants are not compatible with what the shader expects"
                 }
+                ComputePipelineCreationError::SubgroupSizeControlFeatureNotEnabled => {
+                    "the `subgroup_size_control` feature must be enabled"
+                }
+                ComputePipelineCreationError::ComputeFullSubgroupsFeatureNotEnabled => {
+                    "the `compute_full_subgroups` feature must be enabled"
+                }
+                ComputePipelineCreationError::RequiredSubgroupSizeNotSupported => {
+                    "the device doesn't support setting a required subgroup size for compute shaders"
+                }
+                ComputePipelineCreationError::RequiredSubgroupSizeWithVaryingSubgroupSize => {
+                    "a required subgroup size can't be set while allowing the subgroup size to vary"
+                }
+                ComputePipelineCreationError::RequiredSubgroupSizeOutOfRange { .. } => {
+                    "the required subgroup size is not a power of two within the range supported by the device"
+                }
+                ComputePipelineCreationError::MaxComputeWorkgroupSubgroupsExceeded { .. } => {
+                    "the local size has more invocations than the maximum number of subgroups can hold"
+                }
+                ComputePipelineCreationError::LocalSizeNotMultipleOfSubgroupSize { .. } => {
+                    "the first dimension of the local size is not a multiple of the subgroup size"
+                }
             }
         )
     }
@@ -341,6 +617,13 @@ impl From<PipelineLayoutSupersetError> for ComputePipelineCreationError {
     }
 }
 
+impl From<SpecializationMismatchError> for ComputePipelineCreationError {
+    #[inline]
+    fn from(err: SpecializationMismatchError) -> ComputePipelineCreationError {
+        ComputePipelineCreationError::IncompatibleSpecializationConstants(err)
+    }
+}
+
 impl From<Error> for ComputePipelineCreationError {
     #[inline]
     fn from(err: Error) -> ComputePipelineCreationError {
@@ -366,12 +649,17 @@ mod tests {
     use crate::descriptor_set::layout::DescriptorDescTy;
     use crate::descriptor_set::layout::DescriptorSetDesc;
     use crate::descriptor_set::PersistentDescriptorSet;
+    use crate::pipeline::layout::PipelineLayout;
+    use crate::pipeline::shader::LocalSizeValue;
     use crate::pipeline::shader::ShaderModule;
     use crate::pipeline::shader::ShaderStages;
+    use crate::pipeline::shader::SpecializationConstantType;
     use crate::pipeline::shader::SpecializationConstants;
     use crate::pipeline::shader::SpecializationMapEntry;
     use crate::pipeline::ComputePipeline;
+    use crate::pipeline::ComputePipelineCreationError;
     use crate::pipeline::PipelineBindPoint;
+    use crate::pipeline::SubgroupSizeControl;
     use crate::sync::now;
     use crate::sync::GpuFuture;
     use std::ffi::CStr;
@@ -380,6 +668,103 @@ mod tests {
     // TODO: test for basic creation
     // TODO: test for pipeline layout error
 
+    #[test]
+    fn subgroup_size_feature_not_enabled() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let module = unsafe {
+            /*
+             * #version 450
+             * void main() {
+             * }
+             */
+            const MODULE: [u8; 192] = [
+                3, 2, 35, 7, 0, 0, 1, 0, 10, 0, 8, 0, 6, 0, 0, 0, 0, 0, 0, 0, 17, 0, 2, 0, 1, 0, 0,
+                0, 11, 0, 6, 0, 1, 0, 0, 0, 71, 76, 83, 76, 46, 115, 116, 100, 46, 52, 53, 48, 0,
+                0, 0, 0, 14, 0, 3, 0, 0, 0, 0, 0, 1, 0, 0, 0, 15, 0, 5, 0, 5, 0, 0, 0, 4, 0, 0, 0,
+                109, 97, 105, 110, 0, 0, 0, 0, 16, 0, 6, 0, 4, 0, 0, 0, 17, 0, 0, 0, 1, 0, 0, 0, 1,
+                0, 0, 0, 1, 0, 0, 0, 3, 0, 3, 0, 2, 0, 0, 0, 194, 1, 0, 0, 5, 0, 4, 0, 4, 0, 0, 0,
+                109, 97, 105, 110, 0, 0, 0, 0, 19, 0, 2, 0, 2, 0, 0, 0, 33, 0, 3, 0, 3, 0, 0, 0, 2,
+                0, 0, 0, 54, 0, 5, 0, 2, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 248, 0, 2, 0,
+                5, 0, 0, 0, 253, 0, 1, 0, 56, 0, 1, 0,
+            ];
+            ShaderModule::new(device.clone(), &MODULE).unwrap()
+        };
+
+        let shader = unsafe {
+            static NAME: [u8; 5] = [109, 97, 105, 110, 0]; // "main"
+            module
+                .compute_entry_point(CStr::from_ptr(NAME.as_ptr() as *const _), [], None, [])
+                .with_local_size([LocalSizeValue::Literal(1); 3])
+        };
+
+        let pipeline_layout = Arc::new(PipelineLayout::new(device.clone(), [], []).unwrap());
+
+        match ComputePipeline::with_subgroup_size_control(
+            device,
+            &shader,
+            &(),
+            pipeline_layout,
+            SubgroupSizeControl {
+                required_subgroup_size: Some(32),
+                ..SubgroupSizeControl::default()
+            },
+            None,
+        ) {
+            Err(ComputePipelineCreationError::SubgroupSizeControlFeatureNotEnabled) => (),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn required_and_varying_subgroup_size() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let module = unsafe {
+            /*
+             * #version 450
+             * void main() {
+             * }
+             */
+            const MODULE: [u8; 192] = [
+                3, 2, 35, 7, 0, 0, 1, 0, 10, 0, 8, 0, 6, 0, 0, 0, 0, 0, 0, 0, 17, 0, 2, 0, 1, 0, 0,
+                0, 11, 0, 6, 0, 1, 0, 0, 0, 71, 76, 83, 76, 46, 115, 116, 100, 46, 52, 53, 48, 0,
+                0, 0, 0, 14, 0, 3, 0, 0, 0, 0, 0, 1, 0, 0, 0, 15, 0, 5, 0, 5, 0, 0, 0, 4, 0, 0, 0,
+                109, 97, 105, 110, 0, 0, 0, 0, 16, 0, 6, 0, 4, 0, 0, 0, 17, 0, 0, 0, 1, 0, 0, 0, 1,
+                0, 0, 0, 1, 0, 0, 0, 3, 0, 3, 0, 2, 0, 0, 0, 194, 1, 0, 0, 5, 0, 4, 0, 4, 0, 0, 0,
+                109, 97, 105, 110, 0, 0, 0, 0, 19, 0, 2, 0, 2, 0, 0, 0, 33, 0, 3, 0, 3, 0, 0, 0, 2,
+                0, 0, 0, 54, 0, 5, 0, 2, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 3, 0, 0, 0, 248, 0, 2, 0,
+                5, 0, 0, 0, 253, 0, 1, 0, 56, 0, 1, 0,
+            ];
+            ShaderModule::new(device.clone(), &MODULE).unwrap()
+        };
+
+        let shader = unsafe {
+            static NAME: [u8; 5] = [109, 97, 105, 110, 0]; // "main"
+            module
+                .compute_entry_point(CStr::from_ptr(NAME.as_ptr() as *const _), [], None, [])
+                .with_local_size([LocalSizeValue::Literal(1); 3])
+        };
+
+        let pipeline_layout = Arc::new(PipelineLayout::new(device.clone(), [], []).unwrap());
+
+        match ComputePipeline::with_subgroup_size_control(
+            device,
+            &shader,
+            &(),
+            pipeline_layout,
+            SubgroupSizeControl {
+                required_subgroup_size: Some(32),
+                allow_varying_subgroup_size: true,
+                ..SubgroupSizeControl::default()
+            },
+            None,
+        ) {
+            Err(ComputePipelineCreationError::RequiredSubgroupSizeWithVaryingSubgroupSize) => (),
+            _ => panic!(),
+        }
+    }
+
     #[test]
     fn spec_constants() {
         // This test checks whether specialization constants work.
@@ -430,21 +815,31 @@ mod tests {
 
         let shader = unsafe {
             static NAME: [u8; 5] = [109, 97, 105, 110, 0]; // "main"
-            module.compute_entry_point(
-                CStr::from_ptr(NAME.as_ptr() as *const _),
-                [DescriptorSetDesc::new([Some(DescriptorDesc {
-                    ty: DescriptorDescTy::StorageBuffer,
-                    descriptor_count: 1,
-                    stages: ShaderStages {
-                        compute: true,
-                        ..ShaderStages::none()
-                    },
-                    mutable: false,
-                    variable_count: false,
-                })])],
-                None,
-                SpecConsts::descriptors(),
-            )
+            module
+                .compute_entry_point(
+                    CStr::from_ptr(NAME.as_ptr() as *const _),
+                    [DescriptorSetDesc::new([Some(DescriptorDesc {
+                        ty: DescriptorDescTy::StorageBuffer,
+                        descriptor_count: 1,
+                        stages: ShaderStages {
+                            compute: true,
+                            ..ShaderStages::none()
+                        },
+                        mutable: false,
+                        variable_count: false,
+                        name: None,
+                    })])],
+                    None,
+                    [(
+                        83,
+                        SpecializationConstantType::Int {
+                            width: 32,
+                            signed: true,
+                        }
+                        .into(),
+                    )],
+                )
+                .with_local_size([LocalSizeValue::Literal(1); 3])
         };
 
         #[derive(Debug, Copy, Clone)]
@@ -474,6 +869,7 @@ mod tests {
             )
             .unwrap(),
         );
+        assert_eq!(pipeline.local_size(), Some([1, 1, 1]));
 
         let data_buffer =
             CpuAccessibleBuffer::from_data(device.clone(), BufferUsage::all(), false, 0).unwrap();
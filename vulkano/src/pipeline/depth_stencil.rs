@@ -20,11 +20,15 @@
 //! value in the stencil buffer at each fragment's location. Depending on the outcome of the
 //! depth and stencil tests, the value of the stencil buffer at that location can be updated.
 
+use crate::pipeline::hash_f32;
+use std::hash::Hash;
+use std::hash::Hasher;
 use std::ops::Range;
 use std::u32;
 
 /// Configuration of the depth and stencil tests.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DepthStencil {
     /// Comparison to use between the depth value of each fragment and the depth value currently
     /// in the depth buffer.
@@ -80,7 +84,8 @@ impl Default for DepthStencil {
 }
 
 /// Configuration of a stencil test.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Stencil {
     /// The comparison to perform between the existing stencil value in the stencil buffer, and
     /// the reference value (given by `reference`).
@@ -162,7 +167,8 @@ impl Default for Stencil {
 }
 
 /// Operation to perform after the depth and stencil tests.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(i32)]
 pub enum StencilOp {
     Keep = ash::vk::StencilOp::KEEP.as_raw(),
@@ -207,6 +213,7 @@ pub struct DynamicStencilValue {
 
 /// Allows you to ask the GPU to exclude fragments that are outside of a certain range.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DepthBounds {
     /// The test is disabled. All fragments pass the depth bounds test.
     Disabled,
@@ -220,6 +227,23 @@ pub enum DepthBounds {
     Dynamic,
 }
 
+impl Eq for DepthBounds {}
+
+impl Hash for DepthBounds {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            DepthBounds::Disabled => 0u8.hash(state),
+            DepthBounds::Fixed(range) => {
+                1u8.hash(state);
+                hash_f32(range.start, state);
+                hash_f32(range.end, state);
+            }
+            DepthBounds::Dynamic => 2u8.hash(state),
+        }
+    }
+}
+
 impl DepthBounds {
     /// Returns true if equal to `DepthBounds::Dynamic`.
     #[inline]
@@ -234,7 +258,8 @@ impl DepthBounds {
 /// Specifies how two values should be compared to decide whether a test passes or fails.
 ///
 /// Used for both depth testing and stencil testing.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(i32)]
 pub enum Compare {
     /// The test never passes.
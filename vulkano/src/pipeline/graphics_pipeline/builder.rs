@@ -26,6 +26,7 @@ use crate::pipeline::depth_stencil::DepthBounds;
 use crate::pipeline::depth_stencil::DepthStencil;
 use crate::pipeline::graphics_pipeline::GraphicsPipeline;
 use crate::pipeline::graphics_pipeline::GraphicsPipelineCreationError;
+use crate::pipeline::graphics_pipeline::GraphicsPipelineState;
 use crate::pipeline::graphics_pipeline::Inner as GraphicsPipelineInner;
 use crate::pipeline::input_assembly::PrimitiveTopology;
 use crate::pipeline::layout::PipelineLayout;
@@ -39,7 +40,8 @@ use crate::pipeline::raster::Rasterization;
 use crate::pipeline::shader::EntryPointAbstract;
 use crate::pipeline::shader::GraphicsEntryPoint;
 use crate::pipeline::shader::GraphicsShaderType;
-use crate::pipeline::shader::SpecializationConstants;
+use crate::pipeline::shader::SpecializationData;
+use crate::pipeline::shader::SpecializationMapEntry;
 use crate::pipeline::vertex::BuffersDefinition;
 use crate::pipeline::vertex::Vertex;
 use crate::pipeline::vertex::VertexDefinition;
@@ -51,7 +53,6 @@ use crate::render_pass::Subpass;
 use crate::VulkanObject;
 use smallvec::SmallVec;
 use std::collections::hash_map::{Entry, HashMap};
-use std::mem;
 use std::mem::MaybeUninit;
 use std::ptr;
 use std::sync::Arc;
@@ -128,11 +129,11 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
     GraphicsPipelineBuilder<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
 where
     Vdef: VertexDefinition,
-    Vss: SpecializationConstants,
-    Tcss: SpecializationConstants,
-    Tess: SpecializationConstants,
-    Gss: SpecializationConstants,
-    Fss: SpecializationConstants,
+    Vss: SpecializationData,
+    Tcss: SpecializationData,
+    Tess: SpecializationData,
+    Gss: SpecializationData,
+    Fss: SpecializationData,
 {
     /// Builds the graphics pipeline, using an inferred a pipeline layout.
     pub fn build(
@@ -153,7 +154,12 @@ where
     where
         F: FnOnce(&mut [DescriptorSetDesc]),
     {
-        let (descriptor_set_layout_descs, push_constant_ranges) = {
+        let (
+            descriptor_set_layout_descs,
+            push_constant_ranges,
+            push_constants_layout_hashes,
+            push_constant_members,
+        ) = {
             let stages: SmallVec<[&GraphicsEntryPoint; 5]> = std::array::IntoIter::new([
                 self.vertex_shader.as_ref().map(|s| &s.0),
                 self.tessellation
@@ -207,18 +213,43 @@ where
                 })
                 .collect();
 
-            (descriptor_set_layout_descs, push_constant_ranges)
+            let push_constants_layout_hashes: Vec<_> = stages
+                .iter()
+                .filter_map(|stage| {
+                    Some((
+                        stage.push_constant_range().as_ref()?.stages,
+                        stage.push_constants_layout_hash()?,
+                    ))
+                })
+                .collect();
+
+            let push_constant_members: Vec<_> = stages
+                .iter()
+                .map(|stage| stage.push_constant_members().to_vec())
+                .collect();
+
+            (
+                descriptor_set_layout_descs,
+                push_constant_ranges,
+                push_constants_layout_hashes,
+                push_constant_members,
+            )
         };
 
         let descriptor_set_layouts = descriptor_set_layout_descs
             .into_iter()
             .map(|desc| Ok(Arc::new(DescriptorSetLayout::new(device.clone(), desc)?)))
             .collect::<Result<Vec<_>, PipelineLayoutCreationError>>()?;
-        let pipeline_layout = Arc::new(
+        let mut pipeline_layout =
             PipelineLayout::new(device.clone(), descriptor_set_layouts, push_constant_ranges)
-                .unwrap(),
-        );
-        self.with_pipeline_layout(device, pipeline_layout)
+                .unwrap();
+        for (stages, hash) in push_constants_layout_hashes {
+            pipeline_layout.set_push_constants_layout_hash(stages, hash);
+        }
+        for members in push_constant_members {
+            pipeline_layout.add_push_constant_members(&members);
+        }
+        self.with_pipeline_layout(device, Arc::new(pipeline_layout))
     }
 
     /// Builds the graphics pipeline.
@@ -297,50 +328,20 @@ where
         // Creating the specialization constants of the various stages.
         let vertex_shader_specialization = {
             let shader = self.vertex_shader.as_ref().unwrap();
-            let spec_descriptors = Vss::descriptors();
-            if spec_descriptors != shader.0.spec_constants() {
-                return Err(GraphicsPipelineCreationError::IncompatibleSpecializationConstants);
-            }
-
-            let constants = &shader.1;
-            ash::vk::SpecializationInfo {
-                map_entry_count: spec_descriptors.len() as u32,
-                p_map_entries: spec_descriptors.as_ptr() as *const _,
-                data_size: mem::size_of_val(constants),
-                p_data: constants as *const Vss as *const _,
-            }
+            shader.1.check_requirements(shader.0.spec_constants())?;
+            shader.1.map_entries()
         };
 
         let tess_shader_specialization = if let Some(ref tess) = self.tessellation {
             let tcs_spec = {
                 let shader = &tess.tessellation_control_shader;
-                let spec_descriptors = Tcss::descriptors();
-                if spec_descriptors != shader.0.spec_constants() {
-                    return Err(GraphicsPipelineCreationError::IncompatibleSpecializationConstants);
-                }
-
-                let constants = &shader.1;
-                ash::vk::SpecializationInfo {
-                    map_entry_count: spec_descriptors.len() as u32,
-                    p_map_entries: spec_descriptors.as_ptr() as *const _,
-                    data_size: mem::size_of_val(constants),
-                    p_data: constants as *const Tcss as *const _,
-                }
+                shader.1.check_requirements(shader.0.spec_constants())?;
+                shader.1.map_entries()
             };
             let tes_spec = {
                 let shader = &tess.tessellation_evaluation_shader;
-                let spec_descriptors = Tess::descriptors();
-                if spec_descriptors != shader.0.spec_constants() {
-                    return Err(GraphicsPipelineCreationError::IncompatibleSpecializationConstants);
-                }
-
-                let constants = &shader.1;
-                ash::vk::SpecializationInfo {
-                    map_entry_count: spec_descriptors.len() as u32,
-                    p_map_entries: spec_descriptors.as_ptr() as *const _,
-                    data_size: mem::size_of_val(constants),
-                    p_data: constants as *const Tess as *const _,
-                }
+                shader.1.check_requirements(shader.0.spec_constants())?;
+                shader.1.map_entries()
             };
             Some((tcs_spec, tes_spec))
         } else {
@@ -348,39 +349,41 @@ where
         };
 
         let geometry_shader_specialization = if let Some(ref shader) = self.geometry_shader {
-            let spec_descriptors = Gss::descriptors();
-            if spec_descriptors != shader.0.spec_constants() {
-                return Err(GraphicsPipelineCreationError::IncompatibleSpecializationConstants);
-            }
-
-            let constants = &shader.1;
-            Some(ash::vk::SpecializationInfo {
-                map_entry_count: spec_descriptors.len() as u32,
-                p_map_entries: spec_descriptors.as_ptr() as *const _,
-                data_size: mem::size_of_val(constants),
-                p_data: constants as *const Gss as *const _,
-            })
+            shader.1.check_requirements(shader.0.spec_constants())?;
+            Some(shader.1.map_entries())
         } else {
             None
         };
 
         let fragment_shader_specialization = if let Some(ref shader) = self.fragment_shader {
-            let spec_descriptors = Fss::descriptors();
-            if spec_descriptors != shader.0.spec_constants() {
-                return Err(GraphicsPipelineCreationError::IncompatibleSpecializationConstants);
-            }
-
-            let constants = &shader.1;
-            Some(ash::vk::SpecializationInfo {
-                map_entry_count: spec_descriptors.len() as u32,
-                p_map_entries: spec_descriptors.as_ptr() as *const _,
-                data_size: mem::size_of_val(constants),
-                p_data: constants as *const Fss as *const _,
-            })
+            shader.1.check_requirements(shader.0.spec_constants())?;
+            Some(shader.1.map_entries())
         } else {
             None
         };
 
+        let vertex_shader_specialization = specialization_info(
+            &vertex_shader_specialization,
+            &self.vertex_shader.as_ref().unwrap().1,
+        );
+        let tess_shader_specialization = tess_shader_specialization
+            .as_ref()
+            .zip(self.tessellation.as_ref())
+            .map(|((tcs_spec, tes_spec), tess)| {
+                (
+                    specialization_info(tcs_spec, &tess.tessellation_control_shader.1),
+                    specialization_info(tes_spec, &tess.tessellation_evaluation_shader.1),
+                )
+            });
+        let geometry_shader_specialization = geometry_shader_specialization
+            .as_ref()
+            .zip(self.geometry_shader.as_ref())
+            .map(|(spec, shader)| specialization_info(spec, &shader.1));
+        let fragment_shader_specialization = fragment_shader_specialization
+            .as_ref()
+            .zip(self.fragment_shader.as_ref())
+            .map(|(spec, shader)| specialization_info(spec, &shader.1));
+
         // List of shader stages.
         let stages = {
             let mut stages = SmallVec::<[_; 5]>::new();
@@ -1199,6 +1202,7 @@ where
             layout: pipeline_layout,
             subpass: self.subpass.take().unwrap(),
             vertex_input,
+            vertex_shader_input: self.vertex_shader.as_ref().unwrap().0.input().clone(),
 
             dynamic_line_width: self.raster.line_width.is_none(),
             dynamic_viewport: self.viewport.as_ref().unwrap().dynamic_viewports(),
@@ -1278,7 +1282,7 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
         specialization_constants: Vss2,
     ) -> GraphicsPipelineBuilder<'vs2, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss2, Tcss, Tess, Gss, Fss>
     where
-        Vss2: SpecializationConstants,
+        Vss2: SpecializationData,
     {
         GraphicsPipelineBuilder {
             vertex_definition: self.vertex_definition,
@@ -1429,8 +1433,8 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
         tessellation_evaluation_shader_spec_constants: Tess2,
     ) -> GraphicsPipelineBuilder<'vs, 'tcs2, 'tes2, 'gs, 'fs, Vdef, Vss, Tcss2, Tess2, Gss, Fss>
     where
-        Tcss2: SpecializationConstants,
-        Tess2: SpecializationConstants,
+        Tcss2: SpecializationData,
+        Tess2: SpecializationData,
     {
         GraphicsPipelineBuilder {
             vertex_definition: self.vertex_definition,
@@ -1475,7 +1479,7 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
         specialization_constants: Gss2,
     ) -> GraphicsPipelineBuilder<'vs, 'tcs, 'tes, 'gs2, 'fs, Vdef, Vss, Tcss, Tess, Gss2, Fss>
     where
-        Gss2: SpecializationConstants,
+        Gss2: SpecializationData,
     {
         GraphicsPipelineBuilder {
             vertex_definition: self.vertex_definition,
@@ -1757,7 +1761,7 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
         specialization_constants: Fss2,
     ) -> GraphicsPipelineBuilder<'vs, 'tcs, 'tes, 'gs, 'fs2, Vdef, Vss, Tcss, Tess, Gss, Fss2>
     where
-        Fss2: SpecializationConstants,
+        Fss2: SpecializationData,
     {
         GraphicsPipelineBuilder {
             vertex_definition: self.vertex_definition,
@@ -1777,6 +1781,48 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
         }
     }
 
+    /// Sets all the fixed-function states at once: input assembly, viewports, rasterization,
+    /// multisampling, depth/stencil and blending.
+    ///
+    /// This overwrites anything that was previously set through the individual methods.
+    pub fn fixed_function_state(mut self, state: GraphicsPipelineState) -> Self {
+        let GraphicsPipelineState {
+            input_assembly,
+            viewports,
+            rasterization,
+            multisample,
+            depth_stencil,
+            blend,
+        } = state;
+
+        self = self
+            .primitive_topology(input_assembly.topology)
+            .primitive_restart(input_assembly.primitive_restart_enable);
+        self.viewport = Some(viewports);
+        self.raster = rasterization;
+
+        self.multisample.sample_shading_enable = if multisample.sample_shading.is_some() {
+            ash::vk::TRUE
+        } else {
+            ash::vk::FALSE
+        };
+        self.multisample.min_sample_shading = multisample.sample_shading.unwrap_or(0.0);
+        self = if multisample.alpha_to_coverage {
+            self.alpha_to_coverage_enabled()
+        } else {
+            self.alpha_to_coverage_disabled()
+        };
+        self = if multisample.alpha_to_one {
+            self.alpha_to_one_enabled()
+        } else {
+            self.alpha_to_one_disabled()
+        };
+
+        self.depth_stencil = depth_stencil;
+        self.blend = blend;
+        self
+    }
+
     /// Sets the depth/stencil configuration. This function may be removed in the future.
     #[inline]
     pub fn depth_stencil(mut self, depth_stencil: DepthStencil) -> Self {
@@ -1904,6 +1950,24 @@ impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
     }
 }
 
+// Builds the Vulkan specialization info of a shader stage. `map_entries` must have been obtained
+// from `spec_constants` and must outlive the returned struct.
+#[inline]
+fn specialization_info<S>(
+    map_entries: &[SpecializationMapEntry],
+    spec_constants: &S,
+) -> ash::vk::SpecializationInfo
+where
+    S: SpecializationData,
+{
+    ash::vk::SpecializationInfo {
+        map_entry_count: map_entries.len() as u32,
+        p_map_entries: map_entries.as_ptr() as *const _,
+        data_size: spec_constants.data_size(),
+        p_data: spec_constants.data_ptr(),
+    }
+}
+
 impl<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss> Clone
     for GraphicsPipelineBuilder<'vs, 'tcs, 'tes, 'gs, 'fs, Vdef, Vss, Tcss, Tess, Gss, Fss>
 where
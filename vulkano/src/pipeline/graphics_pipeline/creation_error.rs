@@ -11,6 +11,7 @@ use crate::pipeline::input_assembly::PrimitiveTopology;
 use crate::pipeline::layout::PipelineLayoutCreationError;
 use crate::pipeline::layout::PipelineLayoutSupersetError;
 use crate::pipeline::shader::ShaderInterfaceMismatchError;
+use crate::pipeline::shader::SpecializationMismatchError;
 use crate::pipeline::vertex::IncompatibleVertexDefinitionError;
 use crate::Error;
 use crate::OomError;
@@ -31,7 +32,7 @@ pub enum GraphicsPipelineCreationError {
     IncompatiblePipelineLayout(PipelineLayoutSupersetError),
 
     /// The provided specialization constants are not compatible with what the shader expects.
-    IncompatibleSpecializationConstants,
+    IncompatibleSpecializationConstants(SpecializationMismatchError),
 
     /// The output interface of one shader and the input interface of the next shader does not match.
     ShaderStagesMismatch(ShaderInterfaceMismatchError),
@@ -200,6 +201,9 @@ impl error::Error for GraphicsPipelineCreationError {
             GraphicsPipelineCreationError::OomError(ref err) => Some(err),
             GraphicsPipelineCreationError::PipelineLayoutCreationError(ref err) => Some(err),
             GraphicsPipelineCreationError::IncompatiblePipelineLayout(ref err) => Some(err),
+            GraphicsPipelineCreationError::IncompatibleSpecializationConstants(ref err) => {
+                Some(err)
+            }
             GraphicsPipelineCreationError::ShaderStagesMismatch(ref err) => Some(err),
             GraphicsPipelineCreationError::IncompatibleVertexDefinition(ref err) => Some(err),
             _ => None,
@@ -225,7 +229,7 @@ impl fmt::Display for GraphicsPipelineCreationError {
                 GraphicsPipelineCreationError::IncompatiblePipelineLayout(_) => {
                     "the pipeline layout is not compatible with what the shaders expect"
                 }
-                GraphicsPipelineCreationError::IncompatibleSpecializationConstants => {
+                GraphicsPipelineCreationError::IncompatibleSpecializationConstants(_) => {
                     "the provided specialization constants are not compatible with what the shader expects"
                 }
                 GraphicsPipelineCreationError::FragmentShaderRenderPassIncompatible => {
@@ -372,6 +376,13 @@ impl From<PipelineLayoutSupersetError> for GraphicsPipelineCreationError {
     }
 }
 
+impl From<SpecializationMismatchError> for GraphicsPipelineCreationError {
+    #[inline]
+    fn from(err: SpecializationMismatchError) -> GraphicsPipelineCreationError {
+        GraphicsPipelineCreationError::IncompatibleSpecializationConstants(err)
+    }
+}
+
 impl From<IncompatibleVertexDefinitionError> for GraphicsPipelineCreationError {
     #[inline]
     fn from(err: IncompatibleVertexDefinitionError) -> GraphicsPipelineCreationError {
@@ -9,10 +9,14 @@
 
 pub use self::builder::GraphicsPipelineBuilder;
 pub use self::creation_error::GraphicsPipelineCreationError;
+pub use self::state::GraphicsPipelineState;
 use crate::device::Device;
 use crate::device::DeviceOwned;
 use crate::pipeline::layout::PipelineLayout;
+use crate::pipeline::shader::GraphicsEntryPoint;
+use crate::pipeline::shader::ShaderInterface;
 use crate::pipeline::vertex::BuffersDefinition;
+use crate::pipeline::vertex::VertexDefinition;
 use crate::pipeline::vertex::VertexInput;
 use crate::render_pass::Subpass;
 use crate::VulkanObject;
@@ -26,6 +30,7 @@ use std::u32;
 
 mod builder;
 mod creation_error;
+mod state;
 // FIXME: restore
 //mod tests;
 
@@ -38,6 +43,7 @@ pub struct GraphicsPipeline {
     layout: Arc<PipelineLayout>,
     subpass: Subpass,
     vertex_input: VertexInput,
+    vertex_shader_input: ShaderInterface,
 
     dynamic_line_width: bool,
     dynamic_viewport: bool,
@@ -77,6 +83,53 @@ impl GraphicsPipeline {
         GraphicsPipelineBuilder::new()
     }
 
+    /// Starts the building process of a graphics pipeline, with the fixed-function state
+    /// initialized from `state`. The shaders, vertex input and subpass still need to be provided
+    /// to the returned builder.
+    #[inline]
+    pub fn start_with_state(
+        state: GraphicsPipelineState,
+    ) -> GraphicsPipelineBuilder<
+        'static,
+        'static,
+        'static,
+        'static,
+        'static,
+        BuffersDefinition,
+        (),
+        (),
+        (),
+        (),
+        (),
+    > {
+        GraphicsPipelineBuilder::new().fixed_function_state(state)
+    }
+
+    /// Builds a graphics pipeline from its fixed-function state and its vertex and fragment
+    /// entry points, using an inferred pipeline layout.
+    ///
+    /// This is meant for shaders that are only known at runtime, whose entry points are obtained
+    /// with `ShaderModule::reflected_graphics_entry_point`. The specialization constants of the
+    /// shaders keep their default values.
+    pub fn from_state<Vdef>(
+        device: Arc<Device>,
+        state: GraphicsPipelineState,
+        vertex_input: Vdef,
+        vertex_shader: GraphicsEntryPoint,
+        fragment_shader: GraphicsEntryPoint,
+        subpass: Subpass,
+    ) -> Result<GraphicsPipeline, GraphicsPipelineCreationError>
+    where
+        Vdef: VertexDefinition,
+    {
+        GraphicsPipeline::start_with_state(state)
+            .vertex_input(vertex_input)
+            .vertex_shader(vertex_shader, ())
+            .fragment_shader(fragment_shader, ())
+            .render_pass(subpass)
+            .build(device)
+    }
+
     /// Returns the device used to create this pipeline.
     #[inline]
     pub fn device(&self) -> &Arc<Device> {
@@ -101,6 +154,15 @@ impl GraphicsPipeline {
         &self.vertex_input
     }
 
+    /// Returns the location of the input of the vertex shader with the given name, or `None` if
+    /// there is no such input or if the names of the inputs are unknown.
+    #[inline]
+    pub fn vertex_input_location_by_name(&self, name: &str) -> Option<u32> {
+        self.vertex_shader_input
+            .element_by_name(name)
+            .map(|element| element.location.start)
+    }
+
     /// Returns the number of viewports and scissors of this pipeline.
     #[inline]
     pub fn num_viewports(&self) -> u32 {
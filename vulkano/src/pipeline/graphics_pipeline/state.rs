@@ -0,0 +1,145 @@
+// Copyright (c) 2021 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+// TODO: the input assembly state struct is deprecated, but it is the only plain-data description
+// of this stage for now
+#![allow(deprecated)]
+
+use crate::pipeline::blend::Blend;
+use crate::pipeline::depth_stencil::DepthStencil;
+use crate::pipeline::input_assembly::InputAssembly;
+use crate::pipeline::multisample::Multisample;
+use crate::pipeline::raster::Rasterization;
+use crate::pipeline::viewport::ViewportsState;
+
+/// Description of the fixed-function state of a graphics pipeline.
+///
+/// Contrary to `GraphicsPipelineBuilder`, this type isn't generic over the shaders and the vertex
+/// definition. It is therefore possible to store it in a `HashMap`, to compare descriptions in
+/// order to deduplicate pipelines, or (with the `serde` feature) to load it from a file.
+///
+/// Use `GraphicsPipeline::start_with_state` or `GraphicsPipelineBuilder::fixed_function_state`
+/// to build a pipeline from it, or `GraphicsPipeline::from_state` if the shaders are only known
+/// at runtime.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GraphicsPipelineState {
+    /// How vertices are assembled into primitives.
+    pub input_assembly: InputAssembly,
+
+    /// The viewports and scissor boxes.
+    pub viewports: ViewportsState,
+
+    /// State of the rasterizer.
+    pub rasterization: Rasterization,
+
+    /// State of the multisampling.
+    pub multisample: Multisample,
+
+    /// Configuration of the depth and stencil tests.
+    pub depth_stencil: DepthStencil,
+
+    /// How the fragment shader outputs are blended into the attachments.
+    pub blend: Blend,
+}
+
+impl Default for GraphicsPipelineState {
+    /// Returns the same state as a newly-created `GraphicsPipelineBuilder`, with a single dynamic
+    /// viewport and scissor box.
+    #[inline]
+    fn default() -> GraphicsPipelineState {
+        GraphicsPipelineState {
+            input_assembly: InputAssembly::triangle_list(),
+            viewports: ViewportsState::Dynamic { num: 1 },
+            rasterization: Rasterization::default(),
+            multisample: Multisample::disabled(),
+            depth_stencil: DepthStencil::disabled(),
+            blend: Blend::pass_through(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::depth_stencil::DepthStencil;
+    use crate::pipeline::viewport::Viewport;
+    use crate::pipeline::viewport::ViewportsState;
+    use crate::pipeline::GraphicsPipelineState;
+    use std::collections::HashSet;
+
+    #[test]
+    fn dedup_in_hash_set() {
+        let a = GraphicsPipelineState::default();
+        let b = GraphicsPipelineState {
+            depth_stencil: DepthStencil::simple_depth_test(),
+            ..GraphicsPipelineState::default()
+        };
+
+        let mut set = HashSet::new();
+        assert!(set.insert(a.clone()));
+        assert!(set.insert(b.clone()));
+        assert!(!set.insert(a));
+        assert!(!set.insert(b));
+    }
+
+    #[test]
+    fn negative_zero_equal() {
+        let state = |x| GraphicsPipelineState {
+            viewports: ViewportsState::DynamicScissors {
+                viewports: vec![Viewport {
+                    origin: [x, 0.0],
+                    dimensions: [16.0, 16.0],
+                    depth_range: 0.0..1.0,
+                }],
+            },
+            ..GraphicsPipelineState::default()
+        };
+
+        let mut set = HashSet::new();
+        assert!(set.insert(state(0.0)));
+        assert!(!set.insert(state(-0.0)));
+        assert!(set.insert(state(1.0)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        use crate::pipeline::multisample::Multisample;
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::Hash;
+        use std::hash::Hasher;
+
+        let hash = |state: &GraphicsPipelineState| {
+            let mut hasher = DefaultHasher::new();
+            state.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        let state = GraphicsPipelineState {
+            viewports: ViewportsState::DynamicScissors {
+                viewports: vec![Viewport {
+                    origin: [-0.0, 8.5],
+                    dimensions: [16.0, 16.0],
+                    depth_range: 0.0..1.0,
+                }],
+            },
+            multisample: Multisample {
+                sample_shading: Some(0.25),
+                ..Multisample::disabled()
+            },
+            depth_stencil: DepthStencil::simple_depth_test(),
+            ..GraphicsPipelineState::default()
+        };
+
+        let json = serde_json::to_string(&state).unwrap();
+        let deserialized: GraphicsPipelineState = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized, state);
+        assert_eq!(hash(&deserialized), hash(&state));
+    }
+}
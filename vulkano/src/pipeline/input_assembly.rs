@@ -15,7 +15,8 @@
 use crate::DeviceSize;
 
 /// How the input assembly stage should behave.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[deprecated]
 pub struct InputAssembly {
     /// The type of primitives.
@@ -44,7 +45,8 @@ impl InputAssembly {
 /// Describes how vertices must be grouped together to form primitives.
 ///
 /// Note that some topologies don't support primitive restart.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PrimitiveTopology {
     PointList,
     LineList,
@@ -70,10 +70,13 @@
 //! pipeline layouts.
 
 pub use self::limits_check::PipelineLayoutLimitsError;
+pub use self::push_constants::PushConstantMember;
+pub use self::push_constants::PushConstantsLayout;
 pub use self::sys::PipelineLayout;
 pub use self::sys::PipelineLayoutCreationError;
 pub use self::sys::PipelineLayoutPcRange;
 pub use self::sys::PipelineLayoutSupersetError;
 
 mod limits_check;
+mod push_constants;
 mod sys;
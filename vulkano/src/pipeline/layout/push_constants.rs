@@ -0,0 +1,53 @@
+// Copyright (c) 2021 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::borrow::Cow;
+
+/// A Rust type whose memory layout matches the push constant block of a shader.
+///
+/// The `shader!` macro of `vulkano-shaders` implements this trait for the struct generated for
+/// each push constant block, and names it `PushConstants`. Passing such a type to
+/// [`push_constants_typed`](crate::command_buffer::AutoCommandBufferBuilder::push_constants_typed)
+/// checks that it is the type that the shaders of the pipeline expect, instead of only checking
+/// its size.
+///
+/// The type starts at offset 0 of the push constants, like the block in the shader. If the block
+/// only uses a range that starts further, the bytes of the type before `OFFSET` are padding and
+/// are not written.
+///
+/// # Safety
+///
+/// - The type must be `#[repr(C)]` and its members must be at the offsets given by the shader.
+/// - `OFFSET + SIZE` must not be larger than the size of the type.
+///
+pub unsafe trait PushConstantsLayout: Send + Sync + 'static {
+    /// Offset in bytes of the first member of the block. Multiple of 4.
+    const OFFSET: u32;
+
+    /// Size in bytes of the range from the first member to the end of the type. Multiple of 4.
+    const SIZE: u32;
+
+    /// Hash of the offset and type of each member of the block. Two types with the same hash are
+    /// interchangeable.
+    const LAYOUT_HASH: u64;
+
+    /// The members of the block, with their names in the shader.
+    const MEMBERS: &'static [PushConstantMember] = &[];
+}
+
+/// A member of a push constant block.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PushConstantMember {
+    /// Name of the member in the shader.
+    pub name: Cow<'static, str>,
+    /// Offset in bytes of the member from the start of the push constants.
+    pub offset: u32,
+    /// Size in bytes of the member.
+    pub size: u32,
+}
@@ -16,6 +16,7 @@ use crate::descriptor_set::layout::DescriptorSetLayoutError;
 use crate::device::Device;
 use crate::device::DeviceOwned;
 use crate::pipeline::layout::PipelineLayoutLimitsError;
+use crate::pipeline::layout::PushConstantMember;
 use crate::pipeline::shader::ShaderStages;
 use crate::Error;
 use crate::OomError;
@@ -35,6 +36,8 @@ pub struct PipelineLayout {
     device: Arc<Device>,
     descriptor_set_layouts: SmallVec<[Arc<DescriptorSetLayout>; 16]>,
     push_constant_ranges: SmallVec<[PipelineLayoutPcRange; 8]>,
+    push_constants_layout_hashes: SmallVec<[(ShaderStages, u64); 5]>,
+    push_constant_members: Vec<PushConstantMember>,
 }
 
 impl PipelineLayout {
@@ -160,6 +163,8 @@ impl PipelineLayout {
             device: device.clone(),
             descriptor_set_layouts,
             push_constant_ranges,
+            push_constants_layout_hashes: SmallVec::new(),
+            push_constant_members: Vec::new(),
         })
     }
 
@@ -178,6 +183,66 @@ impl PipelineLayout {
         &self.push_constant_ranges
     }
 
+    /// Returns the [`LAYOUT_HASH`](super::PushConstantsLayout::LAYOUT_HASH) of the push
+    /// constants expected by each shader stage, when it is known.
+    ///
+    /// It is known for the stages of the pipelines that created this layout automatically, if the
+    /// entry points were given their push constants type with `with_push_constants`.
+    #[inline]
+    pub fn push_constants_layout_hashes(&self) -> &[(ShaderStages, u64)] {
+        &self.push_constants_layout_hashes
+    }
+
+    // Records the push constants type expected by the given stages.
+    pub(crate) fn set_push_constants_layout_hash(&mut self, stages: ShaderStages, hash: u64) {
+        self.push_constants_layout_hashes.push((stages, hash));
+    }
+
+    /// Returns the set and binding numbers of the descriptor with the given name in the shaders,
+    /// or `None` if there is no such descriptor.
+    ///
+    /// The names are only known if the descriptor set layouts were created from the reflected
+    /// descriptions of the shaders, for example when the pipeline created this layout
+    /// automatically.
+    #[inline]
+    pub fn binding_by_name(&self, name: &str) -> Option<(u32, u32)> {
+        self.descriptor_set_layouts
+            .iter()
+            .enumerate()
+            .find_map(|(set_num, layout)| {
+                let binding_num = layout.desc().binding_by_name(name)?;
+                Some((set_num as u32, binding_num))
+            })
+    }
+
+    /// Returns the members of the push constant blocks of the shaders, when they are known.
+    ///
+    /// They are known for the pipelines that created this layout automatically, if the entry
+    /// points described their push constant members.
+    #[inline]
+    pub fn push_constant_members(&self) -> &[PushConstantMember] {
+        &self.push_constant_members
+    }
+
+    /// Returns the member of the push constant blocks with the given name in the shaders, or
+    /// `None` if there is no such member or if the members are not known.
+    #[inline]
+    pub fn push_constant_member_by_name(&self, name: &str) -> Option<&PushConstantMember> {
+        self.push_constant_members
+            .iter()
+            .find(|member| member.name == name)
+    }
+
+    // Records the members of the push constant block of a shader. Members that are already known
+    // are ignored.
+    pub(crate) fn add_push_constant_members(&mut self, members: &[PushConstantMember]) {
+        for member in members {
+            if !self.push_constant_members.contains(member) {
+                self.push_constant_members.push(member.clone());
+            }
+        }
+    }
+
     /// Makes sure that `self` is a superset of the provided descriptor set layouts and push
     /// constant ranges. Returns an `Err` if this is not the case.
     pub fn ensure_compatible_with_shader(
@@ -251,6 +316,11 @@ impl fmt::Debug for PipelineLayout {
             .field("device", &self.device)
             .field("descriptor_set_layouts", &self.descriptor_set_layouts)
             .field("push_constant_ranges", &self.push_constant_ranges)
+            .field(
+                "push_constants_layout_hashes",
+                &self.push_constants_layout_hashes,
+            )
+            .field("push_constant_members", &self.push_constant_members)
             .finish()
     }
 }
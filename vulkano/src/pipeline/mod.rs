@@ -75,12 +75,16 @@
 // to avoid duplicating code, so we hide the warnings for now
 #![allow(deprecated)]
 
+use std::hash::Hasher;
+
 pub use self::compute_pipeline::ComputePipeline;
 pub use self::compute_pipeline::ComputePipelineCreationError;
 pub use self::compute_pipeline::ComputePipelineSys;
+pub use self::compute_pipeline::SubgroupSizeControl;
 pub use self::graphics_pipeline::GraphicsPipeline;
 pub use self::graphics_pipeline::GraphicsPipelineBuilder;
 pub use self::graphics_pipeline::GraphicsPipelineCreationError;
+pub use self::graphics_pipeline::GraphicsPipelineState;
 pub use self::graphics_pipeline::GraphicsPipelineSys;
 
 pub mod blend;
@@ -109,3 +113,15 @@ impl From<PipelineBindPoint> for ash::vk::PipelineBindPoint {
         Self::from_raw(val as i32)
     }
 }
+
+// Hashes a floating-point value that is part of a fixed-function state.
+//
+// The fixed-function states that contain floating-point values (blend constants, depth bounds,
+// line width, depth bias, viewports, sample shading) implement `Eq` and `Hash` with this
+// function. This assumes that none of these values is NaN, which is never the case in a valid
+// pipeline state. `0.0` and `-0.0` compare equal, so they must also hash to the same value.
+#[inline]
+pub(crate) fn hash_f32<H: Hasher>(value: f32, state: &mut H) {
+    let value = if value == 0.0 { 0.0 } else { value };
+    state.write_u32(value.to_bits());
+}
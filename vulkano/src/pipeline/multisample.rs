@@ -34,14 +34,17 @@
 
 // TODO: handle some weird behaviors with non-floating-point targets
 
+use crate::pipeline::hash_f32;
+use std::hash::Hash;
+use std::hash::Hasher;
+
 /// State of the multisampling.
 ///
-/// See the documentation in this module.
-#[deprecated(note = "No longer needed")]
-#[derive(Debug, Copy, Clone)]
+/// See the documentation in this module. The number of samples isn't part of this state, as it
+/// is always determined by the subpass the pipeline is rendering to.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Multisample {
-    pub rasterization_samples: u32,
-    pub sample_mask: [u32; 4],
     pub sample_shading: Option<f32>,
     pub alpha_to_coverage: bool,
     pub alpha_to_one: bool,
@@ -51,11 +54,23 @@ impl Multisample {
     #[inline]
     pub fn disabled() -> Multisample {
         Multisample {
-            rasterization_samples: 1,
-            sample_mask: [0xffffffff; 4],
             sample_shading: None,
             alpha_to_coverage: false,
             alpha_to_one: false,
         }
     }
 }
+
+impl Eq for Multisample {}
+
+impl Hash for Multisample {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.sample_shading.is_some().hash(state);
+        if let Some(sample_shading) = self.sample_shading {
+            hash_f32(sample_shading, state);
+        }
+        self.alpha_to_coverage.hash(state);
+        self.alpha_to_one.hash(state);
+    }
+}
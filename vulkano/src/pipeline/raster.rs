@@ -13,8 +13,13 @@
 //! of pixels or samples.
 //!
 
+use crate::pipeline::hash_f32;
+use std::hash::Hash;
+use std::hash::Hasher;
+
 /// State of the rasterizer.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rasterization {
     /// If true, then the depth value of the vertices will be clamped to [0.0 ; 1.0]. If false,
     /// fragments whose depth is outside of this range will be discarded.
@@ -58,7 +63,26 @@ impl Default for Rasterization {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+impl Eq for Rasterization {}
+
+impl Hash for Rasterization {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.depth_clamp.hash(state);
+        self.rasterizer_discard.hash(state);
+        self.polygon_mode.hash(state);
+        self.cull_mode.hash(state);
+        self.front_face.hash(state);
+        self.line_width.is_some().hash(state);
+        if let Some(line_width) = self.line_width {
+            hash_f32(line_width, state);
+        }
+        self.depth_bias.hash(state);
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DepthBiasControl {
     Disabled,
     Dynamic,
@@ -75,7 +99,8 @@ impl DepthBiasControl {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DepthBias {
     pub constant_factor: f32,
     /// Requires the `depth_bias_clamp` feature to be enabled.
@@ -83,13 +108,25 @@ pub struct DepthBias {
     pub slope_factor: f32,
 }
 
+impl Eq for DepthBias {}
+
+impl Hash for DepthBias {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_f32(self.constant_factor, state);
+        hash_f32(self.clamp, state);
+        hash_f32(self.slope_factor, state);
+    }
+}
+
 /// Specifies the culling mode.
 ///
 /// This setting works in pair with `front_face`. The `front_face` setting tells the GPU whether
 /// clockwise or counter-clockwise correspond to the front and the back of each triangle. Then
 /// `cull_mode` lets you specify whether front faces should be discarded, back faces should be
 /// discarded, or none, or both.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u32)]
 pub enum CullMode {
     /// No culling.
@@ -117,7 +154,8 @@ impl Default for CullMode {
 }
 
 /// Specifies which triangle orientation corresponds to the front or the triangle.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(i32)]
 pub enum FrontFace {
     /// Triangles whose vertices are oriented counter-clockwise on the screen will be considered
@@ -143,7 +181,8 @@ impl Default for FrontFace {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(i32)]
 pub enum PolygonMode {
     Fill = ash::vk::PolygonMode::FILL.as_raw(),
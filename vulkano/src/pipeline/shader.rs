@@ -13,22 +13,37 @@
 //! code and can contain one or more entry points. Note that for the moment the official
 //! GLSL-to-SPIR-V compiler does not support multiple entry points.
 //!
-//! The vulkano library does not provide any functionality that checks and introspects the SPIR-V
-//! code, therefore the whole shader-related API is unsafe. You are encouraged to use the
-//! `vulkano-shaders` crate that will generate Rust code that wraps around vulkano's shaders API.
+//! The vulkano library does not provide any functionality that checks the SPIR-V code, therefore
+//! the whole shader-related API is unsafe. You are encouraged to use the `vulkano-shaders` crate
+//! that will generate Rust code that wraps around vulkano's shaders API.
+//!
+//! Shader modules that are only known at runtime can be created with
+//! `ShaderModule::from_words_with_reflection`, which introspects the SPIR-V code to describe
+//! its entry points in the same way as the code generated by `vulkano-shaders`.
 
 use crate::check_errors;
 use crate::descriptor_set::layout::DescriptorSetDesc;
 use crate::device::Device;
+use crate::device::DeviceExtensions;
+use crate::device::Features;
 use crate::format::Format;
 use crate::pipeline::input_assembly::PrimitiveTopology;
 use crate::pipeline::layout::PipelineLayoutPcRange;
+use crate::pipeline::layout::PushConstantMember;
+use crate::pipeline::layout::PushConstantsLayout;
+use crate::spirv::{Spirv, SpirvError};
 use crate::sync::PipelineStages;
 use crate::OomError;
+use crate::Version;
 use crate::VulkanObject;
+use half::f16;
+use smallvec::SmallVec;
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::error;
+use std::ffi::c_void;
 use std::ffi::CStr;
+use std::ffi::CString;
 use std::fmt;
 use std::mem;
 use std::mem::MaybeUninit;
@@ -37,6 +52,8 @@ use std::ops::Range;
 use std::ptr;
 use std::sync::Arc;
 
+mod reflect;
+
 /// Contains SPIR-V code with one or more entry points.
 ///
 /// Note that it is advised to wrap around a `ShaderModule` with a struct that is different for
@@ -47,6 +64,8 @@ pub struct ShaderModule {
     module: ash::vk::ShaderModule,
     // Pointer to the device.
     device: Arc<Device>,
+    // The entry points of the module, if it was created with reflection.
+    entry_points: Vec<EntryPointInfo>,
 }
 
 impl ShaderModule {
@@ -60,7 +79,7 @@ impl ShaderModule {
     ///
     pub unsafe fn new(device: Arc<Device>, spirv: &[u8]) -> Result<Arc<ShaderModule>, OomError> {
         debug_assert!((spirv.len() % 4) == 0);
-        Self::from_ptr(device, spirv.as_ptr() as *const _, spirv.len())
+        Self::from_ptr(device, spirv.as_ptr() as *const _, spirv.len(), Vec::new())
     }
 
     /// Builds a new shader module from SPIR-V 32-bit words.
@@ -75,7 +94,42 @@ impl ShaderModule {
         device: Arc<Device>,
         spirv: &[u32],
     ) -> Result<Arc<ShaderModule>, OomError> {
-        Self::from_ptr(device, spirv.as_ptr(), spirv.len() * mem::size_of::<u32>())
+        Self::from_ptr(
+            device,
+            spirv.as_ptr(),
+            spirv.len() * mem::size_of::<u32>(),
+            Vec::new(),
+        )
+    }
+
+    /// Builds a new shader module from SPIR-V 32-bit words, and reflects the SPIR-V code to
+    /// obtain the description of its entry points.
+    ///
+    /// The entry points can then be obtained with `reflected_graphics_entry_point` and
+    /// `reflected_compute_entry_point`, without having to describe them manually. They are
+    /// described in the same way as by the code generated by `vulkano-shaders`:
+    /// `exact_entrypoint_interface` has the same meaning as the option of the `shader!` macro.
+    /// If it is false, every entry point is assumed to use all the descriptors of the module.
+    ///
+    /// # Safety
+    ///
+    /// - The SPIR-V code is not validated.
+    /// - The SPIR-V code may require some features that are not enabled. This isn't checked by
+    ///   this function either.
+    ///
+    pub unsafe fn from_words_with_reflection(
+        device: Arc<Device>,
+        spirv: &[u32],
+        exact_entrypoint_interface: bool,
+    ) -> Result<Arc<ShaderModule>, ShaderCreationError> {
+        let entry_points = EntryPointInfo::from_spirv(spirv, exact_entrypoint_interface)?;
+
+        Ok(Self::from_ptr(
+            device,
+            spirv.as_ptr(),
+            spirv.len() * mem::size_of::<u32>(),
+            entry_points,
+        )?)
     }
 
     /// Builds a new shader module from SPIR-V.
@@ -90,6 +144,7 @@ impl ShaderModule {
         device: Arc<Device>,
         spirv: *const u32,
         spirv_len: usize,
+        entry_points: Vec<EntryPointInfo>,
     ) -> Result<Arc<ShaderModule>, OomError> {
         let module = {
             let infos = ash::vk::ShaderModuleCreateInfo {
@@ -113,6 +168,7 @@ impl ShaderModule {
         Ok(Arc::new(ShaderModule {
             module: module,
             device: device,
+            entry_points,
         }))
     }
 
@@ -127,26 +183,31 @@ impl ShaderModule {
     ///   by Vulkan.
     /// - The input, output and layout must correctly describe the input, output and layout used
     ///   by this stage.
+    /// - The specialization constants must correctly describe the constants declared in the
+    ///   module, indexed by their constant id.
     ///
-    pub unsafe fn graphics_entry_point<'a, D>(
+    pub unsafe fn graphics_entry_point<'a, D, S>(
         &'a self,
         name: &'a CStr,
         descriptor_set_layout_descs: D,
         push_constant_range: Option<PipelineLayoutPcRange>,
-        spec_constants: &'static [SpecializationMapEntry],
+        spec_constants: S,
         input: ShaderInterface,
         output: ShaderInterface,
         ty: GraphicsShaderType,
     ) -> GraphicsEntryPoint<'a>
     where
         D: IntoIterator<Item = DescriptorSetDesc>,
+        S: IntoIterator<Item = (u32, SpecializationConstantRequirements)>,
     {
         GraphicsEntryPoint {
             module: self,
             name,
             descriptor_set_layout_descs: descriptor_set_layout_descs.into_iter().collect(),
             push_constant_range,
-            spec_constants,
+            push_constants_layout_hash: None,
+            push_constant_members: Vec::new(),
+            spec_constants: spec_constants.into_iter().collect(),
             input,
             output,
             ty,
@@ -163,26 +224,87 @@ impl ShaderModule {
     /// - The user must check that the entry point exists in the module, as this is not checked
     ///   by Vulkan.
     /// - The layout must correctly describe the layout used by this stage.
+    /// - The specialization constants must correctly describe the constants declared in the
+    ///   module, indexed by their constant id.
     ///
     #[inline]
-    pub unsafe fn compute_entry_point<'a, D>(
+    pub unsafe fn compute_entry_point<'a, D, S>(
         &'a self,
         name: &'a CStr,
         descriptor_set_layout_descs: D,
         push_constant_range: Option<PipelineLayoutPcRange>,
-        spec_constants: &'static [SpecializationMapEntry],
+        spec_constants: S,
     ) -> ComputeEntryPoint<'a>
     where
         D: IntoIterator<Item = DescriptorSetDesc>,
+        S: IntoIterator<Item = (u32, SpecializationConstantRequirements)>,
     {
         ComputeEntryPoint {
             module: self,
             name,
             descriptor_set_layout_descs: descriptor_set_layout_descs.into_iter().collect(),
             push_constant_range,
-            spec_constants,
+            push_constants_layout_hash: None,
+            push_constant_members: Vec::new(),
+            spec_constants: spec_constants.into_iter().collect(),
+            local_size: None,
         }
     }
+
+    /// Returns the entry points of the module, if it was created with
+    /// `from_words_with_reflection`. Returns an empty slice otherwise.
+    #[inline]
+    pub fn entry_points(&self) -> &[EntryPointInfo] {
+        &self.entry_points
+    }
+
+    /// Returns the graphics entry point named `name`, as described by reflecting the module.
+    ///
+    /// Returns `None` if the module wasn't created with `from_words_with_reflection`, or if it
+    /// has no graphics entry point with this name.
+    pub fn reflected_graphics_entry_point(&self, name: &str) -> Option<GraphicsEntryPoint> {
+        self.entry_points
+            .iter()
+            .filter(|info| info.name.as_bytes() == name.as_bytes())
+            .find_map(|info| match info.execution {
+                ShaderExecution::Graphics(ty) => Some(GraphicsEntryPoint {
+                    module: self,
+                    name: &info.name,
+                    descriptor_set_layout_descs: info.descriptor_set_layout_descs.clone(),
+                    push_constant_range: info.push_constant_range,
+                    push_constants_layout_hash: None,
+                    push_constant_members: info.push_constant_members.clone(),
+                    spec_constants: info.spec_constants.clone(),
+                    input: info.input.clone(),
+                    output: info.output.clone(),
+                    ty,
+                }),
+                ShaderExecution::Compute(_) => None,
+            })
+    }
+
+    /// Returns the compute entry point named `name`, as described by reflecting the module.
+    ///
+    /// Returns `None` if the module wasn't created with `from_words_with_reflection`, or if it
+    /// has no compute entry point with this name.
+    pub fn reflected_compute_entry_point(&self, name: &str) -> Option<ComputeEntryPoint> {
+        self.entry_points
+            .iter()
+            .filter(|info| info.name.as_bytes() == name.as_bytes())
+            .find_map(|info| match info.execution {
+                ShaderExecution::Compute(local_size) => Some(ComputeEntryPoint {
+                    module: self,
+                    name: &info.name,
+                    descriptor_set_layout_descs: info.descriptor_set_layout_descs.clone(),
+                    push_constant_range: info.push_constant_range,
+                    push_constants_layout_hash: None,
+                    push_constant_members: info.push_constant_members.clone(),
+                    spec_constants: info.spec_constants.clone(),
+                    local_size: Some(local_size),
+                }),
+                ShaderExecution::Graphics(_) => None,
+            })
+    }
 }
 
 unsafe impl VulkanObject for ShaderModule {
@@ -205,6 +327,246 @@ impl Drop for ShaderModule {
     }
 }
 
+/// The device requirements of a shader module, as returned by the `requirements` function that
+/// `vulkano-shaders` generates.
+///
+/// When a requirement can be met in several ways, for example with either Vulkan 1.2 or the
+/// `khr_spirv_1_4` extension, only the first one is included. The resulting values can be used
+/// to create a device that can load the shader.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShaderRequirements {
+    /// The minimum API version of the device.
+    pub api_version: Version,
+    /// The extensions that must be enabled on the device.
+    pub device_extensions: DeviceExtensions,
+    /// The features that must be enabled on the device.
+    pub features: Features,
+}
+
+/// A single requirement of a shader module on the device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShaderRequirement {
+    /// The API version of the device must be at least this version.
+    ApiVersion(Version),
+    /// This device extension must be enabled, named after its field in `DeviceExtensions`.
+    DeviceExtension(&'static str),
+    /// This feature must be enabled, named after its field in `Features`.
+    Feature(&'static str),
+}
+
+impl fmt::Display for ShaderRequirement {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            ShaderRequirement::ApiVersion(version) => write!(fmt, "API version {}", version),
+            ShaderRequirement::DeviceExtension(extension) => {
+                write!(fmt, "extension {}", extension)
+            }
+            ShaderRequirement::Feature(feature) => write!(fmt, "feature {}", feature),
+        }
+    }
+}
+
+/// Error that can happen when loading a shader generated by `vulkano-shaders`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShaderLoadError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The device doesn't meet some of the requirements of the shader.
+    ///
+    /// Each element is a requirement that isn't met, given as a list of alternatives. Meeting any
+    /// one of them is enough.
+    RequirementsNotMet(Vec<&'static [ShaderRequirement]>),
+}
+
+impl error::Error for ShaderLoadError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            ShaderLoadError::OomError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ShaderLoadError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            ShaderLoadError::OomError(_) => write!(fmt, "not enough memory available"),
+            ShaderLoadError::RequirementsNotMet(ref missing) => {
+                write!(
+                    fmt,
+                    "the device doesn't meet the requirements of the shader:"
+                )?;
+
+                for (index, alternatives) in missing.iter().enumerate() {
+                    write!(fmt, "{}", if index == 0 { " " } else { "; " })?;
+
+                    if alternatives.len() > 1 {
+                        write!(fmt, "one of ")?;
+                    }
+
+                    for (index, requirement) in alternatives.iter().enumerate() {
+                        if index != 0 {
+                            write!(fmt, ", ")?;
+                        }
+
+                        write!(fmt, "{}", requirement)?;
+                    }
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl From<OomError> for ShaderLoadError {
+    #[inline]
+    fn from(err: OomError) -> ShaderLoadError {
+        ShaderLoadError::OomError(err)
+    }
+}
+
+/// Error that can happen when reflecting a shader module with `from_words_with_reflection`.
+#[derive(Clone, Debug)]
+pub enum ShaderCreationError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The SPIR-V code couldn't be parsed.
+    SpirvError(SpirvError),
+
+    /// The SPIR-V code couldn't be reflected, because it is invalid or uses something that isn't
+    /// supported.
+    ReflectionError(String),
+}
+
+impl error::Error for ShaderCreationError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            ShaderCreationError::OomError(ref err) => Some(err),
+            ShaderCreationError::SpirvError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ShaderCreationError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            ShaderCreationError::OomError(_) => write!(fmt, "not enough memory available"),
+            ShaderCreationError::SpirvError(_) => write!(fmt, "the SPIR-V code couldn't be parsed"),
+            ShaderCreationError::ReflectionError(ref msg) => {
+                write!(fmt, "the SPIR-V code couldn't be reflected: {}", msg)
+            }
+        }
+    }
+}
+
+impl From<OomError> for ShaderCreationError {
+    #[inline]
+    fn from(err: OomError) -> ShaderCreationError {
+        ShaderCreationError::OomError(err)
+    }
+}
+
+impl From<SpirvError> for ShaderCreationError {
+    #[inline]
+    fn from(err: SpirvError) -> ShaderCreationError {
+        ShaderCreationError::SpirvError(err)
+    }
+}
+
+/// The description of an entry point of a shader module, obtained by reflecting its SPIR-V code.
+#[derive(Clone, Debug)]
+pub struct EntryPointInfo {
+    name: CString,
+    execution: ShaderExecution,
+    descriptor_set_layout_descs: Vec<DescriptorSetDesc>,
+    push_constant_range: Option<PipelineLayoutPcRange>,
+    push_constant_members: Vec<PushConstantMember>,
+    spec_constants: HashMap<u32, SpecializationConstantRequirements>,
+    input: ShaderInterface,
+    output: ShaderInterface,
+}
+
+impl EntryPointInfo {
+    /// Reflects SPIR-V code to describe all its entry points, without creating a shader module.
+    ///
+    /// These are the entry points that a module created with
+    /// `ShaderModule::from_words_with_reflection` from the same code and with the same
+    /// `exact_entrypoint_interface` has.
+    #[inline]
+    pub fn from_spirv(
+        spirv: &[u32],
+        exact_entrypoint_interface: bool,
+    ) -> Result<Vec<EntryPointInfo>, ShaderCreationError> {
+        reflect::entry_points(&Spirv::new(spirv)?, exact_entrypoint_interface)
+    }
+
+    /// Returns the name of the entry point.
+    #[inline]
+    pub fn name(&self) -> &CStr {
+        &self.name
+    }
+
+    /// Returns the kind of shader of the entry point.
+    #[inline]
+    pub fn execution(&self) -> &ShaderExecution {
+        &self.execution
+    }
+
+    /// Returns a description of the descriptor set layouts.
+    #[inline]
+    pub fn descriptor_set_layout_descs(&self) -> &[DescriptorSetDesc] {
+        &self.descriptor_set_layout_descs
+    }
+
+    /// Returns the push constant range.
+    #[inline]
+    pub fn push_constant_range(&self) -> &Option<PipelineLayoutPcRange> {
+        &self.push_constant_range
+    }
+
+    /// Returns the members of the push constant block.
+    #[inline]
+    pub fn push_constant_members(&self) -> &[PushConstantMember] {
+        &self.push_constant_members
+    }
+
+    /// Returns the specialization constants declared in the module, indexed by constant id.
+    #[inline]
+    pub fn spec_constants(&self) -> &HashMap<u32, SpecializationConstantRequirements> {
+        &self.spec_constants
+    }
+
+    /// Returns the input attributes of the entry point. Empty for compute shaders.
+    #[inline]
+    pub fn input(&self) -> &ShaderInterface {
+        &self.input
+    }
+
+    /// Returns the output attributes of the entry point. Empty for compute shaders.
+    #[inline]
+    pub fn output(&self) -> &ShaderInterface {
+        &self.output
+    }
+}
+
+/// The kind of shader of an entry point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShaderExecution {
+    /// A stage of a graphics pipeline.
+    Graphics(GraphicsShaderType),
+    /// A compute shader with the given local workgroup size.
+    Compute([LocalSizeValue; 3]),
+}
+
 pub unsafe trait EntryPointAbstract {
     /// Returns the module this entry point comes from.
     fn module(&self) -> &ShaderModule;
@@ -218,8 +580,28 @@ pub unsafe trait EntryPointAbstract {
     /// Returns the push constant ranges.
     fn push_constant_range(&self) -> &Option<PipelineLayoutPcRange>;
 
-    /// Returns the layout of the specialization constants.
-    fn spec_constants(&self) -> &[SpecializationMapEntry];
+    /// Returns the [`LAYOUT_HASH`](PushConstantsLayout::LAYOUT_HASH) of the type of the push
+    /// constants, if it is known.
+    #[inline]
+    fn push_constants_layout_hash(&self) -> Option<u64> {
+        None
+    }
+
+    /// Returns the members of the push constant block, with their names, if they are known.
+    #[inline]
+    fn push_constant_members(&self) -> &[PushConstantMember] {
+        &[]
+    }
+
+    /// Returns the specialization constants declared in the module, indexed by constant id.
+    fn spec_constants(&self) -> &HashMap<u32, SpecializationConstantRequirements>;
+
+    /// Returns the local workgroup size declared by the entry point, if it is a compute entry
+    /// point.
+    #[inline]
+    fn local_size(&self) -> Option<&[LocalSizeValue; 3]> {
+        None
+    }
 }
 
 /// Represents a shader entry point in a shader module.
@@ -232,7 +614,9 @@ pub struct GraphicsEntryPoint<'a> {
 
     descriptor_set_layout_descs: Vec<DescriptorSetDesc>,
     push_constant_range: Option<PipelineLayoutPcRange>,
-    spec_constants: &'static [SpecializationMapEntry],
+    push_constants_layout_hash: Option<u64>,
+    push_constant_members: Vec<PushConstantMember>,
+    spec_constants: HashMap<u32, SpecializationConstantRequirements>,
     input: ShaderInterface,
     output: ShaderInterface,
     ty: GraphicsShaderType,
@@ -256,6 +640,21 @@ impl<'a> GraphicsEntryPoint<'a> {
     pub fn ty(&self) -> GraphicsShaderType {
         self.ty
     }
+
+    /// Declares the Rust type of the push constants of the entry point. Pipelines that create
+    /// their layout automatically record it, so that `push_constants_typed` can check that it is
+    /// the type that is pushed, along with the members of the type.
+    #[inline]
+    pub fn with_push_constants<Pc>(mut self) -> GraphicsEntryPoint<'a>
+    where
+        Pc: PushConstantsLayout,
+    {
+        self.push_constants_layout_hash = Some(Pc::LAYOUT_HASH);
+        if !Pc::MEMBERS.is_empty() {
+            self.push_constant_members = Pc::MEMBERS.to_vec();
+        }
+        self
+    }
 }
 
 unsafe impl<'a> EntryPointAbstract for GraphicsEntryPoint<'a> {
@@ -280,8 +679,18 @@ unsafe impl<'a> EntryPointAbstract for GraphicsEntryPoint<'a> {
     }
 
     #[inline]
-    fn spec_constants(&self) -> &[SpecializationMapEntry] {
-        self.spec_constants
+    fn push_constants_layout_hash(&self) -> Option<u64> {
+        self.push_constants_layout_hash
+    }
+
+    #[inline]
+    fn push_constant_members(&self) -> &[PushConstantMember] {
+        &self.push_constant_members
+    }
+
+    #[inline]
+    fn spec_constants(&self) -> &HashMap<u32, SpecializationConstantRequirements> {
+        &self.spec_constants
     }
 }
 
@@ -345,7 +754,45 @@ pub struct ComputeEntryPoint<'a> {
     name: &'a CStr,
     descriptor_set_layout_descs: Vec<DescriptorSetDesc>,
     push_constant_range: Option<PipelineLayoutPcRange>,
-    spec_constants: &'static [SpecializationMapEntry],
+    push_constants_layout_hash: Option<u64>,
+    push_constant_members: Vec<PushConstantMember>,
+    spec_constants: HashMap<u32, SpecializationConstantRequirements>,
+    local_size: Option<[LocalSizeValue; 3]>,
+}
+
+impl<'a> ComputeEntryPoint<'a> {
+    /// Declares the Rust type of the push constants of the entry point. Pipelines that create
+    /// their layout automatically record it, so that `push_constants_typed` can check that it is
+    /// the type that is pushed, along with the members of the type.
+    #[inline]
+    pub fn with_push_constants<Pc>(mut self) -> ComputeEntryPoint<'a>
+    where
+        Pc: PushConstantsLayout,
+    {
+        self.push_constants_layout_hash = Some(Pc::LAYOUT_HASH);
+        if !Pc::MEMBERS.is_empty() {
+            self.push_constant_members = Pc::MEMBERS.to_vec();
+        }
+        self
+    }
+
+    /// Declares the local workgroup size of the entry point. Compute pipelines use it to check
+    /// the subgroup size control parameters and report it through `ComputePipeline::local_size`.
+    ///
+    /// If it isn't declared, these checks are skipped and the pipeline reports no local size.
+    ///
+    /// # Safety
+    ///
+    /// - The local size must correctly describe the workgroup size declared by the entry point.
+    ///
+    #[inline]
+    pub unsafe fn with_local_size(
+        mut self,
+        local_size: [LocalSizeValue; 3],
+    ) -> ComputeEntryPoint<'a> {
+        self.local_size = Some(local_size);
+        self
+    }
 }
 
 unsafe impl<'a> EntryPointAbstract for ComputeEntryPoint<'a> {
@@ -370,8 +817,65 @@ unsafe impl<'a> EntryPointAbstract for ComputeEntryPoint<'a> {
     }
 
     #[inline]
-    fn spec_constants(&self) -> &[SpecializationMapEntry] {
-        self.spec_constants
+    fn push_constants_layout_hash(&self) -> Option<u64> {
+        self.push_constants_layout_hash
+    }
+
+    #[inline]
+    fn push_constant_members(&self) -> &[PushConstantMember] {
+        &self.push_constant_members
+    }
+
+    #[inline]
+    fn spec_constants(&self) -> &HashMap<u32, SpecializationConstantRequirements> {
+        &self.spec_constants
+    }
+
+    #[inline]
+    fn local_size(&self) -> Option<&[LocalSizeValue; 3]> {
+        self.local_size.as_ref()
+    }
+}
+
+/// One dimension of the local workgroup size of a compute entry point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LocalSizeValue {
+    /// The size is a constant that can't be changed.
+    Literal(u32),
+
+    /// The size is a specialization constant. `default_value` is used if no value is provided
+    /// when creating the pipeline.
+    SpecConstant {
+        constant_id: u32,
+        default_value: u32,
+    },
+}
+
+impl LocalSizeValue {
+    /// Returns the value of this dimension once `spec_constants` have been applied.
+    pub fn specialize<S>(&self, spec_constants: &S) -> u32
+    where
+        S: SpecializationData + ?Sized,
+    {
+        match *self {
+            LocalSizeValue::Literal(value) => value,
+            LocalSizeValue::SpecConstant {
+                constant_id,
+                default_value,
+            } => spec_constants
+                .map_entries()
+                .iter()
+                .find(|entry| entry.constant_id == constant_id)
+                .filter(|entry| {
+                    entry.size == mem::size_of::<u32>()
+                        && entry.offset as usize + entry.size <= spec_constants.data_size()
+                })
+                .map(|entry| unsafe {
+                    let ptr = (spec_constants.data_ptr() as *const u8).add(entry.offset as usize);
+                    ptr::read_unaligned(ptr as *const u32)
+                })
+                .unwrap_or(default_value),
+        }
     }
 }
 
@@ -408,6 +912,15 @@ impl ShaderInterface {
         self.elements.as_ref()
     }
 
+    /// Returns the element with the given name in the shader, or `None` if there is no such
+    /// element or if the names are unknown.
+    #[inline]
+    pub fn element_by_name(&self, name: &str) -> Option<&ShaderInterfaceEntry> {
+        self.elements
+            .iter()
+            .find(|element| element.name.as_deref() == Some(name))
+    }
+
     /// Checks whether the interface is potentially compatible with another one.
     ///
     /// Returns `Ok` if the two interfaces are compatible.
@@ -529,6 +1042,8 @@ impl fmt::Display for ShaderInterfaceMismatchError {
 ///
 /// This trait is implemented on `()` for shaders that don't have any specialization constant.
 ///
+/// If the values of the constants are only known at runtime, use `SpecializationInfo` instead.
+///
 /// Note that it is the shader module that chooses which type that implements
 /// `SpecializationConstants` it is possible to pass when creating the pipeline, through [the
 /// `EntryPointAbstract` trait](trait.EntryPointAbstract.html). Therefore there is generally no
@@ -611,6 +1126,398 @@ pub struct SpecializationMapEntry {
     pub size: usize,
 }
 
+/// Types that provide the values of the specialization constants when creating a pipeline.
+///
+/// This trait is implemented on all the types that implement `SpecializationConstants`, whose
+/// layout is known at compile time, and on `SpecializationInfo`, whose content is only known at
+/// runtime.
+///
+/// # Safety
+///
+/// - The entries returned by `map_entries` must be within the data described by `data_ptr`
+///   and `data_size`.
+///
+pub unsafe trait SpecializationData {
+    /// Returns the list of constants contained in the data.
+    fn map_entries(&self) -> Cow<[SpecializationMapEntry]>;
+
+    /// Returns a pointer to the start of the data.
+    fn data_ptr(&self) -> *const c_void;
+
+    /// Returns the size of the data in bytes.
+    fn data_size(&self) -> usize;
+
+    /// Checks that the constants can be used with a shader that declares `requirements`.
+    ///
+    /// The default implementation checks that each constant exists in the shader and has the
+    /// correct size.
+    fn check_requirements(
+        &self,
+        requirements: &HashMap<u32, SpecializationConstantRequirements>,
+    ) -> Result<(), SpecializationMismatchError> {
+        for entry in self.map_entries().iter() {
+            let reqs = requirements.get(&entry.constant_id).ok_or(
+                SpecializationMismatchError::UnknownConstant {
+                    constant_id: entry.constant_id,
+                },
+            )?;
+
+            if entry.size != reqs.size {
+                return Err(SpecializationMismatchError::SizeMismatch {
+                    constant_id: entry.constant_id,
+                    provided: entry.size,
+                    required: reqs.size,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+unsafe impl<T> SpecializationData for T
+where
+    T: SpecializationConstants,
+{
+    #[inline]
+    fn map_entries(&self) -> Cow<[SpecializationMapEntry]> {
+        Cow::Borrowed(T::descriptors())
+    }
+
+    #[inline]
+    fn data_ptr(&self) -> *const c_void {
+        self as *const T as *const _
+    }
+
+    #[inline]
+    fn data_size(&self) -> usize {
+        mem::size_of_val(self)
+    }
+}
+
+/// The value of a specialization constant.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpecializationConstant {
+    Bool(bool),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    F16(f16),
+    F32(f32),
+    F64(f64),
+}
+
+impl SpecializationConstant {
+    /// Returns the type of the value.
+    #[inline]
+    pub fn ty(&self) -> SpecializationConstantType {
+        match *self {
+            SpecializationConstant::Bool(_) => SpecializationConstantType::Bool,
+            SpecializationConstant::I8(_) => SpecializationConstantType::Int {
+                width: 8,
+                signed: true,
+            },
+            SpecializationConstant::I16(_) => SpecializationConstantType::Int {
+                width: 16,
+                signed: true,
+            },
+            SpecializationConstant::I32(_) => SpecializationConstantType::Int {
+                width: 32,
+                signed: true,
+            },
+            SpecializationConstant::I64(_) => SpecializationConstantType::Int {
+                width: 64,
+                signed: true,
+            },
+            SpecializationConstant::U8(_) => SpecializationConstantType::Int {
+                width: 8,
+                signed: false,
+            },
+            SpecializationConstant::U16(_) => SpecializationConstantType::Int {
+                width: 16,
+                signed: false,
+            },
+            SpecializationConstant::U32(_) => SpecializationConstantType::Int {
+                width: 32,
+                signed: false,
+            },
+            SpecializationConstant::U64(_) => SpecializationConstantType::Int {
+                width: 64,
+                signed: false,
+            },
+            SpecializationConstant::F16(_) => SpecializationConstantType::Float { width: 16 },
+            SpecializationConstant::F32(_) => SpecializationConstantType::Float { width: 32 },
+            SpecializationConstant::F64(_) => SpecializationConstantType::Float { width: 64 },
+        }
+    }
+
+    /// Returns the value as it must be passed to Vulkan. Booleans are stored as 32-bit integers.
+    #[inline]
+    fn to_ne_bytes(&self) -> SmallVec<[u8; 8]> {
+        match *self {
+            SpecializationConstant::Bool(val) => SmallVec::from_slice(&(val as u32).to_ne_bytes()),
+            SpecializationConstant::I8(val) => SmallVec::from_slice(&val.to_ne_bytes()),
+            SpecializationConstant::I16(val) => SmallVec::from_slice(&val.to_ne_bytes()),
+            SpecializationConstant::I32(val) => SmallVec::from_slice(&val.to_ne_bytes()),
+            SpecializationConstant::I64(val) => SmallVec::from_slice(&val.to_ne_bytes()),
+            SpecializationConstant::U8(val) => SmallVec::from_slice(&val.to_ne_bytes()),
+            SpecializationConstant::U16(val) => SmallVec::from_slice(&val.to_ne_bytes()),
+            SpecializationConstant::U32(val) => SmallVec::from_slice(&val.to_ne_bytes()),
+            SpecializationConstant::U64(val) => SmallVec::from_slice(&val.to_ne_bytes()),
+            SpecializationConstant::F16(val) => SmallVec::from_slice(&val.to_ne_bytes()),
+            SpecializationConstant::F32(val) => SmallVec::from_slice(&val.to_ne_bytes()),
+            SpecializationConstant::F64(val) => SmallVec::from_slice(&val.to_ne_bytes()),
+        }
+    }
+}
+
+macro_rules! specialization_constant_from {
+    ($($ty:ty => $variant:ident,)+) => {
+        $(
+            impl From<$ty> for SpecializationConstant {
+                #[inline]
+                fn from(val: $ty) -> Self {
+                    SpecializationConstant::$variant(val)
+                }
+            }
+        )+
+    };
+}
+
+specialization_constant_from! {
+    bool => Bool,
+    i8 => I8,
+    i16 => I16,
+    i32 => I32,
+    i64 => I64,
+    u8 => U8,
+    u16 => U16,
+    u32 => U32,
+    u64 => U64,
+    f16 => F16,
+    f32 => F32,
+    f64 => F64,
+}
+
+/// The type of a specialization constant, as declared in the shader.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SpecializationConstantType {
+    Bool,
+    Int { width: u32, signed: bool },
+    Float { width: u32 },
+}
+
+impl SpecializationConstantType {
+    /// Returns the size in bytes that a value of this type has when passed to Vulkan.
+    #[inline]
+    pub fn size(&self) -> usize {
+        match *self {
+            SpecializationConstantType::Bool => mem::size_of::<u32>(),
+            SpecializationConstantType::Int { width, .. }
+            | SpecializationConstantType::Float { width } => width as usize / 8,
+        }
+    }
+}
+
+/// Describes a specialization constant that is declared in a shader module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SpecializationConstantRequirements {
+    /// The type of the constant.
+    pub ty: SpecializationConstantType,
+
+    /// The size of the constant in bytes (`4` for booleans).
+    pub size: usize,
+}
+
+impl From<SpecializationConstantType> for SpecializationConstantRequirements {
+    #[inline]
+    fn from(ty: SpecializationConstantType) -> Self {
+        SpecializationConstantRequirements {
+            ty,
+            size: ty.size(),
+        }
+    }
+}
+
+/// Values of specialization constants that are only known at runtime.
+///
+/// Contrary to a type that implements `SpecializationConstants`, it is not necessary to provide
+/// a value for every constant of the shader. The constants that are not set keep the default
+/// value that is written in the shader.
+///
+/// # Example
+///
+/// ```rust
+/// use vulkano::pipeline::shader::SpecializationInfo;
+///
+/// let mut spec_info = SpecializationInfo::new();
+/// spec_info.set(0, 16u32).set(1, true).set(2, 0.5f32);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SpecializationInfo {
+    constants: HashMap<u32, SpecializationConstant>,
+
+    // Cached Vulkan representation of `constants`, sorted by constant id.
+    map_entries: Vec<SpecializationMapEntry>,
+    data: Vec<u8>,
+}
+
+impl SpecializationInfo {
+    /// Builds a new `SpecializationInfo` that doesn't contain any constant.
+    #[inline]
+    pub fn new() -> SpecializationInfo {
+        SpecializationInfo::default()
+    }
+
+    /// Sets the value of the constant with the given id, replacing any previous value.
+    pub fn set(&mut self, constant_id: u32, value: impl Into<SpecializationConstant>) -> &mut Self {
+        self.constants.insert(constant_id, value.into());
+        self.rebuild();
+        self
+    }
+
+    /// Removes the value of the constant with the given id. The shader will use its default value.
+    pub fn remove(&mut self, constant_id: u32) -> Option<SpecializationConstant> {
+        let value = self.constants.remove(&constant_id);
+        self.rebuild();
+        value
+    }
+
+    /// Returns the value of the constant with the given id, if it was set.
+    #[inline]
+    pub fn get(&self, constant_id: u32) -> Option<SpecializationConstant> {
+        self.constants.get(&constant_id).copied()
+    }
+
+    /// Returns an iterator to the constants that were set, in no particular order.
+    #[inline]
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (u32, SpecializationConstant)> + '_ {
+        self.constants.iter().map(|(&id, &value)| (id, value))
+    }
+
+    fn rebuild(&mut self) {
+        let mut constants: Vec<_> = self.constants.iter().collect();
+        constants.sort_by_key(|&(&id, _)| id);
+
+        self.map_entries.clear();
+        self.data.clear();
+
+        for (&constant_id, value) in constants {
+            let bytes = value.to_ne_bytes();
+            self.map_entries.push(SpecializationMapEntry {
+                constant_id,
+                offset: self.data.len() as u32,
+                size: bytes.len(),
+            });
+            self.data.extend_from_slice(&bytes);
+        }
+    }
+}
+
+impl PartialEq for SpecializationInfo {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.constants == other.constants
+    }
+}
+
+unsafe impl SpecializationData for SpecializationInfo {
+    #[inline]
+    fn map_entries(&self) -> Cow<[SpecializationMapEntry]> {
+        Cow::Borrowed(&self.map_entries)
+    }
+
+    #[inline]
+    fn data_ptr(&self) -> *const c_void {
+        self.data.as_ptr() as *const _
+    }
+
+    #[inline]
+    fn data_size(&self) -> usize {
+        self.data.len()
+    }
+
+    fn check_requirements(
+        &self,
+        requirements: &HashMap<u32, SpecializationConstantRequirements>,
+    ) -> Result<(), SpecializationMismatchError> {
+        for (&constant_id, value) in self.constants.iter() {
+            let reqs = requirements
+                .get(&constant_id)
+                .ok_or(SpecializationMismatchError::UnknownConstant { constant_id })?;
+
+            if value.ty() != reqs.ty {
+                return Err(SpecializationMismatchError::TypeMismatch {
+                    constant_id,
+                    provided: value.ty(),
+                    required: reqs.ty,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Error that can happen when the specialization constants provided when creating a pipeline
+/// don't match those declared in the shader.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SpecializationMismatchError {
+    /// A value was provided for a constant that the shader doesn't declare.
+    UnknownConstant {
+        /// The id of the constant.
+        constant_id: u32,
+    },
+
+    /// The size of a provided value doesn't match the size of the constant in the shader.
+    SizeMismatch {
+        /// The id of the constant.
+        constant_id: u32,
+        /// Size of the provided value.
+        provided: usize,
+        /// Size of the constant in the shader.
+        required: usize,
+    },
+
+    /// The type of a provided value doesn't match the type of the constant in the shader.
+    TypeMismatch {
+        /// The id of the constant.
+        constant_id: u32,
+        /// Type of the provided value.
+        provided: SpecializationConstantType,
+        /// Type of the constant in the shader.
+        required: SpecializationConstantType,
+    },
+}
+
+impl error::Error for SpecializationMismatchError {}
+
+impl fmt::Display for SpecializationMismatchError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(
+            fmt,
+            "{}",
+            match *self {
+                SpecializationMismatchError::UnknownConstant { .. } => {
+                    "a value was provided for a constant that the shader doesn't declare"
+                }
+                SpecializationMismatchError::SizeMismatch { .. } => {
+                    "the size of a value doesn't match the size of the constant in the shader"
+                }
+                SpecializationMismatchError::TypeMismatch { .. } => {
+                    "the type of a value doesn't match the type of the constant in the shader"
+                }
+            }
+        )
+    }
+}
+
 /// Describes a set of shader stages.
 // TODO: add example with BitOr
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -789,3 +1696,132 @@ impl From<ShaderStages> for PipelineStages {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::shader::LocalSizeValue;
+    use crate::pipeline::shader::ShaderLoadError;
+    use crate::pipeline::shader::ShaderRequirement;
+    use crate::pipeline::shader::SpecializationConstantRequirements;
+    use crate::pipeline::shader::SpecializationConstantType;
+    use crate::pipeline::shader::SpecializationData;
+    use crate::pipeline::shader::SpecializationInfo;
+    use crate::pipeline::shader::SpecializationMapEntry;
+    use crate::pipeline::shader::SpecializationMismatchError;
+    use crate::Version;
+    use std::collections::HashMap;
+    use std::slice;
+
+    #[test]
+    fn spec_info_layout() {
+        let mut spec_info = SpecializationInfo::new();
+        spec_info.set(5, 2.0f64).set(1, true).set(3, -4i16);
+
+        assert_eq!(
+            &*spec_info.map_entries(),
+            &[
+                SpecializationMapEntry {
+                    constant_id: 1,
+                    offset: 0,
+                    size: 4,
+                },
+                SpecializationMapEntry {
+                    constant_id: 3,
+                    offset: 4,
+                    size: 2,
+                },
+                SpecializationMapEntry {
+                    constant_id: 5,
+                    offset: 6,
+                    size: 8,
+                },
+            ]
+        );
+
+        let data = unsafe {
+            slice::from_raw_parts(spec_info.data_ptr() as *const u8, spec_info.data_size())
+        };
+        assert_eq!(&data[0..4], &1u32.to_ne_bytes());
+        assert_eq!(&data[4..6], &(-4i16).to_ne_bytes());
+        assert_eq!(&data[6..14], &2.0f64.to_ne_bytes());
+    }
+
+    #[test]
+    fn spec_info_requirements() {
+        let requirements: HashMap<_, _> = [
+            (0, SpecializationConstantType::Bool.into()),
+            (
+                1,
+                SpecializationConstantRequirements::from(SpecializationConstantType::Int {
+                    width: 32,
+                    signed: false,
+                }),
+            ),
+        ]
+        .iter()
+        .cloned()
+        .collect();
+
+        let mut spec_info = SpecializationInfo::new();
+        spec_info.set(1, 8u32);
+        assert!(spec_info.check_requirements(&requirements).is_ok());
+
+        spec_info.set(0, 1u32);
+        assert_eq!(
+            spec_info.check_requirements(&requirements),
+            Err(SpecializationMismatchError::TypeMismatch {
+                constant_id: 0,
+                provided: SpecializationConstantType::Int {
+                    width: 32,
+                    signed: false,
+                },
+                required: SpecializationConstantType::Bool,
+            })
+        );
+
+        spec_info.remove(0);
+        spec_info.set(2, 1.0f32);
+        assert_eq!(
+            spec_info.check_requirements(&requirements),
+            Err(SpecializationMismatchError::UnknownConstant { constant_id: 2 })
+        );
+    }
+
+    #[test]
+    fn local_size_specialize() {
+        let mut spec_info = SpecializationInfo::new();
+        spec_info.set(1, 16u32).set(2, 1.0f32);
+
+        let literal = LocalSizeValue::Literal(4);
+        let set = LocalSizeValue::SpecConstant {
+            constant_id: 1,
+            default_value: 8,
+        };
+        let unset = LocalSizeValue::SpecConstant {
+            constant_id: 3,
+            default_value: 8,
+        };
+
+        assert_eq!(literal.specialize(&spec_info), 4);
+        assert_eq!(set.specialize(&spec_info), 16);
+        assert_eq!(unset.specialize(&spec_info), 8);
+        assert_eq!(set.specialize(&()), 8);
+    }
+
+    #[test]
+    fn requirements_not_met_display() {
+        static SPIRV_1_4: [ShaderRequirement; 2] = [
+            ShaderRequirement::ApiVersion(Version::V1_2),
+            ShaderRequirement::DeviceExtension("khr_spirv_1_4"),
+        ];
+        static GEOMETRY: [ShaderRequirement; 1] = [ShaderRequirement::Feature("geometry_shader")];
+
+        let err = ShaderLoadError::RequirementsNotMet(vec![&SPIRV_1_4[..], &GEOMETRY[..]]);
+
+        assert_eq!(
+            err.to_string(),
+            "the device doesn't meet the requirements of the shader: one of API version 1.2.0, \
+            extension khr_spirv_1_4; feature geometry_shader"
+        );
+    }
+}
@@ -0,0 +1,1514 @@
+// Copyright (c) 2021 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Extraction of the entry points of a shader module from its SPIR-V code.
+//!
+//! The code generated by `vulkano-shaders` describes the entry points with the result of these
+//! functions, so modules reflected at runtime are described in the same way.
+
+use super::{
+    EntryPointInfo, GeometryShaderExecutionMode, GraphicsShaderType, LocalSizeValue,
+    ShaderCreationError, ShaderExecution, ShaderInterface, ShaderInterfaceEntry, ShaderStages,
+    SpecializationConstantRequirements, SpecializationConstantType,
+};
+use crate::descriptor_set::layout::{
+    DescriptorDesc, DescriptorDescImage, DescriptorDescTy, DescriptorSetDesc,
+};
+use crate::format::Format;
+use crate::image::view::ImageViewType;
+use crate::pipeline::layout::PipelineLayoutPcRange;
+use crate::pipeline::layout::PushConstantMember;
+use crate::spirv::{
+    BuiltIn, Decoration, Dim, ExecutionMode, ExecutionModel, Id, ImageFormat, Instruction, Spirv,
+    StorageClass, StructMemberInfo,
+};
+use std::borrow::Cow;
+use std::cmp;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::ffi::CString;
+
+/// Returns the description of every entry point of the module.
+///
+/// If `exact_interface` is true, the descriptors of each entry point are the ones that it uses.
+/// Otherwise, every entry point is assumed to use all the descriptors of the module.
+pub(super) fn entry_points(
+    spirv: &Spirv,
+    exact_interface: bool,
+) -> Result<Vec<EntryPointInfo>, ShaderCreationError> {
+    let module_descriptors = if exact_interface {
+        None
+    } else {
+        Some(find_descriptors(spirv, None)?)
+    };
+    let push_constant_range = push_constant_range(spirv)?;
+    let push_constant_members = push_constant_members(spirv)?;
+    let spec_constants = spec_constants(spirv)?;
+
+    spirv
+        .iter_entry_point()
+        .map(|instruction| {
+            let (execution_model, id, name, interface) = match instruction {
+                Instruction::EntryPoint {
+                    execution_model,
+                    entry_point,
+                    name,
+                    interface,
+                    ..
+                } => (execution_model, *entry_point, name, interface),
+                _ => unreachable!(),
+            };
+
+            let (execution, stages) = match *execution_model {
+                ExecutionModel::Vertex => (
+                    ShaderExecution::Graphics(GraphicsShaderType::Vertex),
+                    ShaderStages {
+                        vertex: true,
+                        ..ShaderStages::none()
+                    },
+                ),
+                ExecutionModel::TessellationControl => (
+                    ShaderExecution::Graphics(GraphicsShaderType::TessellationControl),
+                    ShaderStages {
+                        tessellation_control: true,
+                        ..ShaderStages::none()
+                    },
+                ),
+                ExecutionModel::TessellationEvaluation => (
+                    ShaderExecution::Graphics(GraphicsShaderType::TessellationEvaluation),
+                    ShaderStages {
+                        tessellation_evaluation: true,
+                        ..ShaderStages::none()
+                    },
+                ),
+                ExecutionModel::Geometry => (
+                    ShaderExecution::Graphics(GraphicsShaderType::Geometry(
+                        geometry_execution_mode(spirv, id, name)?,
+                    )),
+                    ShaderStages {
+                        geometry: true,
+                        ..ShaderStages::none()
+                    },
+                ),
+                ExecutionModel::Fragment => (
+                    ShaderExecution::Graphics(GraphicsShaderType::Fragment),
+                    ShaderStages {
+                        fragment: true,
+                        ..ShaderStages::none()
+                    },
+                ),
+                ExecutionModel::GLCompute => (
+                    ShaderExecution::Compute(local_size(spirv, id)?),
+                    ShaderStages {
+                        compute: true,
+                        ..ShaderStages::none()
+                    },
+                ),
+                _ => {
+                    return Err(reflection_error(format!(
+                        "shaders with the {:?} execution model are not supported",
+                        execution_model
+                    )))
+                }
+            };
+
+            let ignore_first_array_in = match *execution_model {
+                ExecutionModel::TessellationControl
+                | ExecutionModel::TessellationEvaluation
+                | ExecutionModel::Geometry => true,
+                _ => false,
+            };
+            let ignore_first_array_out = *execution_model == ExecutionModel::TessellationControl;
+            let input = interface_of(spirv, interface, StorageClass::Input, ignore_first_array_in)?;
+            let output = interface_of(
+                spirv,
+                interface,
+                StorageClass::Output,
+                ignore_first_array_out,
+            )?;
+
+            let entry_point_descriptors;
+            let descriptors = match module_descriptors {
+                Some(ref descriptors) => descriptors,
+                None => {
+                    let variables = variables_of_entry_point(spirv, id, interface);
+                    entry_point_descriptors = find_descriptors(spirv, Some(&variables))?;
+                    &entry_point_descriptors
+                }
+            };
+
+            let push_constant_range =
+                push_constant_range.map(|(offset, size)| PipelineLayoutPcRange {
+                    offset,
+                    size,
+                    stages,
+                });
+
+            Ok(EntryPointInfo {
+                name: CString::new(name.as_str()).map_err(|_| {
+                    reflection_error(format!("entry point name {:?} contains a nul byte", name))
+                })?,
+                execution,
+                descriptor_set_layout_descs: descriptor_set_layout_descs(descriptors, stages),
+                push_constant_range,
+                push_constant_members: push_constant_members.clone(),
+                spec_constants: spec_constants.clone(),
+                input,
+                output,
+            })
+        })
+        .collect()
+}
+
+#[inline]
+fn reflection_error(message: String) -> ShaderCreationError {
+    ShaderCreationError::ReflectionError(message)
+}
+
+struct Descriptor {
+    set_num: u32,
+    binding_num: u32,
+    name: Option<String>,
+    ty: DescriptorDescTy,
+    descriptor_count: u32,
+    variable_count: bool,
+    mutable: bool,
+}
+
+fn descriptor_set_layout_descs(
+    descriptors: &[Descriptor],
+    stages: ShaderStages,
+) -> Vec<DescriptorSetDesc> {
+    let num_sets = descriptors.iter().map(|d| d.set_num + 1).max().unwrap_or(0);
+
+    (0..num_sets)
+        .map(|set_num| {
+            let num_bindings = descriptors
+                .iter()
+                .filter(|d| d.set_num == set_num)
+                .map(|d| d.binding_num + 1)
+                .max()
+                .unwrap_or(0);
+
+            DescriptorSetDesc::new((0..num_bindings).map(|binding_num| {
+                descriptors
+                    .iter()
+                    .find(|d| d.set_num == set_num && d.binding_num == binding_num)
+                    .map(|d| DescriptorDesc {
+                        ty: d.ty.clone(),
+                        descriptor_count: d.descriptor_count,
+                        stages,
+                        variable_count: d.variable_count,
+                        mutable: d.mutable,
+                        name: d.name.clone().map(Cow::Owned),
+                    })
+            }))
+        })
+        .collect()
+}
+
+// Finds all the global variables that have the `DescriptorSet` decoration, or only those among
+// `variables` if it is `Some`.
+fn find_descriptors(
+    spirv: &Spirv,
+    variables: Option<&HashSet<Id>>,
+) -> Result<Vec<Descriptor>, ShaderCreationError> {
+    let mut descriptors = Vec::new();
+
+    for instruction in spirv.iter_global() {
+        let (variable_id, variable_type_id) = match *instruction {
+            Instruction::Variable {
+                result_id,
+                result_type_id,
+                ..
+            } => (result_id, result_type_id),
+            _ => continue,
+        };
+
+        if variables.map_or(false, |variables| !variables.contains(&variable_id)) {
+            continue;
+        }
+
+        let (pointed_ty, storage_class) = match *spirv.id(variable_type_id).instruction() {
+            Instruction::TypePointer {
+                ty,
+                ref storage_class,
+                ..
+            } => (ty, storage_class),
+            _ => {
+                return Err(reflection_error(format!(
+                    "the type of variable #{} is not a pointer",
+                    variable_id
+                )))
+            }
+        };
+
+        let variable_id_info = spirv.id(variable_id);
+        let set_num =
+            match variable_id_info
+                .iter_decoration()
+                .find_map(|instruction| match *instruction {
+                    Instruction::Decorate {
+                        decoration: Decoration::DescriptorSet { descriptor_set },
+                        ..
+                    } => Some(descriptor_set),
+                    _ => None,
+                }) {
+                Some(set_num) => set_num,
+                None => continue,
+            };
+
+        let name = name_of(spirv, variable_id).unwrap_or("__unnamed");
+
+        let binding_num = variable_id_info
+            .iter_decoration()
+            .find_map(|instruction| match *instruction {
+                Instruction::Decorate {
+                    decoration: Decoration::Binding { binding_point },
+                    ..
+                } => Some(binding_point),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                reflection_error(format!(
+                    "descriptor `{}` has a descriptor set but no binding",
+                    name
+                ))
+            })?;
+
+        let nonwritable = variable_id_info.iter_decoration().any(|instruction| {
+            matches!(
+                instruction,
+                Instruction::Decorate {
+                    decoration: Decoration::NonWritable,
+                    ..
+                }
+            )
+        });
+
+        let (ty, mutable, descriptor_count, variable_count) =
+            descriptor_infos(spirv, pointed_ty, storage_class, false)?.ok_or_else(|| {
+                reflection_error(format!(
+                    "couldn't find relevant type for uniform `{}` (type {}, maybe unimplemented)",
+                    name, pointed_ty,
+                ))
+            })?;
+
+        // Blocks without an instance name are named after their type.
+        let descriptor_name = [variable_id, pointed_ty]
+            .iter()
+            .filter_map(|&id| name_of(spirv, id))
+            .find(|name| !name.is_empty())
+            .map(|name| name.to_owned());
+
+        descriptors.push(Descriptor {
+            set_num,
+            binding_num,
+            name: descriptor_name,
+            ty,
+            descriptor_count,
+            variable_count,
+            mutable: !nonwritable && mutable,
+        });
+    }
+
+    Ok(descriptors)
+}
+
+// Returns the variables used by an entry point. For SPIR-V 1.4+, the interface of the entry point
+// lists the variables of all storage classes, and most tools put all the used variables in it.
+// However, SPIR-V 1.0-1.3 only list the `Input` and `Output` variables, so the functions that the
+// entry point executes must be inspected as well.
+fn variables_of_entry_point(spirv: &Spirv, entry_point: Id, interface: &[Id]) -> HashSet<Id> {
+    let mut variables: HashSet<Id> = interface.iter().cloned().collect();
+    let mut inspected_functions = HashSet::new();
+    find_variables_in_function(spirv, entry_point, &mut inspected_functions, &mut variables);
+    variables
+}
+
+// Recursively finds every pointer used in the execution of a function.
+fn find_variables_in_function(
+    spirv: &Spirv,
+    function: Id,
+    inspected_functions: &mut HashSet<Id>,
+    variables: &mut HashSet<Id>,
+) {
+    inspected_functions.insert(function);
+
+    let instructions = spirv
+        .instructions()
+        .iter()
+        .skip_while(|instruction| {
+            !matches!(
+                **instruction,
+                Instruction::Function { result_id, .. } if result_id == function
+            )
+        })
+        .skip(1);
+
+    // Only the instructions that accept pointers matter.
+    // https://www.khronos.org/registry/spir-v/specs/unified1/SPIRV.html#_universal_validation_rules
+    for instruction in instructions {
+        match *instruction {
+            Instruction::Load { pointer, .. } | Instruction::Store { pointer, .. } => {
+                variables.insert(pointer);
+            }
+            Instruction::AccessChain { base, .. }
+            | Instruction::InBoundsAccessChain { base, .. } => {
+                variables.insert(base);
+            }
+            Instruction::FunctionCall {
+                function,
+                ref arguments,
+                ..
+            } => {
+                variables.extend(arguments.iter().cloned());
+                if !inspected_functions.contains(&function) {
+                    find_variables_in_function(spirv, function, inspected_functions, variables);
+                }
+            }
+            Instruction::ImageTexelPointer {
+                image,
+                coordinate,
+                sample,
+                ..
+            } => {
+                variables.extend([image, coordinate, sample].iter().cloned());
+            }
+            Instruction::CopyMemory { target, source, .. } => {
+                variables.extend([target, source].iter().cloned());
+            }
+            Instruction::CopyObject { operand, .. } => {
+                variables.insert(operand);
+            }
+            Instruction::AtomicLoad { pointer, .. }
+            | Instruction::AtomicIIncrement { pointer, .. }
+            | Instruction::AtomicIDecrement { pointer, .. }
+            | Instruction::AtomicFlagTestAndSet { pointer, .. }
+            | Instruction::AtomicFlagClear { pointer, .. } => {
+                variables.insert(pointer);
+            }
+            Instruction::AtomicStore { pointer, value, .. }
+            | Instruction::AtomicExchange { pointer, value, .. }
+            | Instruction::AtomicIAdd { pointer, value, .. }
+            | Instruction::AtomicISub { pointer, value, .. }
+            | Instruction::AtomicSMin { pointer, value, .. }
+            | Instruction::AtomicUMin { pointer, value, .. }
+            | Instruction::AtomicSMax { pointer, value, .. }
+            | Instruction::AtomicUMax { pointer, value, .. }
+            | Instruction::AtomicAnd { pointer, value, .. }
+            | Instruction::AtomicOr { pointer, value, .. }
+            | Instruction::AtomicXor { pointer, value, .. } => {
+                variables.extend([pointer, value].iter().cloned());
+            }
+            Instruction::AtomicCompareExchange {
+                pointer,
+                value,
+                comparator,
+                ..
+            }
+            | Instruction::AtomicCompareExchangeWeak {
+                pointer,
+                value,
+                comparator,
+                ..
+            } => {
+                variables.extend([pointer, value, comparator].iter().cloned());
+            }
+            Instruction::ExtInst { ref operands, .. } => {
+                // It isn't known which extended instructions take pointers, so every operand is
+                // assumed to be one.
+                variables.extend(operands.iter().cloned());
+            }
+            Instruction::FunctionEnd => return,
+            _ => (),
+        }
+    }
+}
+
+/// Returns the descriptor type, whether the descriptor is mutable, the number of array elements
+/// and whether the count is variable. Returns `None` if the type isn't a descriptor type.
+///
+/// See also section 14.5.2 of the Vulkan specs: Descriptor Set Interface
+fn descriptor_infos(
+    spirv: &Spirv,
+    pointed_ty: Id,
+    pointer_storage: &StorageClass,
+    force_combined_image_sampled: bool,
+) -> Result<Option<(DescriptorDescTy, bool, u32, bool)>, ShaderCreationError> {
+    let id_info = spirv.id(pointed_ty);
+
+    Ok(match *id_info.instruction() {
+        Instruction::TypeStruct { .. } => {
+            let has_decoration = |decoration: Decoration| {
+                id_info
+                    .iter_decoration()
+                    .any(|instruction| match instruction {
+                        Instruction::Decorate { decoration: d, .. } => *d == decoration,
+                        _ => false,
+                    })
+            };
+            let decoration_block = has_decoration(Decoration::Block);
+            let decoration_buffer_block = has_decoration(Decoration::BufferBlock);
+
+            if !(decoration_block ^ decoration_buffer_block) {
+                return Err(reflection_error(
+                    "structs in shader interface are expected to be decorated with one of Block \
+                    or BufferBlock"
+                        .to_owned(),
+                ));
+            }
+
+            if decoration_buffer_block
+                || decoration_block && *pointer_storage == StorageClass::StorageBuffer
+            {
+                // The buffer is only mutable if one of its members isn't `NonWritable`.
+                let nonwritable = id_info.iter_members().all(|member_info| {
+                    member_info.iter_decoration().any(|instruction| {
+                        matches!(
+                            instruction,
+                            Instruction::MemberDecorate {
+                                decoration: Decoration::NonWritable,
+                                ..
+                            }
+                        )
+                    })
+                });
+
+                Some((DescriptorDescTy::StorageBuffer, !nonwritable, 1, false))
+            } else {
+                // Uniforms are never mutable.
+                Some((DescriptorDescTy::UniformBuffer, false, 1, false))
+            }
+        }
+
+        Instruction::TypeImage {
+            ref dim,
+            arrayed,
+            ms,
+            sampled,
+            ref image_format,
+            ..
+        } => {
+            let multisampled = ms != 0;
+            if sampled == 0 {
+                return Err(reflection_error(
+                    "Vulkan requires that variables of type OpTypeImage have a Sampled operand \
+                    of 1 or 2"
+                        .to_owned(),
+                ));
+            }
+            let format = vulkan_format(image_format);
+
+            match *dim {
+                Dim::SubpassData => {
+                    if force_combined_image_sampled {
+                        return Err(reflection_error(
+                            "an OpTypeSampledImage can't point to an OpTypeImage whose dimension \
+                            is SubpassData"
+                                .to_owned(),
+                        ));
+                    }
+                    if *image_format != ImageFormat::Unknown || sampled != 2 || arrayed != 0 {
+                        return Err(reflection_error(
+                            "if Dim is SubpassData, Image Format must be Unknown, Sampled must \
+                            be 2 and Arrayed must be 0"
+                                .to_owned(),
+                        ));
+                    }
+
+                    // Input attachments are never writable.
+                    Some((
+                        DescriptorDescTy::InputAttachment { multisampled },
+                        false,
+                        1,
+                        false,
+                    ))
+                }
+                Dim::Buffer => {
+                    if sampled == 1 {
+                        // Uniforms are never mutable.
+                        Some((
+                            DescriptorDescTy::UniformTexelBuffer { format },
+                            false,
+                            1,
+                            false,
+                        ))
+                    } else {
+                        Some((
+                            DescriptorDescTy::StorageTexelBuffer { format },
+                            true,
+                            1,
+                            false,
+                        ))
+                    }
+                }
+                _ => {
+                    let view_type = match (dim, arrayed) {
+                        (Dim::Dim1D, 0) => ImageViewType::Dim1d,
+                        (Dim::Dim1D, _) => ImageViewType::Dim1dArray,
+                        (Dim::Dim2D, 0) => ImageViewType::Dim2d,
+                        (Dim::Dim2D, _) => ImageViewType::Dim2dArray,
+                        (Dim::Dim3D, 0) => ImageViewType::Dim3d,
+                        (Dim::Cube, 0) => ImageViewType::Cube,
+                        (Dim::Cube, _) => ImageViewType::CubeArray,
+                        _ => {
+                            return Err(reflection_error(format!(
+                                "images with dimension {:?} (arrayed: {}) are not supported by \
+                                Vulkan",
+                                dim,
+                                arrayed != 0
+                            )))
+                        }
+                    };
+
+                    let image_desc = DescriptorDescImage {
+                        format,
+                        multisampled,
+                        view_type,
+                    };
+
+                    if force_combined_image_sampled {
+                        if sampled != 1 {
+                            return Err(reflection_error(
+                                "a combined image sampler must not reference a storage image"
+                                    .to_owned(),
+                            ));
+                        }
+
+                        // Sampled images are never mutable.
+                        Some((
+                            DescriptorDescTy::CombinedImageSampler {
+                                image_desc,
+                                immutable_samplers: Vec::new(),
+                            },
+                            false,
+                            1,
+                            false,
+                        ))
+                    } else if sampled == 1 {
+                        Some((
+                            DescriptorDescTy::SampledImage { image_desc },
+                            false,
+                            1,
+                            false,
+                        ))
+                    } else {
+                        Some((
+                            DescriptorDescTy::StorageImage { image_desc },
+                            true,
+                            1,
+                            false,
+                        ))
+                    }
+                }
+            }
+        }
+
+        Instruction::TypeSampledImage { image_type, .. } => {
+            return descriptor_infos(spirv, image_type, pointer_storage, true);
+        }
+
+        Instruction::TypeSampler { .. } => Some((
+            DescriptorDescTy::Sampler {
+                immutable_samplers: Vec::new(),
+            },
+            false,
+            1,
+            false,
+        )),
+
+        Instruction::TypeArray {
+            element_type,
+            length,
+            ..
+        } => {
+            let (ty, mutable, count, variable_count) =
+                match descriptor_infos(spirv, element_type, pointer_storage, false)? {
+                    None => return Ok(None),
+                    Some(v) => v,
+                };
+            if count != 1 || variable_count {
+                return Err(reflection_error(
+                    "arrays of arrays of descriptors are not supported".to_owned(),
+                ));
+            }
+
+            Some((ty, mutable, constant_u32(spirv, length)?, false))
+        }
+
+        Instruction::TypeRuntimeArray { element_type, .. } => {
+            let (ty, mutable, count, variable_count) =
+                match descriptor_infos(spirv, element_type, pointer_storage, false)? {
+                    None => return Ok(None),
+                    Some(v) => v,
+                };
+            if count != 1 || variable_count {
+                return Err(reflection_error(
+                    "arrays of arrays of descriptors are not supported".to_owned(),
+                ));
+            }
+
+            Some((ty, mutable, 1, true))
+        }
+
+        _ => None,
+    })
+}
+
+// Returns the value of an integer constant that fits in 32 bits.
+fn constant_u32(spirv: &Spirv, id: Id) -> Result<u32, ShaderCreationError> {
+    match *spirv.id(id).instruction() {
+        Instruction::Constant { ref value, .. } if value.len() == 1 => Ok(value[0]),
+        _ => Err(reflection_error(format!(
+            "#{} must be a 32-bit integer constant",
+            id
+        ))),
+    }
+}
+
+fn name_of(spirv: &Spirv, id: Id) -> Option<&str> {
+    spirv
+        .id(id)
+        .iter_name()
+        .find_map(|instruction| match instruction {
+            Instruction::Name { name, .. } => Some(name.as_str()),
+            _ => None,
+        })
+}
+
+// Returns the offset and the size of the push constant range of the module, or `None` if it has no
+// push constant block. The range starts at the first member of the blocks and ends at the end of
+// the largest one.
+fn push_constant_range(spirv: &Spirv) -> Result<Option<(u32, u32)>, ShaderCreationError> {
+    let mut offset = u32::MAX;
+    let mut end = 0;
+
+    for instruction in spirv.iter_global() {
+        if let Instruction::TypePointer {
+            ty,
+            storage_class: StorageClass::PushConstant,
+            ..
+        } = *instruction
+        {
+            let type_size = type_size(spirv, ty, None)?.ok_or_else(|| {
+                reflection_error("push constants can't be runtime-sized".to_owned())
+            })?;
+            let first_offset = spirv
+                .id(ty)
+                .iter_members()
+                .filter_map(|member_info| {
+                    member_info
+                        .iter_decoration()
+                        .find_map(|instruction| match *instruction {
+                            Instruction::MemberDecorate {
+                                decoration: Decoration::Offset { byte_offset },
+                                ..
+                            } => Some(byte_offset),
+                            _ => None,
+                        })
+                })
+                .min()
+                .unwrap_or(0);
+
+            offset = cmp::min(offset, first_offset);
+            end = cmp::max(end, type_size);
+        }
+    }
+
+    Ok(if end == 0 {
+        None
+    } else {
+        Some((offset, end - offset))
+    })
+}
+
+// Returns the named members of the push constant blocks of the module.
+fn push_constant_members(spirv: &Spirv) -> Result<Vec<PushConstantMember>, ShaderCreationError> {
+    let mut members: Vec<PushConstantMember> = Vec::new();
+
+    for instruction in spirv.iter_global() {
+        let ty = match *instruction {
+            Instruction::TypePointer {
+                ty,
+                storage_class: StorageClass::PushConstant,
+                ..
+            } => ty,
+            _ => continue,
+        };
+
+        let member_types = match *spirv.id(ty).instruction() {
+            Instruction::TypeStruct {
+                ref member_types, ..
+            } => member_types,
+            _ => continue,
+        };
+
+        for (member_info, &member_type) in spirv.id(ty).iter_members().zip(member_types.iter()) {
+            let name = match member_info
+                .iter_name()
+                .find_map(|instruction| match instruction {
+                    Instruction::MemberName { name, .. } if !name.is_empty() => Some(name),
+                    _ => None,
+                }) {
+                Some(name) => name,
+                None => continue,
+            };
+            let (offset, size) = member_offset_and_size(spirv, ty, &member_info, member_type)?;
+            let member = PushConstantMember {
+                name: Cow::Owned(name.clone()),
+                offset,
+                size: size.ok_or_else(|| {
+                    reflection_error("push constants can't be runtime-sized".to_owned())
+                })?,
+            };
+
+            if !members.contains(&member) {
+                members.push(member);
+            }
+        }
+    }
+
+    Ok(members)
+}
+
+// Returns the size in bytes of a type that follows an explicit layout, or `None` if it is
+// runtime-sized. Matrices can only appear as struct members or array elements, whose
+// `matrix_stride` and row-majorness are given by the decorations of the member.
+fn type_size(
+    spirv: &Spirv,
+    id: Id,
+    matrix_layout: Option<(u32, bool)>,
+) -> Result<Option<u32>, ShaderCreationError> {
+    Ok(match *spirv.id(id).instruction() {
+        Instruction::TypeBool { .. } => Some(4),
+        Instruction::TypeInt { width, .. } | Instruction::TypeFloat { width, .. } => {
+            Some(width / 8)
+        }
+        Instruction::TypeVector {
+            component_type,
+            component_count,
+            ..
+        } => type_size(spirv, component_type, None)?.map(|size| size * component_count),
+        Instruction::TypeMatrix {
+            column_type,
+            column_count,
+            ..
+        } => match matrix_layout {
+            Some((matrix_stride, row_major)) => {
+                let rows = match *spirv.id(column_type).instruction() {
+                    Instruction::TypeVector {
+                        component_count, ..
+                    } => component_count,
+                    _ => 1,
+                };
+                Some(matrix_stride * if row_major { rows } else { column_count })
+            }
+            None => {
+                return Err(reflection_error(format!(
+                    "matrix type #{} has no matrix stride",
+                    id
+                )))
+            }
+        },
+        Instruction::TypeArray { length, .. } => {
+            let stride = array_stride(spirv, id)?;
+            Some(stride * constant_u32(spirv, length)?)
+        }
+        Instruction::TypeRuntimeArray { .. } => None,
+        Instruction::TypeStruct {
+            ref member_types, ..
+        } => {
+            let mut size = Some(0);
+
+            for (member_info, &member_type) in spirv.id(id).iter_members().zip(member_types.iter())
+            {
+                let (offset, member_size) =
+                    member_offset_and_size(spirv, id, &member_info, member_type)?;
+
+                size = match (size, member_size) {
+                    (Some(size), Some(member_size)) => Some(cmp::max(size, offset + member_size)),
+                    _ => None,
+                };
+            }
+
+            size
+        }
+        _ => {
+            return Err(reflection_error(format!(
+                "type #{} can't be used in a block",
+                id
+            )))
+        }
+    })
+}
+
+// Returns the offset and the size of a member of the struct `struct_id`, from the decorations of
+// the member.
+fn member_offset_and_size(
+    spirv: &Spirv,
+    struct_id: Id,
+    member_info: &StructMemberInfo,
+    member_type: Id,
+) -> Result<(u32, Option<u32>), ShaderCreationError> {
+    let mut offset = None;
+    let mut matrix_stride = None;
+    let mut row_major = false;
+
+    for instruction in member_info.iter_decoration() {
+        match *instruction {
+            Instruction::MemberDecorate {
+                decoration: Decoration::Offset { byte_offset },
+                ..
+            } => offset = Some(byte_offset),
+            Instruction::MemberDecorate {
+                decoration: Decoration::MatrixStride { matrix_stride: s },
+                ..
+            } => matrix_stride = Some(s),
+            Instruction::MemberDecorate {
+                decoration: Decoration::RowMajor,
+                ..
+            } => row_major = true,
+            _ => (),
+        }
+    }
+
+    let offset = offset.ok_or_else(|| {
+        reflection_error(format!("a member of struct #{} has no offset", struct_id))
+    })?;
+    let size = type_size(
+        spirv,
+        member_type,
+        matrix_stride.map(|stride| (stride, row_major)),
+    )?;
+
+    Ok((offset, size))
+}
+
+fn array_stride(spirv: &Spirv, id: Id) -> Result<u32, ShaderCreationError> {
+    spirv
+        .id(id)
+        .iter_decoration()
+        .find_map(|instruction| match *instruction {
+            Instruction::Decorate {
+                decoration: Decoration::ArrayStride { array_stride },
+                ..
+            } => Some(array_stride),
+            _ => None,
+        })
+        .ok_or_else(|| reflection_error(format!("array type #{} has no array stride", id)))
+}
+
+// Returns the specialization constants of the module that have a `SpecId` decoration.
+fn spec_constants(
+    spirv: &Spirv,
+) -> Result<HashMap<u32, SpecializationConstantRequirements>, ShaderCreationError> {
+    let mut requirements = HashMap::new();
+
+    for instruction in spirv.iter_global() {
+        let (result_type_id, result_id) = match *instruction {
+            Instruction::SpecConstantTrue {
+                result_type_id,
+                result_id,
+            }
+            | Instruction::SpecConstantFalse {
+                result_type_id,
+                result_id,
+            }
+            | Instruction::SpecConstant {
+                result_type_id,
+                result_id,
+                ..
+            } => (result_type_id, result_id),
+            _ => continue,
+        };
+
+        let constant_id = match spec_id(spirv, result_id) {
+            Some(constant_id) => constant_id,
+            None => continue,
+        };
+
+        let ty = match *spirv.id(result_type_id).instruction() {
+            Instruction::TypeBool { .. } => SpecializationConstantType::Bool,
+            Instruction::TypeInt {
+                width, signedness, ..
+            } => SpecializationConstantType::Int {
+                width,
+                signed: signedness != 0,
+            },
+            Instruction::TypeFloat { width, .. } => SpecializationConstantType::Float { width },
+            _ => {
+                return Err(reflection_error(format!(
+                    "specialization constant {} has a non-scalar type",
+                    constant_id
+                )))
+            }
+        };
+
+        requirements.insert(constant_id, SpecializationConstantRequirements::from(ty));
+    }
+
+    Ok(requirements)
+}
+
+fn spec_id(spirv: &Spirv, id: Id) -> Option<u32> {
+    spirv
+        .id(id)
+        .iter_decoration()
+        .find_map(|instruction| match *instruction {
+            Instruction::Decorate {
+                decoration:
+                    Decoration::SpecId {
+                        specialization_constant_id,
+                    },
+                ..
+            } => Some(specialization_constant_id),
+            _ => None,
+        })
+}
+
+fn geometry_execution_mode(
+    spirv: &Spirv,
+    id: Id,
+    name: &str,
+) -> Result<GeometryShaderExecutionMode, ShaderCreationError> {
+    spirv
+        .iter_execution_mode()
+        .find_map(|instruction| match *instruction {
+            Instruction::ExecutionMode {
+                entry_point,
+                ref mode,
+            } if entry_point == id => match *mode {
+                ExecutionMode::InputPoints => Some(GeometryShaderExecutionMode::Points),
+                ExecutionMode::InputLines => Some(GeometryShaderExecutionMode::Lines),
+                ExecutionMode::InputLinesAdjacency => {
+                    Some(GeometryShaderExecutionMode::LinesWithAdjacency)
+                }
+                ExecutionMode::Triangles => Some(GeometryShaderExecutionMode::Triangles),
+                ExecutionMode::InputTrianglesAdjacency => {
+                    Some(GeometryShaderExecutionMode::TrianglesWithAdjacency)
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .ok_or_else(|| {
+            reflection_error(format!(
+                "geometry entry point `{}` doesn't declare its input primitive",
+                name
+            ))
+        })
+}
+
+// Returns the local size of the compute entry point `id`. A constant decorated with the
+// `WorkgroupSize` built-in takes precedence over the `LocalSize` and `LocalSizeId` execution
+// modes.
+fn local_size(spirv: &Spirv, id: Id) -> Result<[LocalSizeValue; 3], ShaderCreationError> {
+    let mut local_size = None;
+
+    for instruction in spirv.iter_execution_mode() {
+        match *instruction {
+            Instruction::ExecutionMode {
+                entry_point,
+                mode:
+                    ExecutionMode::LocalSize {
+                        x_size,
+                        y_size,
+                        z_size,
+                    },
+            } if entry_point == id => {
+                local_size = Some([
+                    LocalSizeValue::Literal(x_size),
+                    LocalSizeValue::Literal(y_size),
+                    LocalSizeValue::Literal(z_size),
+                ]);
+            }
+            Instruction::ExecutionModeId {
+                entry_point,
+                mode:
+                    ExecutionMode::LocalSizeId {
+                        x_size,
+                        y_size,
+                        z_size,
+                    },
+            } if entry_point == id => {
+                local_size = Some([
+                    local_size_value(spirv, x_size)?,
+                    local_size_value(spirv, y_size)?,
+                    local_size_value(spirv, z_size)?,
+                ]);
+            }
+            _ => (),
+        }
+    }
+
+    for instruction in spirv.iter_global() {
+        let (result_id, constituents) = match *instruction {
+            Instruction::ConstantComposite {
+                result_id,
+                ref constituents,
+                ..
+            }
+            | Instruction::SpecConstantComposite {
+                result_id,
+                ref constituents,
+                ..
+            } => (result_id, constituents),
+            _ => continue,
+        };
+
+        let is_workgroup_size = spirv.id(result_id).iter_decoration().any(|instruction| {
+            matches!(
+                instruction,
+                Instruction::Decorate {
+                    decoration: Decoration::BuiltIn {
+                        built_in: BuiltIn::WorkgroupSize,
+                    },
+                    ..
+                }
+            )
+        });
+
+        if is_workgroup_size && constituents.len() == 3 {
+            local_size = Some([
+                local_size_value(spirv, constituents[0])?,
+                local_size_value(spirv, constituents[1])?,
+                local_size_value(spirv, constituents[2])?,
+            ]);
+        }
+    }
+
+    local_size.ok_or_else(|| {
+        reflection_error("compute entry point doesn't declare a local size".to_owned())
+    })
+}
+
+fn local_size_value(spirv: &Spirv, id: Id) -> Result<LocalSizeValue, ShaderCreationError> {
+    match *spirv.id(id).instruction() {
+        Instruction::Constant { ref value, .. } => Ok(LocalSizeValue::Literal(value[0])),
+        Instruction::SpecConstant { ref value, .. } => Ok(match spec_id(spirv, id) {
+            Some(constant_id) => LocalSizeValue::SpecConstant {
+                constant_id,
+                default_value: value[0],
+            },
+            None => LocalSizeValue::Literal(value[0]),
+        }),
+        _ => Err(reflection_error(
+            "local size must be an integer constant".to_owned(),
+        )),
+    }
+}
+
+// Returns the interface of an entry point for the given storage class.
+fn interface_of(
+    spirv: &Spirv,
+    interface: &[Id],
+    storage_class: StorageClass,
+    ignore_first_array: bool,
+) -> Result<ShaderInterface, ShaderCreationError> {
+    let mut elements: Vec<ShaderInterfaceEntry> = Vec::new();
+
+    for &variable_id in interface {
+        let result_type_id = match *spirv.id(variable_id).instruction() {
+            Instruction::Variable {
+                result_type_id,
+                storage_class: ref variable_storage_class,
+                ..
+            } if *variable_storage_class == storage_class => result_type_id,
+            _ => continue,
+        };
+
+        if is_builtin(spirv, variable_id) {
+            continue;
+        }
+
+        let name = match name_of(spirv, variable_id) {
+            Some(name) => name,
+            None => continue,
+        };
+
+        let location = spirv
+            .id(variable_id)
+            .iter_decoration()
+            .find_map(|instruction| match *instruction {
+                Instruction::Decorate {
+                    decoration: Decoration::Location { location },
+                    ..
+                } => Some(location),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                reflection_error(format!(
+                    "attribute `{}` (id {}) is missing a location",
+                    name, variable_id
+                ))
+            })?;
+
+        let (format, location_len) = format_from_id(spirv, result_type_id, ignore_first_array)?;
+        let location = location..location + location_len;
+
+        if let Some(other) = elements.iter().find(|other| {
+            other.location.start < location.end && location.start < other.location.end
+        }) {
+            return Err(reflection_error(format!(
+                "the locations of attributes `{}` ({:?}) and `{}` ({:?}) overlap",
+                other.name.as_ref().unwrap(),
+                other.location,
+                name,
+                location
+            )));
+        }
+
+        elements.push(ShaderInterfaceEntry {
+            location,
+            format,
+            name: Some(Cow::Owned(name.to_owned())),
+        });
+    }
+
+    // The checks of `new_unchecked` are performed above.
+    Ok(unsafe { ShaderInterface::new_unchecked(elements) })
+}
+
+// Returns the format and the number of locations of the type of an interface variable.
+//
+// If `ignore_first_array` is true, the type is expected to be an array, whose elements are
+// the actual attributes.
+fn format_from_id(
+    spirv: &Spirv,
+    searched: Id,
+    ignore_first_array: bool,
+) -> Result<(Format, u32), ShaderCreationError> {
+    let not_array = || {
+        reflection_error(format!(
+            "expected type #{} of a per-vertex interface variable to be an array",
+            searched
+        ))
+    };
+
+    match *spirv.id(searched).instruction() {
+        Instruction::TypePointer { ty, .. } => format_from_id(spirv, ty, ignore_first_array),
+        Instruction::TypeArray {
+            element_type,
+            length,
+            ..
+        } => {
+            if ignore_first_array {
+                format_from_id(spirv, element_type, false)
+            } else {
+                let (format, locations) = format_from_id(spirv, element_type, false)?;
+                Ok((format, locations * constant_u32(spirv, length)?))
+            }
+        }
+        Instruction::TypeMatrix {
+            column_type,
+            column_count,
+            ..
+        } if !ignore_first_array => {
+            let (format, locations) = format_from_id(spirv, column_type, false)?;
+            Ok((format, locations * column_count))
+        }
+        Instruction::TypeVector {
+            component_type,
+            component_count,
+            ..
+        } if !ignore_first_array => Ok((scalar_format(spirv, component_type, component_count)?, 1)),
+        Instruction::TypeInt { .. } | Instruction::TypeFloat { .. } if !ignore_first_array => {
+            Ok((scalar_format(spirv, searched, 1)?, 1))
+        }
+        _ if ignore_first_array => Err(not_array()),
+        _ => Err(reflection_error(format!(
+            "type #{} not found or invalid in a shader interface",
+            searched
+        ))),
+    }
+}
+
+// Returns the format of a vector of `count` components of the scalar type `id`.
+fn scalar_format(spirv: &Spirv, id: Id, count: u32) -> Result<Format, ShaderCreationError> {
+    let format = match (spirv.id(id).instruction(), count) {
+        (
+            &Instruction::TypeInt {
+                width: 32,
+                signedness: 1,
+                ..
+            },
+            1,
+        ) => Format::R32_SINT,
+        (
+            &Instruction::TypeInt {
+                width: 32,
+                signedness: 1,
+                ..
+            },
+            2,
+        ) => Format::R32G32_SINT,
+        (
+            &Instruction::TypeInt {
+                width: 32,
+                signedness: 1,
+                ..
+            },
+            3,
+        ) => Format::R32G32B32_SINT,
+        (
+            &Instruction::TypeInt {
+                width: 32,
+                signedness: 1,
+                ..
+            },
+            4,
+        ) => Format::R32G32B32A32_SINT,
+        (&Instruction::TypeInt { width: 32, .. }, 1) => Format::R32_UINT,
+        (&Instruction::TypeInt { width: 32, .. }, 2) => Format::R32G32_UINT,
+        (&Instruction::TypeInt { width: 32, .. }, 3) => Format::R32G32B32_UINT,
+        (&Instruction::TypeInt { width: 32, .. }, 4) => Format::R32G32B32A32_UINT,
+        (&Instruction::TypeFloat { width: 32, .. }, 1) => Format::R32_SFLOAT,
+        (&Instruction::TypeFloat { width: 32, .. }, 2) => Format::R32G32_SFLOAT,
+        (&Instruction::TypeFloat { width: 32, .. }, 3) => Format::R32G32B32_SFLOAT,
+        (&Instruction::TypeFloat { width: 32, .. }, 4) => Format::R32G32B32A32_SFLOAT,
+        // Scalars of other widths can't be part of a vector in an interface.
+        (
+            &Instruction::TypeInt {
+                width: 8,
+                signedness: 1,
+                ..
+            },
+            1,
+        ) => Format::R8_SINT,
+        (&Instruction::TypeInt { width: 8, .. }, 1) => Format::R8_UINT,
+        (
+            &Instruction::TypeInt {
+                width: 16,
+                signedness: 1,
+                ..
+            },
+            1,
+        ) => Format::R16_SINT,
+        (&Instruction::TypeInt { width: 16, .. }, 1) => Format::R16_UINT,
+        (
+            &Instruction::TypeInt {
+                width: 64,
+                signedness: 1,
+                ..
+            },
+            1,
+        ) => Format::R64_SINT,
+        (&Instruction::TypeInt { width: 64, .. }, 1) => Format::R64_UINT,
+        (&Instruction::TypeFloat { width: 64, .. }, 1) => Format::R64_SFLOAT,
+        _ => {
+            return Err(reflection_error(format!(
+                "no format for a vector of {} components of type #{}",
+                count, id
+            )))
+        }
+    };
+
+    Ok(format)
+}
+
+// Returns true if a `BuiltIn` decoration is applied on an id, on one of its members, or on the
+// type that it points to or contains.
+fn is_builtin(spirv: &Spirv, id: Id) -> bool {
+    let id_info = spirv.id(id);
+
+    let decorated = id_info.iter_decoration().any(|instruction| {
+        matches!(
+            instruction,
+            Instruction::Decorate {
+                decoration: Decoration::BuiltIn { .. },
+                ..
+            }
+        )
+    });
+    let member_decorated = id_info
+        .iter_members()
+        .flat_map(|member_info| member_info.iter_decoration())
+        .any(|instruction| {
+            matches!(
+                instruction,
+                Instruction::MemberDecorate {
+                    decoration: Decoration::BuiltIn { .. },
+                    ..
+                }
+            )
+        });
+
+    if decorated || member_decorated {
+        return true;
+    }
+
+    match *id_info.instruction() {
+        Instruction::Variable { result_type_id, .. } => is_builtin(spirv, result_type_id),
+        Instruction::TypeArray { element_type, .. }
+        | Instruction::TypeRuntimeArray { element_type, .. } => is_builtin(spirv, element_type),
+        Instruction::TypeStruct {
+            ref member_types, ..
+        } => member_types.iter().any(|&ty| is_builtin(spirv, ty)),
+        Instruction::TypePointer { ty, .. } => is_builtin(spirv, ty),
+        _ => false,
+    }
+}
+
+// Returns the format that corresponds to the format of a storage image or texel buffer.
+fn vulkan_format(image_format: &ImageFormat) -> Option<Format> {
+    Some(match *image_format {
+        ImageFormat::Unknown => return None,
+        ImageFormat::Rgba32f => Format::R32G32B32A32_SFLOAT,
+        ImageFormat::Rgba16f => Format::R16G16B16A16_SFLOAT,
+        ImageFormat::R32f => Format::R32_SFLOAT,
+        ImageFormat::Rgba8 => Format::R8G8B8A8_UNORM,
+        ImageFormat::Rgba8Snorm => Format::R8G8B8A8_SNORM,
+        ImageFormat::Rg32f => Format::R32G32_SFLOAT,
+        ImageFormat::Rg16f => Format::R16G16_SFLOAT,
+        ImageFormat::R11fG11fB10f => Format::B10G11R11_UFLOAT_PACK32,
+        ImageFormat::R16f => Format::R16_SFLOAT,
+        ImageFormat::Rgba16 => Format::R16G16B16A16_UNORM,
+        ImageFormat::Rgb10A2 => Format::A2B10G10R10_UNORM_PACK32,
+        ImageFormat::Rg16 => Format::R16G16_UNORM,
+        ImageFormat::Rg8 => Format::R8G8_UNORM,
+        ImageFormat::R16 => Format::R16_UNORM,
+        ImageFormat::R8 => Format::R8_UNORM,
+        ImageFormat::Rgba16Snorm => Format::R16G16B16A16_SNORM,
+        ImageFormat::Rg16Snorm => Format::R16G16_SNORM,
+        ImageFormat::Rg8Snorm => Format::R8G8_SNORM,
+        ImageFormat::R16Snorm => Format::R16_SNORM,
+        ImageFormat::R8Snorm => Format::R8_SNORM,
+        ImageFormat::Rgba32i => Format::R32G32B32A32_SINT,
+        ImageFormat::Rgba16i => Format::R16G16B16A16_SINT,
+        ImageFormat::Rgba8i => Format::R8G8B8A8_SINT,
+        ImageFormat::R32i => Format::R32_SINT,
+        ImageFormat::Rg32i => Format::R32G32_SINT,
+        ImageFormat::Rg16i => Format::R16G16_SINT,
+        ImageFormat::Rg8i => Format::R8G8_SINT,
+        ImageFormat::R16i => Format::R16_SINT,
+        ImageFormat::R8i => Format::R8_SINT,
+        ImageFormat::Rgba32ui => Format::R32G32B32A32_UINT,
+        ImageFormat::Rgba16ui => Format::R16G16B16A16_UINT,
+        ImageFormat::Rgba8ui => Format::R8G8B8A8_UINT,
+        ImageFormat::R32ui => Format::R32_UINT,
+        ImageFormat::Rgb10a2ui => Format::A2B10G10R10_UINT_PACK32,
+        ImageFormat::Rg32ui => Format::R32G32_UINT,
+        ImageFormat::Rg16ui => Format::R16G16_UINT,
+        ImageFormat::Rg8ui => Format::R8G8_UINT,
+        ImageFormat::R16ui => Format::R16_UINT,
+        ImageFormat::R8ui => Format::R8_UINT,
+        ImageFormat::R64ui => Format::R64_UINT,
+        ImageFormat::R64i => Format::R64_SINT,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Assembles a SPIR-V 1.0 module from the opcode and the operands of each instruction.
+    fn assemble(instructions: &[(u16, &[u32])]) -> Vec<u32> {
+        let mut words = vec![0x07230203, 0x00010000, 0, 64, 0];
+        for &(opcode, operands) in instructions {
+            words.push((operands.len() as u32 + 1) << 16 | opcode as u32);
+            words.extend_from_slice(operands);
+        }
+        words
+    }
+
+    fn bindings(info: &EntryPointInfo) -> Vec<(u32, u32)> {
+        let mut bindings = Vec::new();
+        for (set_num, set) in info.descriptor_set_layout_descs().iter().enumerate() {
+            for (binding_num, desc) in set.bindings().iter().enumerate() {
+                if desc.is_some() {
+                    bindings.push((set_num as u32, binding_num as u32));
+                }
+            }
+        }
+        bindings
+    }
+
+    #[test]
+    fn descriptors_per_entry_point() {
+        // Entry point `a` (%1) loads the sampler at binding 0. Entry point `b` (%2) calls a
+        // function (%30) that loads the sampler at binding 1.
+        let words = assemble(&[
+            (17, &[1]),              // OpCapability Shader
+            (14, &[0, 1]),           // OpMemoryModel Logical GLSL450
+            (15, &[5, 1, 0x61]),     // OpEntryPoint GLCompute %1 "a"
+            (15, &[5, 2, 0x62]),     // OpEntryPoint GLCompute %2 "b"
+            (16, &[1, 17, 1, 1, 1]), // OpExecutionMode %1 LocalSize 1 1 1
+            (16, &[2, 17, 1, 1, 1]), // OpExecutionMode %2 LocalSize 1 1 1
+            (71, &[10, 34, 0]),      // OpDecorate %10 DescriptorSet 0
+            (71, &[10, 33, 0]),      // OpDecorate %10 Binding 0
+            (71, &[11, 34, 0]),      // OpDecorate %11 DescriptorSet 0
+            (71, &[11, 33, 1]),      // OpDecorate %11 Binding 1
+            (19, &[3]),              // %3 = OpTypeVoid
+            (33, &[4, 3]),           // %4 = OpTypeFunction %3
+            (26, &[5]),              // %5 = OpTypeSampler
+            (32, &[6, 0, 5]),        // %6 = OpTypePointer UniformConstant %5
+            (59, &[6, 10, 0]),       // %10 = OpVariable %6 UniformConstant
+            (59, &[6, 11, 0]),       // %11 = OpVariable %6 UniformConstant
+            (54, &[3, 1, 0, 4]),     // %1 = OpFunction %3 None %4
+            (248, &[20]),            // %20 = OpLabel
+            (61, &[5, 21, 10]),      // %21 = OpLoad %5 %10
+            (253, &[]),              // OpReturn
+            (56, &[]),               // OpFunctionEnd
+            (54, &[3, 2, 0, 4]),     // %2 = OpFunction %3 None %4
+            (248, &[22]),            // %22 = OpLabel
+            (57, &[3, 23, 30]),      // %23 = OpFunctionCall %3 %30
+            (253, &[]),              // OpReturn
+            (56, &[]),               // OpFunctionEnd
+            (54, &[3, 30, 0, 4]),    // %30 = OpFunction %3 None %4
+            (248, &[24]),            // %24 = OpLabel
+            (61, &[5, 25, 11]),      // %25 = OpLoad %5 %11
+            (253, &[]),              // OpReturn
+            (56, &[]),               // OpFunctionEnd
+        ]);
+
+        let entry_points = EntryPointInfo::from_spirv(&words, true).unwrap();
+        assert_eq!(bindings(&entry_points[0]), [(0, 0)]);
+        assert_eq!(bindings(&entry_points[1]), [(0, 1)]);
+
+        let entry_points = EntryPointInfo::from_spirv(&words, false).unwrap();
+        assert_eq!(bindings(&entry_points[0]), [(0, 0), (0, 1)]);
+        assert_eq!(bindings(&entry_points[1]), [(0, 0), (0, 1)]);
+    }
+
+    #[test]
+    fn push_constant_range_offset() {
+        // layout(push_constant) uniform PushConstants {
+        //     layout(offset = 16) vec4 color;
+        //     layout(offset = 32) float scale;
+        // };
+        let words = assemble(&[
+            (17, &[1]),                   // OpCapability Shader
+            (14, &[0, 1]),                // OpMemoryModel Logical GLSL450
+            (15, &[5, 1, 0x6e69616d, 0]), // OpEntryPoint GLCompute %1 "main"
+            (16, &[1, 17, 1, 1, 1]),      // OpExecutionMode %1 LocalSize 1 1 1
+            (72, &[7, 0, 35, 16]),        // OpMemberDecorate %7 0 Offset 16
+            (72, &[7, 1, 35, 32]),        // OpMemberDecorate %7 1 Offset 32
+            (71, &[7, 2]),                // OpDecorate %7 Block
+            (19, &[3]),                   // %3 = OpTypeVoid
+            (33, &[4, 3]),                // %4 = OpTypeFunction %3
+            (22, &[5, 32]),               // %5 = OpTypeFloat 32
+            (23, &[6, 5, 4]),             // %6 = OpTypeVector %5 4
+            (30, &[7, 6, 5]),             // %7 = OpTypeStruct %6 %5
+            (32, &[8, 9, 7]),             // %8 = OpTypePointer PushConstant %7
+            (59, &[8, 9, 9]),             // %9 = OpVariable %8 PushConstant
+            (54, &[3, 1, 0, 4]),          // %1 = OpFunction %3 None %4
+            (248, &[10]),                 // %10 = OpLabel
+            (253, &[]),                   // OpReturn
+            (56, &[]),                    // OpFunctionEnd
+        ]);
+
+        let entry_points = EntryPointInfo::from_spirv(&words, false).unwrap();
+        let range = entry_points[0].push_constant_range().unwrap();
+        // The range covers the members of the block, not the bytes before the first one.
+        assert_eq!(range.offset, 16);
+        assert_eq!(range.size, 20);
+        assert!(range.stages.compute);
+    }
+}
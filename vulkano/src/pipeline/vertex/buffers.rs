@@ -16,6 +16,7 @@ use crate::pipeline::vertex::VertexInput;
 use crate::pipeline::vertex::VertexInputAttribute;
 use crate::pipeline::vertex::VertexInputBinding;
 use crate::pipeline::vertex::VertexInputRate;
+use crate::pipeline::vertex::VertexMemberTy;
 use crate::DeviceSize;
 use std::mem;
 
@@ -131,20 +132,108 @@ unsafe impl VertexDefinition for BuffersDefinition {
                 });
             }
 
+            // Members with an exact format are read with that format, the others are assumed to
+            // have the same format as the shader input.
+            let format = match infos.ty {
+                VertexMemberTy::Format(format) => format,
+                _ => element.format,
+            };
+
             let mut offset = infos.offset as DeviceSize;
             for location in element.location.clone() {
                 attributes.push((
                     location,
                     VertexInputAttribute {
                         binding,
-                        format: element.format,
+                        format,
                         offset: offset as u32,
                     },
                 ));
-                offset += element.format.size().unwrap();
+                offset += format.size().unwrap();
             }
         }
 
         Ok(VertexInput::new(bindings, attributes))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::format::Format;
+    use crate::pipeline::shader::ShaderInterface;
+    use crate::pipeline::shader::ShaderInterfaceEntry;
+    use crate::pipeline::vertex::BuffersDefinition;
+    use crate::pipeline::vertex::IncompatibleVertexDefinitionError;
+    use crate::pipeline::vertex::Vertex;
+    use crate::pipeline::vertex::VertexDefinition;
+    use crate::pipeline::vertex::VertexMemberInfo;
+    use crate::pipeline::vertex::VertexMemberTy;
+    use std::borrow::Cow;
+
+    #[repr(C)]
+    struct ColorVertex {
+        position: [f32; 2],
+        color: [u8; 4],
+    }
+
+    unsafe impl Vertex for ColorVertex {
+        fn member(name: &str) -> Option<VertexMemberInfo> {
+            match name {
+                "position" => Some(VertexMemberInfo {
+                    offset: 0,
+                    ty: VertexMemberTy::F32,
+                    array_size: 2,
+                }),
+                "in_color" => Some(VertexMemberInfo {
+                    offset: 8,
+                    ty: VertexMemberTy::Format(Format::R8G8B8A8_UNORM),
+                    array_size: 1,
+                }),
+                _ => None,
+            }
+        }
+    }
+
+    fn interface(color_format: Format) -> ShaderInterface {
+        unsafe {
+            ShaderInterface::new_unchecked(vec![
+                ShaderInterfaceEntry {
+                    location: 0..1,
+                    format: Format::R32G32_SFLOAT,
+                    name: Some(Cow::Borrowed("position")),
+                },
+                ShaderInterfaceEntry {
+                    location: 1..2,
+                    format: color_format,
+                    name: Some(Cow::Borrowed("in_color")),
+                },
+            ])
+        }
+    }
+
+    #[test]
+    fn exact_format_used() {
+        let definition = BuffersDefinition::new().vertex::<ColorVertex>();
+        let input = definition
+            .definition(&interface(Format::R32G32B32A32_SFLOAT))
+            .unwrap();
+
+        let mut attributes: Vec<_> = input.attributes().collect();
+        attributes.sort_by_key(|&(location, _)| location);
+        assert_eq!(attributes[0].1.format, Format::R32G32_SFLOAT);
+        assert_eq!(attributes[1].1.format, Format::R8G8B8A8_UNORM);
+        assert_eq!(attributes[1].1.offset, 8);
+    }
+
+    #[test]
+    fn exact_format_mismatch() {
+        let definition = BuffersDefinition::new().vertex::<ColorVertex>();
+
+        match definition.definition(&interface(Format::R32G32B32A32_UINT)) {
+            Err(IncompatibleVertexDefinitionError::FormatMismatch { attribute, .. }) => {
+                assert_eq!(attribute, "in_color")
+            }
+            _ => panic!(),
+        }
+    }
+}
@@ -10,6 +10,10 @@
 use crate::pipeline::vertex::VertexMemberTy;
 
 /// Implements the `Vertex` trait on a struct.
+///
+/// The format of each member is deduced from its Rust type. Prefer `#[derive(Vertex)]`, which
+/// also supports explicit formats and generic structs.
+///
 ///# Example
 ///
 ///```
@@ -27,8 +27,9 @@
 //! `SingleBufferDefinition`) require you to use a buffer whose content is `[V]` where `V`
 //! implements the `Vertex` trait.
 //!
-//! The `Vertex` trait is unsafe, but can be implemented on a struct with the `impl_vertex!`
-//! macro.
+//! The `Vertex` trait is unsafe, but can be implemented on a struct with `#[derive(Vertex)]`,
+//! or with the older `impl_vertex!` macro. The derive macro additionally lets you specify the
+//! exact format of each member, see the documentation of the `Vertex` trait.
 //!
 //! # Example
 //!
@@ -69,10 +70,13 @@ pub use self::definition::IncompatibleVertexDefinitionError;
 pub use self::definition::VertexDefinition;
 pub use self::impl_vertex::VertexMember;
 pub use self::vertex::Vertex;
+#[doc(hidden)]
+pub use self::vertex::VertexMemberFormatCheck;
 pub use self::vertex::VertexMemberInfo;
 pub use self::vertex::VertexMemberTy;
 use crate::format::Format;
 use fnv::FnvHashMap;
+pub use vulkano_macros::Vertex;
 
 mod buffers;
 mod collection;
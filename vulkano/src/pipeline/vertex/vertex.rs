@@ -8,12 +8,54 @@
 // according to those terms.
 
 use crate::format::Format;
+use crate::format::NumericType;
+use std::marker::PhantomData;
+use std::mem;
 
 /// Describes an individual `Vertex`. In other words a collection of attributes that can be read
 /// from a vertex shader.
 ///
 /// At this stage, the vertex is in a "raw" format. For example a `[f32; 4]` can match both a
 /// `vec4` or a `float[4]`. The way the things are bound depends on the shader.
+///
+/// # Deriving `Vertex`
+///
+/// The easiest way to implement this trait is `#[derive(Vertex)]`. By default, the format of
+/// each field is deduced from its Rust type through the `VertexMember` trait, and matched with
+/// the type of the shader input that has the same name.
+///
+/// The `#[format(...)]` attribute gives the exact format of a field instead, which makes it
+/// possible to use normalized, packed or half-precision formats. The Rust type of the field then
+/// only needs to have the right size. The `#[name(...)]` attribute gives the names of the shader
+/// inputs the field corresponds to, if they differ from the name of the field.
+///
+/// ```
+/// use vulkano::pipeline::vertex::Vertex;
+///
+/// #[derive(Clone, Copy, Vertex)]
+/// #[repr(C)]
+/// struct MyVertex {
+///     position: [f32; 3],
+///     #[name("in_color")]
+///     #[format(R8G8B8A8_UNORM)]
+///     color: [u8; 4],
+///     #[format(R16G16_SFLOAT)]
+///     uv: [u16; 2],
+/// }
+/// ```
+///
+/// If the size of a field is not a multiple of the size of its format, compilation fails:
+///
+/// ```compile_fail
+/// use vulkano::pipeline::vertex::Vertex;
+///
+/// #[derive(Clone, Copy, Vertex)]
+/// #[repr(C)]
+/// struct MyVertex {
+///     #[format(R32G32B32A32_SFLOAT)]
+///     color: [u8; 4],
+/// }
+/// ```
 pub unsafe trait Vertex: 'static + Send + Sync {
     /// Returns the characteristics of a vertex member by its name.
     fn member(name: &str) -> Option<VertexMemberInfo>;
@@ -48,6 +90,9 @@ pub enum VertexMemberTy {
     U32,
     F32,
     F64,
+    /// The member has an exact format, given by a `#[format(...)]` attribute. The array size is
+    /// the number of consecutive elements of that format.
+    Format(Format),
 }
 
 impl VertexMemberTy {
@@ -64,6 +109,11 @@ impl VertexMemberTy {
             VertexMemberTy::U32 => 4,
             VertexMemberTy::F32 => 4,
             VertexMemberTy::F64 => 8,
+            VertexMemberTy::Format(my_format) => {
+                return array_size == num_locs as usize
+                    && shader_numeric_type(my_format).is_some()
+                    && shader_numeric_type(my_format) == shader_numeric_type(format);
+            }
         };
 
         let format_size = match format.size() {
@@ -74,3 +124,142 @@ impl VertexMemberTy {
         array_size * my_size == format_size * num_locs as usize
     }
 }
+
+// Used by `#[derive(Vertex)]` to obtain the array size of a member that has a `#[format(...)]`
+// attribute. Evaluating `ARRAY_SIZE` fails at compile time if the format has no well-defined size
+// or if the size of the member isn't a multiple of it.
+#[doc(hidden)]
+pub struct VertexMemberFormatCheck<T, const FORMAT_SIZE: usize>(PhantomData<T>);
+
+impl<T, const FORMAT_SIZE: usize> VertexMemberFormatCheck<T, FORMAT_SIZE> {
+    pub const ARRAY_SIZE: usize = {
+        let valid = FORMAT_SIZE != 0 && mem::size_of::<T>() % FORMAT_SIZE == 0;
+        let array_size = if FORMAT_SIZE != 0 {
+            mem::size_of::<T>() / FORMAT_SIZE
+        } else {
+            0
+        };
+
+        // `assert!` can't be used in constants with the minimum supported Rust version, so the
+        // evaluation is made to fail by indexing out of bounds instead.
+        [array_size][!valid as usize]
+    };
+}
+
+/// Returns the numeric type that a shader reads from a vertex attribute of the given format, and
+/// whether it is a 64-bit type.
+fn shader_numeric_type(format: Format) -> Option<(NumericType, bool)> {
+    let ty = match format.type_color()? {
+        NumericType::SINT => NumericType::SINT,
+        NumericType::UINT => NumericType::UINT,
+        _ => NumericType::SFLOAT,
+    };
+
+    Some((ty, format.components()[0] == 64))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::format::Format;
+    use crate::pipeline::vertex::Vertex;
+    use crate::pipeline::vertex::VertexMemberTy;
+
+    #[test]
+    fn exact_format_matches() {
+        let color = VertexMemberTy::Format(Format::R8G8B8A8_UNORM);
+        assert!(color.matches(1, Format::R32G32B32A32_SFLOAT, 1));
+        assert!(!color.matches(1, Format::R32G32B32A32_UINT, 1));
+        assert!(!color.matches(2, Format::R32G32B32A32_SFLOAT, 1));
+
+        let index = VertexMemberTy::Format(Format::R16_UINT);
+        assert!(index.matches(1, Format::R32_UINT, 1));
+        assert!(!index.matches(1, Format::R32_SINT, 1));
+
+        let double = VertexMemberTy::Format(Format::R64G64_SFLOAT);
+        assert!(double.matches(1, Format::R64G64_SFLOAT, 1));
+        assert!(!double.matches(1, Format::R32G32_SFLOAT, 1));
+    }
+
+    #[test]
+    fn derive_default_formats() {
+        #[derive(Clone, Copy, Vertex)]
+        #[repr(C)]
+        struct MyVertex {
+            position: [f32; 3],
+            id: u32,
+        }
+
+        let position = MyVertex::member("position").unwrap();
+        assert_eq!(position.offset, 0);
+        assert_eq!(position.ty, VertexMemberTy::F32);
+        assert_eq!(position.array_size, 3);
+
+        let id = MyVertex::member("id").unwrap();
+        assert_eq!(id.offset, 12);
+        assert_eq!(id.ty, VertexMemberTy::U32);
+        assert_eq!(id.array_size, 1);
+
+        assert!(MyVertex::member("color").is_none());
+    }
+
+    #[test]
+    fn derive_name() {
+        #[derive(Clone, Copy, Vertex)]
+        #[repr(C)]
+        struct MyVertex {
+            #[name("in_position", "in_normal")]
+            position: [f32; 3],
+        }
+
+        assert!(MyVertex::member("position").is_none());
+        assert_eq!(MyVertex::member("in_position").unwrap().offset, 0);
+        assert_eq!(MyVertex::member("in_normal").unwrap().offset, 0);
+    }
+
+    #[test]
+    fn derive_format() {
+        #[derive(Clone, Copy, Vertex)]
+        #[repr(C)]
+        struct MyVertex {
+            position: [f32; 2],
+            #[format(R8G8B8A8_UNORM)]
+            color: [u8; 4],
+            #[format(R16G16_SFLOAT)]
+            uvs: [[u16; 2]; 3],
+        }
+
+        let color = MyVertex::member("color").unwrap();
+        assert_eq!(color.offset, 8);
+        assert_eq!(color.ty, VertexMemberTy::Format(Format::R8G8B8A8_UNORM));
+        assert_eq!(color.array_size, 1);
+
+        let uvs = MyVertex::member("uvs").unwrap();
+        assert_eq!(uvs.offset, 12);
+        assert_eq!(uvs.ty, VertexMemberTy::Format(Format::R16G16_SFLOAT));
+        assert_eq!(uvs.array_size, 3);
+    }
+
+    #[test]
+    fn derive_generic() {
+        #[derive(Clone, Copy, Vertex)]
+        #[repr(C)]
+        struct MyVertex<T, U> {
+            position: T,
+            #[format(R32G32_SFLOAT)]
+            extra: U,
+        }
+
+        let position = MyVertex::<[f32; 4], [f32; 2]>::member("position").unwrap();
+        assert_eq!(position.ty, VertexMemberTy::F32);
+        assert_eq!(position.array_size, 4);
+
+        let extra = MyVertex::<[f32; 4], [f32; 2]>::member("extra").unwrap();
+        assert_eq!(extra.offset, 16);
+        assert_eq!(extra.array_size, 1);
+
+        let extra = MyVertex::<[u32; 2], [[f32; 2]; 2]>::member("extra").unwrap();
+        assert_eq!(extra.offset, 8);
+        assert_eq!(extra.ty, VertexMemberTy::Format(Format::R32G32_SFLOAT));
+        assert_eq!(extra.array_size, 2);
+    }
+}
@@ -48,12 +48,16 @@
 //! In all cases the number of viewports and scissor boxes must be the same.
 //!
 
+use crate::pipeline::hash_f32;
+use std::hash::Hash;
+use std::hash::Hasher;
 use std::ops::Range;
 
 /// List of viewports and scissors that are used when creating a graphics pipeline object.
 ///
 /// Note that the number of viewports and scissors must be the same.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ViewportsState {
     /// The state is known in advance.
     Fixed {
@@ -123,6 +127,7 @@ impl ViewportsState {
 //        x + width must be less than or equal to viewportBoundsRange[0]
 //        y + height must be less than or equal to viewportBoundsRange[1]
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Viewport {
     /// Coordinates in pixels of the top-left hand corner of the viewport.
     pub origin: [f32; 2],
@@ -140,6 +145,20 @@ pub struct Viewport {
     pub depth_range: Range<f32>,
 }
 
+impl Eq for Viewport {}
+
+impl Hash for Viewport {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_f32(self.origin[0], state);
+        hash_f32(self.origin[1], state);
+        hash_f32(self.dimensions[0], state);
+        hash_f32(self.dimensions[1], state);
+        hash_f32(self.depth_range.start, state);
+        hash_f32(self.depth_range.end, state);
+    }
+}
+
 impl From<Viewport> for ash::vk::Viewport {
     #[inline]
     fn from(val: Viewport) -> Self {
@@ -158,7 +177,8 @@ impl From<Viewport> for ash::vk::Viewport {
 // FIXME: add a check:
 //      Evaluation of (offset.x + extent.width) must not cause a signed integer addition overflow
 //      Evaluation of (offset.y + extent.height) must not cause a signed integer addition overflow
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Scissor {
     /// Coordinates in pixels of the top-left hand corner of the box.
     pub origin: [u32; 2],
@@ -12,6 +12,11 @@
 //! This can be used to inspect and validate a SPIR-V module at runtime. The `Spirv` type does some
 //! validation, but you should not assume that code that is read successfully is valid.
 //!
+//! A module can also be modified, for example to change the descriptor set and binding numbers of
+//! its descriptors or to remove its debug information, and then encoded back into words with
+//! `to_words`. `disassemble` returns a textual representation of the module, similar to the one
+//! produced by `spirv-dis`.
+//!
 //! For more information about SPIR-V modules, instructions and types, see the
 //! [SPIR-V specification](https://www.khronos.org/registry/SPIR-V/specs/unified1/SPIRV.html).
 
@@ -27,10 +32,15 @@ use std::{
 // Generated by build.rs
 include!(concat!(env!("OUT_DIR"), "/spirv.rs"));
 
+mod edit;
+
+const MAGIC_NUMBER: u32 = 0x07230203;
+
 /// A parsed and analyzed SPIR-V module.
 #[derive(Clone, Debug)]
 pub struct Spirv {
     version: Version,
+    generator: u32,
     bound: u32,
     instructions: Vec<Instruction>,
     ids: HashMap<Id, IdDataIndices>,
@@ -54,7 +64,7 @@ impl Spirv {
             return Err(SpirvError::InvalidHeader);
         }
 
-        if words[0] != 0x07230203 {
+        if words[0] != MAGIC_NUMBER {
             return Err(SpirvError::InvalidHeader);
         }
 
@@ -64,6 +74,7 @@ impl Spirv {
             patch: words[1] & 0x000000ff,
         };
 
+        let generator = words[2];
         let bound = words[3];
 
         let instructions = {
@@ -96,6 +107,17 @@ impl Spirv {
             ret
         };
 
+        Spirv::from_instructions(version, generator, bound, instructions)
+    }
+
+    // Analyzes a list of instructions. This is also used to rebuild the module after instructions
+    // were removed from it.
+    fn from_instructions(
+        version: Version,
+        generator: u32,
+        bound: u32,
+        instructions: Vec<Instruction>,
+    ) -> Result<Spirv, SpirvError> {
         // It is impossible for a valid SPIR-V file to contain more Ids than instructions, so put
         // a sane upper limit on the allocation. This prevents a malicious file from causing huge
         // memory allocations.
@@ -249,6 +271,7 @@ impl Spirv {
 
         let mut spirv = Spirv {
             version,
+            generator,
             bound,
             instructions,
             ids,
@@ -388,6 +411,61 @@ impl Spirv {
         self.bound
     }
 
+    /// Returns the magic number of the tool that generated the module, or 0 if it is unknown.
+    #[inline]
+    pub fn generator(&self) -> u32 {
+        self.generator
+    }
+
+    /// Encodes the module back into SPIR-V words.
+    pub fn to_words(&self) -> Vec<u32> {
+        let mut words = vec![
+            MAGIC_NUMBER,
+            self.version.major << 16 | self.version.minor << 8,
+            self.generator,
+            self.bound,
+            0,
+        ];
+
+        for instruction in &self.instructions {
+            instruction.encode(&mut words);
+        }
+
+        words
+    }
+
+    /// Returns a textual representation of the module, in a format similar to the one of
+    /// `spirv-dis`.
+    ///
+    /// Each instruction is written on its own line, with its result `Id` aligned on the left of
+    /// the `=` sign. Literal numbers are written as integers regardless of their type.
+    pub fn disassemble(&self) -> String {
+        let mut out = format!(
+            "; SPIR-V\n; Version: {}.{}\n; Generator: {:#010x}\n; Bound: {}\n; Schema: 0\n",
+            self.version.major, self.version.minor, self.generator, self.bound,
+        );
+
+        // The widest result id, so that the `=` signs are aligned.
+        let width = format!("%{} = ", self.bound).len();
+
+        for instruction in &self.instructions {
+            match instruction.result_id() {
+                Some(id) => {
+                    let result = format!("{} = ", id);
+                    out.push_str(&format!("{:>width$}", result, width = width));
+                }
+                None => out.push_str(&" ".repeat(width)),
+            }
+
+            out.push_str("Op");
+            out.push_str(instruction.name());
+            instruction.disassemble_operands(&mut out);
+            out.push('\n');
+        }
+
+        out
+    }
+
     /// Returns information about an `Id`.
     ///
     /// # Panics
@@ -575,6 +653,15 @@ impl Display for Id {
     }
 }
 
+impl Instruction {
+    /// Encodes the instruction, and appends its words to `words`.
+    pub fn encode(&self, words: &mut Vec<u32>) {
+        let mut writer = InstructionWriter::new(words);
+        self.write_operands(&mut writer);
+        writer.finish(self.opcode());
+    }
+}
+
 /// Helper type for parsing the words of an instruction.
 #[derive(Debug)]
 struct InstructionReader<'a> {
@@ -658,6 +745,202 @@ impl<'a> InstructionReader<'a> {
     }
 }
 
+/// Helper type for encoding the words of an instruction.
+#[derive(Debug)]
+struct InstructionWriter<'a> {
+    words: &'a mut Vec<u32>,
+    start: usize,
+}
+
+impl<'a> InstructionWriter<'a> {
+    /// Constructs a new writer that appends an instruction to `words`. The opcode word is
+    /// reserved, and written by `finish`.
+    #[inline]
+    fn new(words: &'a mut Vec<u32>) -> Self {
+        let start = words.len();
+        words.push(0);
+        Self { words, start }
+    }
+
+    /// Writes the opcode word, now that the number of words of the instruction is known.
+    #[inline]
+    fn finish(self, opcode: u16) {
+        let word_count = (self.words.len() - self.start) as u32;
+        self.words[self.start] = word_count << 16 | opcode as u32;
+    }
+
+    /// Appends a word.
+    #[inline]
+    fn push_u32(&mut self, word: u32) {
+        self.words.push(word);
+    }
+
+    /// Appends a `u64` as two words, low-order word first.
+    #[inline]
+    fn push_u64(&mut self, value: u64) {
+        self.push_u32(value as u32);
+        self.push_u32((value >> 32) as u32);
+    }
+
+    /// Appends a nul-terminated string, padded with nul bytes to a multiple of 4 bytes.
+    fn push_string(&mut self, string: &str) {
+        let bytes = string.as_bytes();
+
+        for chunk in bytes.chunks(4) {
+            let mut word = [0; 4];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.push_u32(u32::from_le_bytes(word));
+        }
+
+        // There is always at least one nul byte, even if the length is a multiple of 4.
+        if bytes.len() % 4 == 0 {
+            self.push_u32(0);
+        }
+    }
+}
+
+/// An operand of an instruction, which can be encoded and disassembled.
+trait Operand {
+    /// Appends the words of the operand.
+    fn encode(&self, writer: &mut InstructionWriter);
+
+    /// Appends the textual representation of the operand, preceded by a space.
+    fn disassemble(&self, out: &mut String);
+}
+
+impl Operand for u32 {
+    #[inline]
+    fn encode(&self, writer: &mut InstructionWriter) {
+        writer.push_u32(*self);
+    }
+
+    #[inline]
+    fn disassemble(&self, out: &mut String) {
+        out.push_str(&format!(" {}", self));
+    }
+}
+
+impl Operand for u64 {
+    #[inline]
+    fn encode(&self, writer: &mut InstructionWriter) {
+        writer.push_u64(*self);
+    }
+
+    #[inline]
+    fn disassemble(&self, out: &mut String) {
+        out.push_str(&format!(" {}", self));
+    }
+}
+
+impl Operand for f32 {
+    #[inline]
+    fn encode(&self, writer: &mut InstructionWriter) {
+        writer.push_u32(self.to_bits());
+    }
+
+    #[inline]
+    fn disassemble(&self, out: &mut String) {
+        out.push_str(&format!(" {:?}", self));
+    }
+}
+
+impl Operand for f64 {
+    #[inline]
+    fn encode(&self, writer: &mut InstructionWriter) {
+        writer.push_u64(self.to_bits());
+    }
+
+    #[inline]
+    fn disassemble(&self, out: &mut String) {
+        out.push_str(&format!(" {:?}", self));
+    }
+}
+
+impl Operand for String {
+    #[inline]
+    fn encode(&self, writer: &mut InstructionWriter) {
+        writer.push_string(self);
+    }
+
+    #[inline]
+    fn disassemble(&self, out: &mut String) {
+        out.push_str(&format!(" {:?}", self));
+    }
+}
+
+impl Operand for Id {
+    #[inline]
+    fn encode(&self, writer: &mut InstructionWriter) {
+        writer.push_u32(self.0);
+    }
+
+    #[inline]
+    fn disassemble(&self, out: &mut String) {
+        out.push_str(&format!(" {}", self));
+    }
+}
+
+impl Operand for SpecConstantInstruction {
+    #[inline]
+    fn encode(&self, writer: &mut InstructionWriter) {
+        writer.push_u32(self.opcode() as u32);
+        self.write_operands(writer);
+    }
+
+    #[inline]
+    fn disassemble(&self, out: &mut String) {
+        out.push(' ');
+        out.push_str(self.name());
+        self.disassemble_operands(out);
+    }
+}
+
+impl<T: Operand> Operand for Option<T> {
+    #[inline]
+    fn encode(&self, writer: &mut InstructionWriter) {
+        if let Some(value) = self {
+            value.encode(writer);
+        }
+    }
+
+    #[inline]
+    fn disassemble(&self, out: &mut String) {
+        if let Some(value) = self {
+            value.disassemble(out);
+        }
+    }
+}
+
+impl<T: Operand> Operand for Vec<T> {
+    #[inline]
+    fn encode(&self, writer: &mut InstructionWriter) {
+        for value in self {
+            value.encode(writer);
+        }
+    }
+
+    #[inline]
+    fn disassemble(&self, out: &mut String) {
+        for value in self {
+            value.disassemble(out);
+        }
+    }
+}
+
+impl<A: Operand, B: Operand> Operand for (A, B) {
+    #[inline]
+    fn encode(&self, writer: &mut InstructionWriter) {
+        self.0.encode(writer);
+        self.1.encode(writer);
+    }
+
+    #[inline]
+    fn disassemble(&self, out: &mut String) {
+        self.0.disassemble(out);
+        self.1.disassemble(out);
+    }
+}
+
 /// Error that can happen when reading a SPIR-V module.
 #[derive(Clone, Debug)]
 pub enum SpirvError {
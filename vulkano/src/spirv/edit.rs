@@ -0,0 +1,572 @@
+// Copyright (c) 2021 The Vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or https://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Modifications of a SPIR-V module.
+
+use super::{Decoration, Id, Instruction, Spirv};
+use crate::pipeline::shader::SpecializationData;
+use crate::pipeline::shader::SpecializationMismatchError;
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::slice;
+
+impl Spirv {
+    /// Changes the descriptor set and binding numbers of the descriptors of the module.
+    ///
+    /// `f` is called with the descriptor set and binding numbers of each variable that has both,
+    /// and returns its new numbers. This can be used to combine shaders whose descriptors were
+    /// numbered independently into a single pipeline layout.
+    ///
+    /// Only the decorations that directly target a variable are changed, not those that are
+    /// applied through a decoration group.
+    pub fn remap_bindings<F>(&mut self, mut f: F)
+    where
+        F: FnMut(u32, u32) -> (u32, u32),
+    {
+        let variables: Vec<Id> = self
+            .iter_global()
+            .filter_map(|instruction| match *instruction {
+                Instruction::Variable { result_id, .. } => Some(result_id),
+                _ => None,
+            })
+            .collect();
+
+        for variable in variables {
+            let mut set = None;
+            let mut binding = None;
+
+            for &index in &self.ids[&variable].decorations {
+                match self.instructions[index] {
+                    Instruction::Decorate {
+                        target,
+                        decoration: Decoration::DescriptorSet { descriptor_set },
+                    } if target == variable => set = Some((index, descriptor_set)),
+                    Instruction::Decorate {
+                        target,
+                        decoration: Decoration::Binding { binding_point },
+                    } if target == variable => binding = Some((index, binding_point)),
+                    _ => (),
+                }
+            }
+
+            if let (Some((set_index, set_num)), Some((binding_index, binding_num))) = (set, binding)
+            {
+                let (new_set_num, new_binding_num) = f(set_num, binding_num);
+                self.instructions[set_index] = Instruction::Decorate {
+                    target: variable,
+                    decoration: Decoration::DescriptorSet {
+                        descriptor_set: new_set_num,
+                    },
+                };
+                self.instructions[binding_index] = Instruction::Decorate {
+                    target: variable,
+                    decoration: Decoration::Binding {
+                        binding_point: new_binding_num,
+                    },
+                };
+            }
+        }
+    }
+
+    /// Removes the debug instructions of the module: the names of `Id`s and struct members, the
+    /// source code and the line information.
+    ///
+    /// This makes the module smaller, but the names are also used by `vulkano-shaders` and by the
+    /// reflection of `ShaderModule` to name descriptors and interface variables.
+    pub fn strip_debug_info(&mut self) {
+        self.retain_instructions(|_, instruction| {
+            !matches!(
+                instruction,
+                Instruction::SourceContinued { .. }
+                    | Instruction::Source { .. }
+                    | Instruction::SourceExtension { .. }
+                    | Instruction::String { .. }
+                    | Instruction::Name { .. }
+                    | Instruction::MemberName { .. }
+                    | Instruction::Line { .. }
+                    | Instruction::NoLine
+                    | Instruction::ModuleProcessed { .. }
+            )
+        });
+    }
+
+    /// Replaces the specialization constants that have a value in `data` with regular constants
+    /// of this value.
+    ///
+    /// The constants are no longer specialization constants afterwards, so the values in `data`
+    /// must not be provided again when creating a pipeline. `SpecConstantOp` and
+    /// `SpecConstantComposite` instructions that use them are kept, and are still evaluated when
+    /// the pipeline is created.
+    ///
+    /// Returns an error, and leaves the module unchanged, if `data` contains a constant that the
+    /// module doesn't declare or a value whose size doesn't match the type of its constant.
+    pub fn bake_specialization_constants<S>(
+        &mut self,
+        data: &S,
+    ) -> Result<(), SpecializationMismatchError>
+    where
+        S: SpecializationData + ?Sized,
+    {
+        // The index of the `SpecId` decoration and the `Id` of each specialization constant.
+        let spec_ids: HashMap<u32, (usize, Id)> = self
+            .iter_decoration()
+            .enumerate()
+            .filter_map(|(index, instruction)| match *instruction {
+                Instruction::Decorate {
+                    target,
+                    decoration:
+                        Decoration::SpecId {
+                            specialization_constant_id,
+                        },
+                } => Some((
+                    specialization_constant_id,
+                    (self.range_decoration.start + index, target),
+                )),
+                _ => None,
+            })
+            .collect();
+
+        let bytes =
+            unsafe { slice::from_raw_parts(data.data_ptr() as *const u8, data.data_size()) };
+        let mut replacements = Vec::new();
+
+        for entry in data.map_entries().iter() {
+            let (decoration_index, id) = *spec_ids.get(&entry.constant_id).ok_or(
+                SpecializationMismatchError::UnknownConstant {
+                    constant_id: entry.constant_id,
+                },
+            )?;
+            let value = &bytes[entry.offset as usize..entry.offset as usize + entry.size];
+            let index = self.ids[&id].index;
+
+            let replacement = match self.instructions[index] {
+                Instruction::SpecConstantTrue {
+                    result_type_id,
+                    result_id,
+                }
+                | Instruction::SpecConstantFalse {
+                    result_type_id,
+                    result_id,
+                } => {
+                    let required = mem::size_of::<u32>();
+                    if entry.size != required {
+                        return Err(SpecializationMismatchError::SizeMismatch {
+                            constant_id: entry.constant_id,
+                            provided: entry.size,
+                            required,
+                        });
+                    }
+
+                    if value.iter().any(|&byte| byte != 0) {
+                        Instruction::ConstantTrue {
+                            result_type_id,
+                            result_id,
+                        }
+                    } else {
+                        Instruction::ConstantFalse {
+                            result_type_id,
+                            result_id,
+                        }
+                    }
+                }
+                Instruction::SpecConstant {
+                    result_type_id,
+                    result_id,
+                    ..
+                } => {
+                    let (width, signed) = match *self.id(result_type_id).instruction() {
+                        Instruction::TypeInt {
+                            width, signedness, ..
+                        } => (width, signedness != 0),
+                        Instruction::TypeFloat { width, .. } => (width, false),
+                        _ => continue,
+                    };
+                    let required = width as usize / 8;
+                    if entry.size != required {
+                        return Err(SpecializationMismatchError::SizeMismatch {
+                            constant_id: entry.constant_id,
+                            provided: entry.size,
+                            required,
+                        });
+                    }
+
+                    Instruction::Constant {
+                        result_type_id,
+                        result_id,
+                        value: literal_words(value, signed),
+                    }
+                }
+                _ => continue,
+            };
+
+            replacements.push((index, decoration_index, replacement));
+        }
+
+        let mut spec_id_decorations = HashSet::new();
+        for (index, decoration_index, replacement) in replacements {
+            self.instructions[index] = replacement;
+            spec_id_decorations.insert(decoration_index);
+        }
+
+        // A `SpecId` decoration can only be applied to a specialization constant.
+        self.retain_instructions(|index, _| !spec_id_decorations.contains(&index));
+
+        Ok(())
+    }
+
+    /// Renames the entry points named `name` to `new_name`. Returns `false` if the module has no
+    /// entry point named `name`.
+    pub fn rename_entry_point(&mut self, name: &str, new_name: &str) -> bool {
+        let mut found = false;
+
+        for instruction in &mut self.instructions[self.range_entry_point.clone()] {
+            if let Instruction::EntryPoint {
+                name: entry_point_name,
+                ..
+            } = instruction
+            {
+                if entry_point_name == name {
+                    *entry_point_name = new_name.to_owned();
+                    found = true;
+                }
+            }
+        }
+
+        found
+    }
+
+    /// Removes the entry points named `name`, along with their execution modes. Returns `false` if
+    /// the module has no entry point named `name`.
+    ///
+    /// The functions of the entry points are kept, as they can be called by other functions.
+    pub fn remove_entry_point(&mut self, name: &str) -> bool {
+        let (removed, kept): (Vec<_>, Vec<_>) = self
+            .iter_entry_point()
+            .filter_map(|instruction| match instruction {
+                Instruction::EntryPoint {
+                    entry_point,
+                    name: entry_point_name,
+                    ..
+                } => Some((*entry_point, entry_point_name == name)),
+                _ => None,
+            })
+            .partition(|&(_, removed)| removed);
+
+        if removed.is_empty() {
+            return false;
+        }
+
+        // The same function can be the entry point of several execution models, under the same
+        // name or not. Its execution modes are kept as long as one of them remains.
+        let kept: HashSet<Id> = kept.into_iter().map(|(id, _)| id).collect();
+        let removed: HashSet<Id> = removed
+            .into_iter()
+            .map(|(id, _)| id)
+            .filter(|id| !kept.contains(id))
+            .collect();
+
+        self.retain_instructions(|_, instruction| match instruction {
+            Instruction::EntryPoint {
+                name: entry_point_name,
+                ..
+            } => entry_point_name != name,
+            Instruction::ExecutionMode { entry_point, .. }
+            | Instruction::ExecutionModeId { entry_point, .. } => !removed.contains(entry_point),
+            _ => true,
+        });
+
+        true
+    }
+
+    // Removes the instructions for which `f`, called with the index of the instruction, returns
+    // false, and analyzes the module again.
+    fn retain_instructions<F>(&mut self, mut f: F)
+    where
+        F: FnMut(usize, &Instruction) -> bool,
+    {
+        let instructions = mem::take(&mut self.instructions)
+            .into_iter()
+            .enumerate()
+            .filter(|(index, instruction)| f(*index, instruction))
+            .map(|(_, instruction)| instruction)
+            .collect();
+
+        // The removed instructions are never the only instructions that define or use an `Id`
+        // needed by the remaining ones, so the module stays valid.
+        *self = Spirv::from_instructions(self.version, self.generator, self.bound, instructions)
+            .expect("removing instructions made the module invalid");
+    }
+}
+
+// Converts the native-endian bytes of a specialization constant to the words of a literal number.
+// Literals that are smaller than 32 bits are zero or sign extended.
+fn literal_words(bytes: &[u8], signed: bool) -> Vec<u32> {
+    let mut buf = [0u8; 8];
+    let value = if cfg!(target_endian = "little") {
+        buf[..bytes.len()].copy_from_slice(bytes);
+        u64::from_le_bytes(buf)
+    } else {
+        buf[8 - bytes.len()..].copy_from_slice(bytes);
+        u64::from_be_bytes(buf)
+    };
+
+    match bytes.len() {
+        8 => vec![value as u32, (value >> 32) as u32],
+        4 => vec![value as u32],
+        size => {
+            let bits = size as u32 * 8;
+            let value = value as u32;
+            if signed && value >> (bits - 1) & 1 != 0 {
+                vec![value | !0 << bits]
+            } else {
+                vec![value]
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pipeline::shader::SpecializationInfo;
+    use crate::pipeline::shader::SpecializationMismatchError;
+    use crate::spirv::{
+        AddressingModel, Capability, Decoration, ExecutionMode, ExecutionModel, Id, Instruction,
+        MemoryModel, SourceLanguage, Spirv, StorageClass,
+    };
+
+    // A compute shader with a descriptor and a specialization constant. The entry point doesn't
+    // have a function, which doesn't matter for these tests.
+    fn module() -> Spirv {
+        let instructions = vec![
+            Instruction::Capability {
+                capability: Capability::Shader,
+            },
+            Instruction::MemoryModel {
+                addressing_model: AddressingModel::Logical,
+                memory_model: MemoryModel::GLSL450,
+            },
+            Instruction::EntryPoint {
+                execution_model: ExecutionModel::GLCompute,
+                entry_point: Id(1),
+                name: "main".to_owned(),
+                interface: vec![],
+            },
+            Instruction::ExecutionMode {
+                entry_point: Id(1),
+                mode: ExecutionMode::LocalSize {
+                    x_size: 64,
+                    y_size: 1,
+                    z_size: 1,
+                },
+            },
+            Instruction::Source {
+                source_language: SourceLanguage::GLSL,
+                version: 450,
+                file: None,
+                source: None,
+            },
+            Instruction::Name {
+                target: Id(5),
+                name: "count".to_owned(),
+            },
+            Instruction::Name {
+                target: Id(7),
+                name: "samp".to_owned(),
+            },
+            Instruction::Decorate {
+                target: Id(5),
+                decoration: Decoration::SpecId {
+                    specialization_constant_id: 3,
+                },
+            },
+            Instruction::Decorate {
+                target: Id(7),
+                decoration: Decoration::DescriptorSet { descriptor_set: 0 },
+            },
+            Instruction::Decorate {
+                target: Id(7),
+                decoration: Decoration::Binding { binding_point: 2 },
+            },
+            Instruction::TypeInt {
+                result_id: Id(2),
+                width: 32,
+                signedness: 1,
+            },
+            Instruction::TypeSampler { result_id: Id(3) },
+            Instruction::TypePointer {
+                result_id: Id(4),
+                storage_class: StorageClass::UniformConstant,
+                ty: Id(3),
+            },
+            Instruction::SpecConstant {
+                result_type_id: Id(2),
+                result_id: Id(5),
+                value: vec![16],
+            },
+            Instruction::TypeInt {
+                result_id: Id(6),
+                width: 16,
+                signedness: 1,
+            },
+            Instruction::Variable {
+                result_type_id: Id(4),
+                result_id: Id(7),
+                storage_class: StorageClass::UniformConstant,
+                initializer: None,
+            },
+        ];
+
+        let mut words = vec![0x07230203, 0x00010000, 0, 8, 0];
+        for instruction in &instructions {
+            instruction.encode(&mut words);
+        }
+
+        Spirv::new(&words).unwrap()
+    }
+
+    #[test]
+    fn encode_roundtrip() {
+        let spirv = module();
+        let words = spirv.to_words();
+        let decoded = Spirv::new(&words).unwrap();
+
+        assert_eq!(decoded.instructions(), spirv.instructions());
+        assert_eq!(decoded.to_words(), words);
+    }
+
+    #[test]
+    fn encode_string() {
+        // "main" fills a word, so a word of nul bytes follows it.
+        let mut words = Vec::new();
+        Instruction::Name {
+            target: Id(1),
+            name: "main".to_owned(),
+        }
+        .encode(&mut words);
+        assert_eq!(words.len(), 4);
+        assert_eq!(words[0], 4 << 16 | 5);
+        assert_eq!(words[3], 0);
+    }
+
+    #[test]
+    fn remap_bindings() {
+        let mut spirv = module();
+        spirv.remap_bindings(|set, binding| (set + 1, binding * 2));
+
+        let decorations: Vec<_> = spirv.id(Id(7)).iter_decoration().cloned().collect();
+        assert!(decorations.contains(&Instruction::Decorate {
+            target: Id(7),
+            decoration: Decoration::DescriptorSet { descriptor_set: 1 },
+        }));
+        assert!(decorations.contains(&Instruction::Decorate {
+            target: Id(7),
+            decoration: Decoration::Binding { binding_point: 4 },
+        }));
+    }
+
+    #[test]
+    fn strip_debug_info() {
+        let mut spirv = module();
+        let len = spirv.instructions().len();
+        spirv.strip_debug_info();
+
+        assert_eq!(spirv.instructions().len(), len - 3);
+        assert_eq!(spirv.iter_name().len(), 0);
+        assert_eq!(spirv.id(Id(7)).iter_decoration().len(), 2);
+    }
+
+    #[test]
+    fn bake_specialization_constants() {
+        let mut spirv = module();
+
+        let mut spec_info = SpecializationInfo::new();
+        spec_info.set(3, 64i32);
+        spirv.bake_specialization_constants(&spec_info).unwrap();
+
+        assert_eq!(
+            spirv.id(Id(5)).instruction(),
+            &Instruction::Constant {
+                result_type_id: Id(2),
+                result_id: Id(5),
+                value: vec![64],
+            }
+        );
+        assert_eq!(spirv.id(Id(5)).iter_decoration().len(), 0);
+
+        let mut spec_info = SpecializationInfo::new();
+        spec_info.set(3, 64i32);
+        assert!(matches!(
+            spirv.bake_specialization_constants(&spec_info),
+            Err(SpecializationMismatchError::UnknownConstant { constant_id: 3 })
+        ));
+    }
+
+    #[test]
+    fn bake_specialization_constants_size_mismatch() {
+        let mut spirv = module();
+
+        let mut spec_info = SpecializationInfo::new();
+        spec_info.set(3, 64i64);
+        assert!(matches!(
+            spirv.bake_specialization_constants(&spec_info),
+            Err(SpecializationMismatchError::SizeMismatch {
+                constant_id: 3,
+                provided: 8,
+                required: 4,
+            })
+        ));
+        assert!(matches!(
+            spirv.id(Id(5)).instruction(),
+            Instruction::SpecConstant { .. }
+        ));
+    }
+
+    #[test]
+    fn literal_words() {
+        assert_eq!(super::literal_words(&(-2i16).to_ne_bytes(), true), [!1]);
+        assert_eq!(
+            super::literal_words(&0xfffeu16.to_ne_bytes(), false),
+            [0xfffe]
+        );
+        assert_eq!(
+            super::literal_words(&0x1_0000_0002u64.to_ne_bytes(), false),
+            [2, 1]
+        );
+    }
+
+    #[test]
+    fn entry_points() {
+        let mut spirv = module();
+
+        assert!(spirv.rename_entry_point("main", "cs_main"));
+        assert!(!spirv.rename_entry_point("main", "other"));
+        assert!(matches!(
+            spirv.iter_entry_point().next(),
+            Some(Instruction::EntryPoint { name, .. }) if name == "cs_main"
+        ));
+
+        assert!(!spirv.remove_entry_point("main"));
+        assert!(spirv.remove_entry_point("cs_main"));
+        assert_eq!(spirv.iter_entry_point().len(), 0);
+        assert_eq!(spirv.iter_execution_mode().len(), 0);
+    }
+
+    #[test]
+    fn disassemble() {
+        let text = module().disassemble();
+
+        assert!(text.starts_with("; SPIR-V\n; Version: 1.0\n"));
+        assert!(text.contains("\n     OpCapability Shader\n"));
+        assert!(text.contains("\n     OpEntryPoint GLCompute %1 \"main\"\n"));
+        assert!(text.contains("\n     OpExecutionMode %1 LocalSize 64 1 1\n"));
+        assert!(text.contains("\n     OpDecorate %7 Binding 2\n"));
+        assert!(text.contains("\n%5 = OpSpecConstant %2 16\n"));
+        assert!(text.contains("\n%4 = OpTypePointer UniformConstant %3\n"));
+    }
+}